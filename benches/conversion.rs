@@ -0,0 +1,136 @@
+//! Throughput of the Arrow -> `SeriesData` conversion.
+//!
+//! `cargo bench --bench conversion` prints rows/s of list columns decoded row by row (every row
+//! is a separate array to downcast and decode) next to `to_list`, which decodes short lists as a
+//! whole column and splits them by row afterwards.
+
+#![allow(dead_code)]
+
+#[path = "../src/data_loader.rs"]
+mod data_loader;
+#[cfg(feature = "duckdb")]
+#[path = "../src/duckdb_engine.rs"]
+mod duckdb_engine;
+#[path = "../src/symlog.rs"]
+mod symlog;
+
+use data_loader::{ErrorInfo, SeriesPoints, to_error_info, to_list, to_series_point};
+use datafusion::arrow::array::*;
+use datafusion::arrow::buffer::OffsetBuffer;
+use datafusion::arrow::datatypes::{DataType, Field, Fields};
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const ROWS: usize = 2_000;
+const POINTS: usize = 500;
+
+// `computed` column of the series table: list of { n, value: { real, imag }, deviation }
+fn computed_column() -> ListArray {
+    let total = ROWS * POINTS;
+    let n = Int32Array::from_iter_values((0..total).map(|i| (i % POINTS) as i32));
+    let real =
+        StringArray::from_iter_values((0..total).map(|i| format!("{}e-{}", i % 97, i % 300)));
+    let imag = StringArray::from_iter_values((0..total).map(|_| "0"));
+    let deviation = StringArray::from_iter_values((0..total).map(|i| format!("1.5e-{}", i % 1000)));
+
+    let complex_fields = Fields::from(vec![
+        Field::new("real", DataType::Utf8, true),
+        Field::new("imag", DataType::Utf8, true),
+    ]);
+    let value = StructArray::new(
+        complex_fields.clone(),
+        vec![Arc::new(real), Arc::new(imag)],
+        None,
+    );
+    let point_fields = Fields::from(vec![
+        Field::new("n", DataType::Int32, true),
+        Field::new("value", DataType::Struct(complex_fields), true),
+        Field::new("deviation", DataType::Utf8, true),
+    ]);
+    let points = StructArray::new(
+        point_fields.clone(),
+        vec![Arc::new(n), Arc::new(value), Arc::new(deviation)],
+        None,
+    );
+    ListArray::new(
+        Arc::new(Field::new("item", DataType::Struct(point_fields), true)),
+        OffsetBuffer::from_lengths(std::iter::repeat_n(POINTS, ROWS)),
+        Arc::new(points),
+        None,
+    )
+}
+
+// `errors` column of the accelerations table: short lists of { n, message }
+fn errors_column() -> ListArray {
+    let lengths: Vec<usize> = (0..ROWS * 50).map(|i| i % 4).collect();
+    let total = lengths.iter().sum();
+    let n = Int32Array::from_iter_values((0..total).map(|i| i as i32));
+    let message = StringArray::from_iter_values((0..total).map(|i| format!("error {i}")));
+    let fields = Fields::from(vec![
+        Field::new("n", DataType::Int32, true),
+        Field::new("message", DataType::Utf8, true),
+    ]);
+    let errors = StructArray::new(fields.clone(), vec![Arc::new(n), Arc::new(message)], None);
+    ListArray::new(
+        Arc::new(Field::new("item", DataType::Struct(fields), true)),
+        OffsetBuffer::from_lengths(lengths),
+        Arc::new(errors),
+        None,
+    )
+}
+
+// Runs `f` until a second has passed, returns rows per second
+fn throughput(mut f: impl FnMut() -> usize) -> f64 {
+    let start = Instant::now();
+    let mut rows = 0;
+    while start.elapsed() < Duration::from_secs(1) {
+        rows += f();
+    }
+    rows as f64 / start.elapsed().as_secs_f64()
+}
+
+fn report(name: &str, by_row: f64, to_list: f64) {
+    println!(
+        "{name:<24} by row: {by_row:>12.0} rows/s  to_list: {to_list:>12.0} rows/s  ({:.1}x)",
+        to_list / by_row
+    );
+}
+
+fn main() {
+    let computed = computed_column();
+    report(
+        "computed (list<struct>)",
+        throughput(|| {
+            let res: Vec<Option<SeriesPoints>> = computed
+                .iter()
+                .map(|row| {
+                    row.map(|row| SeriesPoints::from(to_series_point("computed.[]", &row).unwrap()))
+                })
+                .collect();
+            black_box(res).len()
+        }),
+        throughput(|| {
+            let res: Vec<Option<SeriesPoints>> =
+                to_list("computed", &computed, |x| to_series_point("computed.[]", x)).unwrap();
+            black_box(res).len()
+        }),
+    );
+
+    let errors = errors_column();
+    report(
+        "errors (short lists)",
+        throughput(|| {
+            let res: Vec<Option<Vec<ErrorInfo>>> = errors
+                .iter()
+                .map(|row| row.map(|row| to_error_info("errors.[]", &row).unwrap()))
+                .collect();
+            black_box(res).len()
+        }),
+        throughput(|| {
+            let res: Vec<Option<Vec<ErrorInfo>>> =
+                to_list("errors", &errors, |x| to_error_info("errors.[]", x)).unwrap();
+            black_box(res).len()
+        }),
+    );
+}
@@ -0,0 +1,127 @@
+//! Keyboard control and screen-reader descriptions. egui reports widgets to AccessKit by their
+//! text; plots and icon-only buttons have none, so they are described here
+
+use eframe::egui;
+use egui::{Context, Id, Key, Modifiers, Response, Shape, Stroke, Vec2, WidgetInfo, WidgetType};
+use egui_plot::PlotUi;
+
+// Part of the visible range moved by one arrow key press
+const PAN_STEP: f64 = 0.1;
+const ZOOM_STEP: f32 = 1.25;
+
+/// Arrows pan the focused plot, +/- zoom it, 0 fits it to the data again. Part of
+/// [`crate::app::plot_controls`]
+pub fn plot_keys(plot_ui: &mut PlotUi) {
+    let response = plot_ui.response();
+    if !response.has_focus() {
+        return;
+    }
+    let id = response.id;
+    let ctx = plot_ui.ctx().clone();
+    // Otherwise the arrows move the focus to the neighbouring widgets
+    ctx.memory_mut(|m| {
+        m.set_focus_lock_filter(
+            id,
+            egui::EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..Default::default()
+            },
+        )
+    });
+    let (left, right, up, down, zoom_in, zoom_out, reset) = ctx.input_mut(|i| {
+        (
+            i.consume_key(Modifiers::NONE, Key::ArrowLeft),
+            i.consume_key(Modifiers::NONE, Key::ArrowRight),
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+            i.consume_key(Modifiers::NONE, Key::Plus)
+                || i.consume_key(Modifiers::NONE, Key::Equals),
+            i.consume_key(Modifiers::NONE, Key::Minus),
+            i.consume_key(Modifiers::NONE, Key::Num0),
+        )
+    });
+    let bounds = plot_ui.plot_bounds();
+    let dx = bounds.width() * PAN_STEP;
+    let dy = bounds.height() * PAN_STEP;
+    let steps = |minus: bool, plus: bool| plus as i32 as f64 - minus as i32 as f64;
+    let delta = [steps(left, right) * dx, steps(down, up) * dy];
+    if delta != [0.0, 0.0] {
+        plot_ui.translate_bounds(Vec2::new(delta[0] as f32, delta[1] as f32));
+    }
+    if zoom_in {
+        plot_ui.zoom_bounds(Vec2::splat(ZOOM_STEP), bounds.center());
+    }
+    if zoom_out {
+        plot_ui.zoom_bounds(Vec2::splat(1.0 / ZOOM_STEP), bounds.center());
+    }
+    if reset {
+        plot_ui.set_auto_bounds(true.into());
+    }
+}
+
+/// Label of a plot for screen readers: what it shows and how to move around it
+pub fn describe_plot(response: &Response, description: String) {
+    let enabled = response.enabled();
+    response.widget_info(|| {
+        WidgetInfo::labeled(
+            WidgetType::Other,
+            enabled,
+            format!("{description}. Стрелки — сдвиг, +/− — масштаб, 0 — весь график"),
+        )
+    });
+}
+
+/// Name of a button whose text is only an icon, for screen readers and as its tooltip
+pub fn name_button(response: Response, name: &str) -> Response {
+    let enabled = response.enabled();
+    response.widget_info(|| WidgetInfo::labeled(WidgetType::Button, enabled, name));
+    response.on_hover_text(name)
+}
+
+/// Outlines the widget focused from the keyboard; egui only tints it like a hovered one. The
+/// outline shows after Tab and hides on the next mouse click
+pub fn paint_focus(ctx: &Context) {
+    let keyboard_id = Id::new("keyboard_focus");
+    let (tab, pointer) = ctx.input(|i| (i.key_pressed(Key::Tab), i.pointer.any_pressed()));
+    let mut keyboard = ctx.data(|d| d.get_temp(keyboard_id)).unwrap_or(false);
+    keyboard = (keyboard || tab) && !pointer;
+    ctx.data_mut(|d| d.insert_temp(keyboard_id, keyboard));
+    if !keyboard {
+        return;
+    }
+    let Some(response) = ctx
+        .memory(|m| m.focused())
+        .and_then(|id| ctx.read_response(id))
+    else {
+        return;
+    };
+    let rect = response.rect.expand(2.0);
+    let stroke = Stroke::new(2.0, ctx.style().visuals.selection.stroke.color);
+    let corners = vec![
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+    ];
+    ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, keyboard_id))
+        .add(Shape::closed_line(corners, stroke));
+}
+
+/// Label of a widget whose own text only makes sense next to its group's heading, e.g. the
+/// "All" button or a value checkbox of a filter
+pub fn in_group(
+    response: Response,
+    typ: WidgetType,
+    selected: Option<bool>,
+    group: &str,
+    text: &str,
+) -> Response {
+    let enabled = response.enabled();
+    response.widget_info(|| {
+        let mut info = WidgetInfo::labeled(typ, enabled, format!("{group}: {text}"));
+        info.selected = selected;
+        info
+    });
+    response
+}
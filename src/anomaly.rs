@@ -0,0 +1,72 @@
+use crate::app::magnitude;
+use crate::data_loader::{AccelRecord, SeriesRecord};
+
+// Number of trailing steps over which a steadily growing deviation counts as divergence
+const GROWTH_WINDOW: usize = 5;
+
+/// Sign that an acceleration run went wrong
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Anomaly {
+    Growing,
+    NonFinite,
+    WorseThanPartialSums,
+}
+
+impl Anomaly {
+    pub fn description(self) -> String {
+        match self {
+            Self::Growing => format!("Отклонение растёт на последних {GROWTH_WINDOW} шагах"),
+            Self::NonFinite => "Есть NaN или бесконечные значения".to_string(),
+            Self::WorseThanPartialSums => {
+                "Последнее отклонение больше, чем у частичных сумм".to_string()
+            }
+        }
+    }
+}
+
+/// Anomalies of an acceleration run, empty if it looks convergent
+pub fn anomalies(series: &SeriesRecord, accel_record: &AccelRecord) -> Vec<Anomaly> {
+    let mut res = Vec::new();
+    let points: Vec<_> = accel_record
+        .computed
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| Some((i, p?)))
+        .collect();
+
+    if points.iter().any(|(_, p)| {
+        !(p.value.real.0.is_finite() && p.value.imag.0.is_finite() && p.deviation.0.is_finite())
+    }) {
+        res.push(Anomaly::NonFinite);
+    }
+
+    let tail: Vec<f64> = points
+        .iter()
+        .rev()
+        .take(GROWTH_WINDOW + 1)
+        .map(|(_, p)| magnitude(&p.deviation))
+        .filter(|m| m.is_finite())
+        .collect();
+    // `tail` is reversed: every older deviation is smaller than the next one
+    if tail.len() == GROWTH_WINDOW + 1 && tail.windows(2).all(|w| w[0] > w[1]) {
+        res.push(Anomaly::Growing);
+    }
+
+    if let Some((i, last)) = points.last()
+        && *i < series.computed.len()
+        && last.deviation.abs() > series.computed.get(*i).deviation.abs()
+    {
+        res.push(Anomaly::WorseThanPartialSums);
+    }
+
+    res
+}
+
+/// Tooltip listing the anomalies
+pub fn describe(anomalies: &[Anomaly]) -> String {
+    anomalies
+        .iter()
+        .map(|a| format!("⚠ {}", a.description()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,30 +1,95 @@
-use crate::data_loader::{AccelInfo, AccelRecord, DataLoader, Filters, SeriesData, SeriesRecord};
-use crate::symlog::symlog_formatter;
+use crate::accessibility::{describe_plot, in_group, name_button, paint_focus, plot_keys};
+use crate::anomaly::{anomalies, describe as describe_anomalies};
+use crate::data_loader::{
+    AccelInfo, AccelRecord, ComplexNumber, DuplicatePolicy, ExportFormat, Filters, IStr,
+    LoadedPage, Metadata, MetadataPart, NO_EVENTS, NOT_SET, PAGE_SIZE, ParamKind, QueryEstimate,
+    SeriesData, SeriesRecord, accel_params_match, export_dataset, export_json, export_points,
+    intern, params_match, parse_bool, points_table, sort_param_values,
+};
+use crate::data_source::DataSource;
+use crate::diagnose::{Diagnosis, idle_params, idle_params_ui};
+use crate::grouping::{
+    Group, GroupKey, Grouping, family_pattern_ui, grouping_ui, series_family, sync_family_pattern,
+};
+use crate::history::{QueryHistory, QueryRecord};
+use crate::legend::{
+    CommonParts, Dimension, LegendEntry, LegendMode, LineVisibility, StyleIndex, auto_color,
+    full_name, grouped_legend, method_color, value_color,
+};
+use crate::metric::{Metric, MetricParams, MetricValue, Scale, metric_ui};
+use crate::palette::{CommandPalette, PaletteItem};
+use crate::panel::{PanelRegistry, PlotPanel};
+use crate::plot_data::{self, PlotDataWindow};
+use crate::remap::RemapWindow;
+use crate::schema_browser::SchemaBrowser;
+use crate::significance::SignificanceTest;
+use crate::slope::{
+    SlopeFit, SlopeWindow, TailFit, drag_range, log_errors, paint_range, power_of_ten,
+};
+use crate::symlog::{
+    NumberFormat, Scientific, format_f64, log10_formatter, log10_grid_spacer, paint_linear_region,
+    set_number_format, symlog_formatter, symlog_grid_spacer, symlog_inverse,
+};
+use crate::ui_scale::UiScale;
+use crate::vega;
+use crate::workspace::{Annotation, Bookmark, PlotLabels, Workspace};
 use anyhow::Result;
 use eframe::egui;
-
-use egui::{Color32, Context, Stroke, Ui, ViewportCommand};
-use egui_plot::{Line, MarkerShape, Plot, PlotPoint, Points};
-use std::collections::{HashMap, HashSet};
-use std::sync::{mpsc, Arc};
+use serde::{Deserialize, Serialize};
+
+use egui::{Color32, Context, Stroke, Ui, ViewportCommand, WidgetType};
+use egui_plot::{
+    BoxElem, BoxPlot, BoxSpread, HLine, Line, LineStyle, MarkerShape, Plot, PlotBounds, PlotPoint,
+    PlotResponse, PlotUi, Points, Polygon, Text,
+};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, RwLock, mpsc};
 use std::{mem, slice};
 
 // TODO: Current `symlog` flag implementation is absolutely awful. To be fixed.
 
-type SeriesDataRef<'a> = (&'a SeriesRecord, Vec<&'a AccelRecord>);
+pub type SeriesDataRef<'a> = (&'a SeriesRecord, Vec<&'a AccelRecord>);
+
+// Methods whose records set each acceleration parameter, sorted
+fn accel_param_methods(entries: &[SeriesData]) -> HashMap<IStr, Vec<IStr>> {
+    let mut res: HashMap<IStr, BTreeSet<IStr>> = HashMap::new();
+    for (_, accel_records) in entries {
+        for accel_record in accel_records {
+            for key in accel_record.accel_info.additional_args.keys() {
+                res.entry(key.clone())
+                    .or_default()
+                    .insert(accel_record.accel_info.name.clone());
+            }
+        }
+    }
+    res.into_iter()
+        .map(|(key, methods)| (key, methods.into_iter().collect()))
+        .collect()
+}
 
 fn filterable(entries: &[SeriesData]) -> Filters {
     let mut precisions = HashSet::new();
     let mut base_series = HashSet::new();
+    let mut families = HashSet::new();
     let mut base_accel = HashSet::new();
     let mut m_values = HashSet::new();
     let mut accel_params = HashMap::new();
     let mut series_params = HashMap::new();
+    // Records that set each parameter, to offer `NOT_SET` when some don't
+    let mut accel_param_counts: HashMap<IStr, usize> = HashMap::new();
+    let mut series_param_counts: HashMap<IStr, usize> = HashMap::new();
+    let mut with_errors = 0;
+    let mut with_events = 0;
+    let mut divergent = 0;
+    let mut total_accels = 0;
+    let mut event_names = HashSet::new();
 
     for (series, accel_records) in entries.iter() {
         // Collect series-level fields
         precisions.insert(series.precision.clone());
         base_series.insert(series.name.clone());
+        families.insert(intern(&series_family(&series.name)));
 
         // Collect series parameters
         for (key, value) in &series.arguments {
@@ -32,6 +97,7 @@ fn filterable(entries: &[SeriesData]) -> Filters {
                 .entry(key.clone())
                 .or_insert_with(HashSet::new)
                 .insert(value.clone());
+            *series_param_counts.entry(key.clone()).or_default() += 1;
         }
 
         // Collect acceleration-level fields
@@ -45,6 +111,17 @@ fn filterable(entries: &[SeriesData]) -> Filters {
                     .entry(key.clone())
                     .or_insert_with(HashSet::new)
                     .insert(value.clone());
+                *accel_param_counts.entry(key.clone()).or_default() += 1;
+            }
+
+            // Collect outcomes
+            total_accels += 1;
+            with_errors += usize::from(!accel_record.errors.is_empty());
+            with_events += usize::from(!accel_record.events.is_empty());
+            divergent += usize::from(!anomalies(series, accel_record).is_empty());
+            event_names.extend(accel_record.events.iter().map(|e| intern(&e.name)));
+            if accel_record.events.is_empty() {
+                event_names.insert(intern(NO_EVENTS));
             }
         }
     }
@@ -58,6 +135,10 @@ fn filterable(entries: &[SeriesData]) -> Filters {
     if base_series.len() > 1 {
         result.base_series = base_series;
     }
+    // Only if some families have several series, otherwise it's the series filter again
+    if families.len() > 1 && families.len() < result.base_series.len() {
+        result.families = families;
+    }
     if base_accel.len() > 1 {
         result.base_accel = base_accel;
     }
@@ -65,60 +146,813 @@ fn filterable(entries: &[SeriesData]) -> Filters {
         result.m_values = m_values;
     }
 
-    // Only keep parameters that have multiple values
-    for (key, values) in series_params {
+    // Only keep parameters that have multiple values, absence counting as one
+    for (key, mut values) in series_params {
+        if series_param_counts[&key] < entries.len() {
+            values.insert(intern(NOT_SET));
+        }
         if values.len() > 1 {
             result.series_params.insert(key, values);
         }
     }
 
-    for (key, values) in accel_params {
+    for (key, mut values) in accel_params {
+        if accel_param_counts[&key] < total_accels {
+            values.insert(intern(NOT_SET));
+        }
         if values.len() > 1 {
             result.accel_params.insert(key, values);
         }
     }
 
+    // Outcome predicates only make sense if they split the records
+    if with_errors > 0 && with_errors < total_accels {
+        result.has_errors = Some(true);
+    }
+    if with_events > 0 && with_events < total_accels {
+        result.has_events = Some(true);
+        result.event_names = event_names;
+    }
+    if divergent > 0 && divergent < total_accels {
+        result.divergent = Some(true);
+    }
+
     result
 }
 
-pub struct Vis {
-    // Plot options
-    show_partial_sums: bool,
-    show_limits: bool,
+// Identifies an accel line independently of the currently loaded data
+#[derive(Clone, PartialEq)]
+pub struct LineKey {
+    pub series_id: i32,
+    pub accel_info: AccelInfo,
+}
+
+impl LineKey {
+    pub fn new(series: &SeriesRecord, accel_info: &AccelInfo) -> Self {
+        Self {
+            series_id: series.series_id,
+            accel_info: accel_info.clone(),
+        }
+    }
+}
+
+// Plot item id of the `i`-th line of a plot, used to find the clicked line
+pub fn line_id(plot_id: &str, i: impl std::hash::Hash) -> egui::Id {
+    egui::Id::new(plot_id).with(i)
+}
+
+// Accel line that stays on the plots regardless of filters
+struct PinnedLine {
+    key: LineKey,
+    name: String,
+    values: Vec<PlotPoint>,
+    errors: Vec<PlotPoint>,
+    errors_symlog: Vec<PlotPoint>,
+}
+
+impl PinnedLine {
+    fn new(series: &SeriesRecord, accel_record: &AccelRecord) -> Self {
+        let points = || {
+            series
+                .computed
+                .iter()
+                .zip(accel_record.computed.iter())
+                .filter_map(|(c, accel)| Some((c.n as f64, accel?)))
+        };
+        Self {
+            key: LineKey {
+                series_id: series.series_id,
+                accel_info: accel_record.accel_info.clone(),
+            },
+            name: format!("📌 {}", format_item_name(series, &accel_record.accel_info)),
+            values: points()
+                .map(|(n, ap)| PlotPoint::new(n, ap.value.real.approx_f64()))
+                .collect(),
+            errors: points()
+                .map(|(n, ap)| PlotPoint::new(n, ap.deviation.approx_f64()))
+                .collect(),
+            errors_symlog: points()
+                .map(|(n, ap)| PlotPoint::new(n, ap.deviation.symlog()))
+                .collect(),
+        }
+    }
+
+    fn line<'a>(&self, points: &'a [PlotPoint]) -> Line<'a> {
+        Line::new(points)
+            .name(&self.name)
+            .color(method_color(&self.key.accel_info))
+            .style(LineStyle::dashed_loose())
+            .width(2.5)
+    }
+}
+
+// Visibility toggles, saved as part of bookmarks
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlotOptions {
+    pub show_partial_sums: bool,
+    pub show_limits: bool,
     show_imaginary: bool,
     show_real: bool,
-    force_show_imaginary: bool,
+    // Unit roundoff of each loaded precision on the error plot
+    show_epsilon: bool,
+    // Markers where the error curves stop improving
+    show_plateaus: bool,
+    // Markers at the smallest deviation of each error curve, and their "n=…, error" labels
+    show_best: bool,
+    label_best: bool,
+    // Area between each accel curve and its partial sums where the acceleration is ahead
+    show_advantage: bool,
+    // Accel curves extrapolated down to 10^tolerance_exp
+    show_projection: bool,
+    // Iterations on a log10 axis in the convergence and error plots
+    pub log_x: bool,
+    // Tolerance 10^tolerance_exp for the "iterations to tolerance" metrics
+    pub tolerance_exp: i32,
+    pub pivot_metric: Metric,
+    pub recommend_criterion: Metric,
+    pub run_metric: Metric,
+    pub deviation_average: DeviationAverage,
+    pub legend_mode: LegendMode,
+    // Legend names without the parts shared by all lines
+    short_names: bool,
+    // "Compare by": dimension shown by the color of accel lines and the one shown by line style
+    pub color_by: Dimension,
+    pub style_by: Option<Dimension>,
+    // Error plot split into small multiples, one per value of the dimension
+    facet_by: Option<Dimension>,
+    // How the convergence plot shows complex values
+    pub complex_view: ComplexView,
+    // Iteration of `Metric::ErrorAt`
+    pub metric_n: i32,
+    // Axes of the performance plot
+    pub perf_x: Metric,
+    pub perf_y: Metric,
+    // Names next to the performance points, and overlapping ones spread apart
+    pub perf_labels: bool,
+    pub perf_spread: bool,
+    // One marker per group at the medians of its records instead of a point per record
+    pub perf_by_group: bool,
+    // What makes records one group in the aggregated views, and the series name prefix of
+    // `GroupKey::SeriesFamily`
+    pub group_key: GroupKey,
+    pub family_pattern: String,
+    // Deviations below 10^underflow_exp on the error and performance plots
+    pub underflow: Underflow,
+    pub underflow_exp: i32,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            show_partial_sums: true,
+            show_limits: true,
+            show_imaginary: true,
+            show_real: true,
+            show_epsilon: true,
+            show_plateaus: true,
+            show_best: false,
+            label_best: true,
+            show_advantage: false,
+            show_projection: false,
+            log_x: false,
+            tolerance_exp: -10,
+            pivot_metric: Metric::default(),
+            recommend_criterion: Metric::default(),
+            run_metric: Metric::default(),
+            deviation_average: DeviationAverage::default(),
+            legend_mode: LegendMode::default(),
+            short_names: false,
+            color_by: Dimension::Method,
+            style_by: None,
+            facet_by: None,
+            complex_view: ComplexView::default(),
+            metric_n: 10,
+            perf_x: Metric::IterationOfMin,
+            perf_y: Metric::MinError,
+            perf_labels: false,
+            perf_spread: false,
+            perf_by_group: false,
+            group_key: GroupKey::default(),
+            family_pattern: "^[^_(]+".to_string(),
+            underflow: Underflow::default(),
+            underflow_exp: -300,
+        }
+    }
+}
+
+impl PlotOptions {
+    pub fn metric_params(&self) -> MetricParams {
+        MetricParams {
+            tolerance_exp: self.tolerance_exp,
+            n: self.metric_n,
+        }
+    }
+
+    // Coordinate of a metric on a plot axis, see `deviation_y`
+    fn metric_y(&self, value: MetricValue, symlog: bool) -> Option<f64> {
+        match value {
+            MetricValue::Error(e) => self.deviation_y(e, symlog),
+            MetricValue::Iterations(n) => Some(n as f64),
+        }
+    }
+
+    /// Y of a deviation on the error and performance plots; `None` if the underflow policy drops
+    /// it
+    pub fn deviation_y(&self, deviation: Scientific, symlog: bool) -> Option<f64> {
+        let floor = Scientific(1.0, self.underflow_exp);
+        let deviation = match self.underflow {
+            _ if magnitude(&deviation) >= self.underflow_exp as f64 => deviation,
+            Underflow::Keep => deviation,
+            Underflow::Floor => floor,
+            Underflow::ZeroRow if deviation.0 == 0.0 => Scientific(1.0, self.underflow_exp - 1),
+            Underflow::ZeroRow => floor,
+            Underflow::Drop => return None,
+        };
+        Some(if symlog {
+            deviation.symlog()
+        } else {
+            deviation.approx_f64()
+        })
+    }
+
+    fn deviation_points(&self, deviations: &[(f64, Scientific)], symlog: bool) -> Vec<PlotPoint> {
+        deviations
+            .iter()
+            .filter_map(|&(n, d)| Some(PlotPoint::new(n, self.deviation_y(d, symlog)?)))
+            .collect()
+    }
+
+    // Rows the underflow policy puts deviations on, with their names
+    fn underflow_rows(&self, symlog: bool) -> Vec<(f64, String)> {
+        let y = |exp| self.deviation_y(Scientific(1.0, exp), symlog);
+        let floor = (
+            y(self.underflow_exp),
+            format!("Меньше 1e{}", self.underflow_exp),
+        );
+        match self.underflow {
+            Underflow::Keep | Underflow::Drop => Vec::new(),
+            Underflow::Floor => vec![floor],
+            Underflow::ZeroRow => vec![floor, (y(self.underflow_exp - 1), "Точный ноль".into())],
+        }
+        .into_iter()
+        .filter_map(|(y, name)| Some((y?, name)))
+        .collect()
+    }
+
+    // What the underflow policy did, shown above the plots
+    fn underflow_note(&self) -> Option<String> {
+        let exp = self.underflow_exp;
+        match self.underflow {
+            Underflow::Keep => None,
+            Underflow::Floor => Some(format!("Ошибки меньше 1e{exp} подняты до 1e{exp}")),
+            Underflow::ZeroRow => Some(format!(
+                "Ошибки меньше 1e{exp} подняты до 1e{exp}, точные нули — на строке 1e{}",
+                exp - 1
+            )),
+            Underflow::Drop => Some(format!("Ошибки меньше 1e{exp} не показаны")),
+        }
+    }
+}
+
+/// Deviations too small for the plots. The symlog scale squeezes everything below its linear
+/// threshold towards zero, and exact zeros land on zero itself
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Underflow {
+    /// Plotted as they are
+    #[default]
+    Keep,
+    /// Raised to the threshold
+    Floor,
+    /// Raised to the threshold, with exact zeros a decade below it on a row of their own
+    ZeroRow,
+    Drop,
+}
+
+impl Underflow {
+    pub const ALL: [Self; 4] = [Self::Keep, Self::Floor, Self::ZeroRow, Self::Drop];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Keep => "как есть",
+            Self::Floor => "поднять до порога",
+            Self::ZeroRow => "поднять, нули отдельно",
+            Self::Drop => "не показывать",
+        }
+    }
+}
+
+/// Complex values on the convergence plot: as real and imaginary parts, or in polar form around
+/// the limit, where a rotating error term shows up as a steady decay and a steady turn
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ComplexView {
+    #[default]
+    Components,
+    /// |S_n − limit| on the symlog scale
+    Modulus,
+    /// arg(S_n − limit) in radians
+    Argument,
+}
+
+impl ComplexView {
+    pub const ALL: [Self; 3] = [Self::Components, Self::Modulus, Self::Argument];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Components => "Re и Im",
+            Self::Modulus => "Модуль отклонения",
+            Self::Argument => "Аргумент отклонения",
+        }
+    }
+
+    // Annotations are kept in plot coordinates, which differ between the views
+    fn plot_id(self) -> &'static str {
+        match self {
+            Self::Components => "convergence",
+            Self::Modulus => "convergence_modulus",
+            Self::Argument => "convergence_argument",
+        }
+    }
+}
+
+/// What the mouse wheel does over a plot. Ctrl+wheel zooms it and dragging pans it either way
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PlotWheel {
+    /// Scrolls the page past the plot
+    #[default]
+    ScrollPage,
+    /// Pans the plot; the page only scrolls outside of plots
+    PanPlot,
+}
+
+impl PlotWheel {
+    pub const ALL: [Self; 2] = [Self::ScrollPage, Self::PanPlot];
+    const STORAGE_KEY: &str = "plot_wheel";
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ScrollPage => "прокрутка страницы",
+            Self::PanPlot => "сдвиг графика",
+        }
+    }
+
+    fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        storage
+            .and_then(|s| s.get_string(Self::STORAGE_KEY))
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(self, storage: &mut dyn eframe::Storage) {
+        match serde_json::to_string(&self) {
+            Ok(text) => storage.set_string(Self::STORAGE_KEY, text),
+            Err(e) => eprintln!("Failed to save the plot wheel mode: {}", e),
+        }
+    }
+}
 
-    // Screenshot functionality
-    pending_screenshots: HashMap<&'static str, egui::Rect>,
+/// Keyboard control and middle-button panning of a plot. Call at the start of the `Plot::show`
+/// closure
+pub fn plot_controls(plot_ui: &mut PlotUi) {
+    plot_keys(plot_ui);
+    // egui_plot pans only with the primary button
+    if plot_ui.response().dragged_by(egui::PointerButton::Middle) {
+        plot_ui.translate_bounds(-plot_ui.pointer_coordinate_drag_delta());
+    }
+}
+
+pub struct Vis {
+    // Plot options
+    pub opts: PlotOptions,
+
+    // Visible area of each plot ([min_x, min_y, max_x, max_y]) and overrides from bookmarks
+    plot_bounds: HashMap<String, [f64; 4]>,
+    pending_bounds: HashMap<String, [f64; 4]>,
+
+    // Lines kept across filter changes; requests are resolved against the loaded data
+    pinned: Vec<PinnedLine>,
+    pin_requests: Vec<LineKey>,
+
+    // Records selected in the table or on a plot, highlighted everywhere
+    selected: Vec<LineKey>,
+
+    // Lines switched off in the grouped legend, by name
+    hidden_lines: HashSet<String>,
+    // Imaginary parts that are zero everywhere but shown anyway, by name
+    shown_zero_lines: HashSet<String>,
+    // Lines switched off by series, kind and method
+    pub line_visibility: LineVisibility,
+
+    // Text the table rows are narrowed by, without querying again
+    table_search: String,
+
+    // Bookmarks, annotations and record notes
+    workspace: Workspace,
+    annotation_draft: Option<Annotation>,
+    // A record note was edited since the workspace was saved; written once its editor closes
+    unsaved_note: bool,
+
+    // Screenshot functionality: plot id -> (area, filter summary for the provenance)
+    pending_screenshots: HashMap<String, (egui::Rect, String)>,
+    // Resolution multiplier; above 1 the panel is re-rendered alone, see `Export`
+    screenshot_scale: u8,
+    // Provenance caption under the image; also goes through `Export`
+    screenshot_caption: bool,
+    data_dir: String,
+    export_request: Option<Export>,
+    // Panel id and A/B variant of the plot to write as a Vega-Lite specification
+    spec_request: Option<(&'static str, &'static str)>,
+    // Panel id and A/B variant of the plot to list the points of
+    data_request: Option<(&'static str, &'static str)>,
+    // Table row whose raw records to serialize, and where to keep the JSON, see
+    // `resolve_record_json`
+    json_request: Option<(egui::Id, usize)>,
+    // Convergence rates over the range Shift+dragged on the error plot
+    pub slope_fit: Option<SlopeWindow>,
 
     // Plot hover state for scroll control
     plot_hovered: bool,
+    pub plot_wheel: PlotWheel,
 }
 
 impl Vis {
-    fn request_screenshot(&mut self, ctx: &Context, plot_id: &'static str, plot_rect: egui::Rect) {
-        self.pending_screenshots.insert(plot_id, plot_rect);
+    pub fn new(workspace: Workspace, data_dir: String) -> Self {
+        Self {
+            opts: PlotOptions::default(),
+            plot_bounds: HashMap::new(),
+            pending_bounds: HashMap::new(),
+            pinned: Vec::new(),
+            pin_requests: Vec::new(),
+            selected: Vec::new(),
+            hidden_lines: HashSet::new(),
+            shown_zero_lines: HashSet::new(),
+            line_visibility: LineVisibility::default(),
+            table_search: String::new(),
+            workspace,
+            annotation_draft: None,
+            unsaved_note: false,
+            pending_screenshots: HashMap::new(),
+            screenshot_scale: 1,
+            screenshot_caption: false,
+            data_dir,
+            export_request: None,
+            spec_request: None,
+            data_request: None,
+            json_request: None,
+            slope_fit: None,
+            plot_hovered: false,
+            plot_wheel: PlotWheel::default(),
+        }
+    }
+
+    pub fn is_selected(&self, key: &LineKey) -> bool {
+        self.selected.contains(key)
+    }
+
+    fn line_name<'a>(&self, entry: &'a LegendEntry) -> &'a str {
+        if self.opts.short_names {
+            &entry.short
+        } else {
+            &entry.name
+        }
+    }
+
+    fn accel_color(&self, entry: &LegendEntry) -> Color32 {
+        entry
+            .dimension(self.opts.color_by)
+            .map_or(Color32::GRAY, value_color)
+    }
+
+    fn is_hidden(&self, entry: &LegendEntry) -> bool {
+        self.hidden_lines.contains(&entry.name)
+            || self.line_visibility.hides(entry)
+            || (entry.is_zero() && !self.shown_zero_lines.contains(&entry.name))
+    }
+
+    // The grouped legend shows colors before the plot is drawn, so they are assigned up front
+    fn line_color(&self, fixed: Option<Color32>, auto: &mut usize) -> Option<Color32> {
+        if fixed.is_some() || self.opts.legend_mode == LegendMode::Flat {
+            return fixed;
+        }
+        *auto += 1;
+        Some(auto_color(*auto - 1))
+    }
+
+    // Iteration axis of the convergence and error plots
+    pub fn iteration_axis<'a>(&self, plot: Plot<'a>) -> Plot<'a> {
+        if self.opts.log_x {
+            plot.x_grid_spacer(log10_grid_spacer)
+                .x_axis_formatter(|mark, _| log10_formatter(mark.value))
+        } else {
+            plot
+        }
+    }
+
+    // Points of the convergence and error plots moved to the iteration axis
+    pub fn iteration_points<'a>(&self, points: &'a [PlotPoint]) -> Cow<'a, [PlotPoint]> {
+        if !self.opts.log_x {
+            return Cow::Borrowed(points);
+        }
+        points
+            .iter()
+            .filter_map(|p| Some(PlotPoint::new(log_iteration(p.x)?, p.y)))
+            .collect()
+    }
+
+    /// Sets the axis labels, replaced by the custom ones of the plot from the workspace if any
+    pub fn axis_labels<'a>(&self, plot_id: &str, mut plot: Plot<'a>, x: &str, y: &str) -> Plot<'a> {
+        let labels = self.plot_labels(plot_id);
+        let (x, y) = (labels.x_axis_or(x), labels.y_axis_or(y));
+        if !x.is_empty() {
+            plot = plot.x_axis_label(x.to_string());
+        }
+        if !y.is_empty() {
+            plot = plot.y_axis_label(y.to_string());
+        }
+        plot
+    }
+
+    fn plot_labels(&self, plot_id: &str) -> PlotLabels {
+        let labels = self.workspace.plot_labels.get(plot_id);
+        labels.cloned().unwrap_or_default()
+    }
+
+    pub fn set_plot_labels(&mut self, plot_id: &str, labels: PlotLabels) {
+        self.workspace
+            .plot_labels
+            .insert(plot_id.to_string(), labels);
+    }
+
+    /// Whether the wheel over the plot pans it, see [`PlotWheel`]
+    pub fn plot_wheel<'a>(&self, plot: Plot<'a>) -> Plot<'a> {
+        plot.allow_scroll(self.plot_wheel == PlotWheel::PanPlot)
+    }
+
+    fn plot_legend<'a>(&self, plot: Plot<'a>) -> Plot<'a> {
+        match self.opts.legend_mode {
+            LegendMode::Flat => plot.legend(egui_plot::Legend::default()),
+            _ => plot,
+        }
+    }
+
+    // Grouped legend to the left of the plot; the flat one is drawn by egui_plot
+    fn with_legend<R>(
+        &mut self,
+        ui: &mut Ui,
+        plot_id: &str,
+        entries: &[(&LegendEntry, Color32)],
+        add_plot: impl FnOnce(&mut Self, &mut Ui) -> R,
+    ) -> R {
+        let mode = self.opts.legend_mode;
+        if mode == LegendMode::Flat {
+            return add_plot(self, ui);
+        }
+        ui.horizontal_top(|ui| {
+            egui::ScrollArea::vertical()
+                .id_salt((plot_id, "legend"))
+                .max_height(900.0)
+                .show(ui, |ui| {
+                    ui.set_width(280.0);
+                    grouped_legend(
+                        ui,
+                        plot_id,
+                        mode,
+                        entries,
+                        &mut self.hidden_lines,
+                        &mut self.shown_zero_lines,
+                    );
+                });
+            add_plot(self, ui)
+        })
+        .inner
+    }
+
+    /// Click on a row or a line: selects only it, with ctrl held toggles it instead
+    pub fn click_select(&mut self, key: &LineKey, toggle: bool) {
+        if toggle {
+            match self.selected.iter().position(|k| k == key) {
+                Some(i) => {
+                    self.selected.remove(i);
+                }
+                None => self.selected.push(key.clone()),
+            }
+        } else if self.selected.len() == 1 && self.selected[0] == *key {
+            self.selected.clear();
+        } else {
+            self.selected = vec![key.clone()];
+        }
+    }
+
+    /// Selects the line clicked on a plot, `key_of` maps plot item ids to records
+    pub fn select_from_plot<'k>(
+        &mut self,
+        plot: &PlotResponse<()>,
+        key_of: impl Fn(egui::Id) -> Option<&'k LineKey>,
+    ) {
+        if plot.response.clicked()
+            && let Some(key) = plot.hovered_plot_item.and_then(key_of)
+        {
+            let toggle = plot.response.ctx.input(|i| i.modifiers.command);
+            self.click_select(key, toggle);
+        }
+    }
+
+    /// Plots should report their response here so page scrolling doesn't fight plot panning.
+    pub fn track_hover(&mut self, response: &egui::Response) {
+        self.plot_hovered |= response.hovered();
+    }
+
+    /// Zooms the plot to `[min_x, min_y, max_x, max_y]` the next time it's drawn
+    pub fn restore_bounds(&mut self, plot_id: &str, bounds: [f64; 4]) {
+        self.pending_bounds.insert(plot_id.to_string(), bounds);
+    }
+
+    pub fn take_bounds(&mut self, plot_id: &str) -> Option<PlotBounds> {
+        let [min_x, min_y, max_x, max_y] = self.pending_bounds.remove(plot_id)?;
+        Some(PlotBounds::from_min_max([min_x, min_y], [max_x, max_y]))
+    }
+
+    pub fn remember_bounds(&mut self, plot_id: &str, bounds: &PlotBounds) {
+        let ([min_x, min_y], [max_x, max_y]) = (bounds.min(), bounds.max());
+        let bounds = [min_x, min_y, max_x, max_y];
+        match self.plot_bounds.get_mut(plot_id) {
+            Some(b) => *b = bounds,
+            None => {
+                self.plot_bounds.insert(plot_id.to_string(), bounds);
+            }
+        }
+    }
+
+    fn table_columns(&self) -> Vec<(TableColumn, bool)> {
+        TableColumn::normalize(self.workspace.table_columns.clone())
+    }
+
+    fn save_workspace(&self) {
+        if let Err(e) = self.workspace.save() {
+            eprintln!("Failed to save workspace: {}", e);
+        }
+    }
+
+    fn save_note(&mut self) {
+        if mem::take(&mut self.unsaved_note) {
+            self.save_workspace();
+        }
+    }
+
+    /// Annotations are stored in data coordinates; `symlog` and `log_x` tell the plot's scales
+    pub fn draw_annotations(&self, plot_id: &str, plot_ui: &mut PlotUi, symlog: bool, log_x: bool) {
+        for a in self
+            .workspace
+            .annotations
+            .iter()
+            .filter(|a| a.plot == plot_id)
+        {
+            let y = if symlog {
+                Scientific(a.y, 0).symlog()
+            } else {
+                a.y
+            };
+            let x = if log_x { log_iteration(a.x) } else { Some(a.x) };
+            let Some(x) = x else {
+                continue;
+            };
+            plot_ui.text(
+                Text::new(PlotPoint::new(x, y), format!("◆ {}", a.text))
+                    .anchor(egui::Align2::LEFT_BOTTOM),
+            );
+        }
+    }
+
+    // Right-click on a plot opens a menu for adding and removing annotations
+    pub fn annotation_menu(
+        &mut self,
+        plot_id: &str,
+        plot: &PlotResponse<()>,
+        symlog: bool,
+        log_x: bool,
+    ) {
+        if plot.response.secondary_clicked()
+            && let Some(pos) = plot.response.interact_pointer_pos()
+        {
+            let value = plot.transform.value_from_position(pos);
+            self.annotation_draft = Some(Annotation {
+                plot: plot_id.to_string(),
+                x: if log_x { 10f64.powf(value.x) } else { value.x },
+                y: if symlog {
+                    symlog_inverse(value.y)
+                } else {
+                    value.y
+                },
+                text: String::new(),
+            });
+        }
+        plot.response.context_menu(|ui| {
+            if let Some(draft) = &mut self.annotation_draft {
+                ui.label(format!("Заметка в n={:.1}", draft.x));
+                let edit = ui.text_edit_singleline(&mut draft.text);
+                let submit = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button("Добавить").clicked() || submit) && !draft.text.trim().is_empty()
+                {
+                    self.workspace.annotations.push(draft.clone());
+                    self.annotation_draft = None;
+                    self.save_workspace();
+                    ui.close_menu();
+                }
+            }
+            let mut delete = None;
+            for (i, a) in self.workspace.annotations.iter().enumerate() {
+                if a.plot == plot_id && ui.button(format!("✖ {}", a.text)).clicked() {
+                    delete = Some(i);
+                }
+            }
+            if let Some(i) = delete {
+                self.workspace.annotations.remove(i);
+                self.save_workspace();
+            }
+        });
+    }
+
+    fn is_pinned(&self, key: &LineKey) -> bool {
+        self.pinned.iter().any(|p| &p.key == key)
+    }
+
+    fn toggle_pin(&mut self, key: &LineKey) {
+        if self.is_pinned(key) {
+            self.pinned.retain(|p| &p.key != key);
+        } else {
+            self.pin_requests.push(key.clone());
+        }
+    }
+
+    // Serializes the records of the requested table row into the temp storage of the "{ }" menu,
+    // once per opening. `records` are those of the table the request came from
+    fn resolve_record_json(
+        &mut self,
+        ctx: &Context,
+        data: &[SeriesData],
+        records: &[(usize, usize)],
+    ) {
+        let Some((id, row)) = self.json_request.take() else {
+            return;
+        };
+        let Some(&(i, j)) = records.get(row) else {
+            return;
+        };
+        let (series, accel_records) = &data[i];
+        let raw = serde_json::to_string_pretty(&serde_json::json!({
+            "series": series,
+            "accel": &accel_records[j],
+        }))
+        .unwrap_or_else(|e| format!("Ошибка сериализации: {}", e));
+        ctx.data_mut(|d| d.insert_temp(id, Arc::<str>::from(raw)));
+    }
+
+    fn resolve_pins(&mut self, data: &[SeriesData]) {
+        for key in mem::take(&mut self.pin_requests) {
+            let record = data
+                .iter()
+                .filter(|(series, _)| series.series_id == key.series_id)
+                .find_map(|(series, accel_records)| {
+                    let accel = accel_records
+                        .iter()
+                        .find(|a| a.accel_info == key.accel_info)?;
+                    Some((series, accel))
+                });
+            if let Some((series, accel)) = record {
+                self.pinned.push(PinnedLine::new(series, accel));
+            }
+        }
+    }
+
+    fn request_screenshot(
+        &mut self,
+        ctx: &Context,
+        plot_id: String,
+        plot_rect: egui::Rect,
+        filters: String,
+    ) {
+        self.pending_screenshots
+            .insert(plot_id, (plot_rect, filters));
         // Try without parameters first
         ctx.send_viewport_cmd(ViewportCommand::Screenshot(Default::default()));
     }
 
     fn handle_screenshot_events(&mut self, ctx: &Context) -> Result<()> {
-        let mut screenshots_to_save = Vec::new();
-
-        // Find screenshot events
-        for event in &ctx.input(|i| i.events.clone()) {
-            if let egui::Event::Screenshot { image, .. } = event {
-                // Extract pending screenshots
-                for (plot_id, rect) in self.pending_screenshots.drain() {
-                    screenshots_to_save.push((plot_id, rect, image.clone()));
-                }
-            }
+        // Runs every frame, so don't touch the event queue unless a screenshot was requested
+        if self.pending_screenshots.is_empty() {
+            return Ok(());
         }
+        let Some(image) = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        }) else {
+            return Ok(());
+        };
 
-        // Save screenshots
-        for (plot_id, rect, image_data) in screenshots_to_save {
-            self.save_cropped_image(ctx, &plot_id, rect, &image_data)?;
+        for (plot_id, (rect, filters)) in mem::take(&mut self.pending_screenshots) {
+            self.save_cropped_image(ctx, &plot_id, rect, &filters, &image)?;
         }
 
         Ok(())
@@ -129,6 +963,7 @@ impl Vis {
         ctx: &Context,
         plot_id: &str,
         rect: egui::Rect,
+        filters: &str,
         image_data: &std::sync::Arc<egui::ColorImage>,
     ) -> Result<()> {
         let rect = egui::Rect {
@@ -169,68 +1004,271 @@ impl Vis {
         let cropped_img = dynamic_img.crop_imm(x, y, w, h);
 
         // Generate filename with timestamp
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs();
+        let now = std::time::SystemTime::now();
+        let timestamp = now.duration_since(std::time::UNIX_EPOCH)?.as_secs();
         let filename = format!("{}_{}.png", plot_id, timestamp);
 
-        // Save cropped image
-        cropped_img.save(&filename)?;
+        // Save cropped image, with the provenance as PNG text chunks
+        let rgb = cropped_img.to_rgb8();
+        let file = std::io::BufWriter::new(std::fs::File::create(&filename)?);
+        let mut encoder = png::Encoder::new(file, rgb.width(), rgb.height());
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        for (keyword, text) in self.provenance(filters, now) {
+            encoder.add_itxt_chunk(keyword.to_string(), text)?;
+        }
+        encoder.write_header()?.write_image_data(rgb.as_raw())?;
         println!("Screenshot saved: {}", filename);
 
         Ok(())
     }
-}
-
-fn format_series_name_with_args(series: &SeriesRecord) -> String {
-    let mut name = series.precision.clone() + " " + &series.name;
 
-    // Add series parameters
-    if !series.arguments.is_empty() {
-        let params: Vec<String> = series
-            .arguments
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect();
-        name.push_str(&format!(" ({})", params.join(", ")));
+    // Where an exported image came from: PNG keyword -> text
+    fn provenance(
+        &self,
+        filters: &str,
+        time: std::time::SystemTime,
+    ) -> [(&'static str, String); 4] {
+        [
+            ("Source", self.data_dir.clone()),
+            ("Description", filters.to_string()),
+            ("Software", format!("vizr {}", env!("CARGO_PKG_VERSION"))),
+            ("Creation Time", format_utc(time)),
+        ]
     }
 
-    name
+    fn caption(&self, filters: &str) -> String {
+        self.provenance(filters, std::time::SystemTime::now())
+            .map(|(_, text)| text)
+            .join("  ·  ")
+    }
 }
 
-fn format_item_name(series: &SeriesRecord, accel: &AccelInfo) -> String {
-    let mut name = format!("{} {} (m={}) ", series.precision, accel.name, accel.m_value);
+// Date and time, UTC
+fn format_utc(time: std::time::SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
 
-    // Add accel parameters
-    if !accel.additional_args.is_empty() {
-        let params: Vec<String> = accel
-            .additional_args
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect();
-        name.push_str(&format!("({}) ", params.join(", ")));
-    }
+// Filters behind a panel, as recorded in screenshots
+fn provenance_filters([query, quick]: [&Filters; 2]) -> String {
+    format!(
+        "фильтры: {}; быстрые фильтры: {}",
+        query.summary(),
+        quick.summary()
+    )
+}
 
-    name.push_str(&series.name);
+// Markdown cells can't contain the column separator
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
 
-    // Add series parameters
-    if !series.arguments.is_empty() {
-        let params: Vec<String> = series
-            .arguments
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect();
-        name.push_str(&format!(" ({})", params.join(", ")));
+/// Filters, counts and the best error and iterations to the tolerance of each method, as Markdown
+/// to paste into notes and issues
+fn markdown_summary(filters: [&Filters; 2], data: &[SeriesDataRef], tolerance_exp: i32) -> String {
+    let [query, quick] = filters;
+    let records: usize = data
+        .iter()
+        .map(|(_, accel_records)| accel_records.len())
+        .sum();
+    // Method -> (records, best error, fewest iterations to the tolerance)
+    let mut methods: BTreeMap<String, (usize, Option<Scientific>, Option<i32>)> = BTreeMap::new();
+    for (series, accel_records) in data {
+        for accel_record in accel_records {
+            let stats = RowStats::of(series, accel_record);
+            let method = methods
+                .entry(format_method_name(&accel_record.accel_info))
+                .or_default();
+            method.0 += 1;
+            if let Some(min) = stats.min
+                && method
+                    .1
+                    .is_none_or(|best| magnitude(&min) < magnitude(&best))
+            {
+                method.1 = Some(min);
+            }
+            if let Some(n) = stats.iterations_to(tolerance_exp) {
+                method.2 = Some(method.2.map_or(n, |best| best.min(n)));
+            }
+        }
     }
+    let mut methods: Vec<_> = methods.into_iter().collect();
+    // Best first, methods without points last
+    methods.sort_by(|(_, a), (_, b)| {
+        let key = |m: &Option<Scientific>| m.as_ref().map_or(f64::INFINITY, magnitude);
+        key(&a.1).total_cmp(&key(&b.1))
+    });
 
-    name
-}
-
-// Real & Imaginary & ZeroImaginary / Accel & Partial Sum & Limit
-#[derive(Clone, Copy)]
-enum LineReal {
+    let mut res = format!(
+        "**Фильтры:** {}  \n**Быстрые фильтры:** {}  \n**Рядов:** {}, **записей ускорений:** {}\n",
+        markdown_cell(&query.summary()),
+        markdown_cell(&quick.summary()),
+        data.len(),
+        records
+    );
+    if !methods.is_empty() {
+        res.push_str(&format!(
+            "\n| Метод | Записей | Лучшая ошибка | n до 1e{tolerance_exp} |\n|---|---:|---:|---:|\n"
+        ));
+        for (name, (count, best, iterations)) in methods {
+            res.push_str(&format!(
+                "| {} | {count} | {} | {} |\n",
+                markdown_cell(&name),
+                format_stat(best),
+                iterations.map_or("—".to_string(), |n| n.to_string())
+            ));
+        }
+    }
+    res
+}
+
+/// Which parameters the names of series and methods list, e.g. in the legends
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum NameParams {
+    #[default]
+    All,
+    /// Those with a filter on them, top-level or quick
+    Filtered,
+    /// Those with more than one value among the shown records, absence included
+    Differing,
+}
+
+impl NameParams {
+    const ALL: [Self; 3] = [Self::All, Self::Filtered, Self::Differing];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::All => "все",
+            Self::Filtered => "с фильтром",
+            Self::Differing => "различающиеся",
+        }
+    }
+
+    // Parameters to list for the records shown with the filters; `None` for all of them
+    fn shown(
+        self,
+        filtered: &[SeriesDataRef],
+        query_params: &HashSet<IStr>,
+        quick: &Filters,
+    ) -> Option<HashSet<IStr>> {
+        match self {
+            Self::All => None,
+            Self::Filtered => Some(
+                query_params
+                    .iter()
+                    .chain(quick.series_params.keys())
+                    .chain(quick.accel_params.keys())
+                    .cloned()
+                    .collect(),
+            ),
+            Self::Differing => {
+                let mut res = differing_params(filtered.iter().map(|(s, _)| &s.arguments));
+                res.extend(differing_params(filtered.iter().flat_map(|(_, a)| {
+                    a.iter().map(|a| &a.accel_info.additional_args)
+                })));
+                Some(res)
+            }
+        }
+    }
+}
+
+// Parameters with different values in the maps, a missing one counting as a value
+fn differing_params<'a>(maps: impl Iterator<Item = &'a HashMap<IStr, IStr>>) -> HashSet<IStr> {
+    let mut values: HashMap<&IStr, (HashSet<&IStr>, usize)> = HashMap::new();
+    let mut total = 0;
+    for map in maps {
+        total += 1;
+        for (k, v) in map {
+            let (set, count) = values.entry(k).or_default();
+            set.insert(v);
+            *count += 1;
+        }
+    }
+    values
+        .into_iter()
+        .filter(|(_, (set, count))| set.len() > 1 || *count < total)
+        .map(|(k, _)| k.clone())
+        .collect()
+}
+
+static NAME_PARAMS: RwLock<NameParams> = RwLock::new(NameParams::All);
+// Parameters listed under the current `NameParams`, `None` for all. The names are formatted when
+// the panels are built, so this is set just before
+static SHOWN_PARAMS: RwLock<Option<HashSet<IStr>>> = RwLock::new(None);
+
+/// Changes the parameters listed in names. Names already built are not updated.
+pub fn set_name_params(mode: NameParams) {
+    *NAME_PARAMS.write().unwrap() = mode;
+}
+
+// Parameters as " (k=v, ...)", or nothing if there are none
+fn format_params<'a>(args: impl Iterator<Item = (&'a IStr, &'a IStr)>) -> String {
+    let shown = SHOWN_PARAMS.read().unwrap();
+    let params: Vec<String> = args
+        .filter(|(k, _)| shown.as_ref().is_none_or(|shown| shown.contains(*k)))
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    if params.is_empty() {
+        return String::new();
+    }
+    format!(" ({})", params.join(", "))
+}
+
+// Name and arguments; the precision is left out
+pub fn format_series_name(series: &SeriesRecord) -> String {
+    format!("{}{}", series.name, format_params(series.arguments.iter()))
+}
+
+pub fn format_series_name_with_args(series: &SeriesRecord) -> String {
+    format!("{} {}", series.precision, format_series_name(series))
+}
+
+// Method with m and its parameters; the precision belongs to the series
+pub fn format_method_name(accel: &AccelInfo) -> String {
+    format!(
+        "{} (m={}){}",
+        accel.name,
+        accel.m_value,
+        format_params(accel.additional_args.iter())
+    )
+}
+
+pub fn format_item_name(series: &SeriesRecord, accel: &AccelInfo) -> String {
+    format!(
+        "{} {} {}",
+        series.precision,
+        format_method_name(accel),
+        format_series_name(series)
+    )
+}
+
+// Real & Imaginary & Modulus & Argument / Accel & Partial Sum & Limit
+#[derive(Clone, Copy)]
+enum LineReal {
     Real,
-    Imag { zero: bool },
+    // Lines that are zero everywhere are hidden by `Vis::is_hidden`, each on its own
+    Imag,
+    // Polar form of the deviation from the limit, see `ComplexView`
+    Modulus,
+    Argument,
 }
 #[derive(Clone, Copy)]
 enum LineKind {
@@ -238,23 +1276,28 @@ enum LineKind {
     PartialSum,
     Limit,
 }
-const TOTAL_VIS: usize = 9;
+const TOTAL_VIS: usize = 12;
 
 fn vtoind(real: LineReal, kind: LineKind) -> usize {
     use LineKind::*;
     use LineReal::*;
     match (real, kind) {
         (Real, Accel) => 0,
-        (Imag { zero: false }, Accel) => 1,
-        (Imag { zero: true }, Accel) => 2,
+        (Imag, Accel) => 1,
 
-        (Real, PartialSum) => 3,
-        (Imag { zero: false }, PartialSum) => 4,
-        (Imag { zero: true }, PartialSum) => 5,
+        (Real, PartialSum) => 2,
+        (Imag, PartialSum) => 3,
 
-        (Real, Limit) => 6,
-        (Imag { zero: false }, Limit) => 7,
-        (Imag { zero: true }, Limit) => 8,
+        (Real, Limit) => 4,
+        (Imag, Limit) => 5,
+
+        (Modulus, Accel) => 6,
+        (Modulus, PartialSum) => 7,
+        (Modulus, Limit) => 8,
+
+        (Argument, Accel) => 9,
+        (Argument, PartialSum) => 10,
+        (Argument, Limit) => 11,
     }
 }
 
@@ -263,37 +1306,60 @@ fn indtov(i: usize) -> Option<(LineReal, LineKind)> {
     use LineReal::*;
     Some(match i {
         0 => (Real, Accel),
-        1 => (Imag { zero: false }, Accel),
-        2 => (Imag { zero: true }, Accel),
+        1 => (Imag, Accel),
+
+        2 => (Real, PartialSum),
+        3 => (Imag, PartialSum),
+
+        4 => (Real, Limit),
+        5 => (Imag, Limit),
 
-        3 => (Real, PartialSum),
-        4 => (Imag { zero: false }, PartialSum),
-        5 => (Imag { zero: true }, PartialSum),
+        6 => (Modulus, Accel),
+        7 => (Modulus, PartialSum),
+        8 => (Modulus, Limit),
 
-        6 => (Real, Limit),
-        7 => (Imag { zero: false }, Limit),
-        8 => (Imag { zero: true }, Limit),
+        9 => (Argument, Accel),
+        10 => (Argument, PartialSum),
+        11 => (Argument, Limit),
         _ => return None,
     })
 }
 
-type CreateConvergencePlot = impl Fn(&mut Vis, &mut Ui);
+// Modulus (symlog) and argument lines of the values around `limit`
+fn polar_points(
+    values: impl Iterator<Item = (i32, ComplexNumber)>,
+    limit: &ComplexNumber,
+) -> (Vec<PlotPoint>, Vec<PlotPoint>) {
+    values
+        .map(|(n, value)| {
+            let (modulus, argument) = value.polar_around(limit);
+            let n = n as f64;
+            (
+                PlotPoint::new(n, modulus.symlog()),
+                PlotPoint::new(n, argument),
+            )
+        })
+        .unzip()
+}
+
+type CreateConvergencePlot = impl Fn(&mut Vis, &mut Ui) -> Option<egui::Rect>;
 
 #[define_opaque(CreateConvergencePlot)]
 fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
+    let common = CommonParts::of(data);
     use LineKind::*;
     use LineReal::*;
-    let mut lines: [Vec<(String, Vec<PlotPoint>)>; TOTAL_VIS] = [const { Vec::new() }; 9];
+    // Accel lines carry their record, for selection
+    type NamedLine = (LegendEntry, Vec<PlotPoint>, Option<LineKey>);
+    let mut lines: [Vec<NamedLine>; TOTAL_VIS] = [const { Vec::new() }; TOTAL_VIS];
 
     // Calculate X range for 1:1 aspect ratio with fixed Y bounds [-10, 10]
     let mut min_x = f64::INFINITY;
     let mut max_x = f64::NEG_INFINITY;
     for (series, _) in data {
-        if !series.computed.is_empty() {
-            for point in &series.computed {
-                min_x = min_x.min(point.n as f64);
-                max_x = max_x.max(point.n as f64);
-            }
+        for &n in series.computed.n() {
+            min_x = min_x.min(n as f64);
+            max_x = max_x.max(n as f64);
         }
     }
 
@@ -310,8 +1376,9 @@ fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
             .collect();
 
         lines[vtoind(Real, PartialSum)].push((
-            format!("{} (частичные суммы)", format_series_name_with_args(series)),
+            LegendEntry::series(series, "частичные суммы", &common),
             partial_points,
+            None,
         ));
 
         // Imaginary partial sums
@@ -322,15 +1389,26 @@ fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
             .map(|c| PlotPoint::new(c.n as f64, c.value.imag.approx_f64()))
             .collect();
 
-        lines[vtoind(Imag { zero }, PartialSum)].push((
-            format!(
-                "{} (частичные суммы, мнимая часть)",
-                format_series_name_with_args(series)
-            ),
+        lines[vtoind(Imag, PartialSum)].push((
+            LegendEntry::series(series, "частичные суммы, мнимая часть", &common).zero(zero),
             imag_partial_points,
+            None,
         ));
 
         let limit = &series.series_limit;
+        let (modulus, argument) =
+            polar_points(series.computed.iter().map(|c| (c.n, c.value)), limit);
+        lines[vtoind(Modulus, PartialSum)].push((
+            LegendEntry::series(series, "частичные суммы, модуль", &common),
+            modulus,
+            None,
+        ));
+        lines[vtoind(Argument, PartialSum)].push((
+            LegendEntry::series(series, "частичные суммы, аргумент", &common),
+            argument,
+            None,
+        ));
+
         let x_range: Vec<f64> = series.computed.iter().map(|c| c.n as f64).collect();
         if !x_range.is_empty() {
             let min_x = x_range.iter().fold(f64::INFINITY, |a, &b| a.min(b));
@@ -340,24 +1418,18 @@ fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
             let real_y = limit.real.approx_f64();
             let limit_points = vec![PlotPoint::new(min_x, real_y), PlotPoint::new(max_x, real_y)];
             lines[vtoind(Real, Limit)].push((
-                format!("{} (предел)", format_series_name_with_args(series)),
+                LegendEntry::limit(series, "предел", &common),
                 limit_points,
+                None,
             ));
 
             let imag_y = limit.imag.approx_f64();
             let imag_points = vec![PlotPoint::new(min_x, imag_y), PlotPoint::new(max_x, imag_y)];
-            lines[vtoind(
-                Imag {
-                    zero: limit.imag.0 == 0.0,
-                },
-                Limit,
-            )]
-            .push((
-                format!(
-                    "{} (предел, мнимая часть)",
-                    format_series_name_with_args(series)
-                ),
+            lines[vtoind(Imag, Limit)].push((
+                LegendEntry::limit(series, "предел, мнимая часть", &common)
+                    .zero(limit.imag.0 == 0.0),
                 imag_points,
+                None,
             ));
         }
 
@@ -367,7 +1439,9 @@ fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
                 continue;
             }
 
-            let item_name = format_item_name(series, &accel_record.accel_info);
+            let accel_info = &accel_record.accel_info;
+            let key = LineKey::new(series, accel_info);
+            let divergent = !anomalies(series, accel_record).is_empty();
 
             // Main convergence line - zip series computed with accel computed
             let points = series
@@ -379,7 +1453,11 @@ fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
                 })
                 .collect();
 
-            lines[vtoind(Real, Accel)].push((item_name.clone(), points));
+            lines[vtoind(Real, Accel)].push((
+                LegendEntry::accel(series, accel_info, None, &common).flagged(divergent),
+                points,
+                Some(key.clone()),
+            ));
 
             let zero = accel_record
                 .computed
@@ -394,27 +1472,90 @@ fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
                 })
                 .collect();
 
-            lines[vtoind(Imag { zero }, Accel)]
-                .push((format!("{} (мнимая часть)", item_name), imag_points));
+            lines[vtoind(Imag, Accel)].push((
+                LegendEntry::accel(series, accel_info, Some("мнимая часть"), &common)
+                    .flagged(divergent)
+                    .zero(zero),
+                imag_points,
+                Some(key.clone()),
+            ));
+
+            let (modulus, argument) = polar_points(
+                series
+                    .computed
+                    .iter()
+                    .zip(accel_record.computed.iter())
+                    .filter_map(|(c, accel)| Some((c.n, accel?.value))),
+                limit,
+            );
+            lines[vtoind(Modulus, Accel)].push((
+                LegendEntry::accel(series, accel_info, Some("модуль"), &common).flagged(divergent),
+                modulus,
+                Some(key.clone()),
+            ));
+            lines[vtoind(Argument, Accel)].push((
+                LegendEntry::accel(series, accel_info, Some("аргумент"), &common)
+                    .flagged(divergent),
+                argument,
+                Some(key),
+            ));
         }
     }
 
     move |viz, ui| {
         if lines.is_empty() {
             ui.label("Нет данных для отображения");
-            return;
+            return None;
         }
 
-        let mut plot = Plot::new("convergence")
+        // Separate plot memory per x scale and view, since bounds don't carry over between them
+        let log_x = viz.opts.log_x;
+        let view = viz.opts.complex_view;
+        let view_id = view.plot_id();
+        let plot_id = if log_x {
+            format!("{view_id}_log_x")
+        } else {
+            view_id.to_string()
+        };
+        let y_label = |y: f64| match view {
+            ComplexView::Modulus => symlog_formatter(y),
+            ComplexView::Components | ComplexView::Argument => format_f64(y),
+        };
+        let mut plot = Plot::new(&plot_id)
             .allow_zoom(true)
             .allow_drag(true)
             .height(900.0)
-            .x_axis_label("Итерация n")
-            .y_axis_label("Значение")
-            .legend(egui_plot::Legend::default());
-
-        // Set fixed Y bounds [-10, 10] and calculate X bounds for 1:1 aspect ratio
-        if min_x != f64::INFINITY && max_x != f64::NEG_INFINITY {
+            .y_axis_formatter(move |mark, _| y_label(mark.value));
+        let y_axis = match view {
+            ComplexView::Components => "Значение",
+            ComplexView::Modulus => "|S − предел|",
+            ComplexView::Argument => "arg(S − предел), рад",
+        };
+        plot = viz.axis_labels("convergence", plot, "Итерация n", y_axis);
+        if view == ComplexView::Modulus {
+            plot = plot.y_grid_spacer(symlog_grid_spacer);
+        }
+        plot = viz.plot_legend(plot);
+        plot = viz.plot_wheel(plot);
+        plot = viz.iteration_axis(plot);
+
+        // The polar views are fitted to the data: moduli span many orders, arguments stay within ±π
+        let fixed_bounds = view == ComplexView::Components;
+        if fixed_bounds && log_x {
+            // Iterations before the first one aren't shown on the log axis
+            if let (Some(x_min), Some(x_max)) =
+                (log_iteration(min_x.max(1.0)), log_iteration(max_x))
+                && x_max.is_finite()
+            {
+                plot = plot
+                    .auto_bounds(egui::Vec2b::new(false, false))
+                    .include_x(x_min)
+                    .include_x(x_max)
+                    .include_y(-10.0)
+                    .include_y(10.0);
+            }
+        } else if fixed_bounds && min_x != f64::INFINITY && max_x != f64::NEG_INFINITY {
+            // Set fixed Y bounds [-10, 10] and calculate X bounds for 1:1 aspect ratio
             // Y range is fixed at 20 units (from -10 to 10)
             let y_range = 20.0;
             let data_x_range = max_x - min_x;
@@ -433,81 +1574,320 @@ fn create_convergence_plot(data: &[SeriesDataRef]) -> CreateConvergencePlot {
                 .include_y(10.0);
         }
 
-        let plot = plot.show(ui, |plot_ui| {
-            for (i, lines) in lines.iter().enumerate() {
-                let (real, kind) = indtov(i).unwrap();
-                let mut allowed = match real {
-                    Real => viz.show_real,
-                    Imag { zero } => viz.show_imaginary && (viz.force_show_imaginary || !zero),
+        // (category, line, color, stroke) of the lines allowed by the options
+        let mut shown = Vec::new();
+        for (i, lines) in lines.iter().enumerate() {
+            let (real, kind) = indtov(i).unwrap();
+            let mut allowed = match real {
+                Real => view == ComplexView::Components && viz.opts.show_real,
+                Imag => view == ComplexView::Components && viz.opts.show_imaginary,
+                Modulus => view == ComplexView::Modulus,
+                Argument => view == ComplexView::Argument,
+            };
+            allowed &= match kind {
+                Accel => true,
+                PartialSum => viz.opts.show_partial_sums,
+                Limit => viz.opts.show_limits,
+            };
+            if allowed {
+                let color = match (real, kind) {
+                    (Real | Modulus | Argument, PartialSum) => {
+                        Some(Color32::from_rgb(128, 128, 128))
+                    }
+                    (Imag, PartialSum) => Some(Color32::from_rgb(255, 192, 203)),
+                    (Real | Modulus | Argument, Limit) => Some(Color32::from_rgb(255, 0, 0)),
+                    (Imag, Limit) => Some(Color32::from_rgb(255, 100, 100)),
+                    (Real | Modulus | Argument, Accel) => None,
+                    (Imag, Accel) => Some(Color32::from_rgb(255, 165, 0)),
                 };
-                allowed &= match kind {
-                    Accel => true,
-                    PartialSum => viz.show_partial_sums,
-                    Limit => viz.show_limits,
+                let stroke = match (real, kind) {
+                    (Real, Limit) => Some(Stroke::new(3.0, Color32::from_rgb(255, 0, 0))),
+                    (Imag, Limit) => Some(Stroke::new(2.0, Color32::from_rgb(255, 100, 100))),
+                    _ => None,
                 };
-                if allowed {
-                    let color = match (real, kind) {
-                        (Real, PartialSum) => Some(Color32::from_rgb(128, 128, 128)),
-                        (Imag { zero: _ }, PartialSum) => Some(Color32::from_rgb(255, 192, 203)),
-                        (Real, Limit) => Some(Color32::from_rgb(255, 0, 0)),
-                        (Imag { zero: _ }, Limit) => Some(Color32::from_rgb(255, 100, 100)),
-                        (Real, Accel) => None,
-                        (Imag { zero: _ }, Accel) => Some(Color32::from_rgb(255, 165, 0)),
-                    };
-                    let stroke = match (real, kind) {
-                        (Real, Limit) => Some(Stroke::new(3.0, Color32::from_rgb(255, 0, 0))),
-                        (Imag { zero: _ }, Limit) => {
-                            Some(Stroke::new(2.0, Color32::from_rgb(255, 100, 100)))
-                        }
-                        _ => None,
-                    };
-                    for (name, points) in lines {
-                        let mut line = Line::new(points.as_slice()).name(name);
-                        if let Some(color) = color {
-                            line = line.color(color);
-                        }
-                        if let Some(stroke) = stroke {
-                            line = line.stroke(stroke);
-                        }
-                        plot_ui.line(line);
-                    }
+                for (j, (entry, _, _)) in lines.iter().enumerate() {
+                    let color = color.or_else(|| Some(viz.accel_color(entry)));
+                    shown.push((i, j, color, stroke));
                 }
             }
+        }
+        let entries: Vec<_> = shown
+            .iter()
+            .map(|&(i, j, color, _)| (&lines[i][j].0, color.unwrap_or_default()))
+            .collect();
+        let styles = StyleIndex::new(viz.opts.style_by, entries.iter().map(|(e, _)| *e));
+        let plot = plot.label_formatter(|name, value| {
+            let name = full_name(&entries, name);
+            let n = iteration_label(log_x, value.x);
+            format!("{name}\nx={n}\ny={}", y_label(value.y))
         });
-        viz.plot_hovered |= plot.response.hovered();
-        ui.horizontal(|ui| {
-            if ui.button("📸 Снимок экрана").clicked() {
-                viz.request_screenshot(ui.ctx(), "convergence", plot.response.rect);
-            }
+
+        let restore = viz.take_bounds(&plot_id);
+        let plot = viz.with_legend(ui, "convergence", &entries, |viz, ui| {
+            plot.show(ui, |plot_ui| {
+                plot_controls(plot_ui);
+                if let Some(bounds) = restore {
+                    plot_ui.set_plot_bounds(bounds);
+                }
+                for &(i, j, color, stroke) in &shown {
+                    let (entry, points, key) = &lines[i][j];
+                    if viz.is_hidden(entry) {
+                        continue;
+                    }
+                    let points = viz.iteration_points(points);
+                    let mut line = Line::new(&*points)
+                        .name(viz.line_name(entry))
+                        .id(line_id("convergence", (i, j)))
+                        .style(styles.line_style(entry));
+                    if let Some(color) = color {
+                        line = line.color(color);
+                    }
+                    if let Some(stroke) = stroke {
+                        line = line.stroke(stroke);
+                    }
+                    if key.as_ref().is_some_and(|k| viz.is_selected(k)) {
+                        line = line.highlight(true).width(3.0);
+                    }
+                    plot_ui.line(line);
+                }
+                if view == ComplexView::Components && viz.opts.show_real {
+                    for pin in &viz.pinned {
+                        plot_ui.line(pin.line(&viz.iteration_points(&pin.values)));
+                    }
+                }
+                let symlog = view == ComplexView::Modulus;
+                viz.draw_annotations(view_id, plot_ui, symlog, viz.opts.log_x);
+            })
+        });
+        viz.select_from_plot(&plot, |id| {
+            lines.iter().enumerate().find_map(|(i, lines)| {
+                lines
+                    .iter()
+                    .enumerate()
+                    .find(|(j, _)| line_id("convergence", (i, *j)) == id)
+                    .and_then(|(_, (_, _, key))| key.as_ref())
+            })
+        });
+        let symlog = view == ComplexView::Modulus;
+        viz.annotation_menu(view_id, &plot, symlog, viz.opts.log_x);
+        describe_plot(
+            &plot.response,
+            format!(
+                "График сходимости ({}), линий: {}",
+                view.label(),
+                shown.len()
+            ),
+        );
+        viz.track_hover(&plot.response);
+        viz.remember_bounds(&plot_id, plot.transform.bounds());
+        Some(plot.response.rect)
+    }
+}
+
+// (n, low, high) of the points with a known uncertainty, on the plot scale
+fn uncertainty_points(
+    series: &SeriesRecord,
+    accel_record: &AccelRecord,
+    symlog: bool,
+) -> Vec<(f64, f64, f64)> {
+    let y = |d: Scientific| if symlog { d.symlog() } else { d.approx_f64() };
+    series
+        .computed
+        .iter()
+        .zip(&accel_record.uncertainty)
+        .filter_map(|(c, range)| {
+            let [low, high] = (*range)?;
+            Some((c.n as f64, y(low), y(high)))
+        })
+        .collect()
+}
+
+// Significand bits (with the implicit one) of the known precision names
+pub fn significand_bits(precision: &str) -> Option<i32> {
+    Some(match precision {
+        "bf16" => 8,
+        "f16" => 11,
+        "f32" => 24,
+        "f64" => 53,
+        "f80" => 64,
+        "f128" => 113,
+        "f256" => 237,
+        _ => return None,
+    })
+}
+
+// Unit roundoff 2^-p of a precision, as a label and a value
+fn unit_roundoff(precision: &str) -> Option<(String, Scientific)> {
+    let p = significand_bits(precision)?;
+    let exponent = (-p as f64 * std::f64::consts::LOG10_2).floor() as i32;
+    let mantissa = 2f64.powi(-p) / 10f64.powi(exponent);
+    Some((
+        format!("ε {precision} = 2^-{p}"),
+        Scientific(mantissa, exponent),
+    ))
+}
+
+const FACET_HEIGHT: f32 = 300.0;
+const FACET_COLUMNS: usize = 3;
+
+// One small multiple of the faceted error plot
+struct Facet<'a> {
+    title: String,
+    value: &'a str,
+    // Series of its lines; partial sums have no method and go to the facets of their series
+    series: HashSet<&'a str>,
+}
+
+impl Facet<'_> {
+    fn contains(&self, dimension: Dimension, entry: &LegendEntry) -> bool {
+        match entry.dimension(dimension) {
+            Some(value) => value == self.value,
+            None => entry
+                .dimension(Dimension::Series)
+                .is_some_and(|s| self.series.contains(s)),
+        }
+    }
+}
+
+fn facets<'a>(
+    dimension: Dimension,
+    entries: impl Iterator<Item = &'a LegendEntry>,
+) -> Vec<Facet<'a>> {
+    let mut facets = BTreeMap::new();
+    for entry in entries {
+        let Some(value) = entry.dimension(dimension) else {
+            continue;
+        };
+        let facet = facets.entry(value).or_insert_with(|| Facet {
+            title: entry.dimension_label(dimension).unwrap_or_default(),
+            value,
+            series: HashSet::new(),
+        });
+        facet.series.extend(entry.dimension(Dimension::Series));
+    }
+    facets.into_values().collect()
+}
+
+const OVERVIEW_HEIGHT: f32 = 90.0;
+
+// Strip under the error plot with the whole range of the curves and the visible part of the plot
+// as a window: dragging the window or clicking beside it moves the plot along n
+fn overview_strip(
+    vis: &mut Vis,
+    ui: &mut Ui,
+    plot_id: &str,
+    viewport: &PlotBounds,
+    curves: &[(Cow<[PlotPoint]>, Color32)],
+) {
+    let (y_min, y_max) = curves
+        .iter()
+        .flat_map(|(points, _)| points.iter().map(|p| p.y))
+        .filter(|y| y.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), y| {
+            (min.min(y), max.max(y))
         });
+    if y_min > y_max {
+        return;
+    }
+    let ([x_min, view_y_min], [x_max, view_y_max]) = (viewport.min(), viewport.max());
+
+    let mut plot = Plot::new(format!("{plot_id}_overview"))
+        .height(OVERVIEW_HEIGHT)
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false)
+        .allow_double_click_reset(false)
+        .show_x(false)
+        .show_y(false)
+        .show_axes([true, false])
+        .show_grid(false);
+    plot = vis.iteration_axis(plot);
+    let mut shift = None;
+    let window_color = ui.visuals().selection.bg_fill;
+    let response = plot
+        .show(ui, |plot_ui| {
+            for (points, color) in curves {
+                plot_ui.line(Line::new(&**points).color(*color).width(1.0));
+            }
+            plot_ui.polygon(
+                Polygon::new(vec![
+                    [x_min, y_min],
+                    [x_max, y_min],
+                    [x_max, y_max],
+                    [x_min, y_max],
+                ])
+                .fill_color(window_color.gamma_multiply(0.3))
+                .stroke(Stroke::new(1.0, window_color))
+                .allow_hover(false),
+            );
+            let response = plot_ui.response();
+            if response.dragged_by(egui::PointerButton::Primary) {
+                shift = Some(plot_ui.pointer_coordinate_drag_delta().x as f64);
+            } else if response.clicked()
+                && let Some(pointer) = plot_ui.pointer_coordinate()
+            {
+                shift = Some(pointer.x - (x_min + x_max) / 2.0);
+            }
+        })
+        .response
+        .on_hover_cursor(egui::CursorIcon::Grab);
+    describe_plot(
+        &response,
+        "Обзор всего диапазона n с окном видимой части графика ошибок".to_string(),
+    );
+    if let Some(dx) = shift
+        && dx != 0.0
+    {
+        vis.restore_bounds(plot_id, [x_min + dx, view_y_min, x_max + dx, view_y_max]);
     }
 }
 
-type CreateErrorPlot = impl Fn(&mut Vis, &mut Ui);
+type CreateErrorPlot = impl Fn(&mut Vis, &mut Ui) -> Option<egui::Rect>;
 #[define_opaque(CreateErrorPlot)]
 fn create_error_plot(data: &[SeriesDataRef], symlog: bool) -> CreateErrorPlot {
+    let common = CommonParts::of(data);
     let mut partial_lines = Vec::new();
     let mut lines = Vec::new();
+    // Per line of `lines`: whether its run has anomalies, where it stalls, its smallest deviation
+    // and its uncertainty band as quads between neighbouring points
+    let mut divergent = Vec::new();
+    let mut plateaus: Vec<Option<(f64, Scientific, String)>> = Vec::new();
+    let mut bests: Vec<Option<(i32, Scientific)>> = Vec::new();
+    // Per line: neighbouring iterations with the accel deviation below the partial sum's at both,
+    // as (n, accel deviation, partial sum deviation)
+    let mut advantages: Vec<Vec<[(f64, Scientific, Scientific); 2]>> = Vec::new();
+    let mut tails: Vec<Option<TailFit>> = Vec::new();
+    let mut bands: Vec<Vec<[[f64; 2]; 4]>> = Vec::new();
+
+    let epsilons: Vec<(LegendEntry, f64)> = data
+        .iter()
+        .map(|(series, _)| series.precision.as_ref())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(unit_roundoff)
+        .map(|(name, eps)| {
+            (
+                LegendEntry::reference(name),
+                if symlog {
+                    eps.symlog()
+                } else {
+                    eps.approx_f64()
+                },
+            )
+        })
+        .collect();
 
     for (series, _) in data.iter() {
         // Add series deviation line
-        let series_deviation_points: Vec<PlotPoint> = series
+        let series_deviation_points: Vec<(f64, Scientific)> = series
             .computed
             .iter()
-            .map(|c| {
-                PlotPoint::new(
-                    c.n as f64,
-                    if symlog {
-                        c.deviation.symlog()
-                    } else {
-                        c.deviation.approx_f64()
-                    },
-                )
-            })
+            .map(|c| (c.n as f64, c.deviation))
             .collect();
 
         partial_lines.push((
-            format!("{} (частичные суммы)", format_series_name_with_args(series)),
+            LegendEntry::series(series, "частичные суммы", &common),
             series_deviation_points,
         ));
     }
@@ -522,160 +1902,1129 @@ fn create_error_plot(data: &[SeriesDataRef], symlog: bool) -> CreateErrorPlot {
                 continue;
             }
 
-            let item_name = format_item_name(series, &accel_record.accel_info);
+            let is_divergent = !anomalies(series, accel_record).is_empty();
+            let entry = LegendEntry::accel(series, &accel_record.accel_info, None, &common)
+                .flagged(is_divergent);
+            let key = LineKey::new(series, &accel_record.accel_info);
 
-            // Use Euclidean metric with machine epsilon for log scale, clamp to -1000
-            let points: Vec<PlotPoint> = series
+            // Mapped onto the axis when shown, see `PlotOptions::deviation_y`
+            let points: Vec<(f64, Scientific)> = series
                 .computed
                 .iter()
                 .zip(accel_record.computed.iter())
-                .filter_map(|(c, accel)| {
-                    let deviation = accel.as_ref()?.deviation;
-                    Some(PlotPoint::new(
-                        c.n as f64,
-                        if symlog {
-                            deviation.symlog()
-                        } else {
-                            deviation.approx_f64()
-                        },
-                    ))
+                .filter_map(|(c, accel)| Some((c.n as f64, accel.as_ref()?.deviation)))
+                .collect();
+
+            let stats = RowStats::of(series, accel_record);
+            let plateau = stats
+                .plateau()
+                .map(|(n, deviation)| (n as f64, deviation, format_plateau((n, deviation))));
+            let best = stats.iteration_of_min().zip(stats.min);
+            let compared: Vec<(f64, Scientific, Scientific)> = series
+                .computed
+                .iter()
+                .zip(accel_record.computed.iter())
+                .filter_map(|(c, accel)| Some((c.n as f64, accel.as_ref()?.deviation, c.deviation)))
+                .collect();
+            let advantage = compared
+                .windows(2)
+                .filter(|w| {
+                    w.iter()
+                        .all(|(_, accel, partial)| magnitude(accel) < magnitude(partial))
                 })
+                .map(|w| [w[0], w[1]])
                 .collect();
 
-            lines.push((item_name, points));
+            // Quads rather than one polygon: egui_plot only fills convex polygons
+            let ranges = uncertainty_points(series, accel_record, symlog);
+            let band = ranges
+                .windows(2)
+                .map(|w| {
+                    let [(x0, low0, high0), (x1, low1, high1)] = [w[0], w[1]];
+                    [[x0, low0], [x1, low1], [x1, high1], [x0, high0]]
+                })
+                .collect();
+
+            divergent.push(is_divergent);
+            plateaus.push(plateau);
+            bests.push(best);
+            advantages.push(advantage);
+            tails.push(TailFit::of(&points));
+            bands.push(band);
+            lines.push((entry, points, key));
         }
     }
 
     move |vis, ui| {
-        if lines.is_empty() && (!vis.show_partial_sums || partial_lines.is_empty()) {
+        if lines.is_empty() && (!vis.opts.show_partial_sums || partial_lines.is_empty()) {
             ui.label("Нет данных для отображения");
-            return;
+            return None;
         }
 
-        let mut plot = Plot::new("error")
+        let log_x = vis.opts.log_x;
+        let plot_id = if log_x { "error_log_x" } else { "error" };
+        let line_points: Vec<Vec<PlotPoint>> = lines
+            .iter()
+            .map(|(_, deviations, _)| vis.opts.deviation_points(deviations, symlog))
+            .collect();
+        let partial_points: Vec<Vec<PlotPoint>> = partial_lines
+            .iter()
+            .map(|(_, deviations)| vis.opts.deviation_points(deviations, symlog))
+            .collect();
+        let underflow_rows = vis.opts.underflow_rows(symlog);
+        if let Some(note) = vis.opts.underflow_note() {
+            ui.label(egui::RichText::new(note).weak());
+        }
+        // Shift+drag picks a range of n for `SlopeWindow` instead of panning
+        let mut plot = Plot::new(plot_id)
             .allow_zoom(true)
-            .allow_drag(true)
-            .height(900.0)
-            .x_axis_label("Итерация n")
-            .y_axis_label("Абсолютная ошибка")
-            .legend(egui_plot::Legend::default());
+            .allow_drag(!ui.input(|i| i.modifiers.shift))
+            .height(900.0);
+        plot = vis.axis_labels("error", plot, "Итерация n", "Абсолютная ошибка");
+        plot = vis.plot_legend(plot);
+        plot = vis.plot_wheel(plot);
+        plot = vis.iteration_axis(plot);
+        let format_y = if symlog { symlog_formatter } else { format_f64 };
+        plot = plot.y_axis_formatter(move |mark, _| format_y(mark.value));
         if symlog {
-            plot = plot
-                .y_axis_formatter(|mark, _| symlog_formatter(mark.value))
-                .label_formatter(|name, value| {
-                    format!("{name}\nx={}\ny={}", value.x, symlog_formatter(value.y))
-                });
+            plot = plot.y_grid_spacer(symlog_grid_spacer);
+        }
+
+        let partial_color = Color32::from_rgb(255, 0, 0);
+        let line_colors: Vec<_> = lines
+            .iter()
+            .map(|(entry, _, _)| vis.accel_color(entry))
+            .collect();
+        let mut auto = 0;
+        let epsilon_colors: Vec<_> = epsilons
+            .iter()
+            .map(|_| vis.line_color(None, &mut auto))
+            .collect();
+        let mut entries: Vec<_> = lines
+            .iter()
+            .zip(&line_colors)
+            .map(|((entry, _, _), color)| (entry, *color))
+            .collect();
+        if vis.opts.show_partial_sums {
+            entries.extend(
+                partial_lines
+                    .iter()
+                    .map(|(entry, _)| (entry, partial_color)),
+            );
+        }
+        if vis.opts.show_epsilon {
+            entries.extend(
+                epsilons
+                    .iter()
+                    .zip(&epsilon_colors)
+                    .map(|((entry, _), color)| (entry, color.unwrap_or_default())),
+            );
         }
-        let plot = plot.show(ui, |plot_ui| {
-            for (n, points) in &lines {
-                plot_ui.line(Line::new(points.as_slice()).name(n));
+        // Hover text of a line, with the point where it stalls
+        let hover_text = |name: &str, value: &PlotPoint| {
+            let name = full_name(&entries, name);
+            let plateau = lines
+                .iter()
+                .zip(&plateaus)
+                .find(|((entry, _, _), _)| entry.name == name)
+                .and_then(|(_, plateau)| plateau.as_ref())
+                .map_or(String::new(), |(_, _, text)| format!("\n{text}"));
+            let n = iteration_label(log_x, value.x);
+            format!("{name}\nx={n}\ny={}{plateau}", format_y(value.y))
+        };
+        let plot = plot.label_formatter(hover_text);
+        let styles = StyleIndex::new(vis.opts.style_by, entries.iter().map(|(e, _)| *e));
+
+        // Lines of the whole plot, or of one facet
+        let draw = |vis: &Vis, plot_ui: &mut PlotUi, facet: Option<(Dimension, &Facet)>| {
+            let in_facet = |entry| facet.is_none_or(|(d, f)| f.contains(d, entry));
+            let drawn = lines.iter().zip(&line_points).zip(&line_colors);
+            for (i, (((entry, _, key), points), color)) in drawn.enumerate() {
+                if vis.is_hidden(entry) || !in_facet(entry) {
+                    continue;
+                }
+                let points = vis.iteration_points(points);
+                let mut line = Line::new(&*points)
+                    .name(vis.line_name(entry))
+                    .id(line_id("error", i))
+                    .color(*color)
+                    .style(styles.line_style(entry));
+                if vis.is_selected(key) {
+                    line = line.highlight(true).width(3.0);
+                }
+                plot_ui.line(line);
+                // Warning marker at the end of a divergent run
+                if divergent[i]
+                    && let Some(last) = points.last()
+                {
+                    plot_ui.points(
+                        Points::new(slice::from_ref(last))
+                            .name(vis.line_name(entry))
+                            .color(*color)
+                            .shape(MarkerShape::Asterisk)
+                            .radius(8.0),
+                    );
+                }
+                for quad in &bands[i] {
+                    let quad: Option<Vec<[f64; 2]>> = quad
+                        .iter()
+                        .map(|&[x, y]| Some([if log_x { log_iteration(x)? } else { x }, y]))
+                        .collect();
+                    let Some(quad) = quad else {
+                        continue;
+                    };
+                    plot_ui.polygon(
+                        Polygon::new(quad)
+                            .name(vis.line_name(entry))
+                            .fill_color(color.gamma_multiply(0.2))
+                            .stroke(Stroke::NONE)
+                            .allow_hover(false),
+                    );
+                }
+                if vis.opts.show_plateaus
+                    && let Some((n, deviation, _)) = &plateaus[i]
+                    && let Some(y) = vis.opts.deviation_y(*deviation, symlog)
+                    && let Some(point) = vis.iteration_points(&[PlotPoint::new(*n, y)]).first()
+                {
+                    plot_ui.points(
+                        Points::new(slice::from_ref(point))
+                            .name(vis.line_name(entry))
+                            .color(*color)
+                            .shape(MarkerShape::Circle)
+                            .filled(false)
+                            .radius(5.0),
+                    );
+                }
+                if vis.opts.show_advantage {
+                    for segment in &advantages[i] {
+                        // Accel curve forwards, partial sums backwards: a convex quad, as the
+                        // accel curve is below at both ends
+                        let quad: Option<Vec<[f64; 2]>> = [
+                            (segment[0].0, segment[0].1),
+                            (segment[1].0, segment[1].1),
+                            (segment[1].0, segment[1].2),
+                            (segment[0].0, segment[0].2),
+                        ]
+                        .into_iter()
+                        .map(|(n, deviation)| {
+                            let x = if log_x { log_iteration(n)? } else { n };
+                            Some([x, vis.opts.deviation_y(deviation, symlog)?])
+                        })
+                        .collect();
+                        let Some(quad) = quad else {
+                            continue;
+                        };
+                        plot_ui.polygon(
+                            Polygon::new(quad)
+                                .name(vis.line_name(entry))
+                                .fill_color(color.gamma_multiply(0.15))
+                                .stroke(Stroke::NONE)
+                                .allow_hover(false),
+                        );
+                    }
+                }
+                if vis.opts.show_projection
+                    && let Some(tail) = &tails[i]
+                    && let Some(target) = tail.iterations_to(vis.opts.tolerance_exp)
+                {
+                    let projected: Option<Vec<PlotPoint>> = [tail.last_n, target]
+                        .into_iter()
+                        .map(|n| {
+                            let x = if log_x { log_iteration(n)? } else { n };
+                            let y = vis.opts.deviation_y(power_of_ten(tail.at(n)), symlog)?;
+                            Some(PlotPoint::new(x, y))
+                        })
+                        .collect();
+                    if let Some(projected) = projected {
+                        plot_ui.text(
+                            Text::new(projected[1], format!("  ≈ n={target} (экстраполяция)"))
+                                .color(*color)
+                                .anchor(egui::Align2::LEFT_CENTER),
+                        );
+                        plot_ui.line(
+                            Line::new(projected)
+                                .name(vis.line_name(entry))
+                                .color(*color)
+                                .style(LineStyle::dashed_dense())
+                                .allow_hover(false),
+                        );
+                    }
+                }
+                if vis.opts.show_best
+                    && let Some((n, deviation)) = bests[i]
+                    && let Some(y) = vis.opts.deviation_y(deviation, symlog)
+                    && let Some(&point) =
+                        vis.iteration_points(&[PlotPoint::new(n as f64, y)]).first()
+                {
+                    plot_ui.points(
+                        Points::new(slice::from_ref(&point))
+                            .name(vis.line_name(entry))
+                            .color(*color)
+                            .shape(MarkerShape::Diamond)
+                            .filled(true)
+                            .radius(6.0),
+                    );
+                    if vis.opts.label_best {
+                        plot_ui.text(
+                            Text::new(point, format!("  n={n}, {}", deviation.format()))
+                                .color(*color)
+                                .anchor(egui::Align2::LEFT_BOTTOM),
+                        );
+                    }
+                }
             }
-            if vis.show_partial_sums {
-                for (n, points) in &partial_lines {
+            if vis.opts.show_partial_sums {
+                for ((entry, _), points) in partial_lines.iter().zip(&partial_points) {
+                    if vis.is_hidden(entry) || !in_facet(entry) {
+                        continue;
+                    }
                     plot_ui.line(
-                        Line::new(points.as_slice())
-                            .name(n)
-                            .color(Color32::from_rgb(255, 0, 0))
-                            .stroke(Stroke::new(3.0, Color32::from_rgb(255, 0, 0))),
+                        Line::new(&*vis.iteration_points(points))
+                            .name(vis.line_name(entry))
+                            .style(styles.line_style(entry))
+                            .color(partial_color)
+                            .stroke(Stroke::new(3.0, partial_color)),
                     );
                 }
             }
-        });
-        vis.plot_hovered |= plot.response.hovered();
-        ui.horizontal(|ui| {
-            if ui.button("📸 Снимок экрана").clicked() {
-                vis.request_screenshot(ui.ctx(), "error", plot.response.rect);
+            if vis.opts.show_epsilon {
+                for ((entry, y), color) in epsilons.iter().zip(&epsilon_colors) {
+                    if vis.is_hidden(entry) {
+                        continue;
+                    }
+                    let mut hline = HLine::new(*y)
+                        .name(vis.line_name(entry))
+                        .style(LineStyle::dashed_loose());
+                    if let Some(color) = color {
+                        hline = hline.color(*color);
+                    }
+                    plot_ui.hline(hline);
+                }
             }
-        });
-    }
-}
-
-type CreatePerformancePlot = impl Fn(&mut Vis, &mut Ui);
-#[define_opaque(CreatePerformancePlot)]
-fn create_performance_plot(data: &[SeriesDataRef], symlog: bool) -> CreatePerformancePlot {
-    let mut points = Vec::new();
-    let mut min_x = f64::INFINITY;
-    let mut max_x = f64::NEG_INFINITY;
-
-    for (series, accel_records) in data {
-        if series.computed.is_empty() {
-            continue;
-        }
-
-        for accel_record in accel_records {
-            if accel_record.computed.is_empty() {
-                continue;
+            if vis.opts.show_projection
+                && let Some(y) = vis
+                    .opts
+                    .deviation_y(Scientific(1.0, vis.opts.tolerance_exp), symlog)
+            {
+                plot_ui.hline(
+                    HLine::new(y)
+                        .name(format!("Допуск 1e{}", vis.opts.tolerance_exp))
+                        .color(Color32::GRAY)
+                        .style(LineStyle::dashed_loose()),
+                );
             }
+            for (y, name) in &underflow_rows {
+                plot_ui.hline(
+                    HLine::new(*y)
+                        .name(name)
+                        .color(Color32::GRAY)
+                        .style(LineStyle::dotted_dense()),
+                );
+            }
+        };
+        let find_line = |id| {
+            (0..lines.len())
+                .find(|i| line_id("error", i) == id)
+                .map(|i| &lines[i].2)
+        };
 
-            let item_name = format_item_name(series, &accel_record.accel_info);
-
-            // Find minimum error and corresponding iteration
-            let mut min_error = f64::INFINITY;
-            let mut min_error_iter = 0;
-
-            for (c, accel) in series.computed.iter().zip(accel_record.computed.iter()) {
-                if let Some(ap) = accel {
-                    let error = if symlog {
-                        ap.deviation.symlog()
-                    } else {
-                        ap.deviation.approx_f64()
-                    };
-
-                    if error < min_error {
-                        min_error = error;
-                        min_error_iter = c.n;
+        let Some(dimension) = vis.opts.facet_by else {
+            let restore = vis.take_bounds(plot_id);
+            let plot = vis.with_legend(ui, "error", &entries, |vis, ui| {
+                plot.show(ui, |plot_ui| {
+                    plot_controls(plot_ui);
+                    if let Some(bounds) = restore {
+                        plot_ui.set_plot_bounds(bounds);
+                    }
+                    draw(vis, plot_ui, None);
+                    for pin in &vis.pinned {
+                        let points = if symlog {
+                            &pin.errors_symlog
+                        } else {
+                            &pin.errors
+                        };
+                        plot_ui.line(pin.line(&vis.iteration_points(points)));
                     }
+                    vis.draw_annotations("error", plot_ui, symlog, vis.opts.log_x);
+                })
+            });
+            vis.select_from_plot(&plot, find_line);
+            if symlog {
+                paint_linear_region(ui, &plot.transform);
+            }
+            vis.annotation_menu("error", &plot, symlog, vis.opts.log_x);
+            if let Some((from, to)) = drag_range(ui, &plot.response, &plot.transform) {
+                let iteration = |x: f64| if log_x { 10f64.powf(x) } else { x };
+                let range = (iteration(from), iteration(to));
+                let mut fits: Vec<SlopeFit> = lines
+                    .iter()
+                    .zip(&line_colors)
+                    .filter(|((entry, _, _), _)| !vis.is_hidden(entry))
+                    .map(|((entry, deviations, _), color)| {
+                        SlopeFit::new(entry.name.clone(), *color, &log_errors(deviations, range))
+                    })
+                    .collect();
+                if vis.opts.show_partial_sums {
+                    fits.extend(
+                        partial_lines
+                            .iter()
+                            .filter(|(entry, _)| !vis.is_hidden(entry))
+                            .map(|(entry, deviations)| {
+                                let points = log_errors(deviations, range);
+                                SlopeFit::new(entry.name.clone(), partial_color, &points)
+                            }),
+                    );
                 }
+                vis.slope_fit = Some(SlopeWindow::new(range, fits));
             }
-
-            if min_error < f64::INFINITY {
-                min_x = min_x.min(min_error_iter as f64);
-                max_x = max_x.max(min_error_iter as f64);
-                points.push((item_name, PlotPoint::new(min_error_iter as f64, min_error)));
+            if let Some(window) = &vis.slope_fit {
+                let x = |n: f64| if log_x { n.max(1.0).log10() } else { n };
+                paint_range(ui, &plot.transform, (x(window.range.0), x(window.range.1)));
             }
-        }
-    }
+            describe_plot(
+                &plot.response,
+                format!(
+                    "График ошибок, линий: {}. Shift+перетаскивание — скорость сходимости на \
+                     участке",
+                    lines.len()
+                ),
+            );
+            vis.track_hover(&plot.response);
+            vis.remember_bounds(plot_id, plot.transform.bounds());
 
-    move |vis, ui| {
-        if points.is_empty() {
-            ui.label("Нет данных для отображения");
-            return;
-        }
+            let mut curves: Vec<_> = lines
+                .iter()
+                .zip(&line_points)
+                .zip(&line_colors)
+                .filter(|(((entry, _, _), _), _)| !vis.is_hidden(entry))
+                .map(|((_, points), color)| (vis.iteration_points(points), *color))
+                .collect();
+            if vis.opts.show_partial_sums {
+                curves.extend(
+                    partial_lines
+                        .iter()
+                        .zip(&partial_points)
+                        .filter(|((entry, _), _)| !vis.is_hidden(entry))
+                        .map(|(_, points)| (vis.iteration_points(points), partial_color)),
+                );
+            }
+            overview_strip(vis, ui, plot_id, plot.transform.bounds(), &curves);
+            return Some(plot.response.rect);
+        };
 
-        let mut plot = Plot::new("performance")
-            .allow_zoom(true)
-            .allow_drag(true)
-            .height(900.0)
-            .x_axis_label("Итерация достижения минимальной ошибки")
-            .y_axis_label("Минимальная ошибка")
-            .legend(egui_plot::Legend::default());
-        if symlog {
-            plot = plot
-                .y_axis_formatter(|mark, _| symlog_formatter(mark.value))
-                .label_formatter(|name, value| {
-                    format!("{name}\nx={}\ny={}", value.x, symlog_formatter(value.y))
-                });
+        // Small multiples with linked axes, so that the facets share the scales
+        let facet_entries = lines.iter().map(|(entry, _, _)| entry).chain(
+            partial_lines
+                .iter()
+                .filter(|_| vis.opts.show_partial_sums)
+                .map(|(entry, _)| entry),
+        );
+        let facets = facets(dimension, facet_entries);
+        vis.with_legend(ui, "error", &entries, |vis, ui| {
+            ui.vertical(|ui| {
+                let mut rect: Option<egui::Rect> = None;
+                let columns = facets.len().clamp(1, FACET_COLUMNS);
+                let spacing = ui.spacing().item_spacing.x;
+                let width = (ui.available_width() / columns as f32 - spacing).max(200.0);
+                for row in facets.chunks(columns) {
+                    ui.horizontal_top(|ui| {
+                        for facet in row {
+                            ui.vertical(|ui| {
+                                ui.label(egui::RichText::new(&facet.title).strong());
+                                let mut plot = Plot::new(("error_facet", facet.value, log_x))
+                                    .allow_zoom(true)
+                                    .allow_drag(true)
+                                    .width(width)
+                                    .height(FACET_HEIGHT)
+                                    .link_axis("error_facets", true)
+                                    .link_cursor("error_facets", egui::Vec2b::new(true, false))
+                                    .y_axis_formatter(move |mark, _| format_y(mark.value))
+                                    .label_formatter(hover_text);
+                                plot = vis.plot_legend(plot);
+                                plot = vis.plot_wheel(plot);
+                                plot = vis.iteration_axis(plot);
+                                let plot = plot.show(ui, |plot_ui| {
+                                    plot_controls(plot_ui);
+                                    draw(vis, plot_ui, Some((dimension, facet)));
+                                });
+                                vis.select_from_plot(&plot, find_line);
+                                describe_plot(
+                                    &plot.response,
+                                    format!("График ошибок: {}", facet.title),
+                                );
+                                vis.track_hover(&plot.response);
+                                let facet_rect = plot.response.rect;
+                                rect = Some(rect.map_or(facet_rect, |r| r.union(facet_rect)));
+                            });
+                        }
+                    });
+                }
+                rect
+            })
+            .inner
+        })
+    }
+}
+
+// Axis selectors and point display of the performance plot
+fn perf_controls_ui(ui: &mut Ui, opts: &mut PlotOptions) {
+    ui.horizontal(|ui| {
+        let (mut x, mut y) = (opts.perf_x, opts.perf_y);
+        ui.label("X:");
+        metric_ui(ui, "perf_x", &mut x, opts);
+        ui.label("Y:");
+        metric_ui(ui, "perf_y", &mut y, opts);
+        (opts.perf_x, opts.perf_y) = (x, y);
+        ui.checkbox(&mut opts.perf_labels, "Подписи точек");
+        ui.checkbox(&mut opts.perf_spread, "Разнести совпадающие")
+            .on_hover_text("Точки с одинаковыми координатами сдвигаются по горизонтали");
+        ui.checkbox(&mut opts.perf_by_group, "По группам")
+            .on_hover_text("Медиана записей каждой группы, усы — от первого до третьего квартиля");
+        if opts.perf_by_group {
+            grouping_ui(ui, opts);
         }
-        let plot = plot.show(ui, |plot_ui| {
-            for (name, points) in &points {
-                plot_ui.points(
-                    Points::new(slice::from_ref(points))
-                        .name(name)
-                        .shape(MarkerShape::Circle)
-                        .radius(4.0),
-                );
+    });
+}
+
+// Quartiles of a group's records on the performance plot, in plot coordinates
+struct GroupSpread {
+    label: String,
+    color: Color32,
+    records: usize,
+    // First quartile, median and third quartile
+    x: [f64; 3],
+    y: [f64; 3],
+}
+
+fn quartiles(mut values: Vec<f64>) -> [f64; 3] {
+    values.sort_by(f64::total_cmp);
+    // Nearest rank, as in `percentile`
+    [0.25, 0.5, 0.75].map(|p| {
+        let rank = ((p * values.len() as f64).ceil() as usize).clamp(1, values.len());
+        values[rank - 1]
+    })
+}
+
+// Groups in order of their first record
+fn group_spreads(points: impl Iterator<Item = (Group, PlotPoint)>) -> Vec<GroupSpread> {
+    let mut groups: Vec<(Group, Vec<PlotPoint>)> = Vec::new();
+    for (group, point) in points {
+        match groups.iter_mut().find(|(g, _)| g.key == group.key) {
+            Some((_, points)) => points.push(point),
+            None => groups.push((group, vec![point])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(group, points)| GroupSpread {
+            color: value_color(&group.key),
+            label: group.label,
+            records: points.len(),
+            x: quartiles(points.iter().map(|p| p.x).collect()),
+            y: quartiles(points.iter().map(|p| p.y).collect()),
+        })
+        .collect()
+}
+
+// Points with equal coordinates side by side along x, `step` apart, so each stays visible and
+// clickable
+fn spread_overlapping(points: &[PlotPoint], step: f64) -> Vec<PlotPoint> {
+    let mut groups: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (i, p) in points.iter().enumerate() {
+        groups
+            .entry((p.x.to_bits(), p.y.to_bits()))
+            .or_default()
+            .push(i);
+    }
+    let mut spread = points.to_vec();
+    for group in groups.values() {
+        let middle = (group.len() - 1) as f64 / 2.0;
+        for (j, &i) in group.iter().enumerate() {
+            spread[i].x += (j as f64 - middle) * step;
+        }
+    }
+    spread
+}
+
+type CreatePerformancePlot = impl Fn(&mut Vis, &mut Ui) -> Option<egui::Rect>;
+#[define_opaque(CreatePerformancePlot)]
+fn create_performance_plot(data: &[SeriesDataRef], symlog: bool) -> CreatePerformancePlot {
+    let common = CommonParts::of(data);
+    // Per record: its statistics, the error bar of its minimal error if the uncertainty of the
+    // deviations is known, and the series name it's grouped by
+    let mut records = Vec::new();
+
+    for (series, accel_records) in data {
+        if series.computed.is_empty() {
+            continue;
+        }
+
+        for accel_record in accel_records {
+            if accel_record.computed.is_empty() {
+                continue;
             }
-        });
-        vis.plot_hovered |= plot.response.hovered();
-        ui.horizontal(|ui| {
-            if ui.button("📸 Снимок экрана").clicked() {
-                vis.request_screenshot(ui.ctx(), "performance", plot.response.rect);
+
+            let entry = LegendEntry::accel(series, &accel_record.accel_info, None, &common)
+                .flagged(!anomalies(series, accel_record).is_empty());
+            let stats = RowStats::of(series, accel_record);
+            let error_bar = stats.iteration_of_min().and_then(|n| {
+                uncertainty_points(series, accel_record, symlog)
+                    .into_iter()
+                    .find(|(x, _, _)| *x == n as f64)
+                    .map(|(_, low, high)| (low, high))
+            });
+            records.push((
+                entry,
+                stats,
+                error_bar,
+                LineKey::new(series, &accel_record.accel_info),
+                series.name.clone(),
+            ));
+        }
+    }
+
+    move |vis, ui| {
+        if records.is_empty() {
+            ui.label("Нет данных для отображения");
+            return None;
+        }
+
+        perf_controls_ui(ui, &mut vis.opts);
+        let (x_metric, y_metric) = (vis.opts.perf_x, vis.opts.perf_y);
+        let params = vis.opts.metric_params();
+        let (x_log, y_log) = (
+            x_metric.scale() == Scale::Log,
+            y_metric.scale() == Scale::Log,
+        );
+        if (x_log || y_log)
+            && let Some(note) = vis.opts.underflow_note()
+        {
+            ui.label(egui::RichText::new(note).weak());
+        }
+        // Records without one of the metrics are left out
+        let points: Vec<(usize, PlotPoint)> = records
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, stats, ..))| {
+                let x = vis.opts.metric_y(x_metric.of(stats, &params)?, symlog)?;
+                let y = vis.opts.metric_y(y_metric.of(stats, &params)?, symlog)?;
+                Some((i, PlotPoint::new(x, y)))
+            })
+            .collect();
+        // Bounds and annotations don't carry over between metrics; the default keeps the old id
+        let plot_id = if (x_metric, y_metric) == (Metric::IterationOfMin, Metric::MinError) {
+            "performance".to_string()
+        } else {
+            format!("performance_{}_{}", x_metric.id(), y_metric.id())
+        };
+        let shown = if vis.opts.perf_spread {
+            let (min, max) = points
+                .iter()
+                .map(|(_, p)| p.x)
+                .filter(|x| x.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                    (min.min(x), max.max(x))
+                });
+            // 1% of the x range, or of one unit if all points share an x
+            let step = if max > min { (max - min) / 100.0 } else { 0.01 };
+            let coordinates: Vec<PlotPoint> = points.iter().map(|(_, p)| *p).collect();
+            spread_overlapping(&coordinates, step)
+        } else {
+            points.iter().map(|(_, p)| *p).collect()
+        };
+
+        let mut plot = Plot::new(&plot_id)
+            .allow_zoom(true)
+            .allow_drag(true)
+            .height(900.0);
+        plot = vis.axis_labels(
+            "performance",
+            plot,
+            &x_metric.label(&params),
+            &y_metric.label(&params),
+        );
+        plot = vis.plot_legend(plot);
+        plot = vis.plot_wheel(plot);
+        let format = |log: bool| -> fn(f64) -> String {
+            match log {
+                true if symlog => symlog_formatter,
+                true => format_f64,
+                false => |x| x.to_string(),
             }
+        };
+        let (format_x, format_y) = (format(x_log), format(y_log));
+        plot = plot
+            .x_axis_formatter(move |mark, _| format_x(mark.value))
+            .y_axis_formatter(move |mark, _| format_y(mark.value));
+        if symlog && x_log {
+            plot = plot.x_grid_spacer(symlog_grid_spacer);
+        }
+        if symlog && y_log {
+            plot = plot.y_grid_spacer(symlog_grid_spacer);
+        }
+
+        let entries: Vec<_> = points
+            .iter()
+            .map(|&(i, _)| (&records[i].0, vis.accel_color(&records[i].0)))
+            .collect();
+        let styles = StyleIndex::new(vis.opts.style_by, entries.iter().map(|(e, _)| *e));
+        let plot = plot.label_formatter(|name, value| {
+            // All records at the hovered location, even if they are drawn over each other
+            let at = shown.iter().position(|p| p == value).map(|k| points[k].1);
+            let together: Vec<&str> = points
+                .iter()
+                .zip(&entries)
+                .filter(|((_, p), _)| Some(*p) == at)
+                .map(|(_, (entry, _))| entry.name.as_str())
+                .collect();
+            let (x, y) = match at {
+                Some(p) => (p.x, p.y),
+                None => (value.x, value.y),
+            };
+            let name = if together.len() > 1 {
+                format!(
+                    "Записей в этой точке: {}\n{}",
+                    together.len(),
+                    together.join("\n")
+                )
+            } else {
+                full_name(&entries, name).to_string()
+            };
+            format!("{name}\nx={}\ny={}", format_x(x), format_y(y))
+        });
+
+        // Records hidden in the legend don't count towards their group
+        let spreads = vis.opts.perf_by_group.then(|| {
+            let grouping = Grouping::new(&vis.opts);
+            group_spreads(
+                points
+                    .iter()
+                    .zip(&entries)
+                    .filter(|(_, (entry, _))| !vis.is_hidden(entry))
+                    .map(|(&(i, point), _)| {
+                        let (_, _, _, key, series_name) = &records[i];
+                        (grouping.of(series_name, &key.accel_info), point)
+                    }),
+            )
         });
+
+        let restore = vis.take_bounds(&plot_id);
+        let plot = vis.with_legend(ui, "performance", &entries, |vis, ui| {
+            plot.show(ui, |plot_ui| {
+                plot_controls(plot_ui);
+                if let Some(bounds) = restore {
+                    plot_ui.set_plot_bounds(bounds);
+                }
+                if let Some(spreads) = &spreads {
+                    for spread in spreads {
+                        let [x1, x, x3] = spread.x;
+                        let [y1, y, y3] = spread.y;
+                        for whisker in [[[x1, y], [x3, y]], [[x, y1], [x, y3]]] {
+                            plot_ui.line(
+                                Line::new(whisker.to_vec())
+                                    .name(&spread.label)
+                                    .color(spread.color)
+                                    .width(2.0)
+                                    .allow_hover(false),
+                            );
+                        }
+                        plot_ui.points(
+                            Points::new([x, y])
+                                .name(&spread.label)
+                                .color(spread.color)
+                                .shape(MarkerShape::Diamond)
+                                .filled(true)
+                                .radius(10.0),
+                        );
+                        if vis.opts.perf_labels {
+                            plot_ui.text(
+                                Text::new(
+                                    PlotPoint::new(x, y),
+                                    format!("  {} ({})", spread.label, spread.records),
+                                )
+                                .color(spread.color)
+                                .anchor(egui::Align2::LEFT_BOTTOM),
+                            );
+                        }
+                    }
+                }
+                for ((&(i, _), &point), (entry, color)) in points
+                    .iter()
+                    .zip(&shown)
+                    .zip(&entries)
+                    .filter(|_| spreads.is_none())
+                {
+                    let (_, _, error_bar, key, _) = &records[i];
+                    if vis.is_hidden(entry) {
+                        continue;
+                    }
+                    let selected = vis.is_selected(key);
+                    // The uncertainty is of the minimal error only
+                    if y_metric == Metric::MinError
+                        && let Some((low, high)) = *error_bar
+                    {
+                        let y = point.y;
+                        plot_ui.box_plot(
+                            BoxPlot::new(vec![
+                                BoxElem::new(point.x, BoxSpread::new(low, y, y, y, high))
+                                    .box_width(0.0),
+                            ])
+                            .name(vis.line_name(entry))
+                            .color(*color)
+                            .allow_hover(false),
+                        );
+                    }
+                    plot_ui.points(
+                        Points::new(slice::from_ref(&point))
+                            .name(vis.line_name(entry))
+                            .id(line_id("performance", i))
+                            .color(*color)
+                            .shape(styles.marker(entry))
+                            .radius(if selected { 7.0 } else { 4.0 })
+                            .highlight(selected),
+                    );
+                    if vis.opts.perf_labels {
+                        plot_ui.text(
+                            Text::new(point, format!(" {}", vis.line_name(entry)))
+                                .color(*color)
+                                .anchor(egui::Align2::LEFT_BOTTOM),
+                        );
+                    }
+                }
+                vis.draw_annotations(&plot_id, plot_ui, symlog && y_log, false);
+            })
+        });
+        vis.select_from_plot(&plot, |id| {
+            (0..records.len())
+                .find(|i| line_id("performance", i) == id)
+                .map(|i| &records[i].3)
+        });
+        if symlog && y_log {
+            paint_linear_region(ui, &plot.transform);
+        }
+        vis.annotation_menu(&plot_id, &plot, symlog && y_log, false);
+        describe_plot(
+            &plot.response,
+            match &spreads {
+                Some(spreads) => format!(
+                    "{} против {}, медианы по группам: {}",
+                    y_metric.label(&params),
+                    x_metric.label(&params),
+                    spreads.len()
+                ),
+                None => format!(
+                    "{} против {}, точек: {}",
+                    y_metric.label(&params),
+                    x_metric.label(&params),
+                    points.len()
+                ),
+            },
+        );
+        vis.track_hover(&plot.response);
+        vis.remember_bounds(&plot_id, plot.transform.bounds());
+        Some(plot.response.rect)
+    }
+}
+
+// x of iteration `n` on the log10 iteration axis; iterations before the first can't be shown
+fn log_iteration(n: f64) -> Option<f64> {
+    (n >= 1.0).then(|| n.log10())
+}
+
+// Iteration under the cursor, for hover texts
+fn iteration_label(log_x: bool, x: f64) -> String {
+    if log_x {
+        format!("{}", 10f64.powf(x).round())
+    } else {
+        x.to_string()
+    }
+}
+
+// Orders deviations without going through f64, which under/overflows for them
+pub fn magnitude(s: &Scientific) -> f64 {
+    if s.0 == 0.0 {
+        f64::NEG_INFINITY
+    } else {
+        s.0.abs().log10() + s.1 as f64
+    }
+}
+
+// Nearest-rank percentile of values sorted by `magnitude`
+fn percentile(sorted: &[Scientific], p: f64) -> Option<Scientific> {
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len().max(1));
+    sorted.get(rank - 1).copied()
+}
+
+fn sorted_by_magnitude(mut values: Vec<Scientific>) -> Vec<Scientific> {
+    values.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+    values
+}
+
+// Plateau detection: improvement below this many orders of magnitude counts as none
+const PLATEAU_DROP: f64 = 0.5;
+const PLATEAU_MIN_POINTS: usize = 5;
+
+/// Hover text of a plateau, e.g. "застой с n=85, ошибка≈3e-16"
+pub fn format_plateau((n, deviation): (i32, Scientific)) -> String {
+    format!("застой с n={n}, ошибка≈{}", deviation.format())
+}
+
+/// How an acceleration compares with the partial sum at the same iterations
+#[derive(Clone, Copy, Default)]
+pub struct VsPartialSum {
+    /// Iterations at which the accelerated value is closer to the limit
+    pub wins: usize,
+    pub compared: usize,
+    /// Median of partial sum deviation / accelerated deviation, over nonzero accelerated ones
+    pub median_gain: Option<Scientific>,
+}
+
+impl VsPartialSum {
+    fn of(pairs: impl Iterator<Item = (Scientific, Scientific)>) -> Self {
+        let mut result = Self::default();
+        let mut gains = Vec::new();
+        for (series, accel) in pairs {
+            result.compared += 1;
+            if magnitude(&accel) < magnitude(&series) {
+                result.wins += 1;
+            }
+            if accel.0 != 0.0 {
+                gains.push(series.abs() / accel.abs());
+            }
+        }
+        result.median_gain = percentile(&sorted_by_magnitude(gains), 0.5);
+        result
+    }
+
+    /// E.g. "45/60, ×3.2e5"
+    pub fn format(&self) -> String {
+        match self.median_gain {
+            Some(gain) => format!("{}/{}, ×{}", self.wins, self.compared, gain.format()),
+            None => format!("{}/{}", self.wins, self.compared),
+        }
+    }
+}
+
+/// Deviation summary of an accel record, comparable across records
+pub struct RowStats {
+    pub min: Option<Scientific>,
+    pub median: Option<Scientific>,
+    pub last: Option<Scientific>,
+    pub p90: Option<Scientific>,
+    pub errors: usize,
+    pub vs_partial_sum: VsPartialSum,
+    // (n, deviation) in iteration order
+    deviations: Vec<(i32, Scientific)>,
+}
+
+impl RowStats {
+    pub fn of(series: &SeriesRecord, accel_record: &AccelRecord) -> Self {
+        let deviations = series
+            .computed
+            .iter()
+            .zip(accel_record.computed.iter())
+            .filter_map(|(s, a)| Some((s.n, a?.deviation)))
+            .collect();
+        let vs_partial_sum = VsPartialSum::of(
+            series
+                .computed
+                .iter()
+                .zip(accel_record.computed.iter())
+                .filter_map(|(s, a)| Some((s.deviation, a?.deviation))),
+        );
+        Self::new(deviations, accel_record.errors.len(), vs_partial_sum)
+    }
+
+    fn new(
+        deviations: Vec<(i32, Scientific)>,
+        errors: usize,
+        vs_partial_sum: VsPartialSum,
+    ) -> Self {
+        let sorted = sorted_by_magnitude(deviations.iter().map(|(_, d)| *d).collect());
+        Self {
+            min: sorted.first().copied(),
+            median: percentile(&sorted, 0.5),
+            last: deviations.last().map(|(_, d)| *d),
+            p90: percentile(&sorted, 0.9),
+            errors,
+            vs_partial_sum,
+            deviations,
+        }
+    }
+
+    /// First point after which the deviation never drops by more than `PLATEAU_DROP` orders of
+    /// magnitude, if at least `PLATEAU_MIN_POINTS` points remain, e.g. stagnation at roundoff
+    pub fn plateau(&self) -> Option<(i32, Scientific)> {
+        let magnitudes: Vec<f64> = self.deviations.iter().map(|(_, d)| magnitude(d)).collect();
+        let mut suffix_min = magnitudes.clone();
+        for i in (0..suffix_min.len().saturating_sub(1)).rev() {
+            suffix_min[i] = suffix_min[i].min(suffix_min[i + 1]);
+        }
+        let last = magnitudes.len().checked_sub(PLATEAU_MIN_POINTS)?;
+        (0..=last)
+            .find(|&i| !magnitudes[i].is_nan() && suffix_min[i] >= magnitudes[i] - PLATEAU_DROP)
+            .map(|i| self.deviations[i])
+    }
+
+    /// First iteration with the minimal deviation
+    pub fn iteration_of_min(&self) -> Option<i32> {
+        let min = magnitude(self.min.as_ref()?);
+        self.deviations
+            .iter()
+            .find(|(_, d)| magnitude(d) == min)
+            .map(|(n, _)| *n)
+    }
+
+    /// Deviation at iteration `n`
+    pub fn at(&self, n: i32) -> Option<Scientific> {
+        self.deviations
+            .iter()
+            .find(|(m, _)| *m == n)
+            .map(|(_, d)| *d)
     }
+
+    /// First n at which the deviation is within 10^tolerance_exp
+    pub fn iterations_to(&self, tolerance_exp: i32) -> Option<i32> {
+        self.deviations
+            .iter()
+            .find(|(_, d)| magnitude(d) <= tolerance_exp as f64)
+            .map(|(n, _)| *n)
+    }
+}
+
+pub fn format_stat(value: Option<Scientific>) -> String {
+    value.map_or_else(|| "—".to_string(), |v| v.format())
+}
+
+/// How the deviations of a table row are averaged. Deviations span hundreds of orders of
+/// magnitude, so a plain mean is dominated by the largest early ones; the default is in log space.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DeviationAverage {
+    #[default]
+    Geometric = 0,
+    Median = 1,
+    Arithmetic = 2,
 }
 
-type CreateAccelRecordsTable = impl Fn(&mut Ui);
+impl DeviationAverage {
+    const ALL: [Self; 3] = [Self::Geometric, Self::Median, Self::Arithmetic];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Geometric => "Среднее геом.",
+            Self::Median => "Медиана",
+            Self::Arithmetic => "Среднее",
+        }
+    }
+
+    fn of(self, deviations: &[Scientific]) -> Option<Scientific> {
+        match self {
+            Self::Geometric => Scientific::geometric_mean(deviations),
+            Self::Median => percentile(&sorted_by_magnitude(deviations.to_vec()), 0.5),
+            Self::Arithmetic => Scientific::mean(deviations),
+        }
+    }
+}
+
+/// Column of the accel records table. The order and visibility are stored in the workspace.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum TableColumn {
+    Pin,
+    Anomalies,
+    SeriesId,
+    SeriesName,
+    Precision,
+    SeriesLimit,
+    SeriesParams,
+    AccelName,
+    M,
+    AccelParams,
+    SeriesValues,
+    AccelValues,
+    Deviations,
+    /// One per `Metric::ALL`
+    Metric(Metric),
+    ErrorCount,
+    Plateau,
+    VsPartialSum,
+    Errors,
+    Events,
+    Note,
+    Json,
+}
+
+impl TableColumn {
+    // Default order
+    fn all() -> Vec<Self> {
+        let mut all = vec![
+            Self::Pin,
+            Self::Anomalies,
+            Self::SeriesId,
+            Self::SeriesName,
+            Self::Precision,
+            Self::SeriesLimit,
+            Self::SeriesParams,
+            Self::AccelName,
+            Self::M,
+            Self::AccelParams,
+            Self::SeriesValues,
+            Self::AccelValues,
+            Self::Deviations,
+        ];
+        all.extend(Metric::ALL.map(Self::Metric));
+        all.extend([
+            Self::ErrorCount,
+            Self::Plateau,
+            Self::VsPartialSum,
+            Self::Errors,
+            Self::Events,
+            Self::Note,
+            Self::Json,
+        ]);
+        all
+    }
+
+    fn title(self, params: &MetricParams) -> String {
+        let title = match self {
+            Self::Pin => "📌",
+            Self::Anomalies => "⚠",
+            Self::SeriesId => "Series ID",
+            Self::SeriesName => "Название ряда",
+            Self::Precision => "Precision",
+            Self::SeriesLimit => "Предел ряда",
+            Self::SeriesParams => "Параметры ряда",
+            Self::AccelName => "Название ускорения",
+            Self::M => "M",
+            Self::AccelParams => "Параметры ускорения",
+            Self::SeriesValues => "S_n ряда",
+            Self::AccelValues => "S_n ускорения",
+            Self::Deviations => "Отклонения",
+            Self::Metric(metric) => return metric.label(params),
+            Self::ErrorCount => "Кол-во ошибок",
+            Self::Plateau => "Застой",
+            Self::VsPartialSum => "Лучше S_n",
+            Self::Errors => "Ошибки",
+            Self::Events => "Событий",
+            Self::Note => "Заметка",
+            Self::Json => "JSON",
+        };
+        title.to_string()
+    }
+
+    /// Drops duplicates and appends columns missing from a saved layout (e.g. added in a newer version)
+    pub fn normalize(mut layout: Vec<(Self, bool)>) -> Vec<(Self, bool)> {
+        let mut seen = HashSet::new();
+        layout.retain(|(c, _)| seen.insert(*c));
+        for c in Self::all() {
+            if !seen.contains(&c) {
+                layout.push((c, true));
+            }
+        }
+        layout
+    }
+}
+
+type CreateAccelRecordsTable = impl Fn(&mut Vis, &mut Ui);
 #[define_opaque(CreateAccelRecordsTable)]
 fn create_accel_records_table(data: &[SeriesDataRef]) -> CreateAccelRecordsTable {
     type TableRow = (
@@ -692,6 +3041,10 @@ fn create_accel_records_table(data: &[SeriesDataRef]) -> CreateAccelRecordsTable
         Vec<String>, // 10: Отклонения values
         Vec<String>, // 11: Ошибки values
         Vec<String>, // 12: Событий values
+        LineKey,     // 13: pin target
+        RowStats,    // 14: deviation summary
+        String,      // 15: anomalies, empty if none
+        [String; 3], // 16: average deviation, by `DeviationAverage`
     );
     let mut table_rows: Vec<TableRow> = Vec::new();
     for (series, accel_records) in data {
@@ -730,23 +3083,32 @@ fn create_accel_records_table(data: &[SeriesDataRef]) -> CreateAccelRecordsTable
                 .computed
                 .iter()
                 .enumerate()
-                .filter_map(|(i, j)| Some((i, j.as_ref()?)))
+                .filter_map(|(i, j)| Some((i, j?)))
                 .map(|(j, c)| format!("n={}: {}", j, c.value.format()))
                 .collect();
             // Отклонения values
             let mut deviation_values = Vec::new();
-            let mut sum_deviation = 0.0;
-            let mut sum_series_deviation = 0.0;
-            let mut len = 0;
+            let mut accel_deviations = Vec::new();
+            let mut series_deviations = Vec::new();
 
-            for (s, a) in series.computed.iter().zip(accel_record.computed.iter()) {
+            for (i, (s, a)) in series
+                .computed
+                .iter()
+                .zip(accel_record.computed.iter())
+                .enumerate()
+            {
                 if let Some(a) = a {
-                    sum_series_deviation += s.deviation.approx_f64();
-                    sum_deviation += a.deviation.approx_f64();
-                    len += 1;
+                    series_deviations.push(s.deviation);
+                    accel_deviations.push(a.deviation);
 
+                    let spread = match accel_record.uncertainty.get(i) {
+                        Some(Some([low, high])) => {
+                            format!(" [{}; {}]", low.format(), high.format())
+                        }
+                        _ => String::new(),
+                    };
                     deviation_values.push(format!(
-                        "n={}: {} (vs {})",
+                        "n={}: {}{spread} (vs {})",
                         s.n,
                         a.deviation.format(),
                         s.deviation.format()
@@ -754,15 +3116,15 @@ fn create_accel_records_table(data: &[SeriesDataRef]) -> CreateAccelRecordsTable
                 }
             }
 
-            // Add summary as first deviation value if we have data
-            if len > 0 {
-                let summary = format!(
-                    "Среднее: {:.9} (vs {:.9})",
-                    sum_deviation / len as f64,
-                    sum_series_deviation / len as f64
-                );
-                deviation_values.insert(0, summary);
-            }
+            // Summary of the deviations, shown above them, for every kind of average
+            let averages = DeviationAverage::ALL.map(|average| {
+                format!(
+                    "{}: {} (vs {})",
+                    average.label(),
+                    format_stat(average.of(&accel_deviations)),
+                    format_stat(average.of(&series_deviations))
+                )
+            });
             // Ошибки values
             let error_values: Vec<String> = accel_record
                 .errors
@@ -777,11 +3139,19 @@ fn create_accel_records_table(data: &[SeriesDataRef]) -> CreateAccelRecordsTable
                 .collect();
             table_rows.push((
                 series.series_id.to_string(),
-                series.name.clone(),
-                series.precision.clone(),
+                series.name.to_string(),
+                series.precision.to_string(),
                 series.series_limit.format(),
                 series_params,
-                accel_record.accel_info.name.clone(),
+                if accel_record.reruns > 0 {
+                    format!(
+                        "{} (×{})",
+                        accel_record.accel_info.name,
+                        accel_record.reruns + 1
+                    )
+                } else {
+                    accel_record.accel_info.name.to_string()
+                },
                 accel_record.accel_info.m_value.to_string(),
                 accel_params,
                 series_values,
@@ -789,117 +3159,778 @@ fn create_accel_records_table(data: &[SeriesDataRef]) -> CreateAccelRecordsTable
                 deviation_values,
                 error_values,
                 event_values,
+                LineKey {
+                    series_id: series.series_id,
+                    accel_info: accel_record.accel_info.clone(),
+                },
+                RowStats::of(series, accel_record),
+                describe_anomalies(&anomalies(series, accel_record)),
+                averages,
             ));
         }
     }
-    move |ui| {
+    // What the search looks in, lowercased once
+    let searchable: Vec<String> = table_rows
+        .iter()
+        .map(|row| {
+            [&row.1, &row.4, &row.5, &row.7]
+                .map(|text| text.to_lowercase())
+                .join("\n")
+        })
+        .collect();
+    // Rows found and the footer over them, kept while the search and the metric parameters stay
+    struct Found {
+        search: String,
+        params: MetricParams,
+        rows: Vec<usize>,
+        footer: HashMap<TableColumn, Option<String>>,
+    }
+    let search_cache = std::cell::RefCell::new(None::<Found>);
+    // Heights of the rows laid out so far, with the spacing below them; 0 for the others. Only
+    // the rows in the viewport are laid out, the others are skipped by their height or an estimate
+    let heights = std::cell::RefCell::new(vec![0.0f32; table_rows.len()]);
+    // Widest cell of every column so far: the header, the rows and the footer are separate grids,
+    // and the rows are laid out only as they scroll into view
+    let widths = std::cell::RefCell::new(HashMap::<TableColumn, f32>::new());
+    move |vis, ui| {
         if table_rows.is_empty() {
             ui.label("Нет данных для отображения");
             return;
         }
+        // Apart for the A/B halves, the same whichever rows are laid out
+        let table_id = ui.id();
+        ui.horizontal(|ui| {
+            ui.label("Допуск для «n до допуска»:");
+            ui.add(
+                egui::DragValue::new(&mut vis.opts.tolerance_exp)
+                    .range(-1000..=10)
+                    .prefix("1e"),
+            );
+            ui.separator();
+            ui.label("Среднее отклонений:");
+            for average in DeviationAverage::ALL {
+                ui.selectable_value(&mut vis.opts.deviation_average, average, average.label());
+            }
+        });
+        let tolerance_exp = vis.opts.tolerance_exp;
+        let columns = vis.table_columns();
+        ui.menu_button("☰ Столбцы", |ui| {
+            let params = vis.opts.metric_params();
+            if table_columns_ui(ui, &mut vis.workspace.table_columns, &params) {
+                vis.save_workspace();
+            }
+        });
+        let visible: Vec<TableColumn> = columns
+            .into_iter()
+            .filter_map(|(c, visible)| visible.then_some(c))
+            .collect();
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut vis.table_search)
+                    .hint_text("🔍 Ряд, метод или параметры"),
+            );
+            if !vis.table_search.is_empty()
+                && name_button(ui.button("✖"), "Очистить поиск").clicked()
+            {
+                vis.table_search.clear();
+            }
+        });
+        // Case-insensitive substring of the series name, the method or either parameters
+        let search = vis.table_search.to_lowercase();
+        let params = vis.opts.metric_params();
+        let mut cache = search_cache.borrow_mut();
+        if cache
+            .as_ref()
+            .is_some_and(|found| found.search != search || found.params != params)
+        {
+            *cache = None;
+        }
+        let found = cache.get_or_insert_with(|| Found {
+            rows: (0..table_rows.len())
+                .filter(|&i| search.is_empty() || searchable[i].contains(&search))
+                .collect(),
+            search: search.clone(),
+            params,
+            footer: HashMap::new(),
+        });
+        let rows = &found.rows;
+        if !search.is_empty() {
+            ui.label(format!(
+                "Найдено строк: {} из {}",
+                rows.len(),
+                table_rows.len()
+            ));
+        }
+
+        // Footer: aggregates over the rows found
+        let footer_stat = |c: &TableColumn| match c {
+            TableColumn::Metric(Metric::IterationsToTolerance) => {
+                let mut reached: Vec<i32> = rows
+                    .iter()
+                    .filter_map(|&i| table_rows[i].14.iterations_to(tolerance_exp))
+                    .collect();
+                reached.sort();
+                let median_n = reached
+                    .get(reached.len().saturating_sub(1) / 2)
+                    .map_or_else(|| "—".to_string(), |n| n.to_string());
+                Some(format!(
+                    "{}/{} достигли, медиана {median_n}",
+                    reached.len(),
+                    rows.len()
+                ))
+            }
+            // The best value of a metric over the rows for the minimal error, the median otherwise
+            TableColumn::Metric(metric) => {
+                let mut values: Vec<MetricValue> = rows
+                    .iter()
+                    .filter_map(|&i| metric.of(&table_rows[i].14, &params))
+                    .collect();
+                values.sort_by(|a, b| a.score().total_cmp(&b.score()));
+                let i = match metric {
+                    Metric::MinError => 0,
+                    _ => values.len().saturating_sub(1) / 2,
+                };
+                Some(metric.format(values.get(i).copied()))
+            }
+            TableColumn::ErrorCount => Some(
+                rows.iter()
+                    .map(|&i| table_rows[i].14.errors)
+                    .sum::<usize>()
+                    .to_string(),
+            ),
+            _ => None,
+        };
+
+        // Text of a cell as copied, lists with their values separated by "; "; `None` for controls
+        let cell_text = |row: &TableRow, c: TableColumn| -> Option<String> {
+            let list = |values: &[String]| Some(values.join("; "));
+            match c {
+                TableColumn::Pin | TableColumn::Note | TableColumn::Json => None,
+                TableColumn::Anomalies => Some(row.15.clone()),
+                TableColumn::SeriesId => Some(row.0.clone()),
+                TableColumn::SeriesName => Some(row.1.clone()),
+                TableColumn::Precision => Some(row.2.clone()),
+                TableColumn::SeriesLimit => Some(row.3.clone()),
+                TableColumn::SeriesParams => Some(row.4.clone()),
+                TableColumn::AccelName => Some(row.5.clone()),
+                TableColumn::M => Some(row.6.clone()),
+                TableColumn::AccelParams => Some(row.7.clone()),
+                TableColumn::SeriesValues => list(&row.8),
+                TableColumn::AccelValues => list(&row.9),
+                TableColumn::Deviations => list(&row.10),
+                TableColumn::Errors => list(&row.11),
+                TableColumn::Events => list(&row.12),
+                TableColumn::Metric(metric) => Some(metric.format(metric.of(&row.14, &params))),
+                TableColumn::ErrorCount => Some(row.14.errors.to_string()),
+                TableColumn::Plateau => Some(row.14.plateau().map_or_else(
+                    || "—".to_string(),
+                    |(n, deviation)| format!("n={n}, {}", deviation.format()),
+                )),
+                TableColumn::VsPartialSum => Some(row.14.vs_partial_sum.format()),
+            }
+        };
+
         // Set spacing for spacious cells
         ui.spacing_mut().item_spacing = egui::vec2(20.0, 10.0);
-        // Create grid
-        egui::Grid::new("accel_table")
-            .striped(true)
-            .max_col_width(100.0)
+        let spacing = ui.spacing().item_spacing.y;
+        let estimate = ui.spacing().interact_size.y + spacing;
+        let previous = widths.borrow().clone();
+        let width = |c: &TableColumn| previous.get(c).copied().unwrap_or_default();
+        let mut measured = previous.clone();
+        let mut measure = |c: &TableColumn, rect: egui::Rect| {
+            let w = measured.entry(*c).or_default();
+            *w = w.max(rect.width());
+        };
+        let mut note_open = false;
+        // The header stays in place while the rows scroll vertically; both scroll horizontally
+        egui::ScrollArea::horizontal()
+            .id_salt("accel_table_scroll")
             .show(ui, |ui| {
-                // Header row
-                ui.label(egui::RichText::new("Series ID").strong());
-                ui.label(egui::RichText::new("Название ряда").strong());
-                ui.label(egui::RichText::new("Precision").strong());
-                ui.label(egui::RichText::new("Предел ряда").strong());
-                ui.label(egui::RichText::new("Параметры ряда").strong());
-                ui.label(egui::RichText::new("Название ускорения").strong());
-                ui.label(egui::RichText::new("M").strong());
-                ui.label(egui::RichText::new("Параметры ускорения").strong());
-                ui.label(egui::RichText::new("S_n ряда").strong());
-                ui.label(egui::RichText::new("S_n ускорения").strong());
-                ui.label(egui::RichText::new("Отклонения").strong());
-                ui.label(egui::RichText::new("Ошибки").strong());
-                ui.label(egui::RichText::new("Событий").strong());
-                ui.end_row();
-                // Data rows
-                for (i, row) in table_rows.iter().enumerate() {
-                    ui.add(egui::Label::new(&row.0).wrap()); // Series ID
-                    ui.add(egui::Label::new(&row.1).wrap()); // Название ряда
-                    ui.add(egui::Label::new(&row.2).wrap()); // Precision
-                    ui.add(egui::Label::new(&row.3).wrap()); // Предел ряда
-                    ui.add(egui::Label::new(&row.4).wrap()); // Параметры ряда
-                    ui.add(egui::Label::new(&row.5).wrap()); // Название ускорения
-                    ui.add(egui::Label::new(&row.6).wrap()); // M
-                    ui.add(egui::Label::new(&row.7).wrap()); // Параметры ускорения
-                                                             // S_n ряда
-                    if row.8.is_empty() {
-                        ui.add(egui::Label::new("(нет точек)").wrap());
-                    } else {
-                        ui.collapsing(format!("#{i}: {} значений", row.8.len()), |ui| {
-                            for value in &row.8 {
-                                ui.label(value);
+                egui::Grid::new("accel_table_header")
+                    .max_col_width(100.0)
+                    .show(ui, |ui| {
+                        for c in &visible {
+                            let title = c.title(&params);
+                            let cell = sized_cell(ui, width(c), |ui| {
+                                ui.label(egui::RichText::new(title).strong())
+                            });
+                            measure(c, cell.response.rect);
+                        }
+                        ui.end_row();
+                    });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .id_salt("accel_table_rows")
+                    .max_height(ui.ctx().screen_rect().height() * 0.7)
+                    .show_viewport(ui, |ui, viewport| {
+                        let mut heights = heights.borrow_mut();
+                        let row_heights: Vec<f32> = rows
+                            .iter()
+                            .map(|&i| {
+                                if heights[i] > 0.0 {
+                                    heights[i]
+                                } else {
+                                    estimate
+                                }
+                            })
+                            .collect();
+                        // Rows in the viewport, from an even one so that the stripes stay in place
+                        let (mut first, mut top) = (0, 0.0);
+                        while first < rows.len() && top + row_heights[first] <= viewport.min.y {
+                            top += row_heights[first];
+                            first += 1;
+                        }
+                        if first % 2 == 1 {
+                            first -= 1;
+                            top -= row_heights[first];
+                        }
+                        let (mut end, mut bottom) = (first, top);
+                        while end < rows.len() && bottom < viewport.max.y {
+                            bottom += row_heights[end];
+                            end += 1;
+                        }
+                        ui.set_height((row_heights.iter().sum::<f32>() - spacing).max(0.0));
+                        let max_rect = ui.max_rect();
+                        let rect = egui::Rect::from_x_y_ranges(
+                            max_rect.x_range(),
+                            max_rect.top() + top..=max_rect.bottom(),
+                        );
+                        ui.scope_builder(egui::UiBuilder::new().max_rect(rect), |ui| {
+                            egui::Grid::new("accel_table")
+                                .striped(true)
+                                .max_col_width(100.0)
+                                .show(ui, |ui| {
+                                    // Data rows
+                                    for &i in &rows[first..end] {
+                                        let row = &table_rows[i];
+                                        let mut row_height = 0.0f32;
+                                        for c in &visible {
+                                            let cell = sized_cell(ui, width(c), |ui| {
+                                                match c {
+                            TableColumn::Pin => ui
+                                .selectable_label(vis.is_pinned(&row.13), "📌")
+                                .on_hover_text("Закрепить линию на графиках"),
+                            TableColumn::Anomalies => {
+                                if row.15.is_empty() {
+                                    ui.label("")
+                                } else {
+                                    ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+                                        .on_hover_text(&row.15)
+                                }
                             }
-                        });
-                    }
-                    // S_n ускорения
-                    if row.9.is_empty() {
-                        ui.add(egui::Label::new("(нет точек)").wrap());
-                    } else {
-                        ui.collapsing(format!("#{i}: {} значений", row.9.len()), |ui| {
-                            for value in &row.9 {
-                                ui.label(value);
+                            TableColumn::SeriesId => text_cell(ui, vis, &row.13, &row.0),
+                            TableColumn::SeriesName => text_cell(ui, vis, &row.13, &row.1),
+                            TableColumn::Precision => text_cell(ui, vis, &row.13, &row.2),
+                            TableColumn::SeriesLimit => text_cell(ui, vis, &row.13, &row.3),
+                            TableColumn::SeriesParams => text_cell(ui, vis, &row.13, &row.4),
+                            TableColumn::AccelName => text_cell(ui, vis, &row.13, &row.5),
+                            TableColumn::M => text_cell(ui, vis, &row.13, &row.6),
+                            TableColumn::AccelParams => text_cell(ui, vis, &row.13, &row.7),
+                            TableColumn::SeriesValues => {
+                                list_cell(ui, i, None, &row.8, "(нет точек)", "значений")
                             }
-                        });
-                    }
-                    // Отклонения
-                    if row.10.is_empty() {
-                        ui.add(egui::Label::new("(нет данных)").wrap());
-                    } else {
-                        ui.collapsing(format!("#{i}: {} значений", row.10.len()), |ui| {
-                            for value in &row.10 {
-                                ui.label(value);
+                            TableColumn::AccelValues => {
+                                list_cell(ui, i, None, &row.9, "(нет точек)", "значений")
                             }
-                        });
-                    }
-                    // Ошибки
-                    if row.11.is_empty() {
-                        ui.add(egui::Label::new("(нет ошибок)").wrap());
-                    } else {
-                        ui.collapsing(format!("#{i}: {} ошибок", row.11.len()), |ui| {
-                            for value in &row.11 {
-                                ui.label(value);
+                            TableColumn::Deviations => list_cell(
+                                ui,
+                                i,
+                                Some(&row.16[vis.opts.deviation_average as usize]),
+                                &row.10,
+                                "(нет данных)",
+                                "значений",
+                            ),
+                            TableColumn::Errors => {
+                                list_cell(ui, i, None, &row.11, "(нет ошибок)", "ошибок")
                             }
-                        });
-                    }
-                    // Событий
-                    if row.12.is_empty() {
-                        ui.add(egui::Label::new("(нет событий)").wrap());
-                    } else {
-                        ui.collapsing(format!("#{i}: {} событий", row.12.len()), |ui| {
-                            for value in &row.12 {
-                                ui.label(value);
+                            TableColumn::Events => {
+                                list_cell(ui, i, None, &row.12, "(нет событий)", "событий")
+                            }
+                            TableColumn::Plateau => ui
+                                .label(cell_text(row, *c).unwrap_or_default())
+                                .on_hover_text(
+                                    "Итерация, после которой ошибка перестаёт заметно уменьшаться",
+                                ),
+                            TableColumn::VsPartialSum => ui
+                                .label(cell_text(row, *c).unwrap_or_default())
+                                .on_hover_text(
+                                    "На скольких итерациях ускорение точнее частичной суммы \
+                                     и медиана отношения их отклонений",
+                                ),
+                            TableColumn::Metric(_) | TableColumn::ErrorCount => {
+                                ui.label(cell_text(row, *c).unwrap_or_default())
+                            }
+                            TableColumn::Note => {
+                                let key = &row.13;
+                                let note = vis
+                                    .workspace
+                                    .record_note(key.series_id, &key.accel_info)
+                                    .unwrap_or_default()
+                                    .to_string();
+                                let title = if note.is_empty() {
+                                    "📝".to_string()
+                                } else {
+                                    format!("📝 {note}")
+                                };
+                                let menu = ui.menu_button(title, |ui| {
+                                    // Stored only once edited, so that opening the menu adds
+                                    // nothing; written to disk once the editor closes
+                                    let mut draft = note.clone();
+                                    let response = ui.text_edit_multiline(&mut draft);
+                                    if response.changed() {
+                                        vis.workspace.set_record_note(
+                                            key.series_id,
+                                            &key.accel_info,
+                                            draft,
+                                        );
+                                        vis.unsaved_note = true;
+                                    }
+                                    if response.lost_focus() {
+                                        vis.save_note();
+                                    }
+                                });
+                                note_open |= menu.inner.is_some();
+                                menu.response
+                            }
+                            TableColumn::Json => {
+                                // Everything the producer wrote, including fields without a
+                                // column; large with all the points, so serialized by the app
+                                // once the menu opens and dropped once it closes
+                                let json_id = table_id.with(("record_json", i));
+                                let menu = ui.menu_button("{ }", |ui| {
+                                    let Some(raw) = ui.data(|d| d.get_temp::<Arc<str>>(json_id))
+                                    else {
+                                        vis.json_request = Some((json_id, i));
+                                        ui.spinner();
+                                        return;
+                                    };
+                                    if ui.button("📋 Копировать").clicked() {
+                                        ui.ctx().copy_text(raw.to_string());
+                                    }
+                                    egui::ScrollArea::both().max_height(600.0).show(ui, |ui| {
+                                        ui.add(
+                                            egui::Label::new(
+                                                egui::RichText::new(&*raw).monospace(),
+                                            )
+                                            .extend(),
+                                        );
+                                    });
+                                });
+                                if menu.inner.is_none() {
+                                    ui.data_mut(|d| d.remove::<Arc<str>>(json_id));
+                                }
+                                menu.response
+                                    .on_hover_text("Исходные записи ряда и ускорения")
                             }
+                        }
+                                            });
+                                            measure(c, cell.response.rect);
+                                            row_height =
+                                                row_height.max(cell.response.rect.height());
+                                            let response = cell.inner;
+                                            if *c == TableColumn::Pin && response.clicked() {
+                                                vis.toggle_pin(&row.13);
+                                            }
+                                            response.interact(egui::Sense::click()).context_menu(
+                                                |ui| {
+                                                    let values = match c {
+                                                        TableColumn::SeriesValues => Some(&row.8),
+                                                        TableColumn::AccelValues => Some(&row.9),
+                                                        TableColumn::Deviations => Some(&row.10),
+                                                        TableColumn::Errors => Some(&row.11),
+                                                        TableColumn::Events => Some(&row.12),
+                                                        _ => None,
+                                                    };
+                                                    if let Some(values) = values {
+                                                        if ui
+                                                            .button("📋 Копировать значения по n")
+                                                            .clicked()
+                                                        {
+                                                            ui.ctx().copy_text(values.join("\n"));
+                                                            ui.close_menu();
+                                                        }
+                                                    } else if let Some(text) = cell_text(row, *c)
+                                                        && ui
+                                                            .button("📋 Копировать ячейку")
+                                                            .clicked()
+                                                    {
+                                                        ui.ctx().copy_text(text);
+                                                        ui.close_menu();
+                                                    }
+                                                    if ui
+                                                        .button("📋 Копировать строку (TSV)")
+                                                        .clicked()
+                                                    {
+                                                        let cells: Vec<String> = visible
+                                                            .iter()
+                                                            .filter_map(|c| cell_text(row, *c))
+                                                            .collect();
+                                                        ui.ctx().copy_text(cells.join("\t"));
+                                                        ui.close_menu();
+                                                    }
+                                                },
+                                            );
+                                        }
+                                        ui.end_row();
+                                        heights[i] = row_height + spacing;
+                                    }
+                                });
                         });
-                    }
-                    ui.end_row();
-                }
+                    });
+                ui.separator();
+                // Footer, labelled in the first visible column
+                egui::Grid::new("accel_table_footer")
+                    .max_col_width(100.0)
+                    .show(ui, |ui| {
+                        for (i, c) in visible.iter().enumerate() {
+                            let stat = found
+                                .footer
+                                .entry(*c)
+                                .or_insert_with(|| footer_stat(c))
+                                .clone();
+                            let cell = sized_cell(ui, width(c), |ui| {
+                                if i > 0 {
+                                    ui.label(stat.unwrap_or_default());
+                                    return;
+                                }
+                                let text = match stat {
+                                    Some(stat) => format!("Итого: {stat}"),
+                                    None => "Итого".to_string(),
+                                };
+                                ui.label(egui::RichText::new(text).strong()).on_hover_text(
+                                    "Минимум по строкам, медианы по строкам, сумма ошибок",
+                                );
+                            });
+                            measure(c, cell.response.rect);
+                        }
+                        ui.end_row();
+                    });
             });
+        *widths.borrow_mut() = measured;
+        if !note_open {
+            vis.save_note();
+        }
+    }
+}
+
+// Grid cell at least `width` wide, so that the header and the rows line up
+fn sized_cell<R>(
+    ui: &mut Ui,
+    width: f32,
+    add_contents: impl FnOnce(&mut Ui) -> R,
+) -> egui::InnerResponse<R> {
+    ui.scope(|ui| {
+        ui.set_min_width(width);
+        add_contents(ui)
+    })
+}
+
+// Plain cell that selects its row on click (ctrl+click adds to the selection)
+fn text_cell(ui: &mut Ui, vis: &mut Vis, key: &LineKey, text: &str) -> egui::Response {
+    let mut text = egui::RichText::new(text);
+    if vis.is_selected(key) {
+        let selection = ui.visuals().selection;
+        text = text
+            .color(selection.stroke.color)
+            .background_color(selection.bg_fill);
+    }
+    let response = ui.add(egui::Label::new(text).wrap().sense(egui::Sense::click()));
+    if response.clicked() {
+        let toggle = ui.input(|i| i.modifiers.command);
+        vis.click_select(key, toggle);
+    }
+    response
+}
+
+// Collapsible list of per-n values, optionally headed by a summary of them
+fn list_cell(
+    ui: &mut Ui,
+    row: usize,
+    summary: Option<&str>,
+    values: &[String],
+    empty: &str,
+    unit: &str,
+) -> egui::Response {
+    if values.is_empty() {
+        ui.add(egui::Label::new(empty).wrap())
+    } else {
+        ui.collapsing(format!("#{row}: {} {unit}", values.len()), |ui| {
+            if let Some(summary) = summary {
+                ui.label(summary);
+            }
+            for value in values {
+                ui.label(value);
+            }
+        })
+        .header_response
+    }
+}
+
+// Visibility checkboxes and reordering buttons, returns whether the layout changed
+fn table_columns_ui(
+    ui: &mut Ui,
+    layout: &mut Vec<(TableColumn, bool)>,
+    params: &MetricParams,
+) -> bool {
+    // Materialize the default layout, so that it can be edited
+    *layout = TableColumn::normalize(mem::take(layout));
+    let mut changed = false;
+    let mut swap = None;
+    let len = layout.len();
+    for (i, (c, visible)) in layout.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            if ui.add_enabled(i > 0, egui::Button::new("⏶")).clicked() {
+                swap = Some(i - 1);
+            }
+            if ui
+                .add_enabled(i + 1 < len, egui::Button::new("⏷"))
+                .clicked()
+            {
+                swap = Some(i);
+            }
+            changed |= ui.checkbox(visible, c.title(params)).changed();
+        });
+    }
+    if let Some(i) = swap {
+        layout.swap(i, i + 1);
+        changed = true;
     }
+    ui.separator();
+    if ui.button("Сбросить").clicked() {
+        layout.clear();
+        changed = true;
+    }
+    changed
 }
 
+pub struct ConvergencePanel(CreateConvergencePlot);
+
+impl PlotPanel for ConvergencePanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        Self(create_convergence_plot(data))
+    }
+    fn id(&self) -> &'static str {
+        "convergence"
+    }
+    fn title(&self) -> &str {
+        "Сходимость методов"
+    }
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<egui::Rect> {
+        (self.0)(vis, ui)
+    }
+    // Partial sums and limits are plotted on their own
+    fn needs_accelerations(&self) -> bool {
+        false
+    }
+}
+
+pub struct ErrorPanel(CreateErrorPlot);
+
+impl PlotPanel for ErrorPanel {
+    fn build(data: &[SeriesDataRef], symlog: bool) -> Self {
+        Self(create_error_plot(data, symlog))
+    }
+    fn id(&self) -> &'static str {
+        "error"
+    }
+    fn title(&self) -> &str {
+        "Ошибка сходимости"
+    }
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<egui::Rect> {
+        (self.0)(vis, ui)
+    }
+    // Partial sums and limits are plotted on their own
+    fn needs_accelerations(&self) -> bool {
+        false
+    }
+}
+
+pub struct PerformancePanel(CreatePerformancePlot);
+
+impl PlotPanel for PerformancePanel {
+    fn build(data: &[SeriesDataRef], symlog: bool) -> Self {
+        Self(create_performance_plot(data, symlog))
+    }
+    fn id(&self) -> &'static str {
+        "performance"
+    }
+    fn title(&self) -> &str {
+        "Производительность методов"
+    }
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<egui::Rect> {
+        (self.0)(vis, ui)
+    }
+}
+
+pub struct AccelRecordsTablePanel(CreateAccelRecordsTable);
+
+impl PlotPanel for AccelRecordsTablePanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        Self(create_accel_records_table(data))
+    }
+    fn id(&self) -> &'static str {
+        "accel_table"
+    }
+    fn title(&self) -> &str {
+        "Таблица ускорений"
+    }
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<egui::Rect> {
+        (self.0)(vis, ui);
+        None
+    }
+}
+
+// Renders a panel with its screenshot button; `variant` distinguishes the A/B halves
+fn show_panel(
+    panel: &dyn PlotPanel,
+    vis: &mut Vis,
+    ui: &mut Ui,
+    variant: &'static str,
+    filters: [&Filters; 2],
+) {
+    if let Some(rect) = labelled_panel_ui(panel, vis, ui) {
+        ui.horizontal(|ui| {
+            if ui.button("📸 Снимок экрана").clicked() {
+                let filters = provenance_filters(filters);
+                if vis.screenshot_scale > 1 || vis.screenshot_caption {
+                    vis.export_request = Some(Export {
+                        panel_id: panel.id(),
+                        variant,
+                        size: rect.size(),
+                        scale: vis.screenshot_scale as f32,
+                        original_ppp: ui.ctx().pixels_per_point(),
+                        frames: 0,
+                        requested: false,
+                        filters,
+                    });
+                    ui.ctx().request_repaint();
+                } else {
+                    let plot_id = format!("{}{variant}", panel.id());
+                    vis.request_screenshot(ui.ctx(), plot_id, rect, filters);
+                }
+            }
+            ui.add(
+                egui::DragValue::new(&mut vis.screenshot_scale)
+                    .range(1..=4)
+                    .prefix("×"),
+            )
+            .on_hover_text("Разрешение снимка относительно экрана");
+            ui.checkbox(&mut vis.screenshot_caption, "Подпись")
+                .on_hover_text("Данные, фильтры, версия и время под изображением");
+            ui.menu_button("✏ Подписи", |ui| {
+                plot_labels_ui(ui, vis, panel.id())
+            })
+            .response
+            .on_hover_text("Заголовок, подписи осей и подпись к рисунку");
+            if vega::supports(panel.id())
+                && ui
+                    .button("📄 Vega-Lite")
+                    .on_hover_text(
+                        "Спецификация графика с данными в JSON, чтобы оформить его в другой \
+                         программе",
+                    )
+                    .clicked()
+            {
+                vis.spec_request = Some((panel.id(), variant));
+            }
+            if plot_data::supports(panel.id())
+                && ui
+                    .button("🔢 Данные")
+                    .on_hover_text(
+                        "Точки графика таблицей: значения как загружены и как на графике",
+                    )
+                    .clicked()
+            {
+                vis.data_request = Some((panel.id(), variant));
+            }
+        });
+    }
+}
+
+// Panel with its custom title above and caption below; the returned area includes them
+pub fn labelled_panel_ui(panel: &dyn PlotPanel, vis: &mut Vis, ui: &mut Ui) -> Option<egui::Rect> {
+    let labels = vis.plot_labels(panel.id());
+    let title = (!labels.title.is_empty())
+        .then(|| ui.label(egui::RichText::new(&labels.title).heading()).rect);
+    let rect = panel.ui(vis, ui)?;
+    let caption = (!labels.caption.is_empty()).then(|| {
+        ui.add(egui::Label::new(&labels.caption).wrap_mode(egui::TextWrapMode::Wrap))
+            .rect
+    });
+    Some(
+        title
+            .into_iter()
+            .chain(caption)
+            .fold(rect, |r, other| r.union(other)),
+    )
+}
+
+// Editor of the custom texts of a plot, saved to the workspace
+fn plot_labels_ui(ui: &mut Ui, vis: &mut Vis, plot_id: &str) {
+    let labels = vis
+        .workspace
+        .plot_labels
+        .entry(plot_id.to_string())
+        .or_default();
+    let mut save = false;
+    egui::Grid::new(("plot_labels", plot_id))
+        .num_columns(2)
+        .show(ui, |ui| {
+            for (name, text) in [
+                ("Заголовок", &mut labels.title),
+                ("Ось X", &mut labels.x_axis),
+                ("Ось Y", &mut labels.y_axis),
+                ("Подпись", &mut labels.caption),
+            ] {
+                ui.label(name);
+                let edit = egui::TextEdit::singleline(text).hint_text("по умолчанию");
+                save |= ui.add(edit).lost_focus();
+                ui.end_row();
+            }
+        });
+    if save {
+        vis.save_workspace();
+    }
+}
+
+/// Screenshot at a multiple of the screen resolution.
+///
+/// The framebuffer only has as many pixels as the window, so the panel is drawn alone in the
+/// top-left corner at its on-screen size in points, with `pixels_per_point` temporarily raised.
+struct Export {
+    panel_id: &'static str,
+    // "", "_a" or "_b", as in `show_panel`
+    variant: &'static str,
+    size: egui::Vec2,
+    scale: f32,
+    original_ppp: f32,
+    frames: u32,
+    // The screenshot command was sent, waiting for the image
+    requested: bool,
+    filters: String,
+}
+
+// Frames to wait for the scaled screenshot before giving up
+const EXPORT_TIMEOUT_FRAMES: u32 = 30;
+
 // Генерируем UI для фильтров (полноширинный layout с переносом строк)
 fn filter_section_horizontal(
     ui: &mut Ui,
     title: &str,
     items: &[String],
-    selected: &mut HashSet<String>,
+    selected: &mut HashSet<IStr>,
     show_all: &mut bool,
 ) {
     ui.horizontal(|ui| {
         ui.label(format!("{}:", title));
-        if ui.button("All").clicked() {
+        let all = ui.button("All");
+        if in_group(all, WidgetType::Button, None, title, "выбрать все").clicked() {
             *show_all = true;
-            selected.extend(items.iter().cloned());
+            selected.extend(items.iter().map(|item| intern(item)));
         }
-        if ui.button("None").clicked() {
+        let none = ui.button("None");
+        if in_group(none, WidgetType::Button, None, title, "снять все").clicked() {
             *show_all = false;
             selected.clear();
         }
@@ -908,12 +3939,13 @@ fn filter_section_horizontal(
     // Use wrapping layout for checkboxes
     ui.horizontal_wrapped(|ui| {
         for item in items {
-            let mut checked = selected.contains(item);
-            if ui.checkbox(&mut checked, item).changed() {
+            let mut checked = selected.contains(item.as_str());
+            let checkbox = ui.checkbox(&mut checked, item);
+            if in_group(checkbox, WidgetType::Checkbox, Some(checked), title, item).changed() {
                 if checked {
-                    selected.insert(item.clone());
+                    selected.insert(intern(item));
                 } else {
-                    selected.remove(item);
+                    selected.remove(item.as_str());
                 }
             }
         }
@@ -921,12 +3953,161 @@ fn filter_section_horizontal(
     ui.add_space(5.0);
 }
 
+// "any / present / absent" selector for an optional predicate, returns whether it changed
+fn tristate_filter(ui: &mut Ui, title: &str, value: &mut Option<bool>) -> bool {
+    ui.label(format!("{}:", title));
+    let mut changed = false;
+    for (option, text) in [(None, "все"), (Some(true), "есть"), (Some(false), "нет")] {
+        let response = ui.selectable_value(value, option, text);
+        let selected = Some(*value == option);
+        changed |= in_group(response, WidgetType::SelectableLabel, selected, title, text).changed();
+    }
+    changed
+}
+
+// Selecting the first value of a parameter filter also selects `NOT_SET` when it's offered, so
+// that the records without the parameter (e.g. of other methods) stay until it's unchecked
+fn select_param_value(selection: &mut HashSet<IStr>, value: &str, offers_not_set: bool) {
+    if selection.is_empty() && offers_not_set && value != NOT_SET {
+        selection.insert(intern(NOT_SET));
+    }
+    selection.insert(intern(value));
+}
+
+// Whether any of the methods is selected; none selected means all are
+fn any_selected<S: AsRef<str>>(selected: &HashSet<IStr>, methods: &[S]) -> bool {
+    selected.is_empty() || methods.iter().any(|m| selected.contains(m.as_ref()))
+}
+
+// Values of a parameter filter, by their kind: checkboxes for text, a range for numbers, a switch
+// for flags. `values` are sorted by `sort_param_values`. Returns whether the selection changed
+fn param_values_ui(ui: &mut Ui, values: &[&str], selection: &mut HashSet<IStr>) -> bool {
+    let offers_not_set = values.contains(&NOT_SET);
+    let set: Vec<&str> = values.iter().copied().filter(|v| *v != NOT_SET).collect();
+    let mut not_set = offers_not_set && (selection.is_empty() || selection.contains(NOT_SET));
+    let mut changed = false;
+    match ParamKind::of(set.iter().copied()) {
+        ParamKind::Text => {
+            for &value in values {
+                let mut checked = selection.contains(value);
+                if ui.checkbox(&mut checked, value).changed() {
+                    if checked {
+                        select_param_value(selection, value, offers_not_set);
+                    } else {
+                        selection.remove(value);
+                    }
+                    changed = true;
+                }
+            }
+        }
+        ParamKind::Number => {
+            // Range of the known values, by index, so that it always holds some
+            let selected: Vec<usize> = (0..set.len())
+                .filter(|&i| selection.contains(set[i]))
+                .collect();
+            let mut from = selected.first().copied().unwrap_or(0);
+            let mut to = selected.last().copied().unwrap_or(set.len() - 1);
+            let value = |i: f64| set[i as usize].to_string();
+            ui.label("от");
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut from, 0..=set.len() - 1)
+                        .custom_formatter(|i, _| value(i)),
+                )
+                .changed();
+            ui.label("до");
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut to, 0..=set.len() - 1).custom_formatter(|i, _| value(i)),
+                )
+                .changed();
+            if offers_not_set {
+                changed |= ui.checkbox(&mut not_set, NOT_SET).changed();
+            }
+            if changed {
+                let to = to.max(from);
+                set_param_selection(
+                    selection,
+                    &set[from..=to],
+                    set.len(),
+                    not_set,
+                    offers_not_set,
+                );
+            }
+        }
+        ParamKind::Bool => {
+            let flags: HashSet<bool> = set
+                .iter()
+                .filter(|v| selection.contains(**v))
+                .filter_map(|v| parse_bool(v))
+                .collect();
+            let mut flag = if flags.len() == 1 {
+                flags.into_iter().next()
+            } else {
+                None
+            };
+            changed |= ui.selectable_value(&mut flag, None, "все").changed();
+            changed |= ui.selectable_value(&mut flag, Some(true), "да").changed();
+            changed |= ui.selectable_value(&mut flag, Some(false), "нет").changed();
+            if offers_not_set {
+                changed |= ui.checkbox(&mut not_set, NOT_SET).changed();
+            }
+            if changed {
+                let kept: Vec<&str> = set
+                    .iter()
+                    .copied()
+                    .filter(|v| flag.is_none_or(|flag| parse_bool(v) == Some(flag)))
+                    .collect();
+                set_param_selection(selection, &kept, set.len(), not_set, offers_not_set);
+            }
+        }
+    }
+    changed
+}
+
+// Selects `kept` of the `total` values and the records without the parameter if `not_set`; no
+// selection at all when that's everything
+fn set_param_selection(
+    selection: &mut HashSet<IStr>,
+    kept: &[&str],
+    total: usize,
+    not_set: bool,
+    offers_not_set: bool,
+) {
+    selection.clear();
+    if kept.len() == total && (not_set || !offers_not_set) {
+        return;
+    }
+    selection.extend(kept.iter().map(|v| intern(v)));
+    if not_set {
+        selection.insert(intern(NOT_SET));
+    }
+}
+
+// Values of a parameter in the quick filters, see `param_values_ui`
+fn quick_param_ui(
+    ui: &mut Ui,
+    param_name: &IStr,
+    values: &HashSet<IStr>,
+    selected_params: &mut HashMap<IStr, HashSet<IStr>>,
+) -> bool {
+    let mut values: Vec<&str> = values.iter().map(|v| v.as_ref()).collect();
+    sort_param_values(&mut values);
+    let selection = selected_params.entry(param_name.clone()).or_default();
+    let changed = param_values_ui(ui, &values, selection);
+    if selection.is_empty() {
+        selected_params.remove(param_name);
+    }
+    changed
+}
+
 // For top-lvel filtering
 fn param_filter_section(
     ui: &mut Ui,
     title: &str,
     param_info: &HashMap<String, Vec<String>>,
-    selected_params: &mut HashMap<String, HashSet<String>>,
+    param_methods: &HashMap<String, Vec<String>>,
+    selected_params: &mut HashMap<IStr, HashSet<IStr>>,
 ) {
     if param_info.is_empty() {
         return;
@@ -936,39 +4117,30 @@ fn param_filter_section(
     ui.add_space(2.0);
 
     for (param_name, values) in param_info {
-        // Get current selection, but don't create empty entry automatically
-        let param_selected = selected_params.get(param_name).cloned().unwrap_or_default();
-
         // Compact inline layout: parameter name, All/None buttons, and checkboxes all in one wrapped section
         ui.horizontal_wrapped(|ui| {
             ui.label(format!("{}:", param_name));
+            if let Some(methods) = param_methods.get(param_name) {
+                ui.weak(format!("({})", methods.join(", ")));
+            }
 
-            let mut new_selection = param_selected.clone();
+            // Edited in place; an empty selection is removed below
+            let new_selection = selected_params.entry(intern(param_name)).or_default();
 
             if ui.button("All").clicked() {
-                new_selection.extend(values.iter().cloned());
+                new_selection.extend(values.iter().map(|value| intern(value)));
             }
             if ui.button("None").clicked() {
                 new_selection.clear();
             }
 
-            // Add checkboxes inline with the parameter name and buttons
-            for value in values {
-                let mut checked = new_selection.contains(value);
-                if ui.checkbox(&mut checked, value).changed() {
-                    if checked {
-                        new_selection.insert(value.clone());
-                    } else {
-                        new_selection.remove(value);
-                    }
-                }
-            }
+            // Add the values inline with the parameter name and buttons
+            let values: Vec<&str> = values.iter().map(String::as_str).collect();
+            param_values_ui(ui, &values, new_selection);
 
             // Only store the selection if it's not empty, otherwise remove the entry
             if new_selection.is_empty() {
-                selected_params.remove(param_name);
-            } else {
-                selected_params.insert(param_name.clone(), new_selection);
+                selected_params.remove(param_name.as_str());
             }
         });
         ui.add_space(1.0);
@@ -978,10 +4150,32 @@ fn param_filter_section(
 
 pub struct FilteredData {
     selected_filters: Filters,
-    create_convergence_plot: CreateConvergencePlot,
-    create_error_plot: CreateErrorPlot,
-    create_performance_plot: CreatePerformancePlot,
-    create_accel_records_table: CreateAccelRecordsTable,
+    panels: Vec<Box<dyn PlotPanel>>,
+    // Series and accel records that passed the filters
+    matched: (usize, usize),
+    // What left no records, if none passed
+    diagnosis: Option<Diagnosis>,
+    // Methods whose records set each acceleration parameter, in all the data
+    accel_param_methods: HashMap<IStr, Vec<IStr>>,
+    // Parameters filtered on in the query that loaded the data, see `NameParams::Filtered`
+    query_params: HashSet<IStr>,
+    // Series and accel indices in `Data::data` of the records that passed, one per table row
+    records: Vec<(usize, usize)>,
+}
+
+// Whether no quick filter is selected
+fn no_quick_filters(filters: &Filters) -> bool {
+    filters.precisions.is_empty()
+        && filters.base_series.is_empty()
+        && filters.families.is_empty()
+        && filters.base_accel.is_empty()
+        && filters.m_values.is_empty()
+        && filters.series_params.is_empty()
+        && filters.accel_params.is_empty()
+        && filters.has_errors.is_none()
+        && filters.has_events.is_none()
+        && filters.event_names.is_empty()
+        && filters.divergent.is_none()
 }
 
 impl FilteredData {
@@ -989,93 +4183,91 @@ impl FilteredData {
         data_items: &'a [(SeriesRecord, Vec<AccelRecord>)],
         filters: &Filters,
     ) -> Vec<(&'a SeriesRecord, Vec<&'a AccelRecord>)> {
+        Self::records_at(data_items, &Self::filter_indices(data_items, filters))
+    }
+
+    fn records_at<'a>(
+        data_items: &'a [(SeriesRecord, Vec<AccelRecord>)],
+        indices: &[(usize, Vec<usize>)],
+    ) -> Vec<SeriesDataRef<'a>> {
+        indices
+            .iter()
+            .map(|(i, accel_indices)| {
+                let (series, accel_records) = &data_items[*i];
+                (
+                    series,
+                    accel_indices.iter().map(|j| &accel_records[*j]).collect(),
+                )
+            })
+            .collect()
+    }
+
+    // Indices of the series and of their accel records that pass the filters
+    fn filter_indices(
+        data_items: &[(SeriesRecord, Vec<AccelRecord>)],
+        filters: &Filters,
+    ) -> Vec<(usize, Vec<usize>)> {
         // Early return if no filters
-        if filters.precisions.is_empty()
-            && filters.base_series.is_empty()
-            && filters.base_accel.is_empty()
-            && filters.m_values.is_empty()
-            && filters.series_params.is_empty()
-            && filters.accel_params.is_empty()
-        {
+        if no_quick_filters(filters) {
             return data_items
                 .iter()
-                .map(|(series, accel_records)| (series, accel_records.iter().collect()))
+                .enumerate()
+                .map(|(i, (_, accel_records))| (i, (0..accel_records.len()).collect()))
                 .collect();
         }
+        // Parameter filters apply only to the methods that set the parameter
+        let param_methods = if filters.accel_params.is_empty() {
+            HashMap::new()
+        } else {
+            accel_param_methods(data_items)
+        };
+        let accel_match = |series: &SeriesRecord, accel_record: &AccelRecord| {
+            let accel_match = filters.base_accel.is_empty()
+                || filters.base_accel.contains(&accel_record.accel_info.name);
+
+            let m_value_match = filters.m_values.is_empty()
+                || filters.m_values.contains(&accel_record.accel_info.m_value);
+
+            let accel_params_match = accel_params_match(
+                &accel_record.accel_info,
+                &filters.accel_params,
+                &param_methods,
+            );
+            accel_match
+                && m_value_match
+                && accel_params_match
+                && filters.matches_outcome(accel_record)
+                && filters
+                    .divergent
+                    .is_none_or(|d| d == !anomalies(series, accel_record).is_empty())
+        };
         data_items
             .iter()
-            .filter(|(series, accel_records)| {
+            .enumerate()
+            .filter(|(_, (series, _))| {
                 // Series-level filtering
                 let precision_match =
                     filters.precisions.is_empty() || filters.precisions.contains(&series.precision);
 
                 let series_match =
                     filters.base_series.is_empty() || filters.base_series.contains(&series.name);
-
-                let series_params_match = filters.series_params.is_empty()
+                let family_match = filters.families.is_empty()
                     || filters
-                        .series_params
-                        .iter()
-                        .all(|(param_name, allowed_values)| {
-                            series
-                                .arguments
-                                .get(param_name)
-                                .map(|value| allowed_values.contains(value))
-                                .unwrap_or(false)
-                        });
-                if !precision_match || !series_match || !series_params_match {
-                    return false;
-                }
-                // Check if any acceleration records match
-                accel_records.iter().any(|accel_record| {
-                    let accel_match = filters.base_accel.is_empty()
-                        || filters.base_accel.contains(&accel_record.accel_info.name);
-
-                    let m_value_match = filters.m_values.is_empty()
-                        || filters.m_values.contains(&accel_record.accel_info.m_value);
+                        .families
+                        .contains(series_family(&series.name).as_str());
 
-                    let accel_params_match = filters.accel_params.is_empty()
-                        || filters
-                            .accel_params
-                            .iter()
-                            .all(|(param_name, allowed_values)| {
-                                accel_record
-                                    .accel_info
-                                    .additional_args
-                                    .get(param_name)
-                                    .map(|value| allowed_values.contains(value))
-                                    .unwrap_or(false)
-                            });
-                    accel_match && m_value_match && accel_params_match
-                })
+                let series_params_match = params_match(&series.arguments, &filters.series_params);
+                precision_match && series_match && family_match && series_params_match
             })
-            .map(|(series, accel_records)| {
-                // Filter acceleration records for the final result
-                let filtered_accel_records: Vec<&'a AccelRecord> = accel_records
-                    .iter()
-                    .filter(|accel_record| {
-                        let accel_match = filters.base_accel.is_empty()
-                            || filters.base_accel.contains(&accel_record.accel_info.name);
-
-                        let m_value_match = filters.m_values.is_empty()
-                            || filters.m_values.contains(&accel_record.accel_info.m_value);
-
-                        let accel_params_match = filters.accel_params.is_empty()
-                            || filters
-                                .accel_params
-                                .iter()
-                                .all(|(param_name, allowed_values)| {
-                                    accel_record
-                                        .accel_info
-                                        .additional_args
-                                        .get(param_name)
-                                        .map(|value| allowed_values.contains(value))
-                                        .unwrap_or(false)
-                                });
-                        accel_match && m_value_match && accel_params_match
-                    })
+            .filter_map(|(i, (series, accel_records))| {
+                // Acceleration records for the final result; series without any are left out
+                let accel_indices: Vec<usize> = accel_records
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, accel_record)| accel_match(series, accel_record))
+                    .map(|(j, _)| j)
                     .collect();
-                (series, filtered_accel_records)
+                (!accel_indices.is_empty()).then_some((i, accel_indices))
             })
             .collect()
     }
@@ -1084,20 +4276,40 @@ impl FilteredData {
     #[must_use]
     fn dynamic_ui_filter_section(
         ui: &mut Ui,
+        title: &str,
         available_filters: &Filters,
         selected_filters: &mut Filters,
+        accel_param_methods: &HashMap<IStr, Vec<IStr>>,
+        (series, records): (usize, usize),
     ) -> bool {
         if available_filters.precisions.is_empty()
             && available_filters.base_series.is_empty()
+            && available_filters.families.is_empty()
             && available_filters.base_accel.is_empty()
             && available_filters.m_values.is_empty()
             && available_filters.series_params.is_empty()
             && available_filters.accel_params.is_empty()
+            && available_filters.has_errors.is_none()
+            && available_filters.has_events.is_none()
+            && available_filters.divergent.is_none()
         {
             return false;
         }
         let mut updated = false;
-        ui.heading("Быстрые фильтры");
+        ui.horizontal(|ui| {
+            ui.heading(title);
+            let matched = format!("{series} рядов, {records} записей");
+            if records == 0 {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {matched}"));
+            } else {
+                ui.label(matched);
+            }
+            if !no_quick_filters(selected_filters) && ui.button("✖ Сбросить все").clicked()
+            {
+                *selected_filters = Filters::default();
+                updated = true;
+            }
+        });
         ui.add_space(5.0);
 
         let mut first_group = true;
@@ -1118,7 +4330,7 @@ impl FilteredData {
                 ui.label(egui::RichText::new("prec:").strong());
                 for precision in &available_filters.precisions {
                     let mut checked = selected_filters.precisions.contains(precision);
-                    if ui.checkbox(&mut checked, precision).changed() {
+                    if ui.checkbox(&mut checked, precision.as_ref()).changed() {
                         if checked {
                             selected_filters.precisions.insert(precision.clone());
                         } else {
@@ -1137,7 +4349,7 @@ impl FilteredData {
                 ui.label(egui::RichText::new("series:").strong());
                 for series in &available_filters.base_series {
                     let mut checked = selected_filters.base_series.contains(series);
-                    if ui.checkbox(&mut checked, series).changed() {
+                    if ui.checkbox(&mut checked, series.as_ref()).changed() {
                         if checked {
                             selected_filters.base_series.insert(series.clone());
                         } else {
@@ -1149,6 +4361,26 @@ impl FilteredData {
             });
         }
 
+        // Family group
+        if !available_filters.families.is_empty() {
+            add_separator(ui);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("family:").strong())
+                    .on_hover_text("Семейства рядов по шаблону имени, см. «Семейство рядов»");
+                for family in &available_filters.families {
+                    let mut checked = selected_filters.families.contains(family);
+                    if ui.checkbox(&mut checked, family.as_ref()).changed() {
+                        if checked {
+                            selected_filters.families.insert(family.clone());
+                        } else {
+                            selected_filters.families.remove(family);
+                        }
+                        updated = true;
+                    }
+                }
+            });
+        }
+
         // Acceleration group
         if !available_filters.base_accel.is_empty() {
             add_separator(ui);
@@ -1156,7 +4388,7 @@ impl FilteredData {
                 ui.label(egui::RichText::new("accel:").strong());
                 for accel in &available_filters.base_accel {
                     let mut checked = selected_filters.base_accel.contains(accel);
-                    if ui.checkbox(&mut checked, accel).changed() {
+                    if ui.checkbox(&mut checked, accel.as_ref()).changed() {
                         if checked {
                             selected_filters.base_accel.insert(accel.clone());
                         } else {
@@ -1192,60 +4424,60 @@ impl FilteredData {
             add_separator(ui);
             ui.horizontal(|ui| {
                 ui.label(egui::RichText::new(&format!("{}:", param_name)).strong());
-                for value in values {
-                    let param_selected = selected_filters
-                        .series_params
-                        .get(param_name)
-                        .map(|set| set.contains(value))
-                        .unwrap_or(false);
-                    let mut checked = param_selected;
-                    if ui.checkbox(&mut checked, value).changed() {
-                        if checked {
-                            selected_filters
-                                .series_params
-                                .entry(param_name.clone())
-                                .or_insert_with(HashSet::new)
-                                .insert(value.clone());
-                        } else {
-                            if let Some(set) = selected_filters.series_params.get_mut(param_name) {
-                                set.remove(value);
-                                if set.is_empty() {
-                                    selected_filters.series_params.remove(param_name);
-                                }
-                            }
-                        }
-                        updated = true;
-                    }
-                }
+                updated |=
+                    quick_param_ui(ui, param_name, values, &mut selected_filters.series_params);
             });
         }
 
-        // Acceleration parameters groups
+        // Acceleration parameters groups, of the selected methods only
         for (param_name, values) in &available_filters.accel_params {
+            let methods = accel_param_methods.get(param_name);
+            if !methods.is_none_or(|methods| any_selected(&selected_filters.base_accel, methods)) {
+                continue;
+            }
             add_separator(ui);
             ui.horizontal(|ui| {
                 ui.label(egui::RichText::new(&format!("{}:", param_name)).strong());
-                for value in values {
-                    let param_selected = selected_filters
-                        .accel_params
-                        .get(param_name)
-                        .map(|set| set.contains(value))
-                        .unwrap_or(false);
-                    let mut checked = param_selected;
-                    if ui.checkbox(&mut checked, value).changed() {
+                if let Some(methods) = methods {
+                    ui.weak(format!("({})", methods.join(", ")));
+                }
+                updated |=
+                    quick_param_ui(ui, param_name, values, &mut selected_filters.accel_params);
+            });
+        }
+
+        // Outcome group
+        if available_filters.has_errors.is_some()
+            || available_filters.has_events.is_some()
+            || available_filters.divergent.is_some()
+        {
+            add_separator(ui);
+            ui.horizontal_wrapped(|ui| {
+                ui.label(egui::RichText::new("outcome:").strong());
+                if available_filters.has_errors.is_some() {
+                    updated |= tristate_filter(ui, "ошибки", &mut selected_filters.has_errors);
+                }
+                if available_filters.has_events.is_some() {
+                    updated |= tristate_filter(ui, "события", &mut selected_filters.has_events);
+                }
+                if available_filters.divergent.is_some() {
+                    ui.label("сходимость:");
+                    let value = &mut selected_filters.divergent;
+                    updated |= ui.selectable_value(value, None, "все").changed();
+                    updated |= ui
+                        .selectable_value(value, Some(true), "⚠ только расходящиеся")
+                        .changed();
+                    updated |= ui
+                        .selectable_value(value, Some(false), "только сходящиеся")
+                        .changed();
+                }
+                for name in &available_filters.event_names {
+                    let mut checked = selected_filters.event_names.contains(name);
+                    if ui.checkbox(&mut checked, name.as_ref()).changed() {
                         if checked {
-                            selected_filters
-                                .accel_params
-                                .entry(param_name.clone())
-                                .or_insert_with(HashSet::new)
-                                .insert(value.clone());
+                            selected_filters.event_names.insert(name.clone());
                         } else {
-                            if let Some(set) = selected_filters.accel_params.get_mut(param_name) {
-                                set.remove(value);
-                                if set.is_empty() {
-                                    selected_filters.accel_params.remove(param_name);
-                                }
-                            }
+                            selected_filters.event_names.remove(name);
                         }
                         updated = true;
                     }
@@ -1257,158 +4489,1203 @@ impl FilteredData {
         return updated;
     }
 
-    pub fn new(data: &[SeriesData], selected_filters: Filters, symlog: bool) -> Self {
-        let filtered = Self::filter_data_items(data, &selected_filters);
-        Self {
-            selected_filters,
-            create_convergence_plot: create_convergence_plot(&filtered),
-            create_error_plot: create_error_plot(&filtered, symlog),
-            create_performance_plot: create_performance_plot(&filtered, symlog),
-            create_accel_records_table: create_accel_records_table(&filtered),
+    pub fn new(
+        data: &[SeriesData],
+        query_params: HashSet<IStr>,
+        selected_filters: Filters,
+        symlog: bool,
+        panels: &PanelRegistry,
+    ) -> Self {
+        let indices = Self::filter_indices(data, &selected_filters);
+        let filtered = Self::records_at(data, &indices);
+        let records = filtered.iter().map(|(_, records)| records.len()).sum();
+        let name_params = *NAME_PARAMS.read().unwrap();
+        *SHOWN_PARAMS.write().unwrap() =
+            name_params.shown(&filtered, &query_params, &selected_filters);
+        let diagnosis = if records == 0 && data.iter().any(|(_, records)| !records.is_empty()) {
+            let count = |filters: &Filters| {
+                let items = Self::filter_data_items(data, filters);
+                Ok(items.iter().map(|(_, records)| records.len()).sum())
+            };
+            Diagnosis::of(&selected_filters, count).unwrap_or_default()
+        } else {
+            None
+        };
+        Self {
+            panels: panels.build(&filtered, symlog),
+            selected_filters,
+            matched: (filtered.len(), records),
+            diagnosis,
+            accel_param_methods: accel_param_methods(data),
+            query_params,
+            records: indices
+                .into_iter()
+                .flat_map(|(i, accel_indices)| accel_indices.into_iter().map(move |j| (i, j)))
+                .collect(),
+        }
+    }
+
+    fn upd(&mut self, data: &[SeriesData], symlog: bool, panels: &PanelRegistry) {
+        *self = Self::new(
+            data,
+            mem::take(&mut self.query_params),
+            mem::take(&mut self.selected_filters),
+            symlog,
+            panels,
+        );
+    }
+
+    /// Renders filtering ui & updates itself
+    pub fn ui_filter(
+        &mut self,
+        ui: &mut Ui,
+        title: &str,
+        data: &Vec<SeriesData>,
+        available_filters: &Filters,
+        symlog: bool,
+        panels: &PanelRegistry,
+    ) {
+        if Self::dynamic_ui_filter_section(
+            ui,
+            title,
+            available_filters,
+            &mut self.selected_filters,
+            &self.accel_param_methods,
+            self.matched,
+        ) {
+            self.upd(data, symlog, panels);
+        }
+        if let Some(diagnosis) = &self.diagnosis
+            && let Some(relax) = diagnosis.ui(ui)
+        {
+            for predicate in relax {
+                predicate.relax(&mut self.selected_filters);
+            }
+            self.upd(data, symlog, panels);
+        }
+    }
+}
+
+pub struct Data {
+    // Filters of the query that loaded the data
+    query: Filters,
+    data: Vec<SeriesData>,
+    available_filters: Filters,
+    filtered: FilteredData,
+    // Second, independent quick-filter selection shown side by side in A/B mode
+    compare: Option<FilteredData>,
+}
+
+// Parameters with a top-level filter on them
+fn query_params(query: &Filters) -> HashSet<IStr> {
+    query
+        .series_params
+        .keys()
+        .chain(query.accel_params.keys())
+        .cloned()
+        .collect()
+}
+
+impl Data {
+    fn new(
+        query: Filters,
+        data: Vec<SeriesData>,
+        quick_filters: Filters,
+        compare_filters: Option<Filters>,
+        symlog: bool,
+        panels: &PanelRegistry,
+    ) -> Self {
+        let params = query_params(&query);
+        Self {
+            query,
+            available_filters: filterable(&data),
+            filtered: FilteredData::new(&data, params.clone(), quick_filters, symlog, panels),
+            compare: compare_filters.map(|f| FilteredData::new(&data, params, f, symlog, panels)),
+            data,
+        }
+    }
+
+    fn set_compare(&mut self, compare: bool, symlog: bool, panels: &PanelRegistry) {
+        self.compare = compare.then(|| {
+            let query_params = query_params(&self.query);
+            FilteredData::new(&self.data, query_params, Filters::default(), symlog, panels)
+        });
+    }
+
+    fn upd(&mut self, symlog: bool, panels: &PanelRegistry) {
+        self.filtered.upd(&self.data, symlog, panels);
+        if let Some(compare) = &mut self.compare {
+            compare.upd(&self.data, symlog, panels);
+        }
+    }
+
+    // After the family pattern changed: the families on offer are different, and the selected
+    // ones may not exist anymore
+    fn upd_families(&mut self, symlog: bool, panels: &PanelRegistry) {
+        self.available_filters.families = filterable(&self.data).families;
+        self.filtered.selected_filters.families.clear();
+        if let Some(compare) = &mut self.compare {
+            compare.selected_filters.families.clear();
+        }
+        self.upd(symlog, panels);
+    }
+}
+
+// Fallback repaint rate while nothing happens
+const IDLE_REPAINT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Result of a background filter query
+struct LoadResult {
+    filters: Filters,
+    started: std::time::SystemTime,
+    duration: std::time::Duration,
+    data: Result<LoadedPage>,
+}
+
+// What the command palette can do
+enum PaletteAction {
+    // Plot option or window, shown as a checkbox
+    Toggle(fn(&mut DashboardApp) -> &mut bool),
+    Symlog,
+    Compare,
+    Reload,
+    // Panel to open and scroll to
+    Section(&'static str),
+    OpenBookmark(usize),
+    // Current view under the typed name
+    SaveBookmark(String),
+    // Value of a top-level filter
+    ToggleValue(fn(&mut Filters) -> &mut HashSet<IStr>, IStr),
+    ToggleM(i32),
+    ToggleParam {
+        series: bool,
+        param: IStr,
+        value: IStr,
+    },
+}
+
+pub struct DashboardApp {
+    // Background threads wake the UI through it when they send results
+    ctx: Context,
+    loader: Arc<dyn DataSource>,
+    panels: PanelRegistry,
+    filters: Filters,
+    // Last used size of the preview sample, kept while sampling is off
+    sample_size: usize,
+    // Filled in the background, part by part
+    metadata: Metadata,
+    metadata_parts: usize,
+    metadata_step: &'static str,
+    metadata_receiver: Option<mpsc::Receiver<Result<MetadataPart>>>,
+    data: Option<Data>,
+    // Каналы для асинхронной загрузки данных
+    data_sender: Option<mpsc::Sender<LoadResult>>,
+    data_receiver: Option<mpsc::Receiver<LoadResult>>,
+    loading: bool,
+    // Pages of the last loaded result, 1 unless it was paged
+    page_count: usize,
+    history: QueryHistory,
+    show_history: bool,
+    // Dry-run count for the current filters
+    estimate: Option<Result<QueryEstimate, String>>,
+    estimate_receiver: Option<mpsc::Receiver<Result<QueryEstimate>>>,
+    // Why the last query loaded nothing
+    diagnosis: Option<Diagnosis>,
+    diagnosis_receiver: Option<mpsc::Receiver<Result<Option<Diagnosis>>>>,
+    schema_browser: SchemaBrowser,
+    show_schema: bool,
+    significance: SignificanceTest,
+    show_significance: bool,
+    viz: Vis,
+    symlog: bool,
+    compare: bool,
+    number_format: NumberFormat,
+    name_params: NameParams,
+    // Zoom and font size, saved with the window geometry
+    ui_scale: UiScale,
+
+    // Bookmarks sidebar
+    show_bookmarks: bool,
+    bookmark_name: String,
+    bookmark_note: String,
+    // Bookmark whose quick filters and zoom wait for the data it requested
+    pending_view: Option<Bookmark>,
+    // Filter values of the opened bookmark missing from the data
+    remap: Option<RemapWindow>,
+    // High-resolution screenshot in progress
+    export: Option<Export>,
+    export_format: ExportFormat,
+    // Ctrl+P
+    palette: CommandPalette,
+    // Panel to open and scroll to on the next frame, chosen in the palette
+    jump_to: Option<&'static str>,
+    // Points of a plot, opened from under it
+    plot_data: Option<PlotDataWindow>,
+}
+
+impl DashboardApp {
+    pub fn new(
+        ctx: Context,
+        loader: Arc<dyn DataSource>,
+        panels: PanelRegistry,
+        workspace: Workspace,
+        sample: Option<usize>,
+        storage: Option<&dyn eframe::Storage>,
+    ) -> Self {
+        let ui_scale = UiScale::load(storage);
+        ui_scale.apply(&ctx);
+        let (tx, rx) = std::sync::mpsc::channel::<LoadResult>();
+
+        let (metadata_tx, metadata_rx) = mpsc::channel();
+        let metadata_loader = loader.clone();
+        let data_dir = loader.path().to_string();
+        let mut viz = Vis::new(workspace, data_dir);
+        viz.plot_wheel = PlotWheel::load(storage);
+        let metadata_ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let res = rt.block_on(metadata_loader.compute_metadata(&mut |part| {
+                let _ = metadata_tx.send(Ok(part));
+                metadata_ctx.request_repaint();
+            }));
+            if let Err(e) = res {
+                let _ = metadata_tx.send(Err(e));
+                metadata_ctx.request_repaint();
+            }
+        });
+
+        Self {
+            ctx,
+            loader,
+            panels,
+            filters: Filters {
+                sample,
+                ..Default::default()
+            },
+            sample_size: sample.unwrap_or(100),
+            metadata: Metadata::default(),
+            metadata_parts: 0,
+            metadata_step: "",
+            metadata_receiver: Some(metadata_rx),
+            data: None,
+            data_sender: Some(tx),
+            data_receiver: Some(rx),
+            loading: false,
+            page_count: 1,
+            history: QueryHistory::default(),
+            show_history: false,
+            estimate: None,
+            estimate_receiver: None,
+            diagnosis: None,
+            diagnosis_receiver: None,
+            schema_browser: SchemaBrowser::default(),
+            show_schema: false,
+            significance: SignificanceTest::default(),
+            show_significance: false,
+            viz,
+            symlog: true,
+            compare: false,
+            number_format: NumberFormat::default(),
+            name_params: NameParams::default(),
+            ui_scale,
+            show_bookmarks: true,
+            bookmark_name: String::new(),
+            bookmark_note: String::new(),
+            pending_view: None,
+            remap: None,
+            export: None,
+            export_format: ExportFormat::Parquet,
+            palette: CommandPalette::default(),
+            jump_to: None,
+            plot_data: None,
+        }
+    }
+
+    // Methods of the parameters for the loader, unless they're still being computed
+    fn param_methods(&self) -> Option<HashMap<String, Vec<String>>> {
+        (self.metadata_parts == MetadataPart::COUNT)
+            .then(|| self.metadata.accel_param_methods.clone())
+    }
+
+    fn update_data(&mut self) {
+        if let (Some(sender), _) = (&self.data_sender, &self.data_receiver) {
+            let filters = self.filters.clone();
+            let param_methods = self.param_methods();
+            let loader = self.loader.clone();
+            let tx = sender.clone();
+            let ctx = self.ctx.clone();
+
+            // Запускаем загрузку в отдельном потоке
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                let started = std::time::SystemTime::now();
+                let timer = std::time::Instant::now();
+                let data = rt.block_on(loader.filter_data(&filters, param_methods.as_ref()));
+                let _ = tx.send(LoadResult {
+                    filters,
+                    started,
+                    duration: timer.elapsed(),
+                    data,
+                });
+                ctx.request_repaint();
+            });
+
+            self.loading = true;
+        }
+    }
+
+    // Flat table of the data behind the plots, written in the background
+    fn export_data(&self) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let items = FilteredData::filter_data_items(&data.data, &data.filtered.selected_filters);
+        let workspace = &self.viz.workspace;
+        let table = match points_table(&items, |id, info| workspace.record_note(id, info)) {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("Failed to export data: {}", e);
+                return;
+            }
+        };
+        let format = self.export_format;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let filename = format!("vizr_data_{}.{}", timestamp, format.extension());
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            match rt.block_on(export_points(table, &filename, format)) {
+                Ok(()) => println!("Data exported: {}", filename),
+                Err(e) => eprintln!("Failed to export data: {}", e),
+            }
+        });
+    }
+
+    // Points of a plot, from the data of its half in A/B mode
+    fn open_plot_data(&self, panel_id: &str, variant: &str) -> Option<PlotDataWindow> {
+        let data = self.data.as_ref()?;
+        let filtered = match (&data.compare, variant) {
+            (Some(compare), "_b") => compare,
+            _ => &data.filtered,
+        };
+        let items = FilteredData::filter_data_items(&data.data, &filtered.selected_filters);
+        let title = filtered
+            .panels
+            .iter()
+            .find(|p| p.id() == panel_id)
+            .map_or(panel_id, |p| p.title());
+        PlotDataWindow::new(panel_id, title, &items, self.symlog, &self.viz.opts)
+    }
+
+    // Vega-Lite specification of a plot, with the data of its half in A/B mode
+    fn export_spec(&self, panel_id: &str, variant: &str) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let filtered = match (&data.compare, variant) {
+            (Some(compare), "_b") => compare,
+            _ => &data.filtered,
+        };
+        let items = FilteredData::filter_data_items(&data.data, &filtered.selected_filters);
+        let title = filtered
+            .panels
+            .iter()
+            .find(|p| p.id() == panel_id)
+            .map_or(panel_id, |p| p.title());
+        let labels = self.viz.plot_labels(panel_id);
+        let filters = provenance_filters([&data.query, &filtered.selected_filters]);
+        let description = self.viz.caption(&filters);
+        let Some(spec) = vega::spec(
+            panel_id,
+            title,
+            &items,
+            &self.viz.opts,
+            &labels,
+            &self.viz.workspace.annotations,
+            &description,
+        ) else {
+            return;
+        };
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let filename = format!("{panel_id}{variant}_{timestamp}.vl.json");
+        let result = serde_json::to_string_pretty(&spec)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| Ok(std::fs::write(&filename, text)?));
+        match result {
+            Ok(()) => println!("Vega-Lite specification saved: {}", filename),
+            Err(e) => eprintln!("Failed to export the plot specification: {}", e),
+        }
+    }
+
+    // The same data as a dataset vizr can open, e.g. to migrate old archives
+    fn export_dataset(&self) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let items: Vec<SeriesData> =
+            FilteredData::filter_data_items(&data.data, &data.filtered.selected_filters)
+                .into_iter()
+                .map(|(series, accel_records)| {
+                    (series.clone(), accel_records.into_iter().cloned().collect())
+                })
+                .collect();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let dir = std::path::PathBuf::from(format!("vizr_dataset_{}", timestamp));
+        // For the notes
+        let workspace = self.viz.workspace.clone();
+        std::thread::spawn(move || {
+            let items: Vec<SeriesDataRef> = items
+                .iter()
+                .map(|(series, accel_records)| (series, accel_records.iter().collect()))
+                .collect();
+            let note = |id, info: &_| workspace.record_note(id, info);
+            match export_dataset(&items, note, &dir) {
+                Ok(()) => println!("Dataset exported: {}", dir.display()),
+                Err(e) => eprintln!("Failed to export dataset: {}", e),
+            }
+        });
+    }
+
+    // Filtered data as vizr holds it, for notebooks
+    fn export_json(&self) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let items: Vec<SeriesData> =
+            FilteredData::filter_data_items(&data.data, &data.filtered.selected_filters)
+                .into_iter()
+                .map(|(series, accel_records)| {
+                    (series.clone(), accel_records.into_iter().cloned().collect())
+                })
+                .collect();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let filename = format!("vizr_series_{}.json", timestamp);
+        // For the notes
+        let workspace = self.viz.workspace.clone();
+        std::thread::spawn(move || {
+            let items: Vec<SeriesDataRef> = items
+                .iter()
+                .map(|(series, accel_records)| (series, accel_records.iter().collect()))
+                .collect();
+            let result = std::fs::File::create(&filename)
+                .map_err(anyhow::Error::from)
+                .and_then(|file| {
+                    export_json(&items, |id, info| workspace.record_note(id, info), file)
+                });
+            match result {
+                Ok(()) => println!("Data exported: {}", filename),
+                Err(e) => eprintln!("Failed to export JSON: {}", e),
+            }
+        });
+    }
+
+    fn request_estimate(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        let filters = self.filters.clone();
+        let param_methods = self.param_methods();
+        let loader = self.loader.clone();
+        let ctx = self.ctx.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let _ = tx.send(rt.block_on(loader.estimate(&filters, param_methods.as_ref())));
+            ctx.request_repaint();
+        });
+        self.estimate = None;
+        self.estimate_receiver = Some(rx);
+    }
+
+    fn check_for_estimate(&mut self) {
+        if let Some(receiver) = &self.estimate_receiver
+            && let Ok(result) = receiver.try_recv()
+        {
+            self.estimate = Some(result.map_err(|e| e.to_string()));
+            self.estimate_receiver = None;
+        }
+    }
+
+    // Relaxes the filters of a query that loaded nothing, counting the series without loading them
+    fn request_diagnosis(&mut self, filters: Filters) {
+        let (tx, rx) = mpsc::channel();
+        let param_methods = self.param_methods();
+        let loader = self.loader.clone();
+        let ctx = self.ctx.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let count = |filters: &Filters| {
+                Ok(rt
+                    .block_on(loader.estimate(filters, param_methods.as_ref()))?
+                    .series)
+            };
+            let _ = tx.send(Diagnosis::of(&filters, count));
+            ctx.request_repaint();
+        });
+        self.diagnosis_receiver = Some(rx);
+    }
+
+    fn check_for_diagnosis(&mut self) {
+        if let Some(receiver) = &self.diagnosis_receiver
+            && let Ok(result) = receiver.try_recv()
+        {
+            self.diagnosis = result.unwrap_or_else(|e| {
+                eprintln!("Failed to diagnose the empty result: {}", e);
+                None
+            });
+            self.diagnosis_receiver = None;
+        }
+    }
+
+    fn check_for_metadata(&mut self) {
+        let Some(receiver) = &self.metadata_receiver else {
+            return;
+        };
+        while let Ok(part) = receiver.try_recv() {
+            match part {
+                Ok(part) => {
+                    self.metadata_step = part.description();
+                    self.metadata.apply(part);
+                    self.metadata_parts += 1;
+                }
+                Err(e) => {
+                    eprintln!("Failed to compute metadata: {}", e);
+                    self.metadata_receiver = None;
+                    return;
+                }
+            }
+        }
+        if self.metadata_parts == MetadataPart::COUNT {
+            println!(
+                "Found {} precisions, {} series, {} accelerators",
+                self.metadata.precisions.len(),
+                self.metadata.series_names.len(),
+                self.metadata.accel_names.len()
+            );
+            self.metadata_receiver = None;
+        }
+    }
+
+    fn check_for_data(&mut self) {
+        if let Some(receiver) = &self.data_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.history.push(QueryRecord {
+                    started: result.started,
+                    filters: result.filters.clone(),
+                    duration: result.duration,
+                    result: match &result.data {
+                        Ok(page) => Ok(page.data.len()),
+                        Err(e) => Err(e.to_string()),
+                    },
+                });
+                self.diagnosis = None;
+                self.diagnosis_receiver = None;
+                if let Ok(page) = &result.data
+                    && page.data.is_empty()
+                {
+                    self.request_diagnosis(result.filters.clone());
+                }
+                match result.data {
+                    Ok(page) => {
+                        self.page_count = page.page_count();
+                        let LoadedPage { data, total_series } = page;
+                        let len = data.len();
+                        let (quick_filters, compare_filters) = match self.pending_view.take() {
+                            Some(view) => {
+                                self.viz.pending_bounds = view.plot_bounds;
+                                (view.quick_filters, view.compare_filters)
+                            }
+                            None => (Filters::default(), self.compare.then(Filters::default)),
+                        };
+                        self.data = Some(Data::new(
+                            result.filters,
+                            data,
+                            quick_filters,
+                            compare_filters,
+                            self.symlog,
+                            &self.panels,
+                        ));
+                        println!("Loaded {} of {} series after filtering", len, total_series);
+                    }
+                    Err(e) => {
+                        eprintln!("Error filtering data: {}", e);
+                        self.data = None;
+                    }
+                }
+                self.loading = false;
+            }
+        }
+    }
+}
+
+// High-resolution screenshots
+impl DashboardApp {
+    fn start_export(&mut self, ctx: &Context, mut export: Export) {
+        // The panel has to fit into the window at the raised resolution
+        let screen = ctx.screen_rect().size();
+        let fit = (screen.x / export.size.x).min(screen.y / export.size.y);
+        if fit < export.scale {
+            println!("Screenshot scale reduced to {:.1}x to fit the window", fit);
+        }
+        export.scale = export.scale.min(fit).max(1.0);
+        // Re-rendered plots keep their zoom
+        self.viz.pending_bounds = self.viz.plot_bounds.clone();
+        ctx.set_pixels_per_point(export.original_ppp * export.scale);
+        self.export = Some(export);
+    }
+
+    fn finish_export(&mut self, ctx: &Context) {
+        if let Some(export) = self.export.take() {
+            ctx.set_pixels_per_point(export.original_ppp);
+        }
+    }
+
+    // Draws the exported panel alone; returns false when there is no export in progress
+    fn export_ui(&mut self, ctx: &Context) -> bool {
+        let Some(export) = &mut self.export else {
+            return false;
+        };
+        // Saved by `handle_screenshot_events`
+        let saved = export.requested && self.viz.pending_screenshots.is_empty();
+        let timed_out = export.frames > EXPORT_TIMEOUT_FRAMES;
+        let panel = self.data.as_ref().and_then(|data| {
+            let panels = match export.variant {
+                "_b" => &data.compare.as_ref()?.panels,
+                _ => &data.filtered.panels,
+            };
+            panels.iter().find(|p| p.id() == export.panel_id)
+        });
+        let Some(panel) = panel.filter(|_| !saved && !timed_out) else {
+            if timed_out {
+                eprintln!("Screenshot error: no image from the renderer");
+                self.viz.pending_screenshots.clear();
+            }
+            self.finish_export(ctx);
+            return false;
+        };
+
+        export.frames += 1;
+        let target_ppp = export.original_ppp * export.scale;
+        let frame = egui::Frame::central_panel(&ctx.style()).inner_margin(0.0);
+        egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
+            let mut rect = ui
+                .allocate_ui(export.size, |ui| {
+                    ui.set_min_size(export.size);
+                    labelled_panel_ui(panel.as_ref(), &mut self.viz, ui)
+                })
+                .inner;
+            if self.viz.screenshot_caption
+                && let Some(rect) = &mut rect
+            {
+                let caption = self.viz.caption(&export.filters);
+                let response = ui.add(
+                    egui::Label::new(egui::RichText::new(caption).small().weak())
+                        .wrap_mode(egui::TextWrapMode::Wrap),
+                );
+                *rect = rect.union(response.rect);
+            }
+            // The new scale applies from the frame after it was set
+            if !export.requested
+                && (ctx.pixels_per_point() - target_ppp).abs() < 1e-3
+                && let Some(rect) = rect
+            {
+                let plot_id = format!("{}{}", export.panel_id, export.variant);
+                self.viz
+                    .request_screenshot(ctx, plot_id, rect, export.filters.clone());
+                export.requested = true;
+            }
+        });
+        ctx.request_repaint();
+        true
+    }
+}
+
+// Bookmarks
+impl DashboardApp {
+    fn current_view(&self) -> Bookmark {
+        Bookmark {
+            name: self.bookmark_name.trim().to_string(),
+            note: self.bookmark_note.trim().to_string(),
+            filters: self.filters.clone(),
+            quick_filters: self
+                .data
+                .as_ref()
+                .map(|d| d.filtered.selected_filters.clone())
+                .unwrap_or_default(),
+            compare_filters: self
+                .data
+                .as_ref()
+                .and_then(|d| Some(d.compare.as_ref()?.selected_filters.clone())),
+            symlog: self.symlog,
+            plot_options: self.viz.opts.clone(),
+            plot_bounds: self.viz.plot_bounds.clone(),
         }
     }
 
-    fn upd(&mut self, data: &Vec<SeriesData>, symlog: bool) {
-        *self = Self::new(data, mem::take(&mut self.selected_filters), symlog);
+    fn open_bookmark(&mut self, i: usize) {
+        let bookmark = self.viz.workspace.bookmarks[i].clone();
+        self.remap = RemapWindow::new(i, &bookmark, &self.metadata);
+        self.filters = bookmark.filters.clone();
+        self.symlog = bookmark.symlog;
+        self.compare = bookmark.compare_filters.is_some();
+        self.viz.opts = bookmark.plot_options.clone();
+        self.pending_view = Some(bookmark);
+        self.update_data();
     }
 
-    /// Renders filtering ui & updates itself
-    pub fn ui_filter(
-        &mut self,
-        ui: &mut Ui,
-        data: &Vec<SeriesData>,
-        available_filters: &Filters,
-        symlog: bool,
-    ) {
-        if Self::dynamic_ui_filter_section(ui, available_filters, &mut self.selected_filters) {
-            self.upd(data, symlog);
+    fn bookmarks_ui(&mut self, ui: &mut Ui) {
+        ui.heading("Закладки");
+        ui.add(egui::TextEdit::singleline(&mut self.bookmark_name).hint_text("Название"));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.bookmark_note)
+                .hint_text("Заметка")
+                .desired_rows(2),
+        );
+        let can_save = !self.bookmark_name.trim().is_empty();
+        if ui
+            .add_enabled(can_save, egui::Button::new("➕ Сохранить текущий вид"))
+            .clicked()
+        {
+            let bookmark = self.current_view();
+            self.viz.workspace.bookmarks.push(bookmark);
+            self.bookmark_name.clear();
+            self.bookmark_note.clear();
+            self.viz.save_workspace();
         }
-    }
-}
-
-pub struct Data {
-    data: Vec<SeriesData>,
-    available_filters: Filters,
-    filtered: FilteredData,
-}
+        ui.separator();
 
-impl Data {
-    fn new(data: Vec<SeriesData>, symlog: bool) -> Self {
-        Self {
-            available_filters: filterable(&data),
-            filtered: FilteredData::new(&data, Filters::default(), symlog),
-            data,
+        let mut open = None;
+        let mut delete = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, bookmark) in self.viz.workspace.bookmarks.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let mut button = ui.button(&bookmark.name);
+                    if !bookmark.note.is_empty() {
+                        button = button.on_hover_text(&bookmark.note);
+                    }
+                    if button.clicked() {
+                        open = Some(i);
+                    }
+                    if name_button(ui.small_button("✖"), "Удалить закладку").clicked()
+                    {
+                        delete = Some(i);
+                    }
+                });
+            }
+        });
+        if let Some(i) = open {
+            self.open_bookmark(i);
+        }
+        if let Some(i) = delete {
+            self.viz.workspace.bookmarks.remove(i);
+            // Its index would point at the next bookmark
+            self.remap = None;
+            self.viz.save_workspace();
         }
     }
 }
 
-pub struct DashboardApp {
-    loader: Arc<DataLoader>,
-    filters: Filters,
-    data: Option<Data>,
-    // Каналы для асинхронной загрузки данных
-    data_sender: Option<mpsc::Sender<Result<Vec<SeriesData>>>>,
-    data_receiver: Option<mpsc::Receiver<Result<Vec<SeriesData>>>>,
-    loading: bool,
-    viz: Vis,
-    symlog: bool,
-}
-
+// Command palette
 impl DashboardApp {
-    pub fn new(loader: Arc<DataLoader>) -> Self {
-        let (tx, rx) =
-            std::sync::mpsc::channel::<std::result::Result<Vec<SeriesData>, anyhow::Error>>();
-        Self {
-            loader,
-            filters: Filters::default(),
-            data: None,
-            data_sender: Some(tx),
-            data_receiver: Some(rx),
-            loading: false,
-            viz: Vis {
-                show_partial_sums: true,
-                show_limits: true,
-                show_imaginary: true,
-                show_real: true,
-                force_show_imaginary: false,
-                pending_screenshots: HashMap::new(),
-                plot_hovered: false,
-            },
-            symlog: true,
+    fn palette_items(&self) -> Vec<PaletteItem<PaletteAction>> {
+        use PaletteAction::*;
+        let opts = &self.viz.opts;
+        let mut items = vec![
+            PaletteItem::new("🔄 Обновить графики", Reload),
+            PaletteItem::toggle("Symlog", self.symlog, Symlog),
+            PaletteItem::toggle("A/B сравнение", self.compare, Compare),
+            PaletteItem::toggle(
+                "Окно: 🔖 Закладки",
+                self.show_bookmarks,
+                Toggle(|app| &mut app.show_bookmarks),
+            ),
+            PaletteItem::toggle(
+                "Окно: 🕘 История запросов",
+                self.show_history,
+                Toggle(|app| &mut app.show_history),
+            ),
+            PaletteItem::toggle(
+                "Окно: 🗂 Схема данных",
+                self.show_schema,
+                Toggle(|app| &mut app.show_schema),
+            ),
+            PaletteItem::toggle(
+                "Окно: 📐 Значимость",
+                self.show_significance,
+                Toggle(|app| &mut app.show_significance),
+            ),
+            PaletteItem::toggle(
+                "Опция: Короткие имена",
+                opts.short_names,
+                Toggle(|app| &mut app.viz.opts.short_names),
+            ),
+            PaletteItem::toggle(
+                "Опция: Логарифмическая ось n",
+                opts.log_x,
+                Toggle(|app| &mut app.viz.opts.log_x),
+            ),
+            PaletteItem::toggle(
+                "Опция: Частичные суммы",
+                opts.show_partial_sums,
+                Toggle(|app| &mut app.viz.opts.show_partial_sums),
+            ),
+            PaletteItem::toggle(
+                "Опция: Пределы",
+                opts.show_limits,
+                Toggle(|app| &mut app.viz.opts.show_limits),
+            ),
+            PaletteItem::toggle(
+                "Опция: Машинный эпсилон",
+                opts.show_epsilon,
+                Toggle(|app| &mut app.viz.opts.show_epsilon),
+            ),
+            PaletteItem::toggle(
+                "Опция: Застой",
+                opts.show_plateaus,
+                Toggle(|app| &mut app.viz.opts.show_plateaus),
+            ),
+            PaletteItem::toggle(
+                "Опция: Действительные части",
+                opts.show_real,
+                Toggle(|app| &mut app.viz.opts.show_real),
+            ),
+            PaletteItem::toggle(
+                "Опция: Мнимые части",
+                opts.show_imaginary,
+                Toggle(|app| &mut app.viz.opts.show_imaginary),
+            ),
+        ];
+        if let Some(data) = &self.data {
+            for panel in &data.filtered.panels {
+                items.push(PaletteItem::new(
+                    format!("Перейти: {}", panel.title()),
+                    Section(panel.id()),
+                ));
+            }
+        }
+        for (i, bookmark) in self.viz.workspace.bookmarks.iter().enumerate() {
+            items.push(PaletteItem::new(
+                format!("Закладка: {}", bookmark.name),
+                OpenBookmark(i),
+            ));
+        }
+        let name = self.palette.query().trim();
+        if !name.is_empty() {
+            items.push(PaletteItem::new(
+                format!("➕ Сохранить закладку «{name}»"),
+                SaveBookmark(name.to_string()),
+            ));
         }
-    }
-
-    fn update_data(&mut self) {
-        if let (Some(sender), _) = (&self.data_sender, &self.data_receiver) {
-            let filters = self.filters.clone();
-            let loader = self.loader.clone();
-            let tx = sender.clone();
-
-            // Запускаем загрузку в отдельном потоке
-            std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().unwrap();
-                let result: std::result::Result<Vec<SeriesData>, anyhow::Error> =
-                    rt.block_on(loader.filter_data(&filters));
-                let _ = tx.send(result);
-            });
 
-            self.loading = true;
+        let filters = &self.filters;
+        let metadata = &self.metadata;
+        type Selection = fn(&mut Filters) -> &mut HashSet<IStr>;
+        let values: [(&str, &Vec<String>, &HashSet<IStr>, Selection); 4] = [
+            (
+                "Точность",
+                &metadata.precisions,
+                &filters.precisions,
+                |f| &mut f.precisions,
+            ),
+            (
+                "Ряд",
+                &metadata.series_names,
+                &filters.base_series,
+                |f| &mut f.base_series,
+            ),
+            (
+                "Метод",
+                &metadata.accel_names,
+                &filters.base_accel,
+                |f| &mut f.base_accel,
+            ),
+            (
+                "Событие",
+                &metadata.event_names,
+                &filters.event_names,
+                |f| &mut f.event_names,
+            ),
+        ];
+        for (title, all, selected, selection) in values {
+            for value in all {
+                items.push(PaletteItem::toggle(
+                    format!("{title}: {value}"),
+                    selected.contains(value.as_str()),
+                    ToggleValue(selection, intern(value)),
+                ));
+            }
+        }
+        for &m in &metadata.m_values {
+            items.push(PaletteItem::toggle(
+                format!("m={m}"),
+                filters.m_values.contains(&m),
+                ToggleM(m),
+            ));
+        }
+        for (series, title, info, selected) in [
+            (
+                true,
+                "Параметр ряда",
+                &metadata.series_param_info,
+                &filters.series_params,
+            ),
+            (
+                false,
+                "Параметр метода",
+                &metadata.accel_param_info,
+                &filters.accel_params,
+            ),
+        ] {
+            for (param, values) in info {
+                let selection = selected.get(param.as_str());
+                for value in values {
+                    items.push(PaletteItem::toggle(
+                        format!("{title}: {param} = {value}"),
+                        selection.is_some_and(|s| s.contains(value.as_str())),
+                        ToggleParam {
+                            series,
+                            param: intern(param),
+                            value: intern(value),
+                        },
+                    ));
+                }
+            }
         }
+        items
     }
 
-    fn check_for_data(&mut self) {
-        if let Some(receiver) = &self.data_receiver {
-            if let Ok(result) = receiver.try_recv() {
-                match result {
-                    Ok(data) => {
-                        let len = data.len();
-                        self.data = Some(Data::new(data, self.symlog));
-                        println!("Loaded {} series after filtering", len);
-                    }
-                    Err(e) => {
-                        eprintln!("Error filtering data: {}", e);
-                        self.data = None;
-                    }
+    fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::Toggle(option) => {
+                let value = option(self);
+                *value = !*value;
+            }
+            PaletteAction::Symlog => {
+                self.symlog = !self.symlog;
+                if let Some(x) = &mut self.data {
+                    x.upd(self.symlog, &self.panels);
+                }
+            }
+            PaletteAction::Compare => {
+                self.compare = !self.compare;
+                if let Some(x) = &mut self.data {
+                    x.set_compare(self.compare, self.symlog, &self.panels);
+                }
+            }
+            PaletteAction::Reload => {
+                if !self.loading {
+                    self.filters.page = 0;
+                    self.update_data();
+                }
+            }
+            PaletteAction::Section(id) => self.jump_to = Some(id),
+            PaletteAction::OpenBookmark(i) => {
+                self.open_bookmark(i);
+            }
+            PaletteAction::SaveBookmark(name) => {
+                self.bookmark_name = name;
+                let bookmark = self.current_view();
+                self.viz.workspace.bookmarks.push(bookmark);
+                self.bookmark_name.clear();
+                self.viz.save_workspace();
+            }
+            PaletteAction::ToggleValue(selection, value) => {
+                let selection = selection(&mut self.filters);
+                if !selection.remove(&value) {
+                    selection.insert(value);
+                }
+            }
+            PaletteAction::ToggleM(m) => {
+                if !self.filters.m_values.remove(&m) {
+                    self.filters.m_values.insert(m);
+                }
+            }
+            PaletteAction::ToggleParam {
+                series,
+                param,
+                value,
+            } => {
+                let (info, selected) = if series {
+                    (
+                        &self.metadata.series_param_info,
+                        &mut self.filters.series_params,
+                    )
+                } else {
+                    (
+                        &self.metadata.accel_param_info,
+                        &mut self.filters.accel_params,
+                    )
+                };
+                let offers_not_set = info
+                    .get(param.as_ref())
+                    .is_some_and(|values| values.iter().any(|v| v == NOT_SET));
+                let selection = selected.entry(param.clone()).or_default();
+                if !selection.remove(&value) {
+                    select_param_value(selection, &value, offers_not_set);
+                }
+                if selection.is_empty() {
+                    selected.remove(&param);
                 }
-                self.loading = false;
             }
         }
     }
 }
 
 impl eframe::App for DashboardApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.ui_scale.save(storage);
+        self.viz.plot_wheel.save(storage);
+        // A note still being edited when the app closes
+        self.viz.save_note();
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        if sync_family_pattern(&self.viz.opts.family_pattern)
+            && let Some(x) = &mut self.data
+        {
+            x.upd_families(self.symlog, &self.panels);
+        }
         // Проверяем наличие новых данных от фоновых потоков
+        self.check_for_metadata();
+        self.check_for_estimate();
+        self.check_for_diagnosis();
         self.check_for_data();
+        // Everything else repaints on input or when a background thread sends a result
+        ctx.request_repaint_after(IDLE_REPAINT_INTERVAL);
+        self.ui_scale.follow_zoom(ctx);
 
         // Handle screenshot events
         if let Err(e) = self.viz.handle_screenshot_events(ctx) {
             eprintln!("Screenshot error: {}", e);
         }
+        if let Some(export) = self.viz.export_request.take() {
+            self.start_export(ctx, export);
+        }
+        if self.export_ui(ctx) {
+            return;
+        }
+
+        self.palette.handle_shortcut(ctx);
+        if self.palette.is_open() {
+            let items = self.palette_items();
+            if let Some(action) = self.palette.ui(ctx, items) {
+                self.run_palette_action(action);
+            }
+        }
+
+        if let Some(window) = &mut self.plot_data
+            && !window.ui(ctx)
+        {
+            self.plot_data = None;
+        }
+        if let Some(window) = &self.viz.slope_fit
+            && !window.ui(ctx)
+        {
+            self.viz.slope_fit = None;
+        }
+        if let Some(window) = &self.remap {
+            let mut picked = Vec::new();
+            let open = window.ui(ctx, &mut picked);
+            if !picked.is_empty() {
+                // The bookmark itself is fixed, and opened again with the new values
+                let i = window.bookmark;
+                window.apply(&picked, &mut self.viz.workspace.bookmarks[i]);
+                self.viz.save_workspace();
+                self.open_bookmark(i);
+            } else if !open {
+                self.remap = None;
+            }
+        }
+
+        if self.show_history {
+            let mut open = true;
+            let mut rerun = None;
+            egui::Window::new("История запросов")
+                .open(&mut open)
+                .default_width(600.0)
+                .show(ctx, |ui| rerun = self.history.ui(ui));
+            self.show_history = open;
+            if let Some(filters) = rerun {
+                self.filters = filters;
+                self.update_data();
+            }
+        }
+
+        if self.show_schema {
+            egui::Window::new("Схема данных")
+                .open(&mut self.show_schema)
+                .default_width(500.0)
+                .show(ctx, |ui| self.schema_browser.ui(ui, &self.loader));
+        }
+
+        if self.show_significance {
+            egui::Window::new("Значимость различий методов")
+                .open(&mut self.show_significance)
+                .default_width(450.0)
+                .show(ctx, |ui| match &self.data {
+                    Some(data) => {
+                        let items = FilteredData::filter_data_items(
+                            &data.data,
+                            &data.filtered.selected_filters,
+                        );
+                        self.significance.ui(ui, &items);
+                    }
+                    None => {
+                        ui.label("Данные не загружены");
+                    }
+                });
+        }
+
+        if self.show_bookmarks {
+            egui::SidePanel::right("bookmarks")
+                .resizable(true)
+                .default_width(240.0)
+                .show(ctx, |ui| self.bookmarks_ui(ui));
+        }
 
         // Единая прокручиваемая область для всего контента
         egui::CentralPanel::default().show(ctx, |ui| {
             // Configure scroll area based on plot hover state
             let mut scroll_area = egui::ScrollArea::vertical();
-            if self.viz.plot_hovered {
-                // Disable scrolling when any plot is hovered
+            if self.viz.plot_hovered && self.viz.plot_wheel == PlotWheel::PanPlot {
+                // The wheel belongs to the hovered plot
                 scroll_area = scroll_area.enable_scrolling(false);
-                self.viz.plot_hovered = false;
             }
+            self.viz.plot_hovered = false;
 
             scroll_area.show(ui, |ui| {
                 // Фильтры
                 ui.heading("Фильтры");
                 ui.add_space(5.0);
 
+                if self.metadata_receiver.is_some() {
+                    let progress = self.metadata_parts as f32 / MetadataPart::COUNT as f32;
+                    let text = if self.metadata_parts == 0 {
+                        "Сканирование данных...".to_string()
+                    } else {
+                        format!(
+                            "Загружено: {} ({}/{})",
+                            self.metadata_step,
+                            self.metadata_parts,
+                            MetadataPart::COUNT
+                        )
+                    };
+                    ui.add(egui::ProgressBar::new(progress).text(text).animate(true));
+                    ui.add_space(5.0);
+                }
+
                 // Точность
                 ui.push_id("precision_filters", |ui| {
                     let mut show_all =
-                        self.filters.precisions.len() == self.loader.metadata.precisions.len();
+                        self.filters.precisions.len() == self.metadata.precisions.len();
                     filter_section_horizontal(
                         ui,
                         "Точность",
-                        &self.loader.metadata.precisions,
+                        &self.metadata.precisions,
                         &mut self.filters.precisions,
                         &mut show_all,
                     );
@@ -1417,11 +5694,11 @@ impl eframe::App for DashboardApp {
                 // Базовые ряды
                 ui.push_id("series_filters", |ui| {
                     let mut show_all =
-                        self.filters.base_series.len() == self.loader.metadata.series_names.len();
+                        self.filters.base_series.len() == self.metadata.series_names.len();
                     filter_section_horizontal(
                         ui,
                         "Базовые ряды",
-                        &self.loader.metadata.series_names,
+                        &self.metadata.series_names,
                         &mut self.filters.base_series,
                         &mut show_all,
                     );
@@ -1432,7 +5709,8 @@ impl eframe::App for DashboardApp {
                     param_filter_section(
                         ui,
                         "Параметры рядов",
-                        &self.loader.metadata.series_param_info,
+                        &self.metadata.series_param_info,
+                        &HashMap::new(),
                         &mut self.filters.series_params,
                     );
                 });
@@ -1440,11 +5718,11 @@ impl eframe::App for DashboardApp {
                 // Базовые методы ускорения
                 ui.push_id("accel_filters", |ui| {
                     let mut show_all =
-                        self.filters.base_accel.len() == self.loader.metadata.accel_names.len();
+                        self.filters.base_accel.len() == self.metadata.accel_names.len();
                     filter_section_horizontal(
                         ui,
                         "Базовые методы ускорения",
-                        &self.loader.metadata.accel_names,
+                        &self.metadata.accel_names,
                         &mut self.filters.base_accel,
                         &mut show_all,
                     );
@@ -1455,7 +5733,7 @@ impl eframe::App for DashboardApp {
                     ui.horizontal(|ui| {
                         ui.label("Значения m:");
                         if ui.button("All").clicked() {
-                            self.filters.m_values.extend(&self.loader.metadata.m_values);
+                            self.filters.m_values.extend(&self.metadata.m_values);
                         }
                         if ui.button("None").clicked() {
                             self.filters.m_values.clear();
@@ -1464,9 +5742,18 @@ impl eframe::App for DashboardApp {
 
                     // Use wrapping layout for m_values checkboxes
                     ui.horizontal_wrapped(|ui| {
-                        for m in &self.loader.metadata.m_values {
+                        for m in &self.metadata.m_values {
                             let mut checked = self.filters.m_values.contains(m);
-                            if ui.checkbox(&mut checked, format!("m={}", m)).changed() {
+                            let text = format!("m={}", m);
+                            let checkbox = ui.checkbox(&mut checked, &text);
+                            let checkbox = in_group(
+                                checkbox,
+                                WidgetType::Checkbox,
+                                Some(checked),
+                                "Значения m",
+                                &text,
+                            );
+                            if checkbox.changed() {
                                 if checked {
                                     self.filters.m_values.insert(*m);
                                 } else {
@@ -1477,14 +5764,74 @@ impl eframe::App for DashboardApp {
                     });
                 });
 
-                // Параметры ускорения
+                // Параметры ускорения, только выбранных методов
                 ui.push_id("accel_params_filters", |ui| {
+                    let param_methods = &self.metadata.accel_param_methods;
+                    let param_info: HashMap<String, Vec<String>> = self
+                        .metadata
+                        .accel_param_info
+                        .iter()
+                        .filter(|(param, _)| {
+                            param_methods.get(*param).is_none_or(|methods| {
+                                any_selected(&self.filters.base_accel, methods)
+                            })
+                        })
+                        .map(|(param, values)| (param.clone(), values.clone()))
+                        .collect();
                     param_filter_section(
                         ui,
                         "Параметры ускорения",
-                        &self.loader.metadata.accel_param_info,
+                        &param_info,
+                        param_methods,
                         &mut self.filters.accel_params,
                     );
+                    // Filters of parameters hidden above are kept, though they filter nothing
+                    let idle = idle_params(&self.filters, param_methods);
+                    idle_params_ui(ui, &idle, &mut self.filters);
+                });
+
+                // Ошибки и события
+                ui.push_id("outcome_filters", |ui| {
+                    ui.horizontal(|ui| {
+                        tristate_filter(ui, "Ошибки", &mut self.filters.has_errors);
+                        ui.separator();
+                        tristate_filter(ui, "События", &mut self.filters.has_events);
+                    });
+                    if !self.metadata.event_names.is_empty() {
+                        let names: Vec<String> = std::iter::once(NO_EVENTS.to_string())
+                            .chain(self.metadata.event_names.iter().cloned())
+                            .collect();
+                        let mut show_all = self.filters.event_names.len() == names.len();
+                        filter_section_horizontal(
+                            ui,
+                            "Имена событий",
+                            &names,
+                            &mut self.filters.event_names,
+                            &mut show_all,
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Повторные запуски:").on_hover_text(
+                            "Записи одной конфигурации (метод, m, параметры) на одном ряду",
+                        );
+                        for policy in DuplicatePolicy::ALL {
+                            let enabled =
+                                policy != DuplicatePolicy::KeepLatest || self.loader.has_runs();
+                            let selected = self.filters.duplicates == policy;
+                            if ui
+                                .add_enabled(
+                                    enabled,
+                                    egui::SelectableLabel::new(selected, policy.label()),
+                                )
+                                .on_disabled_hover_text(
+                                    "Нужен столбец run_id, чтобы знать, какой запуск последний",
+                                )
+                                .clicked()
+                            {
+                                self.filters.duplicates = policy;
+                            }
+                        }
+                    });
                 });
 
                 ui.separator();
@@ -1497,20 +5844,260 @@ impl eframe::App for DashboardApp {
                     ui.label("Опции графиков:");
                     if ui.checkbox(&mut self.symlog, "Symlog").changed() {
                         if let Some(x) = &mut self.data {
-                            x.filtered.upd(&x.data, self.symlog);
+                            x.upd(self.symlog, &self.panels);
+                        }
+                    }
+                    if ui
+                        .checkbox(&mut self.compare, "A/B сравнение")
+                        .on_hover_text("Две независимые выборки быстрых фильтров рядом")
+                        .changed()
+                        && let Some(x) = &mut self.data
+                    {
+                        x.set_compare(self.compare, self.symlog, &self.panels);
+                    }
+                    ui.menu_button("🔢 Формат чисел", |ui| {
+                        let format = &mut self.number_format;
+                        let mut changed = ui
+                            .add(
+                                egui::Slider::new(&mut format.digits, 1..=15).text("Значащих цифр"),
+                            )
+                            .changed();
+                        changed |= ui
+                            .checkbox(
+                                &mut format.always_scientific,
+                                "Всегда экспоненциальная запись",
+                            )
+                            .changed();
+                        changed |= ui
+                            .checkbox(&mut format.decimal_comma, "Десятичная запятая")
+                            .changed();
+                        if changed {
+                            set_number_format(*format);
+                            // Table cells are formatted when the panels are built
+                            if let Some(x) = &mut self.data {
+                                x.upd(self.symlog, &self.panels);
+                            }
+                        }
+                    });
+                    ui.menu_button("🔍 Масштаб", |ui| self.ui_scale.ui(ui));
+                    let hidden = self.viz.line_visibility.hidden_count();
+                    let visibility = if hidden == 0 {
+                        "👁 Видимость".to_string()
+                    } else {
+                        format!("👁 Видимость (скрыто: {hidden})")
+                    };
+                    ui.menu_button(visibility, |ui| match &self.data {
+                        Some(data) => {
+                            let items = FilteredData::filter_data_items(
+                                &data.data,
+                                &data.filtered.selected_filters,
+                            );
+                            self.viz.line_visibility.ui(ui, &items);
                         }
+                        None => {
+                            ui.label("Данные не загружены");
+                        }
+                    })
+                    .response
+                    .on_hover_text("Показать или скрыть линии отдельных рядов и методов");
+                    ui.toggle_value(&mut self.show_bookmarks, "🔖 Закладки");
+                    ui.toggle_value(&mut self.show_history, "🕘 История запросов");
+                    ui.toggle_value(&mut self.show_schema, "🗂 Схема данных");
+                    ui.toggle_value(&mut self.show_significance, "📐 Значимость");
+                    egui::ComboBox::from_id_salt("name_params")
+                        .selected_text(format!("Параметры в именах: {}", self.name_params.label()))
+                        .show_ui(ui, |ui| {
+                            let mut changed = false;
+                            for mode in NameParams::ALL {
+                                changed |= ui
+                                    .selectable_value(&mut self.name_params, mode, mode.label())
+                                    .changed();
+                            }
+                            if changed {
+                                set_name_params(self.name_params);
+                                // Names are formatted when the panels are built
+                                if let Some(x) = &mut self.data {
+                                    x.upd(self.symlog, &self.panels);
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Какие параметры рядов и методов перечислять в легендах");
+                    egui::ComboBox::from_id_salt("legend_mode")
+                        .selected_text(match self.viz.opts.legend_mode {
+                            LegendMode::Flat => "Легенда: обычная",
+                            LegendMode::BySeries => "Легенда: по рядам",
+                            LegendMode::ByMethod => "Легенда: по методам",
+                        })
+                        .show_ui(ui, |ui| {
+                            let mode = &mut self.viz.opts.legend_mode;
+                            ui.selectable_value(mode, LegendMode::Flat, "Обычная");
+                            ui.selectable_value(mode, LegendMode::BySeries, "По рядам");
+                            ui.selectable_value(mode, LegendMode::ByMethod, "По методам");
+                        });
+                    egui::ComboBox::from_id_salt("plot_wheel")
+                        .selected_text(format!(
+                            "Колесо над графиком: {}",
+                            self.viz.plot_wheel.label()
+                        ))
+                        .show_ui(ui, |ui| {
+                            for mode in PlotWheel::ALL {
+                                ui.selectable_value(&mut self.viz.plot_wheel, mode, mode.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Ctrl+колесо масштабирует график, перетаскивание (в том числе \
+                             средней кнопкой) сдвигает его",
+                        );
+                    let opts = &mut self.viz.opts;
+                    egui::ComboBox::from_id_salt("color_by")
+                        .selected_text(format!("Цвет: {}", opts.color_by.label()))
+                        .show_ui(ui, |ui| {
+                            for d in Dimension::ALL {
+                                ui.selectable_value(&mut opts.color_by, d, d.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Что различается цветом линий ускорения");
+                    egui::ComboBox::from_id_salt("style_by")
+                        .selected_text(format!(
+                            "Стиль линий: {}",
+                            opts.style_by.map_or("нет", Dimension::label)
+                        ))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut opts.style_by, None, "Нет");
+                            for d in Dimension::ALL {
+                                ui.selectable_value(&mut opts.style_by, Some(d), d.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Что различается штрихами линий и формой точек");
+                    egui::ComboBox::from_id_salt("facet_by")
+                        .selected_text(format!(
+                            "Малые графики: {}",
+                            opts.facet_by.map_or("нет", Dimension::label)
+                        ))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut opts.facet_by, None, "Нет");
+                            for d in Dimension::ALL {
+                                ui.selectable_value(&mut opts.facet_by, Some(d), d.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Разбить график ошибок на сетку графиков с общими осями");
+                    ui.label("Семейство рядов:");
+                    family_pattern_ui(ui, &mut opts.family_pattern);
+                    ui.checkbox(&mut self.viz.opts.short_names, "Короткие имена")
+                        .on_hover_text(
+                            "Без общих для всех линий параметров; полное имя во всплывающей подсказке",
+                        );
+                    ui.checkbox(&mut self.viz.opts.log_x, "Логарифмическая ось n")
+                        .on_hover_text("Итерации в логарифмическом масштабе на графиках сходимости и ошибок");
+                    ui.checkbox(&mut self.viz.opts.show_partial_sums, "Частичные суммы");
+                    ui.checkbox(&mut self.viz.opts.show_limits, "Пределы");
+                    ui.checkbox(&mut self.viz.opts.show_epsilon, "Машинный эпсилон")
+                        .on_hover_text("Единица округления каждой точности на графике ошибок");
+                    ui.checkbox(&mut self.viz.opts.show_plateaus, "Застой")
+                        .on_hover_text("Отметить, где ошибка перестаёт уменьшаться");
+                    ui.checkbox(&mut self.viz.opts.show_best, "Лучшая точка")
+                        .on_hover_text(
+                            "Отметить наименьшую ошибку каждой линии — то, что показывает \
+                             график эффективности",
+                        );
+                    if self.viz.opts.show_best {
+                        ui.checkbox(&mut self.viz.opts.label_best, "С подписью");
+                    }
+                    ui.checkbox(&mut self.viz.opts.show_projection, "Прогноз до допуска")
+                        .on_hover_text(
+                            "Продлить пунктиром линии ошибок по наклону их последних точек до \
+                             допуска; не дальше, чем в 100 раз за последней итерацией",
+                        );
+                    if self.viz.opts.show_projection {
+                        ui.add(
+                            egui::DragValue::new(&mut self.viz.opts.tolerance_exp)
+                                .range(-1000..=10)
+                                .prefix("1e"),
+                        );
                     }
-                    ui.checkbox(&mut self.viz.show_partial_sums, "Частичные суммы");
-                    ui.checkbox(&mut self.viz.show_limits, "Пределы");
-                    ui.checkbox(&mut self.viz.show_real, "Действительные части");
-                    ui.checkbox(&mut self.viz.show_imaginary, "Мнимые части");
-                    if self.viz.show_imaginary {
-                        ui.checkbox(
-                            &mut self.viz.force_show_imaginary,
-                            "ВСЕГДА показывать мнимую часть",
+                    ui.checkbox(&mut self.viz.opts.show_advantage, "Выигрыш ускорения")
+                        .on_hover_text(
+                            "Закрасить на графике ошибок участки, где ускоренное значение \
+                             ближе к пределу, чем частичная сумма",
+                        );
+                    let opts = &mut self.viz.opts;
+                    egui::ComboBox::from_id_salt("underflow")
+                        .selected_text(format!(
+                            "Ошибки меньше 1e{}: {}",
+                            opts.underflow_exp,
+                            opts.underflow.label()
+                        ))
+                        .show_ui(ui, |ui| {
+                            for underflow in Underflow::ALL {
+                                ui.selectable_value(
+                                    &mut opts.underflow,
+                                    underflow,
+                                    underflow.label(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Что делать на графиках ошибок и эффективности с ошибками, которые \
+                             шкала symlog прижимает к нулю",
+                        );
+                    if opts.underflow != Underflow::Keep {
+                        ui.add(
+                            egui::DragValue::new(&mut opts.underflow_exp)
+                                .range(-1000..=0)
+                                .prefix("порог 1e"),
                         );
                     }
+                    let opts = &mut self.viz.opts;
+                    egui::ComboBox::from_id_salt("complex_view")
+                        .selected_text(format!("Комплексные: {}", opts.complex_view.label()))
+                        .show_ui(ui, |ui| {
+                            for view in ComplexView::ALL {
+                                ui.selectable_value(&mut opts.complex_view, view, view.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Как показывать комплексные значения на графике сходимости: по \
+                             частям или модулем и аргументом отклонения от предела",
+                        );
+                    ui.checkbox(&mut self.viz.opts.show_real, "Действительные части");
+                    ui.checkbox(&mut self.viz.opts.show_imaginary, "Мнимые части")
+                        .on_hover_text(
+                            "Нулевые везде мнимые части скрыты; показать такую линию можно из \
+                             её контекстного меню в сгруппированной легенде",
+                        );
                 });
+                if !self.viz.pinned.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Закреплённые линии:");
+                        let mut unpin = None;
+                        for (i, pin) in self.viz.pinned.iter().enumerate() {
+                            if ui.button(format!("✖ {}", pin.name)).clicked() {
+                                unpin = Some(i);
+                            }
+                        }
+                        if let Some(i) = unpin {
+                            self.viz.pinned.remove(i);
+                        }
+                        if ui.button("Открепить все").clicked() {
+                            self.viz.pinned.clear();
+                        }
+                    });
+                }
+                if !self.viz.selected.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Выделено записей: {}", self.viz.selected.len()));
+                        if ui.button("Снять выделение").clicked() {
+                            self.viz.selected.clear();
+                        }
+                    });
+                }
 
                 ui.separator();
 
@@ -1521,46 +6108,248 @@ impl eframe::App for DashboardApp {
                         ui.label("Загрузка...");
                     } else {
                         if ui.button("🔄 Обновить графики").clicked() {
+                            self.filters.page = 0;
+                            self.update_data();
+                        }
+                    }
+                    if self.page_count > 1 {
+                        ui.separator();
+                        let page = self.filters.page;
+                        let previous =
+                            ui.add_enabled(!self.loading && page > 0, egui::Button::new("◀"));
+                        if name_button(previous, "Предыдущая страница").clicked() {
+                            self.filters.page -= 1;
                             self.update_data();
                         }
+                        ui.label(format!("Страница {} из {}", page + 1, self.page_count))
+                            .on_hover_text(format!("По {} рядов на странице", PAGE_SIZE));
+                        let next = ui.add_enabled(
+                            !self.loading && page + 1 < self.page_count,
+                            egui::Button::new("▶"),
+                        );
+                        if name_button(next, "Следующая страница").clicked() {
+                            self.filters.page += 1;
+                            self.update_data();
+                        }
+                    }
+                    let mut sample = self.filters.sample.is_some();
+                    ui.checkbox(&mut sample, "Только первые")
+                        .on_hover_text("Быстрая проверка данных без полной загрузки");
+                    ui.add_enabled(
+                        sample,
+                        egui::DragValue::new(&mut self.sample_size).range(1..=usize::MAX),
+                    );
+                    ui.label("рядов");
+                    self.filters.sample = sample.then_some(self.sample_size);
+                    ui.separator();
+                    if ui
+                        .add_enabled(
+                            self.estimate_receiver.is_none(),
+                            egui::Button::new("🔢 Оценить объём"),
+                        )
+                        .on_hover_text("Посчитать записи без загрузки данных")
+                        .clicked()
+                    {
+                        self.request_estimate();
+                    }
+                    match &self.estimate {
+                        _ if self.estimate_receiver.is_some() => {
+                            ui.spinner();
+                        }
+                        Some(Ok(estimate)) => {
+                            ui.label(format!(
+                                "≈ {} рядов, до {} записей ускорений, ~{:.1} МБ на диске",
+                                estimate.series,
+                                estimate.accel_records,
+                                estimate.bytes as f64 / 1e6
+                            ));
+                        }
+                        Some(Err(e)) => {
+                            ui.colored_label(ui.visuals().error_fg_color, e);
+                        }
+                        None => {}
                     }
                     if let Some(data) = &self.data {
                         ui.label(format!("Загружено рядов: {}", data.data.len()));
+                        ui.separator();
+                        egui::ComboBox::from_id_salt("export_format")
+                            .selected_text(self.export_format.extension())
+                            .width(70.0)
+                            .show_ui(ui, |ui| {
+                                let format = &mut self.export_format;
+                                ui.selectable_value(format, ExportFormat::Parquet, "parquet");
+                                ui.selectable_value(format, ExportFormat::Csv, "csv");
+                            });
+                        if ui
+                            .button("📤 Экспорт данных")
+                            .on_hover_text(
+                                "Отфильтрованные данные таблицей: строка на каждую точку ускорения",
+                            )
+                            .clicked()
+                        {
+                            self.export_data();
+                        }
+                        if ui
+                            .button("🗃 Сохранить как набор данных")
+                            .on_hover_text(
+                                "Отфильтрованные данные в формате каталога vizr (последняя версия \
+                                 схемы): для переноса старых архивов и сохранения очищенных данных",
+                            )
+                            .clicked()
+                        {
+                            self.export_dataset();
+                        }
+                        if ui
+                            .button("🧾 Сохранить в JSON")
+                            .on_hover_text(
+                                "Отфильтрованные ряды и ускорения в том виде, в каком их читает \
+                                 vizr: числа парами [мантисса, порядок], например для Jupyter",
+                            )
+                            .clicked()
+                        {
+                            self.export_json();
+                        }
+                        if ui
+                            .button("📋 Сводка в Markdown")
+                            .on_hover_text(
+                                "Фильтры, число рядов и записей и лучшие результаты методов — в \
+                                 буфер обмена",
+                            )
+                            .clicked()
+                        {
+                            let filters = [&data.query, &data.filtered.selected_filters];
+                            let items = FilteredData::filter_data_items(&data.data, filters[1]);
+                            let summary =
+                                markdown_summary(filters, &items, self.viz.opts.tolerance_exp);
+                            ui.ctx().copy_text(summary);
+                        }
                     }
                 });
+                if self.diagnosis_receiver.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Ищем, какое условие ничего не оставляет…");
+                    });
+                }
+                if let Some(diagnosis) = &self.diagnosis
+                    && let Some(relax) = diagnosis.ui(ui)
+                {
+                    for predicate in relax {
+                        predicate.relax(&mut self.filters);
+                    }
+                    self.diagnosis = None;
+                    self.update_data();
+                }
 
                 ui.add_space(20.0);
 
                 // Графики
                 if let Some(data) = &mut self.data {
-                    data.filtered
-                        .ui_filter(ui, &data.data, &data.available_filters, self.symlog);
+                    if let Some(compare) = &mut data.compare {
+                        ui.columns(2, |cols| {
+                            cols[0].push_id("filters_a", |ui| {
+                                data.filtered.ui_filter(
+                                    ui,
+                                    "Быстрые фильтры (A)",
+                                    &data.data,
+                                    &data.available_filters,
+                                    self.symlog,
+                                    &self.panels,
+                                );
+                            });
+                            cols[1].push_id("filters_b", |ui| {
+                                compare.ui_filter(
+                                    ui,
+                                    "Быстрые фильтры (B)",
+                                    &data.data,
+                                    &data.available_filters,
+                                    self.symlog,
+                                    &self.panels,
+                                );
+                            });
+                        });
+                    } else {
+                        data.filtered.ui_filter(
+                            ui,
+                            "Быстрые фильтры",
+                            &data.data,
+                            &data.available_filters,
+                            self.symlog,
+                            &self.panels,
+                        );
+                    }
 
                     ui.separator();
 
-                    // Convergence plot
-                    ui.collapsing("Сходимость методов", |ui| {
-                        let f = &data.filtered.create_convergence_plot;
-                        f(&mut self.viz, ui);
-                    });
-
-                    // Error plot
-                    ui.collapsing("Ошибка сходимости", |ui| {
-                        let f = &data.filtered.create_error_plot;
-                        f(&mut self.viz, ui);
-                    });
-
-                    // Performance plot
-                    ui.collapsing("Производительность методов", |ui| {
-                        let f = &data.filtered.create_performance_plot;
-                        f(&mut self.viz, ui);
-                    });
-
-                    // AccelRecords table
-                    ui.collapsing("Таблица ускорений", |ui| {
-                        let f = &data.filtered.create_accel_records_table;
-                        f(ui);
-                    });
+                    // Plots, table and registered extra panels
+                    let has_accelerations = self.loader.has_accelerations();
+                    for (i, panel) in data.filtered.panels.iter().enumerate() {
+                        ui.push_id(panel.id(), |ui| {
+                            if !has_accelerations && panel.needs_accelerations() {
+                                ui.collapsing(panel.title(), |ui| {
+                                    ui.label(
+                                        "В данных нет таблицы accelerations: здесь нечего \
+                                         показать. Частичные суммы и пределы рядов — на графиках \
+                                         сходимости и ошибки.",
+                                    );
+                                });
+                                return;
+                            }
+                            let jump = self.jump_to == Some(panel.id());
+                            let section = egui::CollapsingHeader::new(panel.title())
+                                .open(jump.then_some(true))
+                                .show(ui, |ui| match &data.compare {
+                                Some(compare) => ui.columns(2, |cols| {
+                                    cols[0].push_id("a", |ui| {
+                                        let filters =
+                                            [&data.query, &data.filtered.selected_filters];
+                                        show_panel(
+                                            panel.as_ref(),
+                                            &mut self.viz,
+                                            ui,
+                                            "_a",
+                                            filters,
+                                        );
+                                        self.viz.resolve_record_json(
+                                            ui.ctx(),
+                                            &data.data,
+                                            &data.filtered.records,
+                                        );
+                                    });
+                                    cols[1].push_id("b", |ui| {
+                                        let panel = compare.panels[i].as_ref();
+                                        let filters = [&data.query, &compare.selected_filters];
+                                        show_panel(panel, &mut self.viz, ui, "_b", filters);
+                                        self.viz.resolve_record_json(
+                                            ui.ctx(),
+                                            &data.data,
+                                            &compare.records,
+                                        );
+                                    });
+                                }),
+                                None => {
+                                    let filters = [&data.query, &data.filtered.selected_filters];
+                                    show_panel(panel.as_ref(), &mut self.viz, ui, "", filters);
+                                    self.viz.resolve_record_json(
+                                        ui.ctx(),
+                                        &data.data,
+                                        &data.filtered.records,
+                                    );
+                                }
+                            });
+                            if jump {
+                                section.header_response.scroll_to_me(Some(egui::Align::TOP));
+                            }
+                        });
+                    }
+                    self.jump_to = None;
+                    self.viz.resolve_pins(&data.data);
+                    if let Some((panel_id, variant)) = self.viz.spec_request.take() {
+                        self.export_spec(panel_id, variant);
+                    }
+                    if let Some((panel_id, variant)) = self.viz.data_request.take() {
+                        self.plot_data = self.open_plot_data(panel_id, variant);
+                    }
                 } else if self.loading {
                     ui.centered_and_justified(|ui| {
                         ui.add_space(50.0);
@@ -1576,5 +6365,7 @@ impl eframe::App for DashboardApp {
                 }
             });
         });
+
+        paint_focus(ctx);
     }
 }
@@ -4,20 +4,28 @@ use datafusion::{
     arrow::{
         array::*,
         datatypes::{
-            DataType, Int8Type, Int16Type, Int32Type, Int64Type, UInt8Type, UInt16Type, UInt32Type,
-            UInt64Type,
+            DataType, Field, Int8Type, Int16Type, Int32Type, Int64Type, Schema, SchemaRef,
+            UInt8Type, UInt16Type, UInt32Type, UInt64Type,
         },
         record_batch::RecordBatch,
     },
     functions::core::expr_ext::FieldAccessor,
+    functions_nested::expr_fn::cardinality,
     logical_expr::{col, lit},
+    parquet::file::reader::{FileReader, SerializedFileReader},
     prelude::*,
 };
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::iter;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
 #[cfg(feature = "perf_tracing")]
-use std::{sync::Mutex, time::Instant};
+use std::time::Instant;
 
 // NOTE: Currently, it allows accepts entries with values _default/-1. Why? Because DataFusion doesn't like vision and doesn't like singular
 // .eq queries over partitioned dataset. We don't know why, it works perfectly on windows.
@@ -35,9 +43,6 @@ struct TimingStats {
     series_processing_time: std::time::Duration,
     accelerations_query_time: std::time::Duration,
     accelerations_processing_time: std::time::Duration,
-    accelerations_table_access_time: std::time::Duration,
-    accelerations_filtering_time: std::time::Duration,
-    accelerations_collect_time: std::time::Duration,
     metadata_query_time: std::time::Duration,
     metadata_processing_time: std::time::Duration,
     total_filter_data_time: std::time::Duration,
@@ -64,15 +69,6 @@ impl TimingStats {
             "Accelerations query time: {:?}",
             self.accelerations_query_time
         );
-        println!(
-            "  - Table access time: {:?}",
-            self.accelerations_table_access_time
-        );
-        println!(
-            "  - Filtering time: {:?}",
-            self.accelerations_filtering_time
-        );
-        println!("  - Collect time: {:?}", self.accelerations_collect_time);
         println!(
             "Accelerations processing time: {:?}",
             self.accelerations_processing_time
@@ -88,36 +84,398 @@ impl TimingStats {
     }
 }
 
-#[derive(Default, Clone)]
+/// Interned string, see [`intern`]
+pub type IStr = Arc<str>;
+
+/// Value of a parameter filter that matches the records without the parameter. A parameter
+/// filter matches only the values selected in it, absence included; an acceleration parameter
+/// filter only among the methods that set the parameter, see `accel_params_match`.
+pub const NOT_SET: &str = "(не задан)";
+
+/// Event name filter value that matches the records without events, so that selecting every
+/// name doesn't leave them out
+pub const NO_EVENTS: &str = "(без событий)";
+
+// Names, precisions and parameters repeat across every row but have few distinct values
+static INTERNER: LazyLock<Mutex<HashSet<IStr>>> = LazyLock::new(Default::default);
+
+/// Shared copy of `s`: equal strings point to the same allocation, so they are stored once and
+/// compare by pointer first.
+pub fn intern(s: &str) -> IStr {
+    let mut pool = INTERNER.lock().unwrap();
+    if let Some(s) = pool.get(s) {
+        return s.clone();
+    }
+    let s: IStr = s.into();
+    pool.insert(s.clone());
+    s
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Filters {
-    pub precisions: HashSet<String>,
-    pub base_series: HashSet<String>,
-    pub base_accel: HashSet<String>,
+    pub precisions: HashSet<IStr>,
+    pub base_series: HashSet<IStr>,
+    // Series families, see `grouping::series_family`. Quick filters only
+    pub families: HashSet<IStr>,
+    pub base_accel: HashSet<IStr>,
     pub m_values: HashSet<i32>,
-    pub accel_params: HashMap<String, HashSet<String>>,
-    pub series_params: HashMap<String, HashSet<String>>,
+    pub accel_params: HashMap<IStr, HashSet<IStr>>,
+    pub series_params: HashMap<IStr, HashSet<IStr>>,
+    // Outcome of the acceleration run. In the available (quick) filters `Some` only marks the
+    // predicate as applicable.
+    pub has_errors: Option<bool>,
+    pub has_events: Option<bool>,
+    pub event_names: HashSet<IStr>,
+    // Whether the run shows signs of divergence (see `anomaly`). Quick filters only
+    pub divergent: Option<bool>,
+    // Preview mode: load only the first N series (by id). Top-level query only
+    pub sample: Option<usize>,
+    // Page to load when more than `PAGE_SIZE` series match. Top-level query only
+    pub page: usize,
+    // Reruns of the same configuration on the same series. Top-level query only
+    pub duplicates: DuplicatePolicy,
+}
+
+/// What to do with accel records of the same (method, m, parameters) on the same series, e.g.
+/// from reruns
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DuplicatePolicy {
+    #[default]
+    KeepAll,
+    // The one of the greatest `AccelRecord::run`, ordered as `sort_param_values`; needs `run_id`
+    KeepLatest,
+    // Pointwise mean: values arithmetic, deviations geometric (exact zeros left out)
+    Average,
+    // Mean with the range of the deviations as its uncertainty
+    Spread,
+}
+
+impl DuplicatePolicy {
+    pub const ALL: [Self; 4] = [Self::KeepAll, Self::KeepLatest, Self::Average, Self::Spread];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::KeepAll => "оставить все",
+            Self::KeepLatest => "последний",
+            Self::Average => "среднее",
+            Self::Spread => "среднее с разбросом",
+        }
+    }
+}
+
+impl Filters {
+    /// Short human-readable description of the non-empty filters
+    pub fn summary(&self) -> String {
+        fn list<T: ToString>(values: impl IntoIterator<Item = T>) -> String {
+            let mut values: Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+            values.sort();
+            values.join(", ")
+        }
+        fn params(params: &HashMap<IStr, HashSet<IStr>>) -> String {
+            let mut res: Vec<String> = params
+                .iter()
+                .map(|(k, v)| format!("{}=[{}]", k, list(v)))
+                .collect();
+            res.sort();
+            res.join(" ")
+        }
+
+        let mut parts = Vec::new();
+        if !self.precisions.is_empty() {
+            parts.push(format!("precision: {}", list(&self.precisions)));
+        }
+        if !self.base_series.is_empty() {
+            parts.push(format!("series: {}", list(&self.base_series)));
+        }
+        if !self.families.is_empty() {
+            parts.push(format!("families: {}", list(&self.families)));
+        }
+        if !self.series_params.is_empty() {
+            parts.push(format!("series params: {}", params(&self.series_params)));
+        }
+        if !self.base_accel.is_empty() {
+            parts.push(format!("accel: {}", list(&self.base_accel)));
+        }
+        if !self.m_values.is_empty() {
+            parts.push(format!("m: {}", list(&self.m_values)));
+        }
+        if !self.accel_params.is_empty() {
+            parts.push(format!("accel params: {}", params(&self.accel_params)));
+        }
+        if let Some(has) = self.has_errors {
+            parts.push(format!("errors: {has}"));
+        }
+        if let Some(has) = self.has_events {
+            parts.push(format!("events: {has}"));
+        }
+        if !self.event_names.is_empty() {
+            parts.push(format!("event names: {}", list(&self.event_names)));
+        }
+        if let Some(divergent) = self.divergent {
+            parts.push(format!("divergent: {divergent}"));
+        }
+        if let Some(n) = self.sample {
+            parts.push(format!("sample: {n}"));
+        }
+        if self.page > 0 {
+            parts.push(format!("page: {}", self.page + 1));
+        }
+        if self.duplicates != DuplicatePolicy::KeepAll {
+            parts.push(format!("duplicates: {:?}", self.duplicates));
+        }
+        if parts.is_empty() {
+            "(без фильтров)".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+
+    /// Error/event predicates, shared by the loader and quick filtering
+    pub fn matches_outcome(&self, record: &AccelRecord) -> bool {
+        self.has_errors
+            .is_none_or(|has| has == !record.errors.is_empty())
+            && self
+                .has_events
+                .is_none_or(|has| has == !record.events.is_empty())
+            && (self.event_names.is_empty()
+                || (record.events.is_empty() && self.event_names.contains(NO_EVENTS))
+                || record
+                    .events
+                    .iter()
+                    .any(|e| self.event_names.contains(e.name.as_str())))
+    }
+}
+
+/// Type of the values of a parameter, detected from all of them (`NOT_SET` aside). Values are
+/// stored as strings whatever their type
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParamKind {
+    Text,
+    Number,
+    Bool,
+}
+
+impl ParamKind {
+    pub fn of<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+        let values: Vec<&str> = values.into_iter().filter(|v| *v != NOT_SET).collect();
+        if values.is_empty() {
+            Self::Text
+        } else if values.iter().all(|v| parse_bool(v).is_some()) {
+            Self::Bool
+        } else if values.iter().all(|v| parse_number(v).is_some()) {
+            Self::Number
+        } else {
+            Self::Text
+        }
+    }
+}
+
+pub fn parse_number(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|x| x.is_finite())
+}
+
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Sorts the values of a parameter by their kind, so that "10" comes after "2"; `NOT_SET` first
+pub fn sort_param_values<S: AsRef<str>>(values: &mut [S]) {
+    let kind = ParamKind::of(values.iter().map(|v| v.as_ref()));
+    values.sort_by(|a, b| {
+        let (a, b) = (a.as_ref(), b.as_ref());
+        (b == NOT_SET).cmp(&(a == NOT_SET)).then_with(|| {
+            let numeric = match kind {
+                ParamKind::Number => parse_number(a)
+                    .zip(parse_number(b))
+                    .map(|(a, b)| a.total_cmp(&b)),
+                _ => None,
+            };
+            numeric.unwrap_or(std::cmp::Ordering::Equal).then(a.cmp(b))
+        })
+    });
+}
+
+// Whether the value of a parameter, or its absence, is selected
+fn param_matches(params: &HashMap<IStr, IStr>, arg: &str, values: &HashSet<IStr>) -> bool {
+    values.is_empty()
+        || params
+            .get(arg)
+            .map_or(values.contains(NOT_SET), |v| values.contains(v))
+}
+
+/// Parameter predicates of the in-memory filtering, as `filter_params` for the loader
+pub fn params_match(params: &HashMap<IStr, IStr>, filters: &HashMap<IStr, HashSet<IStr>>) -> bool {
+    filters
+        .iter()
+        .all(|(arg, values)| param_matches(params, arg, values))
+}
+
+/// Acceleration parameter predicates of the in-memory filtering. A parameter filter applies only to
+/// the records of the methods that set the parameter, by `param_methods`: a Levin-only parameter
+/// leaves the records of the other methods alone, and `NOT_SET` selects the Levin records
+/// without it
+pub fn accel_params_match<K, M>(
+    accel: &AccelInfo,
+    filters: &HashMap<IStr, HashSet<IStr>>,
+    param_methods: &HashMap<K, Vec<M>>,
+) -> bool
+where
+    K: Borrow<str> + Hash + Eq,
+    M: AsRef<str>,
+{
+    filters.iter().all(|(arg, values)| {
+        let applies = param_methods
+            .get(&**arg)
+            .is_some_and(|methods| methods.iter().any(|m| m.as_ref() == &*accel.name));
+        !applies || param_matches(&accel.additional_args, arg, values)
+    })
+}
+
+// Build a DataFusion filter on whether an optional list column is non-empty
+fn filter_non_empty(df: &DataFrame, col_name: &str, non_empty: bool) -> Expr {
+    if !df.schema().has_column_with_unqualified_name(col_name) {
+        // Missing column means no entries at all
+        return lit(!non_empty);
+    }
+    let len = cardinality(col(col_name));
+    if non_empty {
+        len.gt(lit(0u64))
+    } else {
+        col(col_name).is_null().or(len.eq(lit(0u64)))
+    }
+}
+
+// Top-level series filters, shared by loading and counting
+fn filter_series(mut df: DataFrame, filters: &Filters) -> Result<DataFrame> {
+    // Apply series filters
+    if !filters.precisions.is_empty() {
+        let mut filter_expr = col("precision").eq(lit("_default"));
+        for p in filters.precisions.iter() {
+            filter_expr = filter_expr.or(col("precision").eq(lit(p.as_ref())));
+        }
+        df = df.filter(filter_expr)?;
+    }
+
+    if !filters.base_series.is_empty() {
+        let mut filter_expr = col("series_name").eq(lit("_default"));
+        for s in filters.base_series.iter() {
+            filter_expr = filter_expr.or(col("series_name").eq(lit(s.as_ref())));
+        }
+        df = df.filter(filter_expr)?;
+    }
+
+    // Apply series_params filters using SQL
+    if let Some(param_filter) = filter_params("arguments", &filters.series_params, None) {
+        df = df.filter(param_filter)?;
+    }
+
+    if let Some(n) = filters.sample {
+        df = df
+            .sort(vec![col("series_id").sort(true, false)])?
+            .limit(0, Some(n))?;
+    }
+
+    Ok(df)
+}
+
+// Accel filters that can be pushed down. Event names are only checked after decoding
+fn filter_accelerations(
+    mut df: DataFrame,
+    series_ids: &[i32],
+    filters: &Filters,
+    param_methods: &HashMap<String, Vec<String>>,
+) -> Result<DataFrame> {
+    // Filter by series_ids
+    {
+        let mut filter_expr = col("series_id").eq(lit(-1));
+        for &series_id in series_ids.iter() {
+            filter_expr = filter_expr.or(col("series_id").eq(lit(series_id)));
+        }
+        df = df.filter(filter_expr)?;
+    }
+
+    // Apply accel filters
+    if !filters.base_accel.is_empty() {
+        let mut filter_expr = col("accel_name").eq(lit("_default"));
+        for a in filters.base_accel.iter() {
+            filter_expr = filter_expr.or(col("accel_name").eq(lit(a.as_ref())));
+        }
+        df = df.filter(filter_expr)?;
+    }
+
+    if !filters.m_values.is_empty() {
+        let mut filter_expr = col("m_value").eq(lit("_default"));
+        for m in filters.m_values.iter() {
+            filter_expr = filter_expr.or(col("m_value").eq(lit(*m)));
+        }
+        df = df.filter(filter_expr)?;
+    }
+
+    // Apply accel_params filters using SQL
+    if let Some(param_filter) = filter_params(
+        "additional_args",
+        &filters.accel_params,
+        Some(param_methods),
+    ) {
+        df = df.filter(param_filter)?;
+    }
+
+    if let Some(has_errors) = filters.has_errors {
+        let filter_expr = filter_non_empty(&df, "errors", has_errors);
+        df = df.filter(filter_expr)?;
+    }
+    if let Some(has_events) = filters.has_events {
+        let filter_expr = filter_non_empty(&df, "events", has_events);
+        df = df.filter(filter_expr)?;
+    }
+
+    Ok(df)
 }
 
-// Build DataFusion filter expressions for struct field parameters
-fn filter_params(col_name: &str, filters: &HashMap<String, HashSet<String>>) -> Option<Expr> {
+// Build DataFusion filter expressions for struct field parameters, see `NOT_SET`. With
+// `param_methods`, a parameter filter applies only to the methods that set it, see
+// `accel_params_match`
+fn filter_params(
+    col_name: &str,
+    filters: &HashMap<IStr, HashSet<IStr>>,
+    param_methods: Option<&HashMap<String, Vec<String>>>,
+) -> Option<Expr> {
     let mut fin: Option<Expr> = None;
 
     for (arg, values) in filters {
-        let mut curr: Option<Expr> = None;
+        if values.is_empty() {
+            continue;
+        }
+        let methods = param_methods.map(|m| m.get(arg.as_ref()).map_or(&[][..], Vec::as_slice));
+        if methods.is_some_and(<[String]>::is_empty) {
+            // No record sets it, so there's nothing to apply it to
+            continue;
+        }
+        let field = col(col_name).field(arg.as_ref());
+        let mut curr = lit(false);
         for value in values {
-            let f = col(col_name).field(arg).eq(lit(value));
-            curr = Some(match curr {
-                None => f.or(col(col_name).field(arg).eq(lit("_default"))), // ugly fix
-                Some(curr) => curr.or(f),
+            curr = curr.or(if value.as_ref() == NOT_SET {
+                field.clone().is_null()
+            } else {
+                field.clone().eq(lit(value.as_ref()))
             });
         }
-        if let Some(mut curr) = curr {
-            curr = curr.or(col(col_name).field(arg).is_null());
-            fin = Some(match fin {
-                None => curr,
-                Some(fin) => fin.and(curr),
-            });
+        if let Some(methods) = methods {
+            let mut set_by = col("accel_name").eq(lit("_default"));
+            for m in methods {
+                set_by = set_by.or(col("accel_name").eq(lit(m.as_str())));
+            }
+            curr = (!set_by).or(curr);
         }
+        fin = Some(match fin {
+            None => curr,
+            Some(fin) => fin.and(curr),
+        });
     }
 
     fin
@@ -140,15 +498,143 @@ impl ComplexNumber {
             real_str
         }
     }
+
+    /// Modulus and argument (in radians) of `self - center`
+    pub fn polar_around(&self, center: &ComplexNumber) -> (Scientific, f64) {
+        let (re, im) = (self.real - center.real, self.imag - center.imag);
+        let (re, im) = (re.normalize(), im.normalize());
+        // Common exponent, so that differences far below the f64 range don't vanish
+        let exponent = [re, im]
+            .iter()
+            .filter(|x| x.0 != 0.0)
+            .map(|x| x.1)
+            .max()
+            .unwrap_or(0);
+        let x = re.0 * 10f64.powi(re.1 - exponent);
+        let y = im.0 * 10f64.powi(im.1 - exponent);
+        (Scientific(x.hypot(y), exponent).normalize(), y.atan2(x))
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct SeriesPoint {
     pub n: i32,
     pub value: ComplexNumber,
     pub deviation: Scientific,
 }
 
+// `Scientific` values with mantissas and exponents in separate columns, so there is no padding
+#[derive(Debug, Clone, Default)]
+struct ScientificColumn {
+    mantissa: Vec<f64>,
+    exponent: Vec<i32>,
+}
+
+impl ScientificColumn {
+    fn push(&mut self, v: Scientific) {
+        self.mantissa.push(v.0);
+        self.exponent.push(v.1);
+    }
+
+    fn get(&self, i: usize) -> Scientific {
+        Scientific(self.mantissa[i], self.exponent[i])
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ComplexColumn {
+    real: ScientificColumn,
+    imag: ScientificColumn,
+}
+
+impl ComplexColumn {
+    fn push(&mut self, v: ComplexNumber) {
+        self.real.push(v.real);
+        self.imag.push(v.imag);
+    }
+
+    fn get(&self, i: usize) -> ComplexNumber {
+        ComplexNumber {
+            real: self.real.get(i),
+            imag: self.imag.get(i),
+        }
+    }
+}
+
+/// Computed points of a series, stored column by column.
+///
+/// Serialized as a plain list of [`SeriesPoint`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<SeriesPoint>", into = "Vec<SeriesPoint>")]
+pub struct SeriesPoints {
+    n: Vec<i32>,
+    value: ComplexColumn,
+    deviation: ScientificColumn,
+}
+
+impl SeriesPoints {
+    pub fn push(&mut self, point: SeriesPoint) {
+        self.n.push(point.n);
+        self.value.push(point.value);
+        self.deviation.push(point.deviation);
+    }
+
+    pub fn len(&self) -> usize {
+        self.n.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n.is_empty()
+    }
+
+    /// Iteration numbers of all points
+    pub fn n(&self) -> &[i32] {
+        &self.n
+    }
+
+    pub fn get(&self, i: usize) -> SeriesPoint {
+        SeriesPoint {
+            n: self.n[i],
+            value: self.value.get(i),
+            deviation: self.deviation.get(i),
+        }
+    }
+
+    pub fn first(&self) -> Option<SeriesPoint> {
+        (!self.is_empty()).then(|| self.get(0))
+    }
+
+    pub fn last(&self) -> Option<SeriesPoint> {
+        (!self.is_empty()).then(|| self.get(self.len() - 1))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = SeriesPoint> + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+}
+
+impl FromIterator<SeriesPoint> for SeriesPoints {
+    fn from_iter<I: IntoIterator<Item = SeriesPoint>>(iter: I) -> Self {
+        let mut res = Self::default();
+        for point in iter {
+            res.push(point);
+        }
+        res
+    }
+}
+
+impl From<Vec<SeriesPoint>> for SeriesPoints {
+    fn from(points: Vec<SeriesPoint>) -> Self {
+        points.into_iter().collect()
+    }
+}
+
+impl From<SeriesPoints> for Vec<SeriesPoint> {
+    fn from(points: SeriesPoints) -> Self {
+        points.iter().collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorInfo {
     pub n: i32,
@@ -188,7 +674,7 @@ fn parse_scientific(s: &str) -> Result<Scientific> {
 }
 
 // to_x
-fn to_str<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<&'a str>>> {
+pub(crate) fn to_str<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<&'a str>>> {
     if let Some(v) = v.as_string_opt::<i32>() {
         Ok(v.iter().collect())
     } else if let Some(v) = v.as_string_opt::<i64>() {
@@ -205,7 +691,7 @@ fn to_str<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<&'a str>>> {
     }
 }
 
-fn to_i64<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<i64>>> {
+pub(crate) fn to_i64(name: &str, v: &dyn Array) -> Result<Vec<Option<i64>>> {
     // wildly inefficient
     if let Some(v) = v.as_primitive_opt::<Int8Type>() {
         Ok(v.iter().map(|x| x.map(|x| x as i64)).collect())
@@ -233,35 +719,98 @@ fn to_i64<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<i64>>> {
     }
 }
 
-fn to_list<R>(
-    name: &str,
-    v: &dyn Array,
-    f: impl for<'b> Fn(&'b dyn Array) -> Result<R>,
-) -> Result<Vec<Option<R>>> {
+// Run identifiers may be written as strings or as integers (e.g. timestamps)
+fn to_run_ids(name: &str, v: &dyn Array) -> Result<Vec<Option<IStr>>> {
+    if let Ok(ids) = to_str(name, v) {
+        return Ok(ids.into_iter().map(|id| id.map(intern)).collect());
+    }
+    let ids = to_i64(name, v).map_err(|_| {
+        anyhow!(
+            "Expected `{name}` to be string or int, found {}",
+            v.data_type()
+        )
+    })?;
+    Ok(ids
+        .into_iter()
+        .map(|id| id.map(|id| intern(&id.to_string())))
+        .collect())
+}
+
+// Element ranges of every row of a list-like array, `None` for null rows
+fn list_ranges(v: &dyn Array) -> Option<(&ArrayRef, Vec<Option<Range<usize>>>)> {
+    fn ranges<O: OffsetSizeTrait>(
+        v: &dyn Array,
+        offsets: &[O],
+        sizes: Option<&[O]>,
+    ) -> Vec<Option<Range<usize>>> {
+        (0..v.len())
+            .map(|i| {
+                let start = offsets[i].as_usize();
+                let end = match sizes {
+                    Some(sizes) => start + sizes[i].as_usize(),
+                    None => offsets[i + 1].as_usize(),
+                };
+                v.is_valid(i).then_some(start..end)
+            })
+            .collect()
+    }
+
     if let Some(x) = v.as_list_opt::<i32>() {
-        let mut res = Vec::new();
-        for i in x.iter() {
-            res.push(if let Some(i) = i { Some(f(&i)?) } else { None });
-        }
-        Ok(res)
+        Some((x.values(), ranges(v, x.value_offsets(), None)))
     } else if let Some(x) = v.as_list_opt::<i64>() {
-        let mut res = Vec::new();
-        for i in x.iter() {
-            res.push(if let Some(i) = i { Some(f(&i)?) } else { None });
-        }
-        Ok(res)
+        Some((x.values(), ranges(v, x.value_offsets(), None)))
     } else if let Some(x) = v.as_list_view_opt::<i32>() {
-        let mut res = Vec::new();
-        for i in x.iter() {
-            res.push(if let Some(i) = i { Some(f(&i)?) } else { None });
-        }
-        Ok(res)
+        Some((
+            x.values(),
+            ranges(v, x.value_offsets(), Some(x.value_sizes())),
+        ))
     } else if let Some(x) = v.as_list_view_opt::<i64>() {
-        let mut res = Vec::new();
-        for i in x.iter() {
-            res.push(if let Some(i) = i { Some(f(&i)?) } else { None });
+        Some((
+            x.values(),
+            ranges(v, x.value_offsets(), Some(x.value_sizes())),
+        ))
+    } else {
+        None
+    }
+}
+
+// Lists at least this long on average are decoded row by row: the per-row overhead is amortized
+// there, while decoding the whole column first costs an extra copy of every element
+const ROW_BY_ROW_LIST_LEN: usize = 64;
+
+/// Decodes the elements of a list column. Short lists are decoded with a single call to `f` for
+/// all of them and then split by row (see `benches/conversion.rs`).
+pub(crate) fn to_list<T: Clone, R: FromIterator<T>>(
+    name: &str,
+    v: &dyn Array,
+    f: impl Fn(&dyn Array) -> Result<Vec<T>>,
+) -> Result<Vec<Option<R>>> {
+    if let Some((values, ranges)) = list_ranges(v) {
+        if values.len() >= ROW_BY_ROW_LIST_LEN * ranges.len() {
+            return ranges
+                .into_iter()
+                .map(|range| {
+                    range
+                        .map(|range| {
+                            let row = values.slice(range.start, range.len());
+                            Ok(f(row.as_ref())?.into_iter().collect())
+                        })
+                        .transpose()
+                })
+                .collect();
         }
-        Ok(res)
+        let values = f(values.as_ref())?;
+        ranges
+            .into_iter()
+            .map(|range| {
+                range
+                    .map(|range| {
+                        let row = values.get(range).context("List offsets out of bounds")?;
+                        Ok(row.iter().cloned().collect())
+                    })
+                    .transpose()
+            })
+            .collect()
     } else if let Some(v) = v.as_any().downcast_ref::<NullArray>() {
         Ok(iter::repeat_with(|| None).take(v.len()).collect())
     } else {
@@ -288,18 +837,19 @@ fn to_list<R>(
 //     }
 // }
 
-fn to_struct_str<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<HashMap<String, String>>> {
+fn to_struct_str(name: &str, v: &dyn Array) -> Result<Vec<HashMap<IStr, IStr>>> {
     if let Some(struct_array) = v.as_struct_opt() {
-        let mut maps: Vec<HashMap<String, String>> =
+        let mut maps: Vec<HashMap<IStr, IStr>> =
             iter::repeat(HashMap::new()).take(v.len()).collect();
         for (field_name, field_array) in struct_array
             .column_names()
             .into_iter()
             .zip(struct_array.columns())
         {
+            let key = intern(field_name);
             for (i, val) in (0..field_array.len()).zip(to_str(field_name, field_array)?) {
                 if let Some(val) = val {
-                    maps[i].insert(field_name.to_string(), val.to_string());
+                    maps[i].insert(key.clone(), intern(val));
                 }
             }
         }
@@ -340,7 +890,7 @@ fn to_complex<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<ComplexNumb
     ))
 }
 
-fn to_series_point<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<SeriesPoint>> {
+pub(crate) fn to_series_point(name: &str, v: &dyn Array) -> Result<Vec<SeriesPoint>> {
     if let Some(v) = v.as_struct_opt() {
         if let (Some(n), Some(value), Some(deviation)) = (
             v.column_by_name("n"),
@@ -350,7 +900,7 @@ fn to_series_point<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<SeriesPoint>>
             if let (Ok(n), Ok(value), Ok(deviation)) =
                 (to_i64("", n), to_complex("", value), to_str("", deviation))
             {
-                let mut res = Vec::new();
+                let mut res = Vec::with_capacity(v.len());
                 for ((n, value), deviation) in n.into_iter().zip(value).zip(deviation) {
                     res.push(SeriesPoint {
                         n: n.context("n not provided")? as i32,
@@ -368,7 +918,7 @@ fn to_series_point<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<SeriesPoint>>
     ))
 }
 
-fn to_error_info<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<ErrorInfo>> {
+pub(crate) fn to_error_info(name: &str, v: &dyn Array) -> Result<Vec<ErrorInfo>> {
     if let Some(v) = v.as_struct_opt() {
         if let (Some(n), Some(message)) = (v.column_by_name("n"), v.column_by_name("message")) {
             if let (Ok(n), Ok(message)) = (to_i64("", n), to_str("", message)) {
@@ -420,13 +970,13 @@ fn to_event_info<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<EventInfo>> {
     ))
 }
 
-fn to_accel_point<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<AccelPoint>>> {
+pub(crate) fn to_accel_point(name: &str, v: &dyn Array) -> Result<Vec<Option<AccelPoint>>> {
     if let Some(v) = v.as_struct_opt() {
         if let (Some(value), Some(deviation)) =
             (v.column_by_name("value"), v.column_by_name("deviation"))
         {
             if let (Ok(value), Ok(deviation)) = (to_complex("", value), to_str("", deviation)) {
-                let mut res = Vec::new();
+                let mut res = Vec::with_capacity(v.len());
                 for (i, (value, deviation)) in value.into_iter().zip(deviation).enumerate() {
                     res.push(if v.is_null(i) {
                         None
@@ -449,21 +999,66 @@ fn to_accel_point<'a>(name: &str, v: &'a dyn Array) -> Result<Vec<Option<AccelPo
     ))
 }
 
+// Whether the points of a `computed` list column carry the optional `deviation_stddev`
+fn has_stddev(v: &dyn Array) -> bool {
+    list_ranges(v).is_some_and(|(values, _)| {
+        values
+            .as_struct_opt()
+            .is_some_and(|s| s.column_by_name("deviation_stddev").is_some())
+    })
+}
+
+pub(crate) fn to_accel_stddev(name: &str, v: &dyn Array) -> Result<Vec<Option<Scientific>>> {
+    let stddev = v
+        .as_struct_opt()
+        .and_then(|v| v.column_by_name("deviation_stddev"))
+        .with_context(|| format!("Expected `{name}` to have deviation_stddev"))?;
+    to_str(&format!("{name}.deviation_stddev"), stddev)?
+        .into_iter()
+        .map(|s| s.map(parse_scientific).transpose())
+        .collect()
+}
+
+// deviation ± stddev; the lower end is clamped to zero
+fn stddev_range(deviation: Scientific, stddev: Scientific) -> [Scientific; 2] {
+    let (deviation, stddev) = (deviation.abs(), stddev.abs());
+    let zero = Scientific(0.0, 0);
+    let low = deviation - stddev;
+    [if low < zero { zero } else { low }, deviation + stddev]
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SeriesRecord {
-    pub precision: String,
+    pub precision: IStr,
     pub series_id: i32,
-    pub name: String,
-    pub arguments: HashMap<String, String>,
+    pub name: IStr,
+    pub arguments: HashMap<IStr, IStr>,
     pub series_limit: ComplexNumber,
-    pub computed: Vec<SeriesPoint>,
+    pub computed: SeriesPoints,
+    // Experiment batch from the optional `run_id` column, for comparing reruns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<IStr>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccelInfo {
-    pub name: String,
+    pub name: IStr,
     pub m_value: i32,
-    pub additional_args: HashMap<String, String>,
+    pub additional_args: HashMap<IStr, IStr>,
+}
+
+impl Eq for AccelInfo {}
+
+// The arguments are hashed in sorted order, as maps with the same entries are equal whatever
+// their order
+impl std::hash::Hash for AccelInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.m_value.hash(state);
+        let mut args: Vec<_> = self.additional_args.iter().collect();
+        args.sort();
+        args.hash(state);
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -472,129 +1067,964 @@ pub struct AccelPoint {
     pub deviation: Scientific,
 }
 
+/// Computed points of an acceleration, aligned by index with the points of its series; missing
+/// points are `None`. Stored column by column, serialized as a plain list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Option<AccelPoint>>", into = "Vec<Option<AccelPoint>>")]
+pub struct AccelPoints {
+    present: Vec<bool>,
+    // Missing points hold defaults so that indices stay aligned
+    value: ComplexColumn,
+    deviation: ScientificColumn,
+}
+
+impl AccelPoints {
+    pub fn push(&mut self, point: Option<AccelPoint>) {
+        self.present.push(point.is_some());
+        let point = point.unwrap_or(AccelPoint {
+            value: ComplexNumber::default(),
+            deviation: Scientific::default(),
+        });
+        self.value.push(point.value);
+        self.deviation.push(point.deviation);
+    }
+
+    pub fn len(&self) -> usize {
+        self.present.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.present.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<AccelPoint> {
+        self.present[i].then(|| AccelPoint {
+            value: self.value.get(i),
+            deviation: self.deviation.get(i),
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Option<AccelPoint>> + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+}
+
+impl FromIterator<Option<AccelPoint>> for AccelPoints {
+    fn from_iter<I: IntoIterator<Item = Option<AccelPoint>>>(iter: I) -> Self {
+        let mut res = Self::default();
+        for point in iter {
+            res.push(point);
+        }
+        res
+    }
+}
+
+impl From<Vec<Option<AccelPoint>>> for AccelPoints {
+    fn from(points: Vec<Option<AccelPoint>>) -> Self {
+        points.into_iter().collect()
+    }
+}
+
+impl From<AccelPoints> for Vec<Option<AccelPoint>> {
+    fn from(points: AccelPoints) -> Self {
+        points.iter().collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccelRecord {
     pub accel_info: AccelInfo,
-    pub computed: Vec<Option<AccelPoint>>,
+    // Run that produced the record: the optional `run_id` column of the accelerations table, or
+    // else that of its series
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<IStr>,
+    pub computed: AccelPoints,
     pub errors: Vec<ErrorInfo>,
     pub events: Vec<EventInfo>,
+    // Other records of the same configuration merged into this one by the `DuplicatePolicy`
+    #[serde(default)]
+    pub reruns: usize,
+    // Uncertainty of the deviation as a range [low, high], aligned with `computed`: the range over
+    // reruns merged by `DuplicatePolicy::Spread`, or ± the `deviation_stddev` of the points.
+    // Empty if there is none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uncertainty: Vec<Option<[Scientific; 2]>>,
 }
 
 pub type SeriesData = (SeriesRecord, Vec<AccelRecord>);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Metadata {
     pub precisions: Vec<String>,
     pub series_names: Vec<String>,
     pub accel_names: Vec<String>,
     pub m_values: Vec<i32>,
     pub accel_param_info: HashMap<String, Vec<String>>,
+    // Methods whose records set each acceleration parameter
+    pub accel_param_methods: HashMap<String, Vec<String>>,
     pub series_param_info: HashMap<String, Vec<String>>,
+    pub event_names: Vec<String>,
 }
 
+/// Piece of [`Metadata`], reported as soon as it's computed
 #[derive(Clone)]
-pub struct DataLoader {
-    ctx: SessionContext,
-    pub metadata: Metadata,
+pub enum MetadataPart {
+    Precisions(Vec<String>),
+    SeriesNames(Vec<String>),
+    AccelNames(Vec<String>),
+    MValues(Vec<i32>),
+    AccelParamInfo(HashMap<String, Vec<String>>),
+    AccelParamMethods(HashMap<String, Vec<String>>),
+    SeriesParamInfo(HashMap<String, Vec<String>>),
+    EventNames(Vec<String>),
 }
 
-impl DataLoader {
-    pub async fn new(path: &str) -> Result<Self> {
-        let ctx = SessionContext::new();
+impl MetadataPart {
+    /// Number of parts making up the full metadata
+    pub const COUNT: usize = 8;
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Precisions(_) => "точности",
+            Self::SeriesNames(_) => "ряды",
+            Self::AccelNames(_) => "методы ускорения",
+            Self::MValues(_) => "значения m",
+            Self::AccelParamInfo(_) => "параметры ускорения",
+            Self::AccelParamMethods(_) => "параметры методов",
+            Self::SeriesParamInfo(_) => "параметры рядов",
+            Self::EventNames(_) => "события",
+        }
+    }
+}
 
-        // Register series table
-        let series_options = ParquetReadOptions::default().table_partition_cols(vec![
-            ("precision".to_string(), DataType::Utf8),
-            ("series_name".to_string(), DataType::Utf8),
-        ]);
-        ctx.register_parquet("series", &format!("{}/series", path), series_options)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to register series parquet: {}", e))?;
-
-        // Register accelerations table
-        let accel_options = ParquetReadOptions::default()
-            .table_partition_cols(vec![("series_id".to_string(), DataType::Int32)]);
-        ctx.register_parquet(
-            "accelerations",
-            &format!("{}/accelerations", path),
-            accel_options,
-        )
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to register accelerations parquet: {}", e))?;
+impl Metadata {
+    pub fn apply(&mut self, part: MetadataPart) {
+        match part {
+            MetadataPart::Precisions(v) => self.precisions = v,
+            MetadataPart::SeriesNames(v) => self.series_names = v,
+            MetadataPart::AccelNames(v) => self.accel_names = v,
+            MetadataPart::MValues(v) => self.m_values = v,
+            MetadataPart::AccelParamInfo(v) => self.accel_param_info = v,
+            MetadataPart::AccelParamMethods(v) => self.accel_param_methods = v,
+            MetadataPart::SeriesParamInfo(v) => self.series_param_info = v,
+            MetadataPart::EventNames(v) => self.event_names = v,
+        }
+    }
 
-        let metadata = Self::compute_metadata(&ctx).await?;
-        Ok(Self { ctx, metadata })
+    pub fn into_parts(self) -> [MetadataPart; MetadataPart::COUNT] {
+        [
+            MetadataPart::Precisions(self.precisions),
+            MetadataPart::SeriesNames(self.series_names),
+            MetadataPart::AccelNames(self.accel_names),
+            MetadataPart::MValues(self.m_values),
+            MetadataPart::AccelParamInfo(self.accel_param_info),
+            MetadataPart::AccelParamMethods(self.accel_param_methods),
+            MetadataPart::SeriesParamInfo(self.series_param_info),
+            MetadataPart::EventNames(self.event_names),
+        ]
     }
+}
 
-    async fn compute_metadata(ctx: &SessionContext) -> Result<Metadata> {
-        let precisions = Self::get_unique_strings(ctx, "series", "precision").await?;
-        let series_names = Self::get_unique_strings(ctx, "series", "series_name").await?;
-        let accel_names = Self::get_unique_strings(ctx, "accelerations", "accel_name").await?;
-        println!("collecting m_values");
-        let m_values = Self::get_unique_m_values(ctx).await?;
+// Version of what the metadata scan produces; bump it whenever that changes, e.g. new entries or
+// another order of the values, so that sidecars written by older builds are rescanned
+const METADATA_CACHE_VERSION: u32 = 1;
+
+// Metadata sidecar, valid while its version and the fingerprint of the data files match
+#[derive(Serialize, Deserialize)]
+struct MetadataCache {
+    // Missing from the sidecars of the builds before it was added
+    #[serde(default)]
+    version: u32,
+    fingerprint: String,
+    metadata: Metadata,
+}
 
-        println!("collecting accel_param_info");
-        let accel_param_info =
-            Self::get_unique_param_info(ctx, "accelerations", "additional_args").await?;
+/// FNV-1a: unlike `DefaultHasher`, fixed across Rust versions
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
 
-        println!("collecting series_param_info");
-        let series_param_info = Self::get_unique_param_info(ctx, "series", "arguments").await?;
-
-        Ok(Metadata {
-            precisions,
-            series_names,
-            accel_names,
-            m_values,
-            accel_param_info,
-            series_param_info,
-        })
+/// Results with more series than this are loaded page by page
+pub const PAGE_SIZE: usize = 100;
+
+/// Series loaded by a single query
+pub struct LoadedPage {
+    pub data: Vec<SeriesData>,
+    // Number of series matching the filters, over all pages
+    pub total_series: usize,
+}
+
+impl LoadedPage {
+    pub fn page_count(&self) -> usize {
+        self.total_series.div_ceil(PAGE_SIZE).max(1)
     }
+}
 
-    // Extract unique parameter names and values from struct fields
-    async fn get_unique_param_info(
-        ctx: &SessionContext,
-        table: &str,
-        column: &str,
-    ) -> Result<HashMap<String, Vec<String>>> {
-        let df = ctx.table(table).await?;
-        let df = df.select(vec![col(column)])?;
-        let batches: Vec<RecordBatch> = df.collect().await.map_err(|e| {
-            anyhow::anyhow!("Failed to get unique {} from {}: {}", column, table, e)
-        })?;
+/// Result of a count-only dry run
+pub struct QueryEstimate {
+    pub series: usize,
+    // Upper bound: event names are only checked when records are decoded
+    pub accel_records: usize,
+    // Share of the on-disk parquet size the query would read
+    pub bytes: u64,
+}
 
-        let mut param_info: HashMap<String, Vec<String>> = HashMap::new();
+/// Registered table as seen by DataFusion, for the schema browser
+pub struct TableSchema {
+    pub name: String,
+    pub schema: SchemaRef,
+    // Columns derived from the hive-style directory layout
+    pub partition_cols: Vec<String>,
+    // File path (relative to the table directory) -> row count from the parquet footer
+    pub files: Vec<(String, Result<i64, String>)>,
+}
 
-        for batch in batches {
-            let col = batch.column_by_name(column).context("column not found")?;
-            let param_maps = to_struct_str(column, col)?;
+/// Revision of the layout of the result files. Batches read from older files are converted to
+/// the latest layout before decoding, so old archives open without a migration.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum SchemaVersion {
+    // Deviations as f64
+    V1,
+    // Deviations as strings, for exponents beyond f64
+    V2,
+    // Optional `events` list of the accelerations
+    V3,
+}
 
-            for param_map in param_maps {
-                for (key, value) in param_map {
-                    param_info.entry(key).or_insert_with(Vec::new).push(value);
+impl SchemaVersion {
+    pub const LATEST: Self = Self::V3;
+
+    // From the point types and the optional columns. Without the accelerations table there's
+    // nothing to tell v2 and v3 apart by, which makes no difference when reading.
+    fn detect(series: &Schema, accelerations: Option<&Schema>) -> Self {
+        let float_deviations = iter::once(series).chain(accelerations).any(|schema| {
+            schema
+                .fields()
+                .iter()
+                .any(|f| string_deviations(f.data_type()) != *f.data_type())
+        });
+        if float_deviations {
+            Self::V1
+        } else if accelerations.is_some_and(|s| s.field_with_name("events").is_ok()) {
+            Self::V3
+        } else {
+            Self::V2
+        }
+    }
+
+    pub fn description(self) -> String {
+        let version = match self {
+            Self::V1 => "v1 (отклонения как f64)",
+            Self::V2 => "v2 (без событий)",
+            Self::V3 => "v3",
+        };
+        if self == Self::LATEST {
+            format!("Версия схемы: {version}")
+        } else {
+            format!("Версия схемы: {version}, приводится к v3 при чтении")
+        }
+    }
+
+    // Converts a batch read from files of this version to the latest layout
+    fn adapt(self, batch: RecordBatch) -> Result<RecordBatch> {
+        match self {
+            Self::V1 => {
+                let schema = batch.schema();
+                let columns = schema
+                    .fields()
+                    .iter()
+                    .zip(batch.columns())
+                    .map(|(field, column)| {
+                        let data_type = string_deviations(column.data_type());
+                        let column = if data_type == *column.data_type() {
+                            column.clone()
+                        } else {
+                            datafusion::arrow::compute::cast(column, &data_type)?
+                        };
+                        Ok((field.name().clone(), column))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(RecordBatch::try_from_iter(columns)?)
+            }
+            // Missing events are read as no events
+            Self::V2 | Self::V3 => Ok(batch),
+        }
+    }
+}
+
+// `data_type` with float deviations of the points, at any depth, as strings
+fn string_deviations(data_type: &DataType) -> DataType {
+    let field = |f: &Field, data_type| Arc::new(f.clone().with_data_type(data_type));
+    match data_type {
+        DataType::List(f) => DataType::List(field(f, string_deviations(f.data_type()))),
+        DataType::LargeList(f) => DataType::LargeList(field(f, string_deviations(f.data_type()))),
+        DataType::Struct(fields) => DataType::Struct(
+            fields
+                .iter()
+                .map(|f| {
+                    let deviation = matches!(f.name().as_str(), "deviation" | "deviation_stddev");
+                    if deviation && f.data_type().is_floating() {
+                        field(f, DataType::Utf8)
+                    } else {
+                        field(f, string_deviations(f.data_type()))
+                    }
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn series_partition_cols() -> Vec<(String, DataType)> {
+    vec![
+        ("precision".to_string(), DataType::Utf8),
+        ("series_name".to_string(), DataType::Utf8),
+    ]
+}
+
+fn accel_partition_cols() -> Vec<(String, DataType)> {
+    vec![("series_id".to_string(), DataType::Int32)]
+}
+
+fn parquet_files(dir: &Path, res: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            parquet_files(&path, res)?;
+        } else if path.extension().is_some_and(|e| e == "parquet") {
+            res.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn parquet_row_count(path: &Path) -> Result<i64> {
+    let reader = SerializedFileReader::new(std::fs::File::open(path)?)?;
+    Ok(reader.metadata().file_metadata().num_rows())
+}
+
+// Parquet files under `dir` whose footer can be read, and the files and subdirectories that
+// can't with the reason
+pub(crate) fn readable_parquet_files(
+    dir: &Path,
+    readable: &mut Vec<PathBuf>,
+    unreadable: &mut Vec<(PathBuf, String)>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            if let Err(e) = readable_parquet_files(&path, readable, unreadable) {
+                unreadable.push((path, format!("{e:#}")));
+            }
+        } else if path.extension().is_some_and(|e| e == "parquet") {
+            match parquet_row_count(&path) {
+                Ok(_) => readable.push(path),
+                Err(e) => unreadable.push((path, e.to_string())),
+            }
+        }
+    }
+    Ok(())
+}
+
+// Values of the hive-style partition directories between the table directory and a file
+fn partition_values(
+    dir: &Path,
+    file: &Path,
+    partition_cols: &[(String, DataType)],
+) -> Option<Vec<String>> {
+    let rel = file.parent()?.strip_prefix(dir).ok()?;
+    if rel.components().count() != partition_cols.len() {
+        return None;
+    }
+    rel.components()
+        .zip(partition_cols)
+        .map(|(component, (name, _))| {
+            let value = component
+                .as_os_str()
+                .to_str()?
+                .strip_prefix(name.as_str())?;
+            Some(value.strip_prefix('=')?.to_string())
+        })
+        .collect()
+}
+
+// Registers a partitioned table directory. Files that can't be read are left out with a warning
+// instead of failing every query: the table is then assembled from the readable files of each
+// partition, with the partition values as literal columns.
+async fn register_table(
+    ctx: &SessionContext,
+    path: &str,
+    name: &str,
+    partition_cols: Vec<(String, DataType)>,
+) -> Result<()> {
+    let dir = Path::new(path).join(name);
+    let mut readable = Vec::new();
+    let mut unreadable = Vec::new();
+    readable_parquet_files(&dir, &mut readable, &mut unreadable)?;
+    if unreadable.is_empty() {
+        let options = ParquetReadOptions::default().table_partition_cols(partition_cols);
+        ctx.register_parquet(name, &format!("{}/{}", path, name), options)
+            .await
+            .map_err(|e| anyhow!("Failed to register {} parquet: {}", name, e))?;
+        return Ok(());
+    }
+
+    for (file, e) in &unreadable {
+        eprintln!("Skipping unreadable {}: {}", file.display(), e);
+    }
+    let mut partitions: std::collections::BTreeMap<Vec<String>, Vec<String>> = Default::default();
+    for file in readable {
+        match partition_values(&dir, &file, &partition_cols) {
+            Some(values) => partitions
+                .entry(values)
+                .or_default()
+                .push(file.display().to_string()),
+            None => eprintln!("Skipping {}: outside of the partitions", file.display()),
+        }
+    }
+    let mut table: Option<DataFrame> = None;
+    for (values, files) in partitions {
+        let mut df = ctx
+            .read_parquet(files, ParquetReadOptions::default())
+            .await?;
+        for ((col_name, data_type), value) in partition_cols.iter().zip(values) {
+            df = df.with_column(col_name, cast(lit(value), data_type.clone()))?;
+        }
+        table = Some(match table {
+            None => df,
+            // Optional columns may be missing from some partitions
+            Some(table) => table.union_by_name(df)?,
+        });
+    }
+    let table = table.with_context(|| format!("No readable files in {}", dir.display()))?;
+    ctx.register_table(name, table.into_view())?;
+    Ok(())
+}
+
+/// Query engine behind the loader
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Engine {
+    #[default]
+    DataFusion,
+    // Needs the `duckdb` feature
+    DuckDb,
+}
+
+/// What the loader asks of a [`QueryEngine`]. Distinct values come back as the column `value`.
+pub enum Query<'a> {
+    // Every row of `series` or `accelerations`
+    Table(&'static str),
+    // Distinct values of a column, or of a field of a struct column
+    Distinct {
+        table: &'static str,
+        column: &'a str,
+        field: Option<&'a str>,
+    },
+    // Distinct names of the events of the accelerations
+    EventNames,
+    // Distinct methods of the accelerations that set a field of `additional_args`
+    ParamMethods(&'a str),
+    // Series matching the top-level filters. With `page`, `PAGE_SIZE` of them by `series_id`
+    Series {
+        filters: &'a Filters,
+        page: Option<usize>,
+        ids_only: bool,
+    },
+    // Accelerations of the given series matching the filters that can be pushed down, with the
+    // methods that set each filtered parameter, see `accel_params_match`
+    Accelerations {
+        series_ids: &'a [i32],
+        filters: &'a Filters,
+        param_methods: &'a HashMap<String, Vec<String>>,
+    },
+}
+
+/// Query layer of the loader. Engines return the rows as Arrow batches, which are decoded the
+/// same way whichever engine read them.
+pub trait QueryEngine: Send + Sync {
+    /// Schema of a registered table, partition columns included
+    fn schema<'a>(&'a self, table: &'a str) -> BoxFuture<'a, Result<SchemaRef>>;
+    fn collect<'a>(&'a self, query: &'a Query<'a>) -> BoxFuture<'a, Result<Vec<RecordBatch>>>;
+    fn count<'a>(&'a self, query: &'a Query<'a>) -> BoxFuture<'a, Result<usize>>;
+}
+
+struct DataFusionEngine {
+    ctx: SessionContext,
+}
+
+impl DataFusionEngine {
+    async fn new(path: &str, tables: Vec<(&'static str, Vec<(String, DataType)>)>) -> Result<Self> {
+        let ctx = SessionContext::new();
+        for (name, partition_cols) in tables {
+            register_table(&ctx, path, name, partition_cols).await?;
+        }
+        Ok(Self { ctx })
+    }
+
+    async fn frame(&self, query: &Query<'_>) -> Result<DataFrame> {
+        Ok(match *query {
+            Query::Table(table) => self.ctx.table(table).await?,
+            Query::Distinct {
+                table,
+                column,
+                field,
+            } => {
+                let value = match field {
+                    Some(field) => col(column).field(field),
+                    None => col(column),
+                };
+                self.ctx
+                    .table(table)
+                    .await?
+                    .select(vec![value.alias("value")])?
+                    .distinct()?
+            }
+            Query::EventNames => self
+                .ctx
+                .table("accelerations")
+                .await?
+                .select(vec![col("events")])?
+                .unnest_columns(&["events"])?
+                .select(vec![col("events").field("name").alias("value")])?
+                .distinct()?,
+            Query::ParamMethods(param) => self
+                .ctx
+                .table("accelerations")
+                .await?
+                .filter(col("additional_args").field(param).is_not_null())?
+                .select(vec![col("accel_name").alias("value")])?
+                .distinct()?,
+            Query::Series {
+                filters,
+                page,
+                ids_only,
+            } => {
+                let mut df = filter_series(self.ctx.table("series").await?, filters)?;
+                if let Some(page) = page {
+                    df = df
+                        .sort(vec![col("series_id").sort(true, false)])?
+                        .limit(page * PAGE_SIZE, Some(PAGE_SIZE))?;
+                }
+                if ids_only {
+                    df = df.select(vec![col("series_id")])?;
                 }
+                df
             }
+            Query::Accelerations {
+                series_ids,
+                filters,
+                param_methods,
+            } => filter_accelerations(
+                self.ctx.table("accelerations").await?,
+                series_ids,
+                filters,
+                param_methods,
+            )?,
+        })
+    }
+}
+
+impl QueryEngine for DataFusionEngine {
+    fn schema<'a>(&'a self, table: &'a str) -> BoxFuture<'a, Result<SchemaRef>> {
+        Box::pin(async move { Ok(self.ctx.table(table).await?.schema().inner().clone()) })
+    }
+
+    fn collect<'a>(&'a self, query: &'a Query<'a>) -> BoxFuture<'a, Result<Vec<RecordBatch>>> {
+        Box::pin(async move { Ok(self.frame(query).await?.collect().await?) })
+    }
+
+    fn count<'a>(&'a self, query: &'a Query<'a>) -> BoxFuture<'a, Result<usize>> {
+        Box::pin(async move { Ok(self.frame(query).await?.count().await?) })
+    }
+}
+
+// Tables of a data directory with their partition columns
+fn table_layout(has_accelerations: bool) -> Vec<(&'static str, Vec<(String, DataType)>)> {
+    let mut res = vec![("series", series_partition_cols())];
+    if has_accelerations {
+        res.push(("accelerations", accel_partition_cols()));
+    }
+    res
+}
+
+#[derive(Clone)]
+pub struct DataLoader {
+    engine: Arc<dyn QueryEngine>,
+    path: String,
+    // The accelerations table is optional: without it only the series are loaded
+    has_accelerations: bool,
+    // Either table has a `run_id` column
+    has_runs: bool,
+    version: SchemaVersion,
+}
+
+impl DataLoader {
+    /// Data directory the loader was opened with
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the data has the accelerations table
+    pub fn has_accelerations(&self) -> bool {
+        self.has_accelerations
+    }
+
+    /// Whether the records have a run to order reruns by, see `DuplicatePolicy::KeepLatest`
+    pub fn has_runs(&self) -> bool {
+        self.has_runs
+    }
+
+    pub async fn new(path: &str) -> Result<Self> {
+        Self::with_engine(path, Engine::DataFusion).await
+    }
+
+    pub async fn with_engine(path: &str, engine: Engine) -> Result<Self> {
+        let has_accelerations = Path::new(path).join("accelerations").is_dir();
+        if !has_accelerations {
+            eprintln!(
+                "No accelerations table in {}, loading the series only",
+                path
+            );
         }
+        let tables = table_layout(has_accelerations);
+        let engine: Arc<dyn QueryEngine> = match engine {
+            Engine::DataFusion => Arc::new(DataFusionEngine::new(path, tables).await?),
+            #[cfg(feature = "duckdb")]
+            Engine::DuckDb => Arc::new(crate::duckdb_engine::DuckDbEngine::new(path, &tables)?),
+            #[cfg(not(feature = "duckdb"))]
+            Engine::DuckDb => return Err(anyhow!("vizr was built without the `duckdb` feature")),
+        };
 
-        // Remove duplicates and sort each parameter's values
-        for values in param_info.values_mut() {
-            values.sort();
-            values.dedup();
+        let series = engine.schema("series").await?;
+        let accelerations = if has_accelerations {
+            Some(engine.schema("accelerations").await?)
+        } else {
+            None
+        };
+        let version = SchemaVersion::detect(&series, accelerations.as_deref());
+        let has_runs = std::iter::once(&series)
+            .chain(&accelerations)
+            .any(|schema| schema.field_with_name("run_id").is_ok());
+        if version != SchemaVersion::LATEST {
+            println!(
+                "Schema {:?}, adapting to {:?}",
+                version,
+                SchemaVersion::LATEST
+            );
+        }
+
+        Ok(Self {
+            engine,
+            path: path.to_string(),
+            has_accelerations,
+            has_runs,
+            version,
+        })
+    }
+
+    /// Layout of the data files
+    pub fn schema_version(&self) -> SchemaVersion {
+        self.version
+    }
+
+    fn tables(&self) -> Vec<(&'static str, Vec<(String, DataType)>)> {
+        table_layout(self.has_accelerations)
+    }
+
+    /// Schemas of the registered tables with per-file row counts
+    pub async fn table_schemas(&self) -> Result<Vec<TableSchema>> {
+        let mut res = Vec::new();
+        for (name, partition_cols) in self.tables() {
+            let schema = self.engine.schema(name).await?;
+            let dir = Path::new(&self.path).join(name);
+            let mut paths = Vec::new();
+            parquet_files(&dir, &mut paths)?;
+            paths.sort();
+            let files = paths
+                .iter()
+                .map(|p| {
+                    let rel = p.strip_prefix(&dir).unwrap_or(p).display().to_string();
+                    (rel, parquet_row_count(p).map_err(|e| e.to_string()))
+                })
+                .collect();
+            res.push(TableSchema {
+                name: name.to_string(),
+                schema,
+                partition_cols: partition_cols.into_iter().map(|(c, _)| c).collect(),
+                files,
+            });
+        }
+        Ok(res)
+    }
+
+    fn metadata_cache_path(&self) -> PathBuf {
+        Path::new(&self.path).join(".vizr-metadata.json")
+    }
+
+    // Hash of the paths, sizes and modification times of all parquet files
+    fn data_fingerprint(&self) -> Result<String> {
+        let mut paths = Vec::new();
+        for (table, _) in self.tables() {
+            parquet_files(&Path::new(&self.path).join(table), &mut paths)?;
+        }
+        paths.sort();
+        let mut listing = String::new();
+        for path in paths {
+            let meta = std::fs::metadata(&path)?;
+            let modified = meta.modified()?.duration_since(std::time::UNIX_EPOCH)?;
+            listing.push_str(&format!(
+                "{}\0{}\0{}\n",
+                path.display(),
+                meta.len(),
+                modified.as_nanos()
+            ));
+        }
+        Ok(format!("{:016x}", fnv1a(listing.as_bytes())))
+    }
+
+    fn load_metadata_cache(&self, fingerprint: &str) -> Option<Metadata> {
+        let text = std::fs::read_to_string(self.metadata_cache_path()).ok()?;
+        let cache: MetadataCache = serde_json::from_str(&text).ok()?;
+        (cache.version == METADATA_CACHE_VERSION && cache.fingerprint == fingerprint)
+            .then_some(cache.metadata)
+    }
+
+    fn save_metadata_cache(&self, fingerprint: String, metadata: Metadata) -> Result<()> {
+        let cache = MetadataCache {
+            version: METADATA_CACHE_VERSION,
+            fingerprint,
+            metadata,
+        };
+        std::fs::write(self.metadata_cache_path(), serde_json::to_string(&cache)?)?;
+        Ok(())
+    }
+
+    /// Reports the metadata part by part, from the sidecar cache if the data hasn't changed since
+    /// it was written
+    pub async fn compute_metadata(&self, mut on_part: impl FnMut(MetadataPart)) -> Result<()> {
+        let fingerprint = self
+            .data_fingerprint()
+            .inspect_err(|e| eprintln!("Failed to fingerprint data, not caching metadata: {}", e))
+            .ok();
+        if let Some(metadata) = fingerprint
+            .as_deref()
+            .and_then(|f| self.load_metadata_cache(f))
+        {
+            println!("Using cached metadata");
+            metadata.into_parts().into_iter().for_each(on_part);
+            return Ok(());
+        }
+
+        let mut metadata = Metadata::default();
+        self.scan_metadata(|part| {
+            metadata.apply(part.clone());
+            on_part(part);
+        })
+        .await?;
+        if let Some(fingerprint) = fingerprint
+            && let Err(e) = self.save_metadata_cache(fingerprint, metadata)
+        {
+            eprintln!("Failed to save metadata cache: {}", e);
+        }
+        Ok(())
+    }
+
+    // Runs the metadata scans, reporting each part as it completes
+    async fn scan_metadata(&self, mut on_part: impl FnMut(MetadataPart)) -> Result<()> {
+        let engine = self.engine.as_ref();
+        on_part(MetadataPart::Precisions(
+            Self::get_unique_strings(engine, "series", "precision").await?,
+        ));
+        on_part(MetadataPart::SeriesNames(
+            Self::get_unique_strings(engine, "series", "series_name").await?,
+        ));
+
+        println!("collecting series_param_info");
+        on_part(MetadataPart::SeriesParamInfo(
+            Self::get_unique_param_info(engine, "series", "arguments").await?,
+        ));
+
+        if !self.has_accelerations {
+            on_part(MetadataPart::AccelNames(Vec::new()));
+            on_part(MetadataPart::MValues(Vec::new()));
+            on_part(MetadataPart::AccelParamInfo(HashMap::new()));
+            on_part(MetadataPart::AccelParamMethods(HashMap::new()));
+            on_part(MetadataPart::EventNames(Vec::new()));
+            return Ok(());
+        }
+
+        on_part(MetadataPart::AccelNames(
+            Self::get_unique_strings(engine, "accelerations", "accel_name").await?,
+        ));
+
+        println!("collecting m_values");
+        on_part(MetadataPart::MValues(
+            Self::get_unique_m_values(engine).await?,
+        ));
+
+        println!("collecting accel_param_info");
+        let accel_param_info =
+            Self::get_unique_param_info(engine, "accelerations", "additional_args").await?;
+        let accel_param_methods = Self::get_param_methods(engine, accel_param_info.keys()).await?;
+        on_part(MetadataPart::AccelParamInfo(accel_param_info));
+        on_part(MetadataPart::AccelParamMethods(accel_param_methods));
+
+        println!("collecting event_names");
+        on_part(MetadataPart::EventNames(
+            Self::get_unique_event_names(engine).await?,
+        ));
+        Ok(())
+    }
+
+    // `events` is optional
+    async fn get_unique_event_names(engine: &dyn QueryEngine) -> Result<Vec<String>> {
+        let schema = engine.schema("accelerations").await?;
+        if schema.field_with_name("events").is_err() {
+            return Ok(Vec::new());
+        }
+        let batches: Vec<RecordBatch> = engine
+            .collect(&Query::EventNames)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get unique event names: {}", e))?;
+
+        let mut res = Vec::new();
+        for batch in batches {
+            let col = batch.column_by_name("value").context("column not found")?;
+            res.extend(
+                to_str("name", col)?
+                    .into_iter()
+                    .flatten()
+                    .map(str::to_string),
+            );
+        }
+        res.sort();
+        Ok(res)
+    }
+
+    // Extract unique parameter names and values from struct fields
+    // Distinct values are computed by the engine per struct field, so only the distinct values
+    // are ever materialized
+    async fn get_unique_param_info(
+        engine: &dyn QueryEngine,
+        table: &'static str,
+        column: &str,
+    ) -> Result<HashMap<String, Vec<String>>> {
+        let schema = engine.schema(table).await?;
+        let Ok(field) = schema.field_with_name(column) else {
+            return Ok(HashMap::new());
+        };
+        let field_names: Vec<String> = match field.data_type() {
+            DataType::Struct(fields) => fields.iter().map(|f| f.name().clone()).collect(),
+            t => {
+                return Err(anyhow!(
+                    "Expected `{column}` to be struct of strings, found {t}"
+                ));
+            }
+        };
+
+        let mut param_info: HashMap<String, Vec<String>> = HashMap::new();
+        for field_name in field_names {
+            let query = Query::Distinct {
+                table,
+                column,
+                field: Some(&field_name),
+            };
+            let batches: Vec<RecordBatch> = engine.collect(&query).await.map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to get unique {}.{} from {}: {}",
+                    column,
+                    field_name,
+                    table,
+                    e
+                )
+            })?;
+
+            let mut values = Vec::new();
+            let mut not_set = false;
+            for batch in batches {
+                let col = batch.column_by_name("value").context("column not found")?;
+                for value in to_str(&field_name, col)? {
+                    match value {
+                        Some(value) => values.push(value.to_string()),
+                        None => not_set = true,
+                    }
+                }
+            }
+            // Fields that are null everywhere don't show up, as before
+            if !values.is_empty() {
+                if not_set {
+                    values.push(NOT_SET.to_string());
+                }
+                sort_param_values(&mut values);
+                param_info.insert(field_name, values);
+            }
         }
 
         Ok(param_info)
     }
 
+    // Methods that set each of the acceleration parameters
+    async fn get_param_methods(
+        engine: &dyn QueryEngine,
+        params: impl Iterator<Item = &String>,
+    ) -> Result<HashMap<String, Vec<String>>> {
+        let mut res = HashMap::new();
+        for param in params {
+            let batches: Vec<RecordBatch> = engine
+                .collect(&Query::ParamMethods(param))
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to get methods with {}: {}", param, e))?;
+            let mut methods = Vec::new();
+            for batch in batches {
+                let col = batch.column_by_name("value").context("column not found")?;
+                methods.extend(
+                    to_str("accel_name", col)?
+                        .into_iter()
+                        .flatten()
+                        .map(str::to_string),
+                );
+            }
+            methods.sort();
+            res.insert(param.clone(), methods);
+        }
+        Ok(res)
+    }
+
+    // Methods that set the parameters the filters select values of, for `Query::Accelerations`
+    // when the caller has no `Metadata::accel_param_methods` at hand
+    async fn filtered_param_methods(
+        &self,
+        filters: &Filters,
+    ) -> Result<HashMap<String, Vec<String>>> {
+        let params: Vec<String> = filters
+            .accel_params
+            .iter()
+            .filter(|(_, values)| !values.is_empty())
+            .map(|(param, _)| param.to_string())
+            .collect();
+        Self::get_param_methods(self.engine.as_ref(), params.iter()).await
+    }
+
     // Not null
     async fn get_unique_strings(
-        ctx: &SessionContext,
-        table: &str,
+        engine: &dyn QueryEngine,
+        table: &'static str,
         column: &str,
     ) -> Result<Vec<String>> {
         #[cfg(feature = "perf_tracing")]
         let query_start = Instant::now();
-        let df = ctx.table(table).await?;
-        let df = df.select(vec![col(column)])?.distinct()?;
-        let batches: Vec<RecordBatch> = df.collect().await.map_err(|e| {
+        let query = Query::Distinct {
+            table,
+            column,
+            field: None,
+        };
+        let batches: Vec<RecordBatch> = engine.collect(&query).await.map_err(|e| {
             anyhow::anyhow!("Failed to get unique {} from {}: {}", column, table, e)
         })?;
         #[cfg(feature = "perf_tracing")]
@@ -604,7 +2034,7 @@ impl DataLoader {
         let processing_start = Instant::now();
         let mut res = Vec::new();
         for batch in batches {
-            let col = batch.column_by_name(column).context("column not found")?;
+            let col = batch.column_by_name("value").context("column not found")?;
             for i in to_str(column, col)? {
                 res.push(
                     i.with_context(|| format!("Didn't expect null in {column}"))?
@@ -627,12 +2057,15 @@ impl DataLoader {
     }
 
     // Not null
-    async fn get_unique_m_values(ctx: &SessionContext) -> Result<Vec<i32>> {
+    async fn get_unique_m_values(engine: &dyn QueryEngine) -> Result<Vec<i32>> {
         #[cfg(feature = "perf_tracing")]
         let query_start = Instant::now();
-        let df = ctx.table("accelerations").await?;
-        let df = df.select(vec![col("m_value")])?.distinct()?;
-        let batches: Vec<RecordBatch> = df.collect().await.map_err(|e| {
+        let query = Query::Distinct {
+            table: "accelerations",
+            column: "m_value",
+            field: None,
+        };
+        let batches: Vec<RecordBatch> = engine.collect(&query).await.map_err(|e| {
             anyhow::anyhow!("Failed to get unique m_values from accelerations: {}", e)
         })?;
         #[cfg(feature = "perf_tracing")]
@@ -643,7 +2076,7 @@ impl DataLoader {
         let mut res = Vec::new();
         for batch in batches {
             let col = batch
-                .column_by_name("m_value")
+                .column_by_name("value")
                 .context("m_value column not found")?;
             for i in to_i64("m_value", col)? {
                 res.push(i.with_context(|| "Didn't expect null in m_value")? as i32);
@@ -663,68 +2096,124 @@ impl DataLoader {
     }
 }
 
+// Merges the records of the same configuration, keeping the order of first appearance
+fn resolve_duplicates(records: Vec<AccelRecord>, policy: DuplicatePolicy) -> Vec<AccelRecord> {
+    let mut groups: Vec<Vec<AccelRecord>> = Vec::new();
+    let mut index: HashMap<AccelInfo, usize> = HashMap::new();
+    for record in records {
+        match index.get(&record.accel_info) {
+            Some(&i) => groups[i].push(record),
+            None => {
+                index.insert(record.accel_info.clone(), groups.len());
+                groups.push(vec![record]);
+            }
+        }
+    }
+    groups
+        .into_iter()
+        .map(|mut group| {
+            let reruns = group.len() - 1;
+            if reruns == 0 {
+                return group.pop().unwrap();
+            }
+            match policy {
+                DuplicatePolicy::KeepAll => unreachable!(),
+                DuplicatePolicy::KeepLatest => {
+                    let run = |r: &AccelRecord| r.run.clone().unwrap_or_else(|| intern(NOT_SET));
+                    let mut runs: Vec<IStr> = group.iter().map(run).collect();
+                    sort_param_values(&mut runs);
+                    // Of the same run, the one read last
+                    let latest = runs.last().cloned();
+                    let i = group.iter().rposition(|r| Some(run(r)) == latest).unwrap();
+                    let mut latest = group.swap_remove(i);
+                    latest.reruns = reruns;
+                    latest
+                }
+                DuplicatePolicy::Average | DuplicatePolicy::Spread => {
+                    let len = group.iter().map(|r| r.computed.len()).max().unwrap_or(0);
+                    let mut computed = AccelPoints::default();
+                    let mut spread = Vec::new();
+                    for i in 0..len {
+                        let points: Vec<AccelPoint> = group
+                            .iter()
+                            .filter(|r| i < r.computed.len())
+                            .filter_map(|r| r.computed.get(i))
+                            .collect();
+                        if points.is_empty() {
+                            computed.push(None);
+                            spread.push(None);
+                            continue;
+                        }
+                        let real: Vec<Scientific> = points.iter().map(|p| p.value.real).collect();
+                        let imag: Vec<Scientific> = points.iter().map(|p| p.value.imag).collect();
+                        let deviations: Vec<Scientific> =
+                            points.iter().map(|p| p.deviation).collect();
+                        let min = deviations
+                            .iter()
+                            .copied()
+                            .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+                        let max = deviations
+                            .iter()
+                            .copied()
+                            .max_by(|a, b| a.abs().total_cmp(&b.abs()));
+                        spread.push(min.zip(max).map(|(min, max)| [min, max]));
+                        computed.push(Some(AccelPoint {
+                            value: ComplexNumber {
+                                real: Scientific::mean(&real).unwrap_or_default(),
+                                imag: Scientific::mean(&imag).unwrap_or_default(),
+                            },
+                            deviation: Scientific::geometric_mean(&deviations).unwrap_or_default(),
+                        }));
+                    }
+                    let first = &group[0];
+                    AccelRecord {
+                        accel_info: first.accel_info.clone(),
+                        run: None,
+                        computed,
+                        errors: group.iter().flat_map(|r| r.errors.clone()).collect(),
+                        events: group.iter().flat_map(|r| r.events.clone()).collect(),
+                        reruns,
+                        uncertainty: if policy == DuplicatePolicy::Spread {
+                            spread
+                        } else {
+                            Vec::new()
+                        },
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
 // Filtering
 impl DataLoader {
     async fn load_accelerations_for_multiple_series(
         &self,
         series_ids: &[i32],
         filters: &Filters,
+        param_methods: &HashMap<String, Vec<String>>,
     ) -> Result<HashMap<i32, Vec<AccelRecord>>> {
         #[cfg(feature = "perf_tracing")]
-        let table_start = Instant::now();
-        let mut df = self.ctx.table("accelerations").await?;
-        #[cfg(feature = "perf_tracing")]
-        let table_time = table_start.elapsed();
-
-        // Filter by series_ids
-        #[cfg(feature = "perf_tracing")]
-        let filter_start = Instant::now();
-        {
-            let mut filter_expr = col("series_id").eq(lit(-1));
-            for &series_id in series_ids.iter() {
-                filter_expr = filter_expr.or(col("series_id").eq(lit(series_id)));
-            }
-            df = df.filter(filter_expr)?;
-        }
-
-        // Apply accel filters
-        if !filters.base_accel.is_empty() {
-            let mut filter_expr = col("accel_name").eq(lit("_default"));
-            for a in filters.base_accel.iter() {
-                filter_expr = filter_expr.or(col("accel_name").eq(lit(a.clone())));
-            }
-            df = df.filter(filter_expr)?;
-        }
-
-        if !filters.m_values.is_empty() {
-            let mut filter_expr = col("m_value").eq(lit("_default"));
-            for m in filters.m_values.iter() {
-                filter_expr = filter_expr.or(col("m_value").eq(lit(*m)));
-            }
-            df = df.filter(filter_expr)?;
-        }
-
-        // Apply accel_params filters using SQL
-        if let Some(param_filter) = filter_params("additional_args", &filters.accel_params) {
-            df = df.filter(param_filter)?;
-        }
-        #[cfg(feature = "perf_tracing")]
-        let filter_time = filter_start.elapsed();
-
-        #[cfg(feature = "perf_tracing")]
-        let collect_start = Instant::now();
-        let batches: Vec<RecordBatch> = df
-            .collect()
+        let query_start = Instant::now();
+        let query = Query::Accelerations {
+            series_ids,
+            filters,
+            param_methods,
+        };
+        let batches: Vec<RecordBatch> = self
+            .engine
+            .collect(&query)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to execute accelerations query: {}", e))?;
         #[cfg(feature = "perf_tracing")]
-        let collect_time = collect_start.elapsed();
+        let query_time = query_start.elapsed();
 
         #[cfg(feature = "perf_tracing")]
         let processing_start = Instant::now();
         let mut result: HashMap<i32, Vec<AccelRecord>> = HashMap::new();
 
         for batch in batches {
+            let batch = self.version.adapt(batch)?;
             let series_id = to_i64(
                 "series_id",
                 batch
@@ -752,13 +2241,19 @@ impl DataLoader {
                 vec![HashMap::new(); batch.num_rows()]
             };
 
-            let computed = to_list(
-                "computed",
-                batch
-                    .column_by_name("computed")
-                    .context("No computed in accelerations")?,
-                |x| to_accel_point("computed.[]", x),
-            )?;
+            let computed_col = batch
+                .column_by_name("computed")
+                .context("No computed in accelerations")?;
+            let computed = to_list("computed", computed_col, |x| {
+                to_accel_point("computed.[]", x)
+            })?;
+            let stddev = if has_stddev(computed_col) {
+                to_list("computed", computed_col, |x| {
+                    to_accel_stddev("computed.[]", x)
+                })?
+            } else {
+                vec![None; batch.num_rows()]
+            };
 
             let errors = if let Some(col) = batch.column_by_name("errors") {
                 to_list("errors", col, |x| to_error_info("errors.[]", x))?
@@ -777,10 +2272,18 @@ impl DataLoader {
             } else {
                 vec![Vec::new(); batch.num_rows()]
             };
+            let mut run_ids = batch
+                .column_by_name("run_id")
+                .map(|c| to_run_ids("run_id", c))
+                .transpose()?
+                .map(Vec::into_iter);
 
             for (
-                (((((series_id, accel_name), m_value), additional_args), computed), errors),
-                events,
+                (
+                    (((((series_id, accel_name), m_value), additional_args), computed), errors),
+                    events,
+                ),
+                stddev,
             ) in series_id
                 .into_iter()
                 .zip(accel_name)
@@ -789,11 +2292,22 @@ impl DataLoader {
                 .zip(computed)
                 .zip(errors)
                 .zip(events)
+                .zip(stddev)
             {
                 let series_id = series_id.context("series_id is null")? as i32;
-                let accel_name = accel_name.context("accel_name is null")?.to_string();
+                let accel_name = intern(accel_name.context("accel_name is null")?);
                 let m_value = m_value.context("m_value is null")? as i32;
                 let additional_args = additional_args;
+                let computed: AccelPoints = computed.context("computed is null")?;
+                let uncertainty: Vec<Option<[Scientific; 2]>> = stddev
+                    .map(|stddev: Vec<Option<Scientific>>| {
+                        computed
+                            .iter()
+                            .zip(stddev)
+                            .map(|(point, stddev)| Some(stddev_range(point?.deviation, stddev?)))
+                            .collect()
+                    })
+                    .unwrap_or_default();
 
                 let accel_record = AccelRecord {
                     accel_info: AccelInfo {
@@ -801,11 +2315,19 @@ impl DataLoader {
                         m_value,
                         additional_args,
                     },
-                    computed: computed.context("computed is null")?,
+                    run: run_ids.as_mut().and_then(|ids| ids.next()).flatten(),
+                    computed,
                     errors,
                     events,
+                    reruns: 0,
+                    uncertainty,
                 };
 
+                // Event names live inside list elements, so they are matched after decoding
+                if !filters.matches_outcome(&accel_record) {
+                    continue;
+                }
+
                 result.entry(series_id).or_default().push(accel_record);
             }
         }
@@ -815,10 +2337,7 @@ impl DataLoader {
         // Update global stats
         #[cfg(feature = "perf_tracing")]
         if let Ok(mut stats) = TIMING_STATS.lock() {
-            stats.accelerations_query_time += table_time + filter_time + collect_time;
-            stats.accelerations_table_access_time += table_time;
-            stats.accelerations_filtering_time += filter_time;
-            stats.accelerations_collect_time += collect_time;
+            stats.accelerations_query_time += query_time;
             stats.accelerations_processing_time += processing_time;
             let total_accel_count: usize = result.values().map(|v| v.len()).sum();
             stats.accel_count += total_accel_count;
@@ -827,45 +2346,130 @@ impl DataLoader {
         Ok(result)
     }
 
-    pub async fn filter_data(
+    /// Count-only dry run of [`Self::filter_data`]
+    pub async fn estimate(
         &self,
         filters: &Filters,
-    ) -> Result<Vec<(SeriesRecord, Vec<AccelRecord>)>> {
-        // Reset global timing stats
-        #[cfg(feature = "perf_tracing")]
-        if let Ok(mut stats) = TIMING_STATS.lock() {
-            stats.reset();
+        param_methods: Option<&HashMap<String, Vec<String>>>,
+    ) -> Result<QueryEstimate> {
+        let batches = self
+            .engine
+            .collect(&Query::Series {
+                filters,
+                page: None,
+                ids_only: true,
+            })
+            .await?;
+        let mut series_ids = Vec::new();
+        for batch in batches {
+            let ids = to_i64(
+                "series_id",
+                batch
+                    .column_by_name("series_id")
+                    .context("No series_id in series")?,
+            )?;
+            series_ids.extend(ids.into_iter().flatten().map(|id| id as i32));
         }
 
-        #[cfg(feature = "perf_tracing")]
-        let total_start = Instant::now();
-        let mut df = self.ctx.table("series").await?;
+        let accel_records = if series_ids.is_empty() || !self.has_accelerations {
+            0
+        } else {
+            let computed;
+            let param_methods = match param_methods {
+                Some(param_methods) => param_methods,
+                None => {
+                    computed = self.filtered_param_methods(filters).await?;
+                    &computed
+                }
+            };
+            self.engine
+                .count(&Query::Accelerations {
+                    series_ids: &series_ids,
+                    filters,
+                    param_methods,
+                })
+                .await?
+        };
 
-        // Apply series filters
-        if !filters.precisions.is_empty() {
-            let mut filter_expr = col("precision").eq(lit("_default"));
-            for p in filters.precisions.iter() {
-                filter_expr = filter_expr.or(col("precision").eq(lit(p.clone())));
-            }
-            df = df.filter(filter_expr)?;
+        let bytes = self.table_share_bytes("series", series_ids.len()).await?
+            + self
+                .table_share_bytes("accelerations", accel_records)
+                .await?;
+        Ok(QueryEstimate {
+            series: series_ids.len(),
+            accel_records,
+            bytes,
+        })
+    }
+
+    // On-disk size of a table scaled by the fraction of its rows
+    async fn table_share_bytes(&self, table: &'static str, rows: usize) -> Result<u64> {
+        if rows == 0 {
+            return Ok(0);
+        }
+        let total_rows = self.engine.count(&Query::Table(table)).await?;
+        if total_rows == 0 {
+            return Ok(0);
         }
+        let mut paths = Vec::new();
+        parquet_files(&Path::new(&self.path).join(table), &mut paths)?;
+        let total_bytes: u64 = paths
+            .iter()
+            .filter_map(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum();
+        Ok((total_bytes as f64 * rows as f64 / total_rows as f64) as u64)
+    }
 
-        if !filters.base_series.is_empty() {
-            let mut filter_expr = col("series_name").eq(lit("_default"));
-            for s in filters.base_series.iter() {
-                filter_expr = filter_expr.or(col("series_name").eq(lit(s.clone())));
+    /// Every series matching the filters, over all pages, for exports and figures
+    pub async fn filter_all(&self, filters: &Filters) -> Result<Vec<SeriesData>> {
+        let param_methods = self.filtered_param_methods(filters).await?;
+        let mut filters = filters.clone();
+        filters.page = 0;
+        let mut data = Vec::new();
+        loop {
+            let page = self.filter_data(&filters, Some(&param_methods)).await?;
+            let pages = page.page_count();
+            data.extend(page.data);
+            filters.page += 1;
+            if filters.page >= pages {
+                return Ok(data);
             }
-            df = df.filter(filter_expr)?;
         }
+    }
 
-        // Apply series_params filters using SQL
-        if let Some(param_filter) = filter_params("arguments", &filters.series_params) {
-            df = df.filter(param_filter)?;
+    /// One page of the series matching the filters. `param_methods` is
+    /// `Metadata::accel_param_methods`, looked up for the filtered parameters when `None`
+    pub async fn filter_data(
+        &self,
+        filters: &Filters,
+        param_methods: Option<&HashMap<String, Vec<String>>>,
+    ) -> Result<LoadedPage> {
+        // Reset global timing stats
+        #[cfg(feature = "perf_tracing")]
+        if let Ok(mut stats) = TIMING_STATS.lock() {
+            stats.reset();
         }
 
+        #[cfg(feature = "perf_tracing")]
+        let total_start = Instant::now();
+        let total_series = self
+            .engine
+            .count(&Query::Series {
+                filters,
+                page: None,
+                ids_only: false,
+            })
+            .await?;
+        let query = Query::Series {
+            filters,
+            page: (total_series > PAGE_SIZE).then_some(filters.page),
+            ids_only: false,
+        };
+
         #[cfg(feature = "perf_tracing")]
         let query_start = Instant::now();
-        let batches: Vec<RecordBatch> = df.collect().await?;
+        let batches: Vec<RecordBatch> = self.engine.collect(&query).await?;
         #[cfg(feature = "perf_tracing")]
         let query_time = query_start.elapsed();
 
@@ -876,6 +2480,7 @@ impl DataLoader {
 
         // First, collect all series records and series_ids
         for batch in batches {
+            let batch = self.version.adapt(batch)?;
             let precision = to_str(
                 "precision",
                 batch
@@ -894,12 +2499,12 @@ impl DataLoader {
                     .column_by_name("series_name")
                     .context("No series_name in series")?,
             )?;
-            let arguments = to_struct_str(
-                "arguments",
-                batch
-                    .column_by_name("arguments")
-                    .context("No arguments in series")?,
-            )?;
+            // Optional like `additional_args`: series without parameters have nothing to store
+            let arguments = if let Some(col) = batch.column_by_name("arguments") {
+                to_struct_str("arguments", col)?
+            } else {
+                vec![HashMap::new(); batch.num_rows()]
+            };
 
             let series_limit = to_complex(
                 "series_limit",
@@ -915,6 +2520,11 @@ impl DataLoader {
                     .context("No computed in series")?,
                 |x| to_series_point("computed.[]", x),
             )?;
+            let mut run_ids = batch
+                .column_by_name("run_id")
+                .map(|c| to_run_ids("run_id", c))
+                .transpose()?
+                .map(Vec::into_iter);
 
             for (((((precision, series_id), series_name), arguments), series_limit), computed) in
                 precision
@@ -925,9 +2535,9 @@ impl DataLoader {
                     .zip(series_limit)
                     .zip(computed)
             {
-                let precision = precision.context("precision is null")?.to_string();
+                let precision = intern(precision.context("precision is null")?);
                 let series_id = series_id.context("series_id is null")? as i32;
-                let series_name = series_name.context("name is null")?.to_string();
+                let series_name = intern(series_name.context("name is null")?);
                 let arguments = arguments;
                 let computed = computed.context("computed is null")?;
 
@@ -939,13 +2549,22 @@ impl DataLoader {
                     arguments,
                     series_limit: series_limit.unwrap_or_default(),
                     computed,
+                    run: run_ids.as_mut().and_then(|ids| ids.next()).flatten(),
                 });
             }
         }
 
         // Load all accelerations for all series in a single query
-        let accelerations_map = if !series_ids.is_empty() {
-            self.load_accelerations_for_multiple_series(&series_ids, filters)
+        let accelerations_map = if !series_ids.is_empty() && self.has_accelerations {
+            let computed;
+            let param_methods = match param_methods {
+                Some(param_methods) => param_methods,
+                None => {
+                    computed = self.filtered_param_methods(filters).await?;
+                    &computed
+                }
+            };
+            self.load_accelerations_for_multiple_series(&series_ids, filters, param_methods)
                 .await?
         } else {
             HashMap::new()
@@ -954,10 +2573,18 @@ impl DataLoader {
         // Combine series records with their accelerations
         let mut result = Vec::new();
         for series_record in series_records {
-            let accels = accelerations_map
+            let mut accels = accelerations_map
                 .get(&series_record.series_id)
                 .cloned()
                 .unwrap_or_default();
+            for accel in &mut accels {
+                if accel.run.is_none() {
+                    accel.run = series_record.run.clone();
+                }
+            }
+            if filters.duplicates != DuplicatePolicy::KeepAll {
+                accels = resolve_duplicates(accels, filters.duplicates);
+            }
             result.push((series_record, accels));
         }
 
@@ -977,6 +2604,461 @@ impl DataLoader {
         }
 
         println!("filtering complete");
-        Ok(result)
+        Ok(LoadedPage {
+            data: result,
+            total_series,
+        })
+    }
+}
+
+/// File format of the exported data
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+// Exact value as "<mantissa>e<exponent>", parsed back by any reader of floats
+fn scientific_string(s: Scientific) -> String {
+    format!("{}e{}", s.0, s.1)
+}
+
+/// Flat table of the data: one row per accel point, with the series, the method and each of
+/// their parameters as columns (`series.<name>`, `accel.<name>`; null where a record has none)
+/// and the `note` of the record by its series id, if any
+pub fn points_table<'a>(
+    data: &[(&SeriesRecord, Vec<&AccelRecord>)],
+    note: impl Fn(i32, &AccelInfo) -> Option<&'a str>,
+) -> Result<RecordBatch> {
+    let mut series_params = std::collections::BTreeSet::new();
+    let mut accel_params = std::collections::BTreeSet::new();
+    for (series, accel_records) in data {
+        series_params.extend(series.arguments.keys().cloned());
+        for accel_record in accel_records {
+            accel_params.extend(accel_record.accel_info.additional_args.keys().cloned());
+        }
+    }
+
+    let mut series_id = Int32Builder::new();
+    let mut precision = StringBuilder::new();
+    let mut series_name = StringBuilder::new();
+    let mut method = StringBuilder::new();
+    let mut m = Int32Builder::new();
+    let mut series_args: Vec<StringBuilder> =
+        series_params.iter().map(|_| StringBuilder::new()).collect();
+    let mut accel_args: Vec<StringBuilder> =
+        accel_params.iter().map(|_| StringBuilder::new()).collect();
+    let mut notes = StringBuilder::new();
+    let mut n = Int32Builder::new();
+    let mut value_real = StringBuilder::new();
+    let mut value_imag = StringBuilder::new();
+    let mut deviation = StringBuilder::new();
+    let mut deviation_log10 = Float64Builder::new();
+
+    for (series, accel_records) in data {
+        for accel_record in accel_records {
+            let accel = &accel_record.accel_info;
+            let record_note = note(series.series_id, accel);
+            let points = series.computed.n().iter().zip(accel_record.computed.iter());
+            for (&point_n, point) in points {
+                let Some(point) = point else {
+                    continue;
+                };
+                series_id.append_value(series.series_id);
+                precision.append_value(&series.precision);
+                series_name.append_value(&series.name);
+                method.append_value(&accel.name);
+                m.append_value(accel.m_value);
+                for (builder, param) in series_args.iter_mut().zip(&series_params) {
+                    builder.append_option(series.arguments.get(param));
+                }
+                for (builder, param) in accel_args.iter_mut().zip(&accel_params) {
+                    builder.append_option(accel.additional_args.get(param));
+                }
+                notes.append_option(record_note);
+                n.append_value(point_n);
+                value_real.append_value(scientific_string(point.value.real));
+                value_imag.append_value(scientific_string(point.value.imag));
+                deviation.append_value(scientific_string(point.deviation));
+                let Scientific(mantissa, exponent) = point.deviation;
+                deviation_log10.append_option(
+                    (mantissa != 0.0).then(|| mantissa.abs().log10() + exponent as f64),
+                );
+            }
+        }
+    }
+
+    let mut columns: Vec<(String, ArrayRef)> = vec![
+        ("series_id".into(), Arc::new(series_id.finish())),
+        ("precision".into(), Arc::new(precision.finish())),
+        ("series".into(), Arc::new(series_name.finish())),
+        ("method".into(), Arc::new(method.finish())),
+        ("m".into(), Arc::new(m.finish())),
+    ];
+    for (param, mut builder) in series_params.iter().zip(series_args) {
+        columns.push((format!("series.{param}"), Arc::new(builder.finish())));
+    }
+    for (param, mut builder) in accel_params.iter().zip(accel_args) {
+        columns.push((format!("accel.{param}"), Arc::new(builder.finish())));
+    }
+    columns.extend([
+        ("note".into(), Arc::new(notes.finish()) as ArrayRef),
+        ("n".into(), Arc::new(n.finish())),
+        ("value_real".into(), Arc::new(value_real.finish())),
+        ("value_imag".into(), Arc::new(value_imag.finish())),
+        ("deviation".into(), Arc::new(deviation.finish())),
+        ("deviation_log10".into(), Arc::new(deviation_log10.finish())),
+    ]);
+    Ok(RecordBatch::try_from_iter(columns)?)
+}
+
+/// Writes `points_table` to a single file
+pub async fn export_points(table: RecordBatch, path: &str, format: ExportFormat) -> Result<()> {
+    let df = SessionContext::new().read_batch(table)?;
+    let options = datafusion::dataframe::DataFrameWriteOptions::new().with_single_file_output(true);
+    match format {
+        ExportFormat::Parquet => df.write_parquet(path, options, None).await?,
+        ExportFormat::Csv => df.write_csv(path, options, None).await?,
+    };
+    Ok(())
+}
+
+// Columns of the layout the loader reads, the inverse of the `to_x` decoders
+
+fn string_column<S: AsRef<str>>(values: impl IntoIterator<Item = Option<S>>) -> ArrayRef {
+    Arc::new(values.into_iter().collect::<StringArray>())
+}
+
+fn struct_column(fields: Vec<(&str, ArrayRef)>, valid: Option<Vec<bool>>) -> ArrayRef {
+    let (fields, columns): (Vec<_>, Vec<_>) = fields
+        .into_iter()
+        .map(|(name, c)| (Field::new(name, c.data_type().clone(), true), c))
+        .unzip();
+    Arc::new(StructArray::new(
+        fields.into(),
+        columns,
+        valid.map(Into::into),
+    ))
+}
+
+fn list_column(values: ArrayRef, lengths: impl IntoIterator<Item = usize>) -> ArrayRef {
+    let field = Field::new_list_field(values.data_type().clone(), true);
+    let offsets = datafusion::arrow::buffer::OffsetBuffer::from_lengths(lengths);
+    Arc::new(ListArray::new(Arc::new(field), offsets, values, None))
+}
+
+fn complex_column(values: &[Option<ComplexNumber>]) -> ArrayRef {
+    let part = |f: fn(&ComplexNumber) -> Scientific| {
+        string_column(
+            values
+                .iter()
+                .map(|v| v.as_ref().map(|v| scientific_string(f(v)))),
+        )
+    };
+    struct_column(
+        vec![("real", part(|v| v.real)), ("imag", part(|v| v.imag))],
+        Some(values.iter().map(Option::is_some).collect()),
+    )
+}
+
+// Struct of the given parameters, null where a record doesn't have one
+fn params_column(names: &[IStr], params: &[&HashMap<IStr, IStr>]) -> ArrayRef {
+    struct_column(
+        names
+            .iter()
+            .map(|name| {
+                (
+                    name.as_ref(),
+                    string_column(params.iter().map(|p| p.get(name))),
+                )
+            })
+            .collect(),
+        None,
+    )
+}
+
+fn series_batch(series: &[&SeriesRecord], params: &[IStr], with_runs: bool) -> Result<RecordBatch> {
+    let points: Vec<SeriesPoint> = series.iter().flat_map(|s| s.computed.iter()).collect();
+    let computed = struct_column(
+        vec![
+            (
+                "n",
+                Arc::new(points.iter().map(|p| p.n).collect::<Int32Array>()),
+            ),
+            (
+                "value",
+                complex_column(&points.iter().map(|p| Some(p.value)).collect::<Vec<_>>()),
+            ),
+            (
+                "deviation",
+                string_column(points.iter().map(|p| Some(scientific_string(p.deviation)))),
+            ),
+        ],
+        None,
+    );
+
+    let mut columns: Vec<(&str, ArrayRef)> = vec![(
+        "series_id",
+        Arc::new(series.iter().map(|s| s.series_id).collect::<Int32Array>()),
+    )];
+    if !params.is_empty() {
+        let arguments: Vec<_> = series.iter().map(|s| &s.arguments).collect();
+        columns.push(("arguments", params_column(params, &arguments)));
+    }
+    columns.push((
+        "series_limit",
+        complex_column(
+            &series
+                .iter()
+                .map(|s| Some(s.series_limit))
+                .collect::<Vec<_>>(),
+        ),
+    ));
+    columns.push((
+        "computed",
+        list_column(computed, series.iter().map(|s| s.computed.len())),
+    ));
+    if with_runs {
+        columns.push((
+            "run_id",
+            string_column(series.iter().map(|s| s.run.as_ref())),
+        ));
+    }
+    Ok(RecordBatch::try_from_iter(columns)?)
+}
+
+// `notes` are aligned with `records`; `None` leaves out the column
+fn accelerations_batch(
+    records: &[&AccelRecord],
+    params: &[IStr],
+    with_runs: bool,
+    notes: Option<Vec<Option<&str>>>,
+) -> Result<RecordBatch> {
+    let points: Vec<Option<AccelPoint>> = records.iter().flat_map(|r| r.computed.iter()).collect();
+    let computed = struct_column(
+        vec![
+            (
+                "value",
+                complex_column(
+                    &points
+                        .iter()
+                        .map(|p| p.map(|p| p.value))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+            (
+                "deviation",
+                string_column(
+                    points
+                        .iter()
+                        .map(|p| p.map(|p| scientific_string(p.deviation))),
+                ),
+            ),
+        ],
+        Some(points.iter().map(Option::is_some).collect()),
+    );
+
+    let errors: Vec<&ErrorInfo> = records.iter().flat_map(|r| &r.errors).collect();
+    let errors = struct_column(
+        vec![
+            (
+                "n",
+                Arc::new(errors.iter().map(|e| e.n).collect::<Int32Array>()),
+            ),
+            (
+                "message",
+                string_column(errors.iter().map(|e| Some(&e.message))),
+            ),
+        ],
+        None,
+    );
+    let events: Vec<&EventInfo> = records.iter().flat_map(|r| &r.events).collect();
+    let events = struct_column(
+        vec![
+            (
+                "n",
+                Arc::new(events.iter().map(|e| e.n).collect::<Int32Array>()),
+            ),
+            ("name", string_column(events.iter().map(|e| Some(&e.name)))),
+            (
+                "description",
+                string_column(events.iter().map(|e| Some(&e.description))),
+            ),
+        ],
+        None,
+    );
+
+    let mut columns: Vec<(&str, ArrayRef)> = vec![
+        (
+            "accel_name",
+            string_column(records.iter().map(|r| Some(&r.accel_info.name))),
+        ),
+        (
+            "m_value",
+            Arc::new(
+                records
+                    .iter()
+                    .map(|r| r.accel_info.m_value)
+                    .collect::<Int32Array>(),
+            ),
+        ),
+    ];
+    if !params.is_empty() {
+        let args: Vec<_> = records
+            .iter()
+            .map(|r| &r.accel_info.additional_args)
+            .collect();
+        columns.push(("additional_args", params_column(params, &args)));
+    }
+    columns.extend([
+        (
+            "computed",
+            list_column(computed, records.iter().map(|r| r.computed.len())),
+        ),
+        (
+            "errors",
+            list_column(errors, records.iter().map(|r| r.errors.len())),
+        ),
+        (
+            "events",
+            list_column(events, records.iter().map(|r| r.events.len())),
+        ),
+    ]);
+    if with_runs {
+        columns.push((
+            "run_id",
+            string_column(records.iter().map(|r| r.run.as_ref())),
+        ));
+    }
+    if let Some(notes) = notes {
+        columns.push(("note", string_column(notes)));
+    }
+    Ok(RecordBatch::try_from_iter(columns)?)
+}
+
+fn write_partition(dir: &Path, batch: &RecordBatch) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let file = std::fs::File::create(dir.join("part-0.parquet"))?;
+    let mut writer = datafusion::parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None)?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+// Accel record as exported, with its note if it has one
+#[derive(Serialize)]
+struct NotedRecord<'a> {
+    #[serde(flatten)]
+    record: &'a AccelRecord,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<&'a str>,
+}
+
+/// Writes the data as JSON, exactly as it's held in memory: a list of `[series, accelerations]`
+/// pairs with the `Scientific` numbers as `[mantissa, exponent]`, e.g. for notebooks. Records
+/// with a note get it as their `note` field
+pub fn export_json<'a>(
+    data: &[(&SeriesRecord, Vec<&AccelRecord>)],
+    note: impl Fn(i32, &AccelInfo) -> Option<&'a str>,
+    writer: impl std::io::Write,
+) -> Result<()> {
+    let data: Vec<(&SeriesRecord, Vec<NotedRecord>)> = data
+        .iter()
+        .map(|(series, accel_records)| {
+            let records = accel_records
+                .iter()
+                .map(|record| NotedRecord {
+                    record,
+                    note: note(series.series_id, &record.accel_info),
+                })
+                .collect();
+            (*series, records)
+        })
+        .collect();
+    serde_json::to_writer(std::io::BufWriter::new(writer), &data)?;
+    Ok(())
+}
+
+/// Writes the data as a new dataset in the layout and latest schema version the loader reads:
+/// `series/precision=<p>/series_name=<name>` and `accelerations/series_id=<id>`. Merged reruns
+/// are written as single records; their uncertainty ranges are not kept. Notes go to a `note`
+/// column of the accelerations if any record has one; the loader doesn't read them back.
+pub fn export_dataset<'a>(
+    data: &[(&SeriesRecord, Vec<&AccelRecord>)],
+    note: impl Fn(i32, &AccelInfo) -> Option<&'a str>,
+    dir: &Path,
+) -> Result<()> {
+    if dir.exists() {
+        return Err(anyhow!("{} already exists", dir.display()));
+    }
+    // Every partition gets the same columns, so that the schemas merge
+    let mut series_params = std::collections::BTreeSet::new();
+    let mut accel_params = std::collections::BTreeSet::new();
+    for (series, accel_records) in data {
+        series_params.extend(series.arguments.keys().cloned());
+        for accel_record in accel_records {
+            accel_params.extend(accel_record.accel_info.additional_args.keys().cloned());
+        }
+    }
+    let series_params: Vec<IStr> = series_params.into_iter().collect();
+    let accel_params: Vec<IStr> = accel_params.into_iter().collect();
+    let with_runs = data.iter().any(|(series, _)| series.run.is_some());
+    let accel_with_runs = data
+        .iter()
+        .flat_map(|(_, accel_records)| accel_records)
+        .any(|r| r.run.is_some());
+    let notes: Vec<Vec<Option<&str>>> = data
+        .iter()
+        .map(|(series, accel_records)| {
+            accel_records
+                .iter()
+                .map(|r| note(series.series_id, &r.accel_info))
+                .collect()
+        })
+        .collect();
+    let with_notes = notes.iter().flatten().any(Option::is_some);
+
+    let mut partitions: std::collections::BTreeMap<(&str, &str), Vec<&SeriesRecord>> =
+        Default::default();
+    for (series, _) in data {
+        partitions
+            .entry((&series.precision, &series.name))
+            .or_default()
+            .push(series);
+    }
+    for ((precision, name), series) in partitions {
+        let partition = dir
+            .join("series")
+            .join(format!("precision={precision}"))
+            .join(format!("series_name={name}"));
+        write_partition(
+            &partition,
+            &series_batch(&series, &series_params, with_runs)?,
+        )?;
+    }
+
+    // Without any accel records this is a series-only dataset
+    for ((series, accel_records), notes) in data.iter().zip(notes) {
+        if accel_records.is_empty() {
+            continue;
+        }
+        let partition = dir
+            .join("accelerations")
+            .join(format!("series_id={}", series.series_id));
+        let notes = with_notes.then_some(notes);
+        write_partition(
+            &partition,
+            &accelerations_batch(accel_records, &accel_params, accel_with_runs, notes)?,
+        )?;
     }
+    Ok(())
 }
@@ -0,0 +1,97 @@
+use crate::data_loader::{
+    DataLoader, Filters, LoadedPage, MetadataPart, QueryEstimate, SchemaVersion, TableSchema,
+};
+use anyhow::Result;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+
+/// Where the dashboard gets its data. [`DataLoader`] reads a directory of parquet files; other
+/// sources only have to produce the same records and metadata.
+pub trait DataSource: Send + Sync {
+    /// Shown to the user and used for the default workspace location
+    fn path(&self) -> &str;
+
+    /// Whether there are acceleration records at all
+    fn has_accelerations(&self) -> bool;
+
+    /// Whether the records have a run to order reruns by
+    fn has_runs(&self) -> bool {
+        false
+    }
+
+    /// Distinct values of the filterable columns, reported part by part
+    fn compute_metadata<'a>(
+        &'a self,
+        on_part: &'a mut (dyn FnMut(MetadataPart) + Send),
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Series matching the filters with their acceleration records, one page of them.
+    /// `param_methods` is `Metadata::accel_param_methods` once it's computed
+    fn filter_data<'a>(
+        &'a self,
+        filters: &'a Filters,
+        param_methods: Option<&'a HashMap<String, Vec<String>>>,
+    ) -> BoxFuture<'a, Result<LoadedPage>>;
+
+    /// Counts of what [`Self::filter_data`] would load
+    fn estimate<'a>(
+        &'a self,
+        filters: &'a Filters,
+        param_methods: Option<&'a HashMap<String, Vec<String>>>,
+    ) -> BoxFuture<'a, Result<QueryEstimate>>;
+
+    /// Layout of the stored data, for sources backed by files
+    fn schema_version(&self) -> SchemaVersion {
+        SchemaVersion::LATEST
+    }
+
+    /// Tables for the schema browser; sources without tables have none
+    fn table_schemas(&self) -> BoxFuture<'_, Result<Vec<TableSchema>>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+impl DataSource for DataLoader {
+    fn path(&self) -> &str {
+        DataLoader::path(self)
+    }
+
+    fn has_accelerations(&self) -> bool {
+        DataLoader::has_accelerations(self)
+    }
+
+    fn has_runs(&self) -> bool {
+        DataLoader::has_runs(self)
+    }
+
+    fn compute_metadata<'a>(
+        &'a self,
+        on_part: &'a mut (dyn FnMut(MetadataPart) + Send),
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(DataLoader::compute_metadata(self, on_part))
+    }
+
+    fn filter_data<'a>(
+        &'a self,
+        filters: &'a Filters,
+        param_methods: Option<&'a HashMap<String, Vec<String>>>,
+    ) -> BoxFuture<'a, Result<LoadedPage>> {
+        Box::pin(DataLoader::filter_data(self, filters, param_methods))
+    }
+
+    fn estimate<'a>(
+        &'a self,
+        filters: &'a Filters,
+        param_methods: Option<&'a HashMap<String, Vec<String>>>,
+    ) -> BoxFuture<'a, Result<QueryEstimate>> {
+        Box::pin(DataLoader::estimate(self, filters, param_methods))
+    }
+
+    fn schema_version(&self) -> SchemaVersion {
+        DataLoader::schema_version(self)
+    }
+
+    fn table_schemas(&self) -> BoxFuture<'_, Result<Vec<TableSchema>>> {
+        Box::pin(DataLoader::table_schemas(self))
+    }
+}
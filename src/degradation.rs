@@ -0,0 +1,188 @@
+use crate::accessibility::describe_plot;
+use crate::app::{
+    LineKey, RowStats, SeriesDataRef, Vis, format_method_name, format_series_name, line_id,
+    plot_controls, significand_bits,
+};
+use crate::legend::{method_color, method_key, series_key};
+use crate::panel::PlotPanel;
+use crate::symlog::{format_f64, paint_linear_region, symlog_formatter};
+use eframe::egui;
+use egui::{Color32, Rect, Ui};
+use egui_plot::{Line, MarkerShape, Plot, PlotPoint, Points, uniform_grid_spacer};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Same series and method configuration at every loaded precision
+struct Configuration {
+    name: String,
+    color: Color32,
+    // One point per precision, x is the index into `precisions`
+    points: Vec<PlotPoint>,
+    keys: Vec<LineKey>,
+}
+
+/// Minimal achievable error against precision, one connected line per (series, method, m,
+/// parameters), to see how methods degrade with floating-point precision.
+///
+/// Precisions are placed at x = 0, 1, 2... ordered by the significand width; unknown names go last.
+pub struct DegradationPanel {
+    precisions: Vec<String>,
+    configurations: Vec<Configuration>,
+    symlog: bool,
+}
+
+impl PlotPanel for DegradationPanel {
+    fn build(data: &[SeriesDataRef], symlog: bool) -> Self {
+        let mut precisions: Vec<String> = data
+            .iter()
+            .map(|(series, _)| series.precision.to_string())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        precisions.sort_by_key(|p| significand_bits(p).unwrap_or(i32::MAX));
+        let index: BTreeMap<&str, usize> = precisions
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.as_str(), i))
+            .collect();
+
+        let mut configurations = BTreeMap::<(String, String), Configuration>::new();
+        for (series, accel_records) in data {
+            for accel_record in accel_records {
+                let accel_info = &accel_record.accel_info;
+                let Some(min) = RowStats::of(series, accel_record).min else {
+                    continue;
+                };
+                let configuration = configurations
+                    .entry((series_key(series), method_key(accel_info)))
+                    .or_insert_with(|| Configuration {
+                        name: format!(
+                            "{} {}",
+                            format_method_name(accel_info),
+                            format_series_name(series)
+                        ),
+                        color: method_color(accel_info),
+                        points: Vec::new(),
+                        keys: Vec::new(),
+                    });
+                let y = if symlog {
+                    min.symlog()
+                } else {
+                    min.approx_f64()
+                };
+                let x = index[series.precision.as_ref()] as f64;
+                configuration.points.push(PlotPoint::new(x, y));
+                configuration.keys.push(LineKey::new(series, accel_info));
+            }
+        }
+
+        let mut configurations: Vec<Configuration> = configurations.into_values().collect();
+        for configuration in &mut configurations {
+            // Connect the points left to right
+            let mut pairs: Vec<_> = configuration
+                .points
+                .drain(..)
+                .zip(configuration.keys.drain(..))
+                .collect();
+            pairs.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+            (configuration.points, configuration.keys) = pairs.into_iter().unzip();
+        }
+
+        Self {
+            precisions,
+            configurations,
+            symlog,
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        "degradation"
+    }
+
+    fn title(&self) -> &str {
+        "Ошибка в зависимости от точности"
+    }
+
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect> {
+        if self.configurations.is_empty() {
+            ui.label("Нет данных для отображения");
+            return None;
+        }
+        if self.precisions.len() < 2 {
+            ui.label("Загружена только одна точность");
+            return None;
+        }
+
+        let symlog = self.symlog;
+        let format_y = if symlog { symlog_formatter } else { format_f64 };
+        let precision_name = |x: f64| {
+            let i = x.round();
+            if (x - i).abs() > 1e-6 || i < 0.0 {
+                return None;
+            }
+            self.precisions.get(i as usize)
+        };
+        let plot = Plot::new("degradation")
+            .allow_zoom(true)
+            .allow_drag(true)
+            .height(600.0)
+            .x_grid_spacer(uniform_grid_spacer(|_| [1.0, 5.0, 25.0]))
+            .x_axis_formatter(move |mark, _| {
+                precision_name(mark.value).cloned().unwrap_or_default()
+            })
+            .y_axis_formatter(move |mark, _| format_y(mark.value))
+            .label_formatter(move |name, value| {
+                let precision = precision_name(value.x).map_or("", String::as_str);
+                format!("{name}\n{precision}\ny={}", format_y(value.y))
+            })
+            .legend(egui_plot::Legend::default());
+        let plot = vis.axis_labels("degradation", plot, "Точность", "Минимальная ошибка");
+        let plot = vis.plot_wheel(plot);
+
+        let restore = vis.take_bounds("degradation");
+        let plot = plot.show(ui, |plot_ui| {
+            plot_controls(plot_ui);
+            if let Some(bounds) = restore {
+                plot_ui.set_plot_bounds(bounds);
+            }
+            for (i, configuration) in self.configurations.iter().enumerate() {
+                let selected = configuration.keys.iter().any(|key| vis.is_selected(key));
+                plot_ui.line(
+                    Line::new(configuration.points.as_slice())
+                        .name(&configuration.name)
+                        .id(line_id("degradation", i))
+                        .color(configuration.color)
+                        .highlight(selected)
+                        .width(if selected { 3.0 } else { 1.5 }),
+                );
+                plot_ui.points(
+                    Points::new(configuration.points.as_slice())
+                        .name(&configuration.name)
+                        .color(configuration.color)
+                        .shape(MarkerShape::Circle)
+                        .radius(4.0),
+                );
+            }
+            vis.draw_annotations("degradation", plot_ui, symlog, false);
+        });
+        // A line spans several series, one per precision; clicking it selects the first one
+        vis.select_from_plot(&plot, |id| {
+            (0..self.configurations.len())
+                .find(|i| line_id("degradation", i) == id)
+                .and_then(|i| self.configurations[i].keys.first())
+        });
+        if symlog {
+            paint_linear_region(ui, &plot.transform);
+        }
+        vis.annotation_menu("degradation", &plot, symlog, false);
+        describe_plot(
+            &plot.response,
+            format!(
+                "Минимальная ошибка по точностям, конфигураций: {}",
+                self.configurations.len()
+            ),
+        );
+        vis.track_hover(&plot.response);
+        vis.remember_bounds("degradation", plot.transform.bounds());
+        Some(plot.response.rect)
+    }
+}
@@ -0,0 +1,429 @@
+//! In-memory data for `vizr --demo`: classic series with known sums and the standard
+//! accelerations of their partial sums, computed on start in f64. Needs no files, so the GUI can
+//! be tried out and exercised in tests.
+
+use crate::data_loader::{
+    AccelInfo, AccelPoint, AccelRecord, ComplexNumber, ErrorInfo, Filters, LoadedPage, Metadata,
+    MetadataPart, PAGE_SIZE, QueryEstimate, SeriesData, SeriesPoint, SeriesRecord,
+    accel_params_match, intern, params_match, sort_param_values,
+};
+use crate::data_source::DataSource;
+use crate::symlog::Scientific;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::f64::consts::{LN_2, PI};
+
+// Partial sums per series
+const TERMS: usize = 60;
+
+// Methods with their orders m
+const METHODS: [(&str, &[i32]); 3] = [
+    ("aitken", &[1, 2]),
+    ("wynn_epsilon", &[1, 2, 3]),
+    ("levin_u", &[3, 6]),
+];
+
+struct Series {
+    name: &'static str,
+    arguments: Vec<(&'static str, String)>,
+    limit: f64,
+    // k-th term, k from 1
+    term: Box<dyn Fn(f64) -> f64>,
+}
+
+fn series() -> Vec<Series> {
+    let mut res = vec![Series {
+        name: "alternating_harmonic",
+        arguments: Vec::new(),
+        limit: LN_2,
+        term: Box::new(|k| if k % 2.0 == 1.0 { 1.0 / k } else { -1.0 / k }),
+    }];
+    // ζ(3) has no closed form
+    for (s, limit) in [
+        (2, PI.powi(2) / 6.0),
+        (3, 1.202_056_903_159_594_2),
+        (4, PI.powi(4) / 90.0),
+    ] {
+        res.push(Series {
+            name: "zeta",
+            arguments: vec![("s", s.to_string())],
+            limit,
+            term: Box::new(move |k| k.powi(-s)),
+        });
+    }
+    // -ln(1 - x)
+    for x in [0.5f64, 0.9] {
+        res.push(Series {
+            name: "logarithmic",
+            arguments: vec![("x", x.to_string())],
+            limit: -(1.0 - x).ln(),
+            term: Box::new(move |k| x.powf(k) / k),
+        });
+    }
+    res
+}
+
+fn partial_sums(series: &Series) -> Vec<f64> {
+    (1..=TERMS)
+        .scan(0.0, |sum, k| {
+            *sum += (series.term)(k as f64);
+            Some(*sum)
+        })
+        .collect()
+}
+
+// Accelerated values aligned with the partial sums: `None` until enough sums are available, a
+// non-finite value where the method broke down
+
+// Iterated Δ²: each iteration uses three consecutive values
+fn aitken(sums: &[f64], iterations: i32) -> Vec<Option<f64>> {
+    let mut values: Vec<Option<f64>> = sums.iter().copied().map(Some).collect();
+    for _ in 0..iterations {
+        values = (0..values.len())
+            .map(|i| {
+                let window = values.get(i.checked_sub(2)?..=i)?;
+                let (a, b, c) = (window[0]?, window[1]?, window[2]?);
+                Some(c - (c - b).powi(2) / (c - 2.0 * b + a))
+            })
+            .collect();
+    }
+    values
+}
+
+// ε_{2m} from the last 2m + 1 sums
+fn wynn_epsilon(sums: &[f64], m: i32) -> Vec<Option<f64>> {
+    let width = 2 * m as usize;
+    (0..sums.len())
+        .map(|i| {
+            let window = sums.get((i + 1).checked_sub(width + 1)?..=i)?;
+            let mut prev = vec![0.0; window.len() + 1];
+            let mut cur = window.to_vec();
+            for _ in 0..width {
+                let next: Vec<f64> = (0..cur.len() - 1)
+                    .map(|j| prev[j + 1] + 1.0 / (cur[j + 1] - cur[j]))
+                    .collect();
+                prev = cur;
+                cur = next;
+            }
+            Some(cur[0])
+        })
+        .collect()
+}
+
+// Levin's u-transform of order k from the last k + 1 sums, remainder estimates n·a_n
+fn levin_u(sums: &[f64], k: i32) -> Vec<Option<f64>> {
+    let k_usize = k as usize;
+    (0..sums.len())
+        .map(|i| {
+            let first = i.checked_sub(k_usize)?;
+            let (mut num, mut den) = (0.0, 0.0);
+            let mut binomial = 1.0;
+            for j in 0..=k_usize {
+                // 1-based index of the sum
+                let n = (first + j + 1) as f64;
+                let term = sums[first + j]
+                    - if first + j == 0 {
+                        0.0
+                    } else {
+                        sums[first + j - 1]
+                    };
+                let weight = if j % 2 == 0 { binomial } else { -binomial }
+                    * (n / (first + k_usize + 1) as f64).powi(k - 1)
+                    / (n * term);
+                num += weight * sums[first + j];
+                den += weight;
+                binomial = binomial * (k_usize - j) as f64 / (j + 1) as f64;
+            }
+            Some(num / den)
+        })
+        .collect()
+}
+
+fn complex(real: f64) -> ComplexNumber {
+    ComplexNumber {
+        real: Scientific::from_f64(real),
+        imag: Scientific::default(),
+    }
+}
+
+fn accel_record(name: &str, m_value: i32, values: Vec<Option<f64>>, limit: f64) -> AccelRecord {
+    let mut errors = Vec::new();
+    let computed = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| match value? {
+            value if value.is_finite() => Some(AccelPoint {
+                value: complex(value),
+                deviation: Scientific::from_f64(value - limit),
+            }),
+            _ => {
+                errors.push(ErrorInfo {
+                    n: i as i32 + 1,
+                    message: "division by zero".to_string(),
+                });
+                None
+            }
+        })
+        .collect();
+    AccelRecord {
+        accel_info: AccelInfo {
+            name: intern(name),
+            m_value,
+            additional_args: HashMap::new(),
+        },
+        run: None,
+        computed,
+        errors,
+        events: Vec::new(),
+        reruns: 0,
+        uncertainty: Vec::new(),
+    }
+}
+
+fn generate() -> Vec<SeriesData> {
+    series()
+        .into_iter()
+        .enumerate()
+        .map(|(id, series)| {
+            let sums = partial_sums(&series);
+            let computed = sums
+                .iter()
+                .enumerate()
+                .map(|(i, sum)| SeriesPoint {
+                    n: i as i32 + 1,
+                    value: complex(*sum),
+                    deviation: Scientific::from_f64(sum - series.limit),
+                })
+                .collect();
+            let mut records = Vec::new();
+            for (method, m_values) in METHODS {
+                for &m in m_values {
+                    let values = match method {
+                        "aitken" => aitken(&sums, m),
+                        "wynn_epsilon" => wynn_epsilon(&sums, m),
+                        _ => levin_u(&sums, m),
+                    };
+                    records.push(accel_record(method, m, values, series.limit));
+                }
+            }
+            let record = SeriesRecord {
+                precision: intern("f64"),
+                series_id: id as i32,
+                name: intern(series.name),
+                arguments: series
+                    .arguments
+                    .iter()
+                    .map(|(k, v)| (intern(k), intern(v)))
+                    .collect(),
+                series_limit: complex(series.limit),
+                computed,
+                run: None,
+            };
+            (record, records)
+        })
+        .collect()
+}
+
+// Records without a parameter match any of its values, as in the loader
+fn contains<T: Eq + std::hash::Hash>(set: &HashSet<T>, value: &T) -> bool {
+    set.is_empty() || set.contains(value)
+}
+
+/// Synthetic [`DataSource`]
+pub struct DemoSource {
+    data: Vec<SeriesData>,
+}
+
+impl Default for DemoSource {
+    fn default() -> Self {
+        Self { data: generate() }
+    }
+}
+
+impl DemoSource {
+    // Matching series with their matching records, over all pages
+    fn select(&self, filters: &Filters) -> Vec<SeriesData> {
+        // No method of the demo sets acceleration parameters, so their filters apply to nothing
+        let no_params: HashMap<String, Vec<String>> = HashMap::new();
+        let series = self.data.iter().filter(|(series, _)| {
+            contains(&filters.precisions, &series.precision)
+                && contains(&filters.base_series, &series.name)
+                && params_match(&series.arguments, &filters.series_params)
+        });
+        series
+            .take(filters.sample.unwrap_or(usize::MAX))
+            .map(|(series, records)| {
+                let records = records
+                    .iter()
+                    .filter(|r| {
+                        contains(&filters.base_accel, &r.accel_info.name)
+                            && contains(&filters.m_values, &r.accel_info.m_value)
+                            && accel_params_match(&r.accel_info, &filters.accel_params, &no_params)
+                            && filters.matches_outcome(r)
+                    })
+                    .cloned()
+                    .collect();
+                (series.clone(), records)
+            })
+            .collect()
+    }
+
+    fn metadata(&self) -> Metadata {
+        let mut precisions = BTreeSet::new();
+        let mut series_names = BTreeSet::new();
+        let mut accel_names = BTreeSet::new();
+        let mut m_values = BTreeSet::new();
+        let mut series_params: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for (series, records) in &self.data {
+            precisions.insert(series.precision.to_string());
+            series_names.insert(series.name.to_string());
+            for (k, v) in &series.arguments {
+                series_params
+                    .entry(k.to_string())
+                    .or_default()
+                    .insert(v.to_string());
+            }
+            for record in records {
+                accel_names.insert(record.accel_info.name.to_string());
+                m_values.insert(record.accel_info.m_value);
+            }
+        }
+        Metadata {
+            precisions: precisions.into_iter().collect(),
+            series_names: series_names.into_iter().collect(),
+            accel_names: accel_names.into_iter().collect(),
+            m_values: m_values.into_iter().collect(),
+            accel_param_info: HashMap::new(),
+            accel_param_methods: HashMap::new(),
+            series_param_info: series_params
+                .into_iter()
+                .map(|(k, v)| {
+                    let mut v: Vec<String> = v.into_iter().collect();
+                    sort_param_values(&mut v);
+                    (k, v)
+                })
+                .collect(),
+            event_names: Vec::new(),
+        }
+    }
+}
+
+impl DataSource for DemoSource {
+    fn path(&self) -> &str {
+        "demo"
+    }
+
+    fn has_accelerations(&self) -> bool {
+        true
+    }
+
+    fn compute_metadata<'a>(
+        &'a self,
+        on_part: &'a mut (dyn FnMut(MetadataPart) + Send),
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.metadata().into_parts().into_iter().for_each(on_part);
+            Ok(())
+        })
+    }
+
+    // The demo methods set no parameters, see `select`
+    fn filter_data<'a>(
+        &'a self,
+        filters: &'a Filters,
+        _param_methods: Option<&'a HashMap<String, Vec<String>>>,
+    ) -> BoxFuture<'a, Result<LoadedPage>> {
+        Box::pin(async move {
+            let mut data = self.select(filters);
+            let total_series = data.len();
+            if total_series > PAGE_SIZE {
+                data = data
+                    .into_iter()
+                    .skip(filters.page * PAGE_SIZE)
+                    .take(PAGE_SIZE)
+                    .collect();
+            }
+            Ok(LoadedPage { data, total_series })
+        })
+    }
+
+    fn estimate<'a>(
+        &'a self,
+        filters: &'a Filters,
+        _param_methods: Option<&'a HashMap<String, Vec<String>>>,
+    ) -> BoxFuture<'a, Result<QueryEstimate>> {
+        Box::pin(async move {
+            let data = self.select(filters);
+            Ok(QueryEstimate {
+                series: data.len(),
+                accel_records: data.iter().map(|(_, records)| records.len()).sum(),
+                bytes: 0,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error(values: &[Option<f64>], limit: f64) -> f64 {
+        let value = values.iter().rev().flatten().find(|v| v.is_finite());
+        (value.unwrap() - limit).abs()
+    }
+
+    #[test]
+    fn accelerations_converge() {
+        let series = series();
+        let harmonic = &series[0];
+        let sums = partial_sums(harmonic);
+        assert!((sums[TERMS - 1] - harmonic.limit).abs() > 1e-3);
+        assert!(last_error(&aitken(&sums, 2), harmonic.limit) < 1e-6);
+        assert!(last_error(&wynn_epsilon(&sums, 3), harmonic.limit) < 1e-10);
+        assert!(last_error(&levin_u(&sums, 6), harmonic.limit) < 1e-10);
+
+        // Logarithmic convergence, where only Levin's u helps much
+        let zeta = &series[1];
+        assert!(last_error(&levin_u(&partial_sums(zeta), 3), zeta.limit) < 1e-8);
+    }
+
+    #[test]
+    fn points_wait_for_enough_sums() {
+        let sums = partial_sums(&series()[0]);
+        assert_eq!(
+            aitken(&sums, 2).iter().take_while(|v| v.is_none()).count(),
+            4
+        );
+        assert_eq!(
+            wynn_epsilon(&sums, 3)
+                .iter()
+                .take_while(|v| v.is_none())
+                .count(),
+            6
+        );
+        assert_eq!(
+            levin_u(&sums, 3).iter().take_while(|v| v.is_none()).count(),
+            3
+        );
+    }
+
+    #[test]
+    fn filters_apply() {
+        let source = DemoSource::default();
+        let mut filters = Filters {
+            base_series: [intern("zeta")].into(),
+            series_params: [(intern("s"), [intern("3")].into())].into(),
+            m_values: [1].into(),
+            ..Default::default()
+        };
+        let data = source.select(&filters);
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].0.arguments[&intern("s")], intern("3"));
+        let names: Vec<&str> = data[0].1.iter().map(|r| &*r.accel_info.name).collect();
+        assert_eq!(names, ["aitken", "wynn_epsilon"]);
+
+        filters.sample = Some(0);
+        assert!(source.select(&filters).is_empty());
+    }
+}
@@ -0,0 +1,368 @@
+//! Why filters match nothing: the predicates whose removal brings records back, found by relaxing
+//! the filters one predicate at a time. Also acceleration parameter filters that apply to none
+//! of the selected methods, found by the metadata before the query is run
+
+use crate::data_loader::{Filters, IStr};
+use anyhow::Result;
+use eframe::egui;
+use egui::Ui;
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// One condition of the filters
+#[derive(Clone)]
+pub enum Predicate {
+    Divergent,
+    Errors,
+    Events,
+    EventNames,
+    AccelParam(IStr),
+    MValue,
+    Accel,
+    SeriesParam(IStr),
+    Family,
+    Series,
+    Precision,
+}
+
+fn list<T: ToString>(values: &HashSet<T>) -> String {
+    let mut values: Vec<String> = values.iter().map(T::to_string).collect();
+    values.sort();
+    values.join(", ")
+}
+
+impl Predicate {
+    /// Conditions set in the filters, the most specific first: that's the order they are relaxed in
+    fn active(filters: &Filters) -> Vec<Self> {
+        let mut res = Vec::new();
+        if filters.divergent.is_some() {
+            res.push(Self::Divergent);
+        }
+        if filters.has_errors.is_some() {
+            res.push(Self::Errors);
+        }
+        if filters.has_events.is_some() {
+            res.push(Self::Events);
+        }
+        if !filters.event_names.is_empty() {
+            res.push(Self::EventNames);
+        }
+        let mut accel_params: Vec<&IStr> = filters.accel_params.keys().collect();
+        accel_params.sort();
+        res.extend(accel_params.into_iter().cloned().map(Self::AccelParam));
+        if !filters.m_values.is_empty() {
+            res.push(Self::MValue);
+        }
+        if !filters.base_accel.is_empty() {
+            res.push(Self::Accel);
+        }
+        let mut series_params: Vec<&IStr> = filters.series_params.keys().collect();
+        series_params.sort();
+        res.extend(series_params.into_iter().cloned().map(Self::SeriesParam));
+        if !filters.families.is_empty() {
+            res.push(Self::Family);
+        }
+        if !filters.base_series.is_empty() {
+            res.push(Self::Series);
+        }
+        if !filters.precisions.is_empty() {
+            res.push(Self::Precision);
+        }
+        res
+    }
+
+    // Which side of the data the condition is on
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Divergent | Self::Errors | Self::Events | Self::EventNames => "исход",
+            Self::AccelParam(_) | Self::SeriesParam(_) => "параметр",
+            Self::MValue | Self::Accel => "ускорения",
+            Self::Family | Self::Series | Self::Precision => "ряды",
+        }
+    }
+
+    /// The condition with its values, as in the filter headings
+    fn describe(&self, filters: &Filters) -> String {
+        let flag = |name: &str, value: Option<bool>| match value {
+            Some(true) => format!("{name}: есть"),
+            _ => format!("{name}: нет"),
+        };
+        match self {
+            Self::Divergent => flag("расходимость", filters.divergent),
+            Self::Errors => flag("ошибки", filters.has_errors),
+            Self::Events => flag("события", filters.has_events),
+            Self::EventNames => format!("события: {}", list(&filters.event_names)),
+            Self::AccelParam(key) => format!("{key}: {}", list(&filters.accel_params[key])),
+            Self::MValue => format!("m: {}", list(&filters.m_values)),
+            Self::Accel => format!("accel: {}", list(&filters.base_accel)),
+            Self::SeriesParam(key) => format!("{key}: {}", list(&filters.series_params[key])),
+            Self::Family => format!("family: {}", list(&filters.families)),
+            Self::Series => format!("series: {}", list(&filters.base_series)),
+            Self::Precision => format!("prec: {}", list(&filters.precisions)),
+        }
+    }
+
+    /// Removes the condition from the filters
+    pub fn relax(&self, filters: &mut Filters) {
+        match self {
+            Self::Divergent => filters.divergent = None,
+            Self::Errors => filters.has_errors = None,
+            Self::Events => filters.has_events = None,
+            Self::EventNames => filters.event_names.clear(),
+            Self::AccelParam(key) => {
+                filters.accel_params.remove(key);
+            }
+            Self::MValue => filters.m_values.clear(),
+            Self::Accel => filters.base_accel.clear(),
+            Self::SeriesParam(key) => {
+                filters.series_params.remove(key);
+            }
+            Self::Family => filters.families.clear(),
+            Self::Series => filters.base_series.clear(),
+            Self::Precision => filters.precisions.clear(),
+        }
+    }
+}
+
+fn relaxed<'a>(filters: &Filters, predicates: impl IntoIterator<Item = &'a Predicate>) -> Filters {
+    let mut filters = filters.clone();
+    for predicate in predicates {
+        predicate.relax(&mut filters);
+    }
+    filters
+}
+
+/// Predicates with their description, and the number of records matched without them
+pub enum Diagnosis {
+    /// Without any one of these alone there are records
+    Each(Vec<(Predicate, String, usize)>),
+    /// Only without all of these together
+    Together(Vec<(Predicate, String)>, usize),
+    /// Nothing even without the filters
+    NoData,
+}
+
+impl Diagnosis {
+    /// Relaxes the filters, counting the records each time with `count`, until something matches;
+    /// `None` if the filters have no conditions to relax
+    pub fn of(
+        filters: &Filters,
+        mut count: impl FnMut(&Filters) -> Result<usize>,
+    ) -> Result<Option<Self>> {
+        let active = Predicate::active(filters);
+        if active.is_empty() {
+            return Ok(None);
+        }
+        let describe = |p: &Predicate| (p.clone(), p.describe(filters));
+
+        let mut each = Vec::new();
+        for predicate in &active {
+            let records = count(&relaxed(filters, [predicate]))?;
+            if records > 0 {
+                let (predicate, text) = describe(predicate);
+                each.push((predicate, text, records));
+            }
+        }
+        if !each.is_empty() {
+            return Ok(Some(Self::Each(each)));
+        }
+
+        // Relaxed cumulatively until something matches...
+        let mut end = None;
+        for n in 2..=active.len() {
+            if count(&relaxed(filters, &active[..n]))? > 0 {
+                end = Some(n);
+                break;
+            }
+        }
+        let Some(end) = end else {
+            return Ok(Some(Self::NoData));
+        };
+        // ...then the ones that turn out not to matter are put back
+        let mut culprits: Vec<&Predicate> = active[..end].iter().collect();
+        let mut i = 0;
+        while i + 1 < culprits.len() {
+            let mut without = culprits.clone();
+            without.remove(i);
+            if count(&relaxed(filters, without.iter().copied()))? > 0 {
+                culprits = without;
+            } else {
+                i += 1;
+            }
+        }
+        let records = count(&relaxed(filters, culprits.iter().copied()))?;
+        Ok(Some(Self::Together(
+            culprits.into_iter().map(describe).collect(),
+            records,
+        )))
+    }
+
+    /// Shows the diagnosis; returns the predicates to remove once one of the buttons is clicked
+    pub fn ui(&self, ui: &mut Ui) -> Option<Vec<Predicate>> {
+        let mut relax = None;
+        let warn = ui.visuals().warn_fg_color;
+        match self {
+            Self::Each(each) => {
+                ui.colored_label(
+                    warn,
+                    "⚠ Ничего не найдено. Без любого из этих условий записи есть:",
+                );
+                for (predicate, text, records) in each {
+                    ui.horizontal(|ui| {
+                        ui.weak(predicate.kind());
+                        ui.label(text);
+                        ui.label(format!("— записей: {records}"));
+                        if ui.small_button("Снять").clicked() {
+                            relax = Some(vec![predicate.clone()]);
+                        }
+                    });
+                }
+            }
+            Self::Together(culprits, records) => {
+                ui.colored_label(
+                    warn,
+                    "⚠ Ничего не найдено. Записи появляются, только если снять вместе:",
+                );
+                for (predicate, text) in culprits {
+                    ui.horizontal(|ui| {
+                        ui.weak(predicate.kind());
+                        ui.label(text);
+                    });
+                }
+                if ui
+                    .small_button(format!("Снять все — записей: {records}"))
+                    .clicked()
+                {
+                    relax = Some(culprits.iter().map(|(p, _)| p.clone()).collect());
+                }
+            }
+            Self::NoData => {
+                ui.colored_label(warn, "⚠ Ничего не найдено, даже без фильтров");
+            }
+        }
+        relax
+    }
+}
+
+/// Acceleration parameter filter that applies to none of the selected methods, e.g. left over
+/// from an earlier selection while the section of the parameter is hidden: it filters nothing
+pub struct IdleParam {
+    param: IStr,
+    /// Methods that set the parameter
+    methods: Vec<String>,
+}
+
+impl IdleParam {
+    fn describe(&self) -> String {
+        format!(
+            "«{}» задают только методы {}, а они не выбраны: фильтр ни на что не влияет",
+            self.param,
+            self.methods.join(", ")
+        )
+    }
+}
+
+/// Acceleration parameter filters set only by methods that aren't selected, by the methods that
+/// set each parameter
+pub fn idle_params<K, M>(filters: &Filters, param_methods: &HashMap<K, Vec<M>>) -> Vec<IdleParam>
+where
+    K: Borrow<str> + Hash + Eq,
+    M: AsRef<str>,
+{
+    if filters.base_accel.is_empty() {
+        return Vec::new();
+    }
+    let mut res: Vec<IdleParam> = filters
+        .accel_params
+        .iter()
+        .filter(|(_, values)| !values.is_empty())
+        .filter_map(|(param, _)| {
+            let methods = param_methods.get(&**param)?;
+            let selected = methods
+                .iter()
+                .any(|m| filters.base_accel.contains(m.as_ref()));
+            (!methods.is_empty() && !selected).then(|| IdleParam {
+                param: param.clone(),
+                methods: methods.iter().map(|m| m.as_ref().to_string()).collect(),
+            })
+        })
+        .collect();
+    res.sort_by(|a, b| a.param.cmp(&b.param));
+    res
+}
+
+/// Warns about the idle filters; removes the filter of a parameter once its button is clicked
+pub fn idle_params_ui(ui: &mut Ui, idle: &[IdleParam], filters: &mut Filters) {
+    let warn = ui.visuals().warn_fg_color;
+    for idle in idle {
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(warn, format!("⚠ {}", idle.describe()));
+            if ui.small_button(format!("Снять «{}»", idle.param)).clicked() {
+                filters.accel_params.remove(&idle.param);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_loader::{NOT_SET, intern};
+
+    #[test]
+    fn together_keeps_only_the_culprits() {
+        let mut filters = Filters::default();
+        filters.precisions.insert(intern("f64"));
+        filters.base_series.insert(intern("zeta"));
+        filters.base_accel.insert(intern("shanks"));
+        filters.has_errors = Some(true);
+        // Records appear only once both the series and the accel conditions are gone
+        let count = |f: &Filters| {
+            Ok(usize::from(
+                f.base_series.is_empty() && f.base_accel.is_empty(),
+            ))
+        };
+        let Some(Diagnosis::Together(culprits, 1)) = Diagnosis::of(&filters, count).unwrap() else {
+            panic!("expected a joint diagnosis");
+        };
+        let culprits: Vec<&str> = culprits.iter().map(|(p, _)| p.kind()).collect();
+        assert_eq!(culprits, ["ускорения", "ряды"]);
+
+        let count = |f: &Filters| Ok(usize::from(f.has_errors.is_none()));
+        assert!(matches!(
+            Diagnosis::of(&filters, count).unwrap(),
+            Some(Diagnosis::Each(each)) if each.len() == 1
+        ));
+        assert!(
+            Diagnosis::of(&Filters::default(), |_| Ok(0))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn idle_params_of_unselected_methods() {
+        let methods = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let param_methods = HashMap::from([
+            ("beta".to_string(), methods(&["levin"])),
+            ("eps".to_string(), methods(&["wynn"])),
+        ]);
+        let mut filters = Filters::default();
+        for (param, value) in [("beta", "1"), ("eps", NOT_SET)] {
+            let values = filters.accel_params.entry(intern(param)).or_default();
+            values.insert(intern(value));
+        }
+        // Without a method selection every filter applies to its methods
+        assert!(idle_params(&filters, &param_methods).is_empty());
+        filters.base_accel.insert(intern("levin"));
+        let idle: Vec<String> = idle_params(&filters, &param_methods)
+            .iter()
+            .map(IdleParam::describe)
+            .collect();
+        assert_eq!(
+            idle,
+            ["«eps» задают только методы wynn, а они не выбраны: фильтр ни на что не влияет"]
+        );
+    }
+}
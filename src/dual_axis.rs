@@ -0,0 +1,225 @@
+use crate::accessibility::describe_plot;
+use crate::app::{
+    LineKey, SeriesDataRef, Vis, format_item_name, format_series_name_with_args, line_id,
+    plot_controls,
+};
+use crate::legend::{method_color, series_key, value_color};
+use crate::panel::PlotPanel;
+use crate::symlog::{format_f64, symlog_formatter};
+use eframe::egui;
+use egui::{Color32, Rect, Ui};
+use egui_plot::{AxisHints, HPlacement, Legend, Line, LineStyle, Plot, PlotPoint};
+
+/// Value and error of one partial sum or accel record
+struct Curve {
+    name: String,
+    color: Color32,
+    // Real part of the value
+    values: Vec<PlotPoint>,
+    // Deviation on the symlog scale, before it's mapped onto the value axis
+    errors: Vec<PlotPoint>,
+    key: Option<LineKey>,
+}
+
+// Linear map of the error scale onto the value scale, so that both ranges fill the plot height
+#[derive(Clone, Copy)]
+struct ErrorAxis {
+    errors: (f64, f64),
+    values: (f64, f64),
+}
+
+impl ErrorAxis {
+    fn new(curves: &[Curve]) -> Self {
+        let range = |points: &mut dyn Iterator<Item = &PlotPoint>| {
+            let (min, max) = points
+                .map(|p| p.y)
+                .filter(|y| y.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), y| {
+                    (min.min(y), max.max(y))
+                });
+            match (min, max) {
+                _ if min > max => (0.0, 1.0),
+                // A flat range still needs some height
+                _ if min == max => (min - 0.5, max + 0.5),
+                range => range,
+            }
+        };
+        Self {
+            errors: range(&mut curves.iter().flat_map(|c| &c.errors)),
+            values: range(&mut curves.iter().flat_map(|c| &c.values)),
+        }
+    }
+
+    fn scale(&self) -> f64 {
+        (self.values.1 - self.values.0) / (self.errors.1 - self.errors.0)
+    }
+
+    fn to_values(self, error: f64) -> f64 {
+        self.values.0 + (error - self.errors.0) * self.scale()
+    }
+
+    fn to_errors(self, value: f64) -> f64 {
+        self.errors.0 + (value - self.values.0) / self.scale()
+    }
+}
+
+/// Values (left axis, linear) and errors (right axis, symlog) of the same lines on one plot, to
+/// see how the oscillation of the values relates to the decay of the error. Errors are dashed
+pub struct DualAxisPanel {
+    curves: Vec<Curve>,
+    axis: ErrorAxis,
+}
+
+impl PlotPanel for DualAxisPanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        let mut curves = Vec::new();
+        for (series, accel_records) in data {
+            if series.computed.is_empty() {
+                continue;
+            }
+            curves.push(Curve {
+                name: format!("частичные суммы {}", format_series_name_with_args(series)),
+                color: value_color(&series_key(series)),
+                values: series
+                    .computed
+                    .iter()
+                    .map(|c| PlotPoint::new(c.n as f64, c.value.real.approx_f64()))
+                    .collect(),
+                errors: series
+                    .computed
+                    .iter()
+                    .map(|c| PlotPoint::new(c.n as f64, c.deviation.symlog()))
+                    .collect(),
+                key: None,
+            });
+            for accel_record in accel_records {
+                let points = || {
+                    series
+                        .computed
+                        .iter()
+                        .zip(accel_record.computed.iter())
+                        .filter_map(|(c, accel)| Some((c.n as f64, accel?)))
+                };
+                let values: Vec<PlotPoint> = points()
+                    .map(|(n, accel)| PlotPoint::new(n, accel.value.real.approx_f64()))
+                    .collect();
+                if values.is_empty() {
+                    continue;
+                }
+                curves.push(Curve {
+                    name: format_item_name(series, &accel_record.accel_info),
+                    color: method_color(&accel_record.accel_info),
+                    values,
+                    errors: points()
+                        .map(|(n, accel)| PlotPoint::new(n, accel.deviation.symlog()))
+                        .collect(),
+                    key: Some(LineKey::new(series, &accel_record.accel_info)),
+                });
+            }
+        }
+        let axis = ErrorAxis::new(&curves);
+        Self { curves, axis }
+    }
+
+    fn id(&self) -> &'static str {
+        "dual_axis"
+    }
+
+    fn title(&self) -> &str {
+        "Значение и ошибка на одном графике"
+    }
+
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect> {
+        if self.curves.is_empty() {
+            ui.label("Нет данных для отображения");
+            return None;
+        }
+
+        let axis = self.axis;
+        let plot_id = if vis.opts.log_x {
+            "dual_axis_log_x"
+        } else {
+            "dual_axis"
+        };
+        let plot = Plot::new(plot_id)
+            .allow_zoom(true)
+            .allow_drag(true)
+            .height(600.0)
+            .custom_y_axes(vec![
+                AxisHints::new_y()
+                    .label("Значение")
+                    .formatter(|mark, _| format_f64(mark.value)),
+                AxisHints::new_y()
+                    .label("Ошибка")
+                    .placement(HPlacement::Right)
+                    .formatter(move |mark, _| symlog_formatter(axis.to_errors(mark.value))),
+            ])
+            .label_formatter(move |name, value| {
+                format!(
+                    "{name}\nx={}\nзначение={}\nошибка на этой высоте={}",
+                    value.x,
+                    format_f64(value.y),
+                    symlog_formatter(axis.to_errors(value.y))
+                )
+            })
+            .legend(Legend::default());
+        let plot = vis.axis_labels("dual_axis", plot, "Итерация n", "");
+        let plot = vis.plot_wheel(plot);
+        let plot = vis.iteration_axis(plot);
+
+        let restore = vis.take_bounds(plot_id);
+        let plot = plot.show(ui, |plot_ui| {
+            plot_controls(plot_ui);
+            if let Some(bounds) = restore {
+                plot_ui.set_plot_bounds(bounds);
+            }
+            for (i, curve) in self.curves.iter().enumerate() {
+                let selected = curve.key.as_ref().is_some_and(|k| vis.is_selected(k));
+                let width = if selected { 3.0 } else { 1.5 };
+                // Same name: one legend entry toggles both lines
+                plot_ui.line(
+                    Line::new(&*vis.iteration_points(&curve.values))
+                        .name(&curve.name)
+                        .id(line_id("dual_axis", (i, 0)))
+                        .color(curve.color)
+                        .highlight(selected)
+                        .width(width),
+                );
+                let errors: Vec<PlotPoint> = vis
+                    .iteration_points(&curve.errors)
+                    .iter()
+                    .map(|p| PlotPoint::new(p.x, axis.to_values(p.y)))
+                    .collect();
+                plot_ui.line(
+                    Line::new(errors)
+                        .name(&curve.name)
+                        .id(line_id("dual_axis", (i, 1)))
+                        .color(curve.color)
+                        .style(LineStyle::dashed_loose())
+                        .highlight(selected)
+                        .width(width),
+                );
+            }
+        });
+        vis.select_from_plot(&plot, |id| {
+            (0..self.curves.len())
+                .find(|&i| (0..2).any(|j| line_id("dual_axis", (i, j)) == id))
+                .and_then(|i| self.curves[i].key.as_ref())
+        });
+        describe_plot(
+            &plot.response,
+            format!(
+                "Значения (слева) и ошибки (справа, пунктир), линий: {}",
+                self.curves.len()
+            ),
+        );
+        vis.track_hover(&plot.response);
+        vis.remember_bounds(plot_id, plot.transform.bounds());
+        Some(plot.response.rect)
+    }
+
+    // Partial sums are plotted on their own
+    fn needs_accelerations(&self) -> bool {
+        false
+    }
+}
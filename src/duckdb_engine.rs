@@ -0,0 +1,305 @@
+//! DuckDB implementation of the loader's query layer, `--engine duckdb`. Each [`Query`] is
+//! translated to SQL over views of the parquet files; the batches are decoded by the loader as
+//! for DataFusion, so the two engines can be checked against each other.
+
+use crate::data_loader::{
+    Filters, IStr, NOT_SET, PAGE_SIZE, Query, QueryEngine, readable_parquet_files,
+};
+use anyhow::{Context, Result, anyhow};
+use datafusion::arrow::datatypes::{DataType, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use duckdb::Connection;
+use futures::future::BoxFuture;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+pub struct DuckDbEngine {
+    conn: Mutex<Connection>,
+    // Missing optional columns are left out of the predicates, as with DataFusion
+    schemas: HashMap<&'static str, SchemaRef>,
+}
+
+// SQL string literal
+fn string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+// Quoted identifier: `precision` and the like are keywords
+fn ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn sql_type(data_type: &DataType) -> Result<&'static str> {
+    match data_type {
+        DataType::Utf8 => Ok("VARCHAR"),
+        DataType::Int32 => Ok("INTEGER"),
+        t => Err(anyhow!("No DuckDB type for partition column of type {t}")),
+    }
+}
+
+fn field(column: &str, field: &str) -> String {
+    format!("struct_extract({}, {})", ident(column), string(field))
+}
+
+// `FALSE` for no values, which `IN ()` doesn't accept
+fn in_list(expr: &str, values: impl IntoIterator<Item = String>) -> String {
+    let values: Vec<String> = values.into_iter().collect();
+    if values.is_empty() {
+        "FALSE".to_string()
+    } else {
+        format!("{expr} IN ({})", values.join(", "))
+    }
+}
+
+fn strings<'a>(values: impl IntoIterator<Item = &'a IStr>) -> impl Iterator<Item = String> {
+    values.into_iter().map(|v| string(v))
+}
+
+// Records without the parameter match `NOT_SET`. With `param_methods`, a condition applies only
+// to the methods that set the parameter, see `accel_params_match`
+fn params_conditions(
+    column: &str,
+    filters: &HashMap<IStr, HashSet<IStr>>,
+    param_methods: Option<&HashMap<String, Vec<String>>>,
+) -> Vec<String> {
+    filters
+        .iter()
+        .filter(|(_, values)| !values.is_empty())
+        .filter_map(|(arg, values)| {
+            let methods = param_methods.map(|m| m.get(arg.as_ref()).map_or(&[][..], Vec::as_slice));
+            if methods.is_some_and(<[String]>::is_empty) {
+                // No record sets it, so there's nothing to apply it to
+                return None;
+            }
+            let value = field(column, arg);
+            let set = in_list(
+                &value,
+                strings(values.iter().filter(|v| v.as_ref() != NOT_SET)),
+            );
+            let condition = if values.contains(NOT_SET) {
+                format!("({set} OR {value} IS NULL)")
+            } else {
+                set
+            };
+            Some(match methods {
+                Some(methods) => format!(
+                    "(NOT {} OR {condition})",
+                    in_list(&ident("accel_name"), methods.iter().map(|m| string(m)))
+                ),
+                None => condition,
+            })
+        })
+        .collect()
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    }
+}
+
+impl DuckDbEngine {
+    /// Opens the readable parquet files of each table as a view of the same name
+    pub fn new(path: &str, tables: &[(&'static str, Vec<(String, DataType)>)]) -> Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        let mut schemas = HashMap::new();
+        for (name, partition_cols) in tables {
+            let dir = Path::new(path).join(name);
+            let mut readable = Vec::new();
+            let mut unreadable = Vec::new();
+            readable_parquet_files(&dir, &mut readable, &mut unreadable)?;
+            for (file, e) in &unreadable {
+                eprintln!("Skipping unreadable {}: {}", file.display(), e);
+            }
+            if readable.is_empty() {
+                return Err(anyhow!("No readable files in {}", dir.display()));
+            }
+            let files: Vec<String> = readable
+                .iter()
+                .map(|f| string(&f.display().to_string()))
+                .collect();
+            let types = partition_cols
+                .iter()
+                .map(|(col, t)| Ok(format!("{}: {}", string(col), sql_type(t)?)))
+                .collect::<Result<Vec<_>>>()?;
+            // Optional columns may be missing from some files
+            conn.execute_batch(&format!(
+                "CREATE VIEW {name} AS SELECT * FROM read_parquet([{}], hive_partitioning = true, \
+                 hive_types = {{{}}}, union_by_name = true)",
+                files.join(", "),
+                types.join(", ")
+            ))
+            .with_context(|| format!("Failed to register {name} parquet"))?;
+            let schema = conn
+                .prepare(&format!("SELECT * FROM {name} LIMIT 0"))?
+                .query_arrow([])?
+                .get_schema();
+            schemas.insert(*name, schema);
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+            schemas,
+        })
+    }
+
+    fn has_column(&self, table: &str, column: &str) -> bool {
+        self.schemas
+            .get(table)
+            .is_some_and(|s| s.field_with_name(column).is_ok())
+    }
+
+    // Whether an optional list column is non-empty
+    fn non_empty(&self, column: &str, non_empty: bool) -> String {
+        if !self.has_column("accelerations", column) {
+            // Missing column means no entries at all
+            return if non_empty { "FALSE" } else { "TRUE" }.to_string();
+        }
+        let column = ident(column);
+        if non_empty {
+            format!("len({column}) > 0")
+        } else {
+            format!("({column} IS NULL OR len({column}) = 0)")
+        }
+    }
+
+    fn series_sql(filters: &Filters, page: Option<usize>, ids_only: bool) -> String {
+        let mut conditions = Vec::new();
+        if !filters.precisions.is_empty() {
+            conditions.push(in_list(&ident("precision"), strings(&filters.precisions)));
+        }
+        if !filters.base_series.is_empty() {
+            conditions.push(in_list(
+                &ident("series_name"),
+                strings(&filters.base_series),
+            ));
+        }
+        conditions.extend(params_conditions("arguments", &filters.series_params, None));
+
+        let mut sql = format!("SELECT * FROM series{}", where_clause(&conditions));
+        if let Some(n) = filters.sample {
+            sql = format!("{sql} ORDER BY \"series_id\" LIMIT {n}");
+        }
+        if let Some(page) = page {
+            sql = format!(
+                "SELECT * FROM ({sql}) ORDER BY \"series_id\" LIMIT {PAGE_SIZE} OFFSET {}",
+                page * PAGE_SIZE
+            );
+        }
+        if ids_only {
+            sql = format!("SELECT \"series_id\" FROM ({sql})");
+        }
+        sql
+    }
+
+    fn accelerations_sql(
+        &self,
+        series_ids: &[i32],
+        filters: &Filters,
+        param_methods: &HashMap<String, Vec<String>>,
+    ) -> String {
+        let mut conditions = vec![in_list(
+            &ident("series_id"),
+            series_ids.iter().map(i32::to_string),
+        )];
+        if !filters.base_accel.is_empty() {
+            conditions.push(in_list(&ident("accel_name"), strings(&filters.base_accel)));
+        }
+        if !filters.m_values.is_empty() {
+            conditions.push(in_list(
+                &ident("m_value"),
+                filters.m_values.iter().map(i32::to_string),
+            ));
+        }
+        conditions.extend(params_conditions(
+            "additional_args",
+            &filters.accel_params,
+            Some(param_methods),
+        ));
+        if let Some(has_errors) = filters.has_errors {
+            conditions.push(self.non_empty("errors", has_errors));
+        }
+        if let Some(has_events) = filters.has_events {
+            conditions.push(self.non_empty("events", has_events));
+        }
+        format!("SELECT * FROM accelerations{}", where_clause(&conditions))
+    }
+
+    fn sql(&self, query: &Query) -> String {
+        match *query {
+            Query::Table(table) => format!("SELECT * FROM {table}"),
+            Query::Distinct {
+                table,
+                column,
+                field: None,
+            } => format!("SELECT DISTINCT {} AS value FROM {table}", ident(column)),
+            Query::Distinct {
+                table,
+                column,
+                field: Some(name),
+            } => format!(
+                "SELECT DISTINCT {} AS value FROM {table}",
+                field(column, name)
+            ),
+            Query::EventNames => format!(
+                "SELECT DISTINCT {} AS value FROM (SELECT unnest(\"events\") AS event FROM accelerations)",
+                field("event", "name")
+            ),
+            Query::ParamMethods(param) => format!(
+                "SELECT DISTINCT {} AS value FROM accelerations WHERE {} IS NOT NULL",
+                ident("accel_name"),
+                field("additional_args", param)
+            ),
+            Query::Series {
+                filters,
+                page,
+                ids_only,
+            } => Self::series_sql(filters, page, ids_only),
+            Query::Accelerations {
+                series_ids,
+                filters,
+                param_methods,
+            } => self.accelerations_sql(series_ids, filters, param_methods),
+        }
+    }
+
+    fn run(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare(sql)
+            .with_context(|| format!("Failed to prepare `{sql}`"))?;
+        Ok(statement.query_arrow([])?.collect())
+    }
+
+    fn run_count(&self, sql: &str) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let count: i64 = conn
+            .query_row(&format!("SELECT count(*) FROM ({sql})"), [], |row| {
+                row.get(0)
+            })
+            .with_context(|| format!("Failed to count `{sql}`"))?;
+        Ok(count as usize)
+    }
+}
+
+// DuckDB is synchronous: the queries run when the futures are polled
+impl QueryEngine for DuckDbEngine {
+    fn schema<'a>(&'a self, table: &'a str) -> BoxFuture<'a, Result<SchemaRef>> {
+        Box::pin(async move {
+            self.schemas
+                .get(table)
+                .cloned()
+                .with_context(|| format!("No table {table}"))
+        })
+    }
+
+    fn collect<'a>(&'a self, query: &'a Query<'a>) -> BoxFuture<'a, Result<Vec<RecordBatch>>> {
+        Box::pin(async move { self.run(&self.sql(query)) })
+    }
+
+    fn count<'a>(&'a self, query: &'a Query<'a>) -> BoxFuture<'a, Result<usize>> {
+        Box::pin(async move { self.run_count(&self.sql(query)) })
+    }
+}
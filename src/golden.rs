@@ -0,0 +1,176 @@
+//! Golden-image tests of the panels (feature `golden`).
+//!
+//! Every panel that can be captured is drawn off-screen from the fixture below with
+//! [`render_panel`] and compared with `tests/golden/<panel id>.png`, which are committed. A missing
+//! image fails the test, so that a fresh checkout doesn't pass by comparing nothing; to create the
+//! images or accept intended changes, run with `VIZR_UPDATE_GOLDEN=1`. On a mismatch, or if the
+//! image is missing, the new one is written next to it as `<panel id>.actual.png`.
+
+use crate::app::{SeriesDataRef, Vis};
+use crate::data_loader::{
+    AccelInfo, AccelPoint, AccelRecord, ComplexNumber, SeriesData, SeriesPoint, SeriesRecord,
+    intern,
+};
+use crate::panel::PanelRegistry;
+use crate::render::render_panel;
+use crate::symlog::Scientific;
+use crate::workspace::Workspace;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const SIZE: [f32; 2] = [1200.0, 1000.0];
+// Channel difference up to which pixels are considered equal (antialiasing, font hinting)
+const CHANNEL_TOLERANCE: u8 = 16;
+// Share of differing pixels that still passes
+const MAX_DIFFERENT: f64 = 0.002;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn complex(real: f64) -> ComplexNumber {
+    ComplexNumber {
+        real: Scientific::from_f64(real),
+        imag: Scientific::default(),
+    }
+}
+
+// Series with known limits and two methods: one converging fast down to a plateau at the
+// roundoff, one stalling at a larger error
+fn fixture() -> Vec<SeriesData> {
+    let series = [
+        (
+            "geometric",
+            10.0,
+            (1..=40)
+                .map(|n| 10.0 * 0.9f64.powi(n + 1))
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "alternating_harmonic",
+            std::f64::consts::LN_2,
+            (1..=40).map(|n| 1.0 / (2.0 * n as f64)).collect(),
+        ),
+    ];
+    series
+        .into_iter()
+        .enumerate()
+        .map(|(id, (name, limit, deviations))| {
+            let computed = deviations
+                .iter()
+                .enumerate()
+                .map(|(i, d)| SeriesPoint {
+                    n: i as i32 + 1,
+                    value: complex(limit + d),
+                    deviation: Scientific::from_f64(*d),
+                })
+                .collect();
+            let accel = |method: &str, m_value: i32, deviation: &dyn Fn(usize, f64) -> f64| {
+                AccelRecord {
+                    accel_info: AccelInfo {
+                        name: intern(method),
+                        m_value,
+                        additional_args: HashMap::new(),
+                    },
+                    run: None,
+                    computed: deviations
+                        .iter()
+                        .enumerate()
+                        .map(|(i, d)| {
+                            // The first m points can't be accelerated yet
+                            (i >= m_value as usize).then(|| {
+                                let deviation = deviation(i, *d);
+                                AccelPoint {
+                                    value: complex(limit + deviation),
+                                    deviation: Scientific::from_f64(deviation),
+                                }
+                            })
+                        })
+                        .collect(),
+                    errors: Vec::new(),
+                    events: Vec::new(),
+                    reruns: 0,
+                    uncertainty: Vec::new(),
+                }
+            };
+            let records = vec![
+                accel("shanks", 1, &|_, d| (d * d).max(1e-16)),
+                accel("levin", 2, &|i, d| d / (i as f64 + 1.0).powi(2) + 1e-6),
+            ];
+            let series = SeriesRecord {
+                precision: intern("f64"),
+                series_id: id as i32,
+                name: intern(name),
+                arguments: HashMap::new(),
+                series_limit: complex(limit),
+                computed,
+                run: None,
+            };
+            (series, records)
+        })
+        .collect()
+}
+
+// Share of pixels differing by more than the tolerance, 1 if the sizes differ
+fn difference(a: &image::RgbaImage, b: &image::RgbaImage) -> f64 {
+    if a.dimensions() != b.dimensions() {
+        return 1.0;
+    }
+    let different = a
+        .pixels()
+        .zip(b.pixels())
+        .filter(|(a, b)| {
+            a.0.iter()
+                .zip(b.0)
+                .any(|(a, b)| a.abs_diff(b) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    different as f64 / (a.width() * a.height()).max(1) as f64
+}
+
+#[test]
+fn panels_match_golden_images() {
+    let update = std::env::var_os("VIZR_UPDATE_GOLDEN").is_some();
+    let dir = golden_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let data = fixture();
+    let refs: Vec<SeriesDataRef> = data.iter().map(|(s, a)| (s, a.iter().collect())).collect();
+    let mut failures = Vec::new();
+    for symlog in [true, false] {
+        for panel in PanelRegistry::with_builtin().build(&refs, symlog) {
+            let mut vis = Vis::new(Workspace::default(), String::new());
+            let Some(image) = render_panel(panel.as_ref(), &mut vis, SIZE, 1.0) else {
+                continue;
+            };
+            let name = if symlog {
+                panel.id().to_string()
+            } else {
+                format!("{}_linear", panel.id())
+            };
+            let path = dir.join(format!("{name}.png"));
+            if update {
+                image.save(&path).unwrap();
+                println!("Golden image written: {}", path.display());
+                continue;
+            }
+            if !path.exists() {
+                image.save(dir.join(format!("{name}.actual.png"))).unwrap();
+                failures.push(format!(
+                    "{name}: no golden image, run with VIZR_UPDATE_GOLDEN=1 to create it"
+                ));
+                continue;
+            }
+            let golden = image::open(&path).unwrap().to_rgba8();
+            let difference = difference(&golden, &image);
+            if difference > MAX_DIFFERENT {
+                image.save(dir.join(format!("{name}.actual.png"))).unwrap();
+                failures.push(format!(
+                    "{name}: {:.2}% of pixels differ",
+                    difference * 100.0
+                ));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,156 @@
+//! Which accel records count as one group in the aggregated views: the medians of the
+//! performance plot and the wins of the recommendations. Also series families: series whose
+//! names share a prefix, e.g. `zeta_s2` and `zeta_s3`, extracted by a user pattern so that the
+//! data producer doesn't have to store them
+
+use crate::app::{PlotOptions, format_method_name};
+use crate::data_loader::AccelInfo;
+use crate::legend::method_key;
+use eframe::egui;
+use egui::Ui;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+// Pattern the families are extracted by, and its compiled form; `None` if it doesn't compile.
+// Families are extracted when the filters and the panels are built, so this is set just before,
+// see `sync_family_pattern`
+static FAMILY_PATTERN: RwLock<(String, Option<Regex>)> = RwLock::new((String::new(), None));
+
+/// Makes `pattern` the one families are extracted by; returns whether it changed, in which case
+/// the filters and the panels need to be rebuilt
+pub fn sync_family_pattern(pattern: &str) -> bool {
+    let mut current = FAMILY_PATTERN.write().unwrap();
+    if current.0 == pattern {
+        return false;
+    }
+    *current = (pattern.to_string(), Regex::new(pattern).ok());
+    true
+}
+
+// Prefix of a series name matched by the pattern: its first capture group if it has one,
+// otherwise the whole match; the full name if it doesn't match or there's no pattern
+fn family_of<'a>(regex: Option<&Regex>, series_name: &'a str) -> &'a str {
+    regex
+        .and_then(|regex| regex.captures(series_name))
+        .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+        .map_or(series_name, |m| m.as_str())
+}
+
+/// Family of a series by the current pattern
+pub fn series_family(series_name: &str) -> String {
+    let pattern = FAMILY_PATTERN.read().unwrap();
+    family_of(pattern.1.as_ref(), series_name).to_string()
+}
+
+/// Dimensions that define a group. Variant names are stored in bookmarks and workspaces
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GroupKey {
+    #[default]
+    Method,
+    MethodM,
+    MethodParams,
+    /// Series whose names share a prefix, see `PlotOptions::family_pattern`
+    SeriesFamily,
+}
+
+impl GroupKey {
+    pub const ALL: [Self; 4] = [
+        Self::Method,
+        Self::MethodM,
+        Self::MethodParams,
+        Self::SeriesFamily,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Method => "Метод",
+            Self::MethodM => "Метод и m",
+            Self::MethodParams => "Метод, m и параметры",
+            Self::SeriesFamily => "Семейство рядов",
+        }
+    }
+}
+
+pub struct Group {
+    /// Equal for the records of one group; also picks its color
+    pub key: String,
+    pub label: String,
+}
+
+/// Group keys of the current options
+pub struct Grouping {
+    key: GroupKey,
+}
+
+impl Grouping {
+    pub fn new(opts: &PlotOptions) -> Self {
+        Self {
+            key: opts.group_key,
+        }
+    }
+
+    pub fn key(&self) -> GroupKey {
+        self.key
+    }
+
+    pub fn of(&self, series_name: &str, accel: &AccelInfo) -> Group {
+        let (key, label) = match self.key {
+            GroupKey::Method => (accel.name.to_string(), accel.name.to_string()),
+            GroupKey::MethodM => (
+                format!("{}\0{}", accel.name, accel.m_value),
+                format!("{} (m={})", accel.name, accel.m_value),
+            ),
+            GroupKey::MethodParams => (method_key(accel), format_method_name(accel)),
+            GroupKey::SeriesFamily => {
+                let family = series_family(series_name);
+                (family.clone(), family)
+            }
+        };
+        Group { key, label }
+    }
+}
+
+/// Group key selector, with the family pattern when it's in use
+pub fn grouping_ui(ui: &mut Ui, opts: &mut PlotOptions) {
+    ui.label("Группировать:");
+    egui::ComboBox::from_id_salt("group_key")
+        .selected_text(opts.group_key.label())
+        .show_ui(ui, |ui| {
+            for key in GroupKey::ALL {
+                ui.selectable_value(&mut opts.group_key, key, key.label());
+            }
+        });
+    if opts.group_key == GroupKey::SeriesFamily {
+        family_pattern_ui(ui, &mut opts.family_pattern);
+    }
+}
+
+/// Editor of the family pattern; it takes effect on the next frame, see `sync_family_pattern`
+pub fn family_pattern_ui(ui: &mut Ui, pattern: &mut String) {
+    let response = ui
+        .add(egui::TextEdit::singleline(pattern).desired_width(120.0))
+        .on_hover_text(
+            "Регулярное выражение для начала имени ряда; если в нём есть группа в скобках, \
+             семейство — её содержимое",
+        );
+    if let Err(e) = Regex::new(pattern) {
+        response.on_hover_text(e.to_string());
+        ui.colored_label(ui.visuals().error_fg_color, "Ошибка в выражении");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn family_is_the_matched_prefix() {
+        let family = |pattern: &str, name| family_of(Regex::new(pattern).ok().as_ref(), name);
+        assert_eq!(family("^[^_]+", "zeta_s2"), "zeta");
+        assert_eq!(family("^(.+)_s[0-9]+$", "bessel_j_s10"), "bessel_j");
+        // No match and a broken pattern both leave the name as it is
+        assert_eq!(family("^x", "zeta_s2"), "zeta_s2");
+        assert_eq!(family("(", "zeta_s2"), "zeta_s2");
+    }
+}
@@ -0,0 +1,80 @@
+use crate::data_loader::Filters;
+use eframe::egui;
+use egui::Ui;
+use std::time::{Duration, SystemTime};
+
+/// One executed top-level filter query.
+pub struct QueryRecord {
+    pub started: SystemTime,
+    pub filters: Filters,
+    pub duration: Duration,
+    // Number of loaded series or the error message
+    pub result: Result<usize, String>,
+}
+
+/// Queries executed during this session, newest last.
+#[derive(Default)]
+pub struct QueryHistory {
+    records: Vec<QueryRecord>,
+}
+
+// Wall-clock time of day, UTC
+fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+impl QueryHistory {
+    pub fn push(&mut self, record: QueryRecord) {
+        self.records.push(record);
+    }
+
+    /// Lists the queries; returns the filters of the query to re-run, if requested.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<Filters> {
+        let mut rerun = None;
+        if self.records.is_empty() {
+            ui.label("Запросов ещё не было");
+            return None;
+        }
+        if ui.button("Очистить").clicked() {
+            self.records.clear();
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("query_history")
+            .show(ui, |ui| {
+                egui::Grid::new("query_history_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("Время (UTC)").strong());
+                        ui.label(egui::RichText::new("Длительность").strong());
+                        ui.label(egui::RichText::new("Результат").strong());
+                        ui.label(egui::RichText::new("Фильтры").strong());
+                        ui.label("");
+                        ui.end_row();
+                        for record in self.records.iter().rev() {
+                            ui.label(format_time(record.started));
+                            ui.label(format!("{:.2} с", record.duration.as_secs_f64()));
+                            match &record.result {
+                                Ok(count) => ui.label(format!("{count} рядов")),
+                                Err(e) => ui
+                                    .colored_label(ui.visuals().error_fg_color, "ошибка")
+                                    .on_hover_text(e),
+                            };
+                            ui.add(egui::Label::new(record.filters.summary()).wrap());
+                            if ui.button("↻ Повторить").clicked() {
+                                rerun = Some(record.filters.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        rerun
+    }
+}
@@ -0,0 +1,618 @@
+use crate::app::{
+    SeriesDataRef, format_item_name, format_method_name, format_series_name,
+    format_series_name_with_args,
+};
+use crate::data_loader::{AccelInfo, IStr, SeriesRecord, fnv1a};
+use crate::grouping::series_family;
+use eframe::egui;
+use egui::{Color32, Ui};
+use egui_plot::{LineStyle, MarkerShape};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Series names in short names are cut to this many characters
+const SHORT_SERIES_NAME: usize = 24;
+
+/// How the plots list their lines
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum LegendMode {
+    /// egui_plot's own legend on top of the plot
+    #[default]
+    Flat,
+    BySeries,
+    ByMethod,
+}
+
+/// Property of a line that can be mapped to its color or style
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Dimension {
+    Precision,
+    Method,
+    Series,
+    // Series name prefix, see `grouping::series_family`
+    Family,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 4] = [
+        Dimension::Precision,
+        Dimension::Method,
+        Dimension::Series,
+        Dimension::Family,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Dimension::Precision => "Точность",
+            Dimension::Method => "Метод",
+            Dimension::Series => "Ряд",
+            Dimension::Family => "Семейство",
+        }
+    }
+}
+
+/// What a line of a series shows, see `LineVisibility`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineRole {
+    PartialSums,
+    Limit,
+    Accel,
+}
+
+impl LineRole {
+    const ALL: [LineRole; 3] = [LineRole::PartialSums, LineRole::Limit, LineRole::Accel];
+
+    fn label(self) -> &'static str {
+        match self {
+            LineRole::PartialSums => "Частичные суммы",
+            LineRole::Limit => "Предел",
+            LineRole::Accel => "Ускорения",
+        }
+    }
+}
+
+/// Plot line as listed in the grouped legend
+pub struct LegendEntry {
+    /// Full name, as in the flat legend and the tooltips
+    pub name: String,
+    /// Name without the parts shared by all loaded lines, see `CommonParts`
+    pub short: String,
+    // Series with precision and arguments; `None` for reference lines
+    series: Option<String>,
+    // Method with m and parameters; `None` for lines of the series itself
+    method: Option<String>,
+    // What the line shows, e.g. "мнимая часть"
+    detail: Option<&'static str>,
+    // Values of the dimensions, independent of the parameter order
+    precision: Option<IStr>,
+    series_key: Option<String>,
+    method_key: Option<String>,
+    series_label: Option<String>,
+    family: Option<String>,
+    role: Option<LineRole>,
+    // Imaginary part that is zero everywhere, see `zero`
+    zero: bool,
+}
+
+fn with_detail(name: String, detail: Option<&'static str>) -> String {
+    match detail {
+        Some(detail) => format!("{name} ({detail})"),
+        None => name,
+    }
+}
+
+/// Parts of the names that are the same for every loaded line
+pub struct CommonParts {
+    precision: Option<IStr>,
+    m_value: Option<i32>,
+    series_args: HashMap<IStr, IStr>,
+    accel_args: HashMap<IStr, IStr>,
+}
+
+// Arguments with the same value in every map
+fn common_args<'a>(mut maps: impl Iterator<Item = &'a HashMap<IStr, IStr>>) -> HashMap<IStr, IStr> {
+    let mut res = maps.next().cloned().unwrap_or_default();
+    for map in maps {
+        res.retain(|k, v| map.get(k) == Some(v));
+    }
+    res
+}
+
+// The value if all of them are equal
+fn common_value<T: PartialEq>(mut values: impl Iterator<Item = T>) -> Option<T> {
+    let first = values.next()?;
+    values.all(|v| v == first).then_some(first)
+}
+
+impl CommonParts {
+    pub fn of(data: &[SeriesDataRef]) -> Self {
+        let accels = || {
+            data.iter()
+                .flat_map(|(_, accel_records)| accel_records.iter().map(|a| &a.accel_info))
+        };
+        Self {
+            precision: common_value(data.iter().map(|(s, _)| &s.precision)).cloned(),
+            m_value: common_value(accels().map(|a| a.m_value)),
+            series_args: common_args(data.iter().map(|(s, _)| &s.arguments)),
+            accel_args: common_args(accels().map(|a| &a.additional_args)),
+        }
+    }
+
+    fn params(common: &HashMap<IStr, IStr>, args: &HashMap<IStr, IStr>) -> String {
+        let mut params: Vec<String> = args
+            .iter()
+            .filter(|(k, _)| !common.contains_key(*k))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        if params.is_empty() {
+            return String::new();
+        }
+        params.sort();
+        format!(" ({})", params.join(", "))
+    }
+
+    fn series_name(&self, series: &SeriesRecord) -> String {
+        let name = match series.name.char_indices().nth(SHORT_SERIES_NAME) {
+            Some((i, _)) => format!("{}…", &series.name[..i]),
+            None => series.name.to_string(),
+        };
+        format!(
+            "{name}{}",
+            Self::params(&self.series_args, &series.arguments)
+        )
+    }
+
+    fn precision(&self, series: &SeriesRecord) -> String {
+        match &self.precision {
+            Some(_) => String::new(),
+            None => format!("{} ", series.precision),
+        }
+    }
+
+    fn method_name(&self, accel: &AccelInfo) -> String {
+        let mut name = accel.name.to_string();
+        if self.m_value.is_none() {
+            name.push_str(&format!(" (m={})", accel.m_value));
+        }
+        name + &Self::params(&self.accel_args, &accel.additional_args)
+    }
+}
+
+impl LegendEntry {
+    /// Line of the series itself: partial sums, limit
+    pub fn series(series: &SeriesRecord, detail: &'static str, common: &CommonParts) -> Self {
+        let short = format!(
+            "{}{} ({detail})",
+            common.precision(series),
+            common.series_name(series)
+        );
+        let series_name = format_series_name_with_args(series);
+        Self {
+            name: format!("{series_name} ({detail})"),
+            short,
+            series: Some(series_name),
+            method: None,
+            detail: Some(detail),
+            precision: Some(series.precision.clone()),
+            series_key: Some(series_key(series)),
+            method_key: None,
+            series_label: Some(format_series_name(series)),
+            family: Some(series_family(&series.name)),
+            role: Some(LineRole::PartialSums),
+            zero: false,
+        }
+    }
+
+    /// Limit line of a series
+    pub fn limit(series: &SeriesRecord, detail: &'static str, common: &CommonParts) -> Self {
+        Self {
+            role: Some(LineRole::Limit),
+            ..Self::series(series, detail, common)
+        }
+    }
+
+    /// Line of an accel record
+    pub fn accel(
+        series: &SeriesRecord,
+        accel: &AccelInfo,
+        detail: Option<&'static str>,
+        common: &CommonParts,
+    ) -> Self {
+        let short = format!(
+            "{}{} {}",
+            common.precision(series),
+            common.method_name(accel),
+            common.series_name(series)
+        );
+        Self {
+            name: with_detail(format_item_name(series, accel), detail),
+            short: with_detail(short, detail),
+            series: Some(format_series_name_with_args(series)),
+            method: Some(format_method_name(accel)),
+            detail,
+            precision: Some(series.precision.clone()),
+            series_key: Some(series_key(series)),
+            method_key: Some(method_key(accel)),
+            series_label: Some(format_series_name(series)),
+            family: Some(series_family(&series.name)),
+            role: Some(LineRole::Accel),
+            zero: false,
+        }
+    }
+
+    /// Marks the line of a run with anomalies (see `anomaly`)
+    pub fn flagged(mut self, flagged: bool) -> Self {
+        if flagged {
+            self.name = format!("⚠ {}", self.name);
+            self.short = format!("⚠ {}", self.short);
+        }
+        self
+    }
+
+    /// Marks an imaginary part that is zero everywhere: it is hidden unless shown from the
+    /// grouped legend
+    pub fn zero(mut self, zero: bool) -> Self {
+        self.zero = zero;
+        self
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.zero
+    }
+
+    /// Line that belongs to no series, e.g. the unit roundoff
+    pub fn reference(name: String) -> Self {
+        Self {
+            short: name.clone(),
+            name,
+            series: None,
+            method: None,
+            detail: None,
+            precision: None,
+            series_key: None,
+            method_key: None,
+            series_label: None,
+            family: None,
+            role: None,
+            zero: false,
+        }
+    }
+
+    /// Value of the dimension as shown to the user
+    pub fn dimension_label(&self, dimension: Dimension) -> Option<String> {
+        match dimension {
+            Dimension::Precision => self.precision.as_ref().map(|p| p.to_string()),
+            Dimension::Method => self.method.clone(),
+            Dimension::Series => self.series_label.clone(),
+            Dimension::Family => self.family.clone(),
+        }
+    }
+
+    pub fn dimension(&self, dimension: Dimension) -> Option<&str> {
+        match dimension {
+            Dimension::Precision => self.precision.as_deref(),
+            Dimension::Method => self.method_key.as_deref(),
+            Dimension::Series => self.series_key.as_deref(),
+            Dimension::Family => self.family.as_deref(),
+        }
+    }
+
+    // Group header and the label under it
+    fn group(&self, mode: LegendMode) -> (&str, String) {
+        let (group, label) = match mode {
+            LegendMode::ByMethod => (
+                self.method
+                    .as_deref()
+                    .or(self.series.as_ref().map(|_| "Частичные суммы и пределы")),
+                self.series.clone(),
+            ),
+            _ => (self.series.as_deref(), self.method.clone()),
+        };
+        match (group, label) {
+            (Some(group), Some(label)) => (group, with_detail(label, self.detail)),
+            (Some(group), None) => (group, self.detail.unwrap_or_default().to_string()),
+            (None, _) => ("Прочее", self.name.clone()),
+        }
+    }
+}
+
+/// Full name of the line shown as `name`, for the hover label
+pub fn full_name<'a>(entries: &[(&'a LegendEntry, Color32)], name: &'a str) -> &'a str {
+    entries
+        .iter()
+        .find(|(entry, _)| entry.short == name)
+        .map_or(name, |(entry, _)| &entry.name)
+}
+
+fn sorted_params(args: &HashMap<IStr, IStr>) -> Vec<(&IStr, &IStr)> {
+    let mut params: Vec<_> = args.iter().collect();
+    params.sort();
+    params
+}
+
+pub fn method_key(accel: &AccelInfo) -> String {
+    let params = sorted_params(&accel.additional_args);
+    format!("{}\0{}\0{:?}", accel.name, accel.m_value, params)
+}
+
+// Series name and arguments, without the precision
+pub fn series_key(series: &SeriesRecord) -> String {
+    format!("{}\0{:?}", series.name, sorted_params(&series.arguments))
+}
+
+/// Color of a method configuration, see `value_color`
+pub fn method_color(accel: &AccelInfo) -> Color32 {
+    value_color(&method_key(accel))
+}
+
+/// Color from a hash of a dimension value, so it does not depend on what else is loaded and
+/// stays the same across refreshes, sessions and exports
+pub fn value_color(key: &str) -> Color32 {
+    let hash = fnv1a(key.as_bytes());
+    let hue = (hash & 0xffff) as f32 / 65536.0;
+    // Two brightness levels double the number of distinguishable colors
+    let value = if hash & 0x10000 == 0 { 0.5 } else { 0.7 };
+    egui::epaint::Hsva::new(hue, 0.85, value, 1.0).into()
+}
+
+/// Styles of the values of one dimension, numbered in sorted order of the values
+pub struct StyleIndex<'a> {
+    dimension: Option<Dimension>,
+    values: BTreeMap<&'a str, usize>,
+}
+
+impl<'a> StyleIndex<'a> {
+    pub fn new(
+        dimension: Option<Dimension>,
+        entries: impl IntoIterator<Item = &'a LegendEntry>,
+    ) -> Self {
+        let mut values: BTreeMap<&str, usize> = match dimension {
+            Some(dimension) => entries
+                .into_iter()
+                .filter_map(|e| e.dimension(dimension))
+                .map(|v| (v, 0))
+                .collect(),
+            None => BTreeMap::new(),
+        };
+        for (i, index) in values.values_mut().enumerate() {
+            *index = i;
+        }
+        Self { dimension, values }
+    }
+
+    // 0 for lines without a value, which are drawn as usual
+    fn index(&self, entry: &LegendEntry) -> usize {
+        self.dimension
+            .and_then(|d| entry.dimension(d))
+            .and_then(|v| self.values.get(v))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn line_style(&self, entry: &LegendEntry) -> LineStyle {
+        match self.index(entry) % 5 {
+            0 => LineStyle::Solid,
+            1 => LineStyle::dashed_dense(),
+            2 => LineStyle::dotted_dense(),
+            3 => LineStyle::dashed_loose(),
+            _ => LineStyle::dotted_loose(),
+        }
+    }
+
+    pub fn marker(&self, entry: &LegendEntry) -> MarkerShape {
+        const MARKERS: [MarkerShape; 6] = [
+            MarkerShape::Circle,
+            MarkerShape::Square,
+            MarkerShape::Diamond,
+            MarkerShape::Up,
+            MarkerShape::Cross,
+            MarkerShape::Asterisk,
+        ];
+        MARKERS[self.index(entry) % MARKERS.len()]
+    }
+}
+
+/// Color egui_plot would give to its `i`-th automatically colored item
+pub fn auto_color(i: usize) -> Color32 {
+    let golden_ratio = (5.0_f32.sqrt() - 1.0) / 2.0;
+    egui::epaint::Hsva::new(i as f32 * golden_ratio, 0.85, 0.5, 1.0).into()
+}
+
+/// Collapsible groups of lines with visibility checkboxes; hidden lines are kept by name, as are
+/// the lines that are zero everywhere (`LegendEntry::zero`) but shown anyway
+pub fn grouped_legend(
+    ui: &mut Ui,
+    plot_id: &str,
+    mode: LegendMode,
+    entries: &[(&LegendEntry, Color32)],
+    hidden: &mut HashSet<String>,
+    shown_zero: &mut HashSet<String>,
+) {
+    let mut groups: BTreeMap<&str, Vec<(String, &LegendEntry, Color32)>> = BTreeMap::new();
+    for &(entry, color) in entries {
+        let (group, label) = entry.group(mode);
+        groups.entry(group).or_default().push((label, entry, color));
+    }
+
+    for (group, items) in groups {
+        let id = ui.make_persistent_id((plot_id, group));
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+            .show_header(ui, |ui| {
+                // Hidden zero lines are left out, so they don't keep the group half-checked
+                let toggled: Vec<&LegendEntry> = items
+                    .iter()
+                    .map(|(_, entry, _)| *entry)
+                    .filter(|entry| !entry.zero || shown_zero.contains(&entry.name))
+                    .collect();
+                let shown = toggled
+                    .iter()
+                    .filter(|entry| !hidden.contains(&entry.name))
+                    .count();
+                let mut all = shown == toggled.len();
+                let checkbox = egui::Checkbox::new(&mut all, group)
+                    .indeterminate(shown > 0 && shown < toggled.len());
+                if ui.add(checkbox).changed() {
+                    for entry in toggled {
+                        if all {
+                            hidden.remove(&entry.name);
+                        } else {
+                            hidden.insert(entry.name.clone());
+                        }
+                    }
+                }
+            })
+            .body(|ui| {
+                for (label, entry, color) in &items {
+                    ui.horizontal(|ui| {
+                        let auto_hidden = entry.zero && !shown_zero.contains(&entry.name);
+                        let mut visible = !hidden.contains(&entry.name) && !auto_hidden;
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                        let color = if visible {
+                            *color
+                        } else {
+                            color.gamma_multiply(0.3)
+                        };
+                        ui.painter().rect_filled(rect.shrink(2.0), 2.0, color);
+                        let label = if entry.zero {
+                            format!("{label} — нулевая")
+                        } else {
+                            label.clone()
+                        };
+                        let checkbox = ui.checkbox(&mut visible, label).on_hover_text(&entry.name);
+                        if checkbox.changed() {
+                            if visible {
+                                hidden.remove(&entry.name);
+                                if entry.zero {
+                                    shown_zero.insert(entry.name.clone());
+                                }
+                            } else {
+                                hidden.insert(entry.name.clone());
+                            }
+                        }
+                        if entry.zero {
+                            checkbox.context_menu(|ui| {
+                                let mut show = shown_zero.contains(&entry.name);
+                                if ui
+                                    .checkbox(&mut show, "Показывать, хотя она нулевая")
+                                    .changed()
+                                {
+                                    if show {
+                                        shown_zero.insert(entry.name.clone());
+                                        hidden.remove(&entry.name);
+                                    } else {
+                                        shown_zero.remove(&entry.name);
+                                    }
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                    });
+                }
+            });
+    }
+}
+
+/// Lines switched off by series and kind or by method, on every plot at once. Applies on top of
+/// the global toggles of the plot options and the per-line checkboxes of the grouped legend
+#[derive(Default)]
+pub struct LineVisibility {
+    // Series key -> kinds of its lines that are hidden
+    hidden_parts: HashMap<String, HashSet<LineRole>>,
+    hidden_methods: HashSet<String>,
+}
+
+impl LineVisibility {
+    pub fn hides(&self, entry: &LegendEntry) -> bool {
+        let method = entry
+            .method_key
+            .as_ref()
+            .is_some_and(|m| self.hidden_methods.contains(m));
+        let part = match (&entry.series_key, entry.role) {
+            (Some(series), Some(role)) => self
+                .hidden_parts
+                .get(series)
+                .is_some_and(|roles| roles.contains(&role)),
+            _ => false,
+        };
+        method || part
+    }
+
+    /// Number of switched off toggles
+    pub fn hidden_count(&self) -> usize {
+        self.hidden_parts.values().map(HashSet::len).sum::<usize>() + self.hidden_methods.len()
+    }
+
+    /// Tree of checkboxes: series with the kinds of their lines, then methods
+    pub fn ui(&mut self, ui: &mut Ui, data: &[SeriesDataRef]) {
+        // Label -> key, in the order of the labels
+        let mut series = BTreeMap::new();
+        let mut methods = BTreeMap::new();
+        for (s, accel_records) in data {
+            series.insert(format_series_name(s), series_key(s));
+            for accel_record in accel_records {
+                let accel = &accel_record.accel_info;
+                methods.insert(format_method_name(accel), method_key(accel));
+            }
+        }
+
+        if ui.button("Показать все линии").clicked() {
+            *self = Self::default();
+        }
+        egui::ScrollArea::vertical()
+            .max_height(500.0)
+            .show(ui, |ui| {
+                ui.strong("Ряды");
+                for (label, key) in &series {
+                    let id = ui.make_persistent_id(("line_visibility", key));
+                    egui::collapsing_header::CollapsingState::load_with_default_open(
+                        ui.ctx(),
+                        id,
+                        false,
+                    )
+                    .show_header(ui, |ui| {
+                        let hidden = self.hidden_parts.get(key).map_or(0, HashSet::len);
+                        let mut all = hidden == 0;
+                        let checkbox = egui::Checkbox::new(&mut all, label.as_str())
+                            .indeterminate(hidden > 0 && hidden < LineRole::ALL.len());
+                        if ui.add(checkbox).changed() {
+                            if all {
+                                self.hidden_parts.remove(key);
+                            } else {
+                                self.hidden_parts
+                                    .insert(key.clone(), LineRole::ALL.into_iter().collect());
+                            }
+                        }
+                    })
+                    .body(|ui| {
+                        for role in LineRole::ALL {
+                            let roles = self.hidden_parts.entry(key.clone()).or_default();
+                            let mut visible = !roles.contains(&role);
+                            if ui.checkbox(&mut visible, role.label()).changed() {
+                                if visible {
+                                    roles.remove(&role);
+                                } else {
+                                    roles.insert(role);
+                                }
+                            }
+                        }
+                        if self.hidden_parts.get(key).is_some_and(HashSet::is_empty) {
+                            self.hidden_parts.remove(key);
+                        }
+                    });
+                }
+                ui.separator();
+                ui.strong("Методы");
+                for (label, key) in &methods {
+                    let mut visible = !self.hidden_methods.contains(key);
+                    if ui.checkbox(&mut visible, label.as_str()).changed() {
+                        if visible {
+                            self.hidden_methods.remove(key);
+                        } else {
+                            self.hidden_methods.insert(key.clone());
+                        }
+                    }
+                }
+            });
+    }
+}
@@ -1,43 +1,363 @@
 #![feature(type_alias_impl_trait)]
+mod accessibility;
+mod anomaly;
 mod app;
 mod data_loader;
+mod data_source;
+mod degradation;
+mod demo;
+mod diagnose;
+mod dual_axis;
+#[cfg(feature = "duckdb")]
+mod duckdb_engine;
+#[cfg(all(test, feature = "golden"))]
+mod golden;
+mod grouping;
+mod history;
+mod legend;
+mod metric;
+mod palette;
+mod panel;
+mod phase_plane;
+mod pivot;
+mod plot_data;
+mod recommend;
+mod remap;
+mod render;
+mod runs;
+mod schema_browser;
+mod significance;
+mod slope;
 mod symlog;
+mod timeline;
+mod ui_scale;
+mod vega;
+mod workspace;
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use eframe::egui;
+use eframe::egui_wgpu::WgpuConfiguration;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Renderer {
+    Glow,
+    Wgpu,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Engine {
+    #[value(name = "datafusion")]
+    DataFusion,
+    /// Needs a build with the `duckdb` feature
+    #[value(name = "duckdb")]
+    DuckDb,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render the figures listed in a TOML manifest to image files, without opening a window
+    Render {
+        /// Manifest with the data directory and the figures, see `render::Manifest`
+        manifest: PathBuf,
+    },
+    /// Write the series and accelerations matching the filters, as vizr parses them
+    Dump {
+        /// Path to the directory containing parquet files
+        data_dir: String,
+        /// JSON: `[series, accelerations]` pairs, numbers as `[mantissa, exponent]`, with the
+        /// record notes of the workspace
+        #[arg(long, required = true)]
+        json: bool,
+        /// JSON file with the filters, as in the `filters` of a render manifest (default: none)
+        #[arg(long)]
+        filters: Option<PathBuf>,
+        /// Output file (default: standard output)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Instead of the points, every metric of every acceleration, see `metric::Metric`
+        #[arg(long)]
+        stats: bool,
+        /// Tolerance 10^EXP of the iterations-to-tolerance metric
+        #[arg(long, value_name = "EXP", default_value_t = -10, allow_negative_numbers = true)]
+        tolerance_exp: i32,
+        /// Iteration of the error-at-n metric
+        #[arg(long, default_value_t = 10)]
+        n: i32,
+    },
+}
 
 #[derive(Parser)]
 #[command(name = "vizr")]
 #[command(about = "A high-performance parquet data visualizer")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the directory containing parquet files
-    data_dir: String,
-}
+    #[arg(required_unless_present = "demo")]
+    data_dir: Option<String>,
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    env_logger::init();
-    let args = Args::parse();
+    /// Synthetic series and accelerations computed in memory, instead of a data directory
+    #[arg(long, conflicts_with = "data_dir")]
+    demo: bool,
 
-    println!("Loading parquet data from: {}", args.data_dir);
-    let loader = data_loader::DataLoader::new(&args.data_dir).await?;
-    println!(
-        "Found {} precisions, {} series, {} accelerators",
-        loader.metadata.precisions.len(),
-        loader.metadata.series_names.len(),
-        loader.metadata.accel_names.len()
-    );
+    /// Workspace file with bookmarks (default: <data_dir>/.vizr-workspace.json)
+    #[arg(long)]
+    workspace: Option<PathBuf>,
 
-    // Запускаем GUI
-    let options = eframe::NativeOptions::default();
+    /// Preview mode: load only the first N series
+    #[arg(long, value_name = "N")]
+    sample: Option<usize>,
+
+    /// Query engine over the parquet files
+    #[arg(long, value_enum, default_value_t = Engine::DataFusion)]
+    engine: Engine,
+
+    /// Graphics backend
+    #[arg(long, value_enum, default_value_t = Renderer::Glow)]
+    renderer: Renderer,
+
+    /// Disable vertical sync
+    #[arg(long)]
+    no_vsync: bool,
 
+    /// Multisample anti-aliasing samples (0 to disable)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    msaa: u16,
+
+    /// Software rendering (OpenGL through Mesa llvmpipe), e.g. over X forwarding or in VMs.
+    /// Also used as a fallback when the selected backend fails to start
+    #[arg(long)]
+    software: bool,
+
+    /// Fixed window size in points, e.g. 1600x900, for screenshots of reproducible dimensions.
+    /// The saved window geometry is neither used nor updated then
+    #[arg(long, value_name = "WxH", value_parser = parse_window_size)]
+    window_size: Option<[f32; 2]>,
+}
+
+fn parse_window_size(s: &str) -> Result<[f32; 2], String> {
+    let (w, h) = s
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got `{s}`"))?;
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|v| *v > 0.0)
+            .ok_or_else(|| format!("invalid window dimension `{v}`"))
+    };
+    Ok([parse(w)?, parse(h)?])
+}
+
+fn native_options(args: &Args) -> eframe::NativeOptions {
+    let software = args.software;
+    let mut viewport = egui::ViewportBuilder::default().with_app_id("vizr");
+    if let Some(size) = args.window_size {
+        viewport = viewport.with_inner_size(size);
+    }
+    eframe::NativeOptions {
+        viewport,
+        // Size, position and maximized state are restored from the app data directory
+        persist_window: args.window_size.is_none(),
+        renderer: match args.renderer {
+            _ if software => eframe::Renderer::Glow,
+            Renderer::Glow => eframe::Renderer::Glow,
+            Renderer::Wgpu => eframe::Renderer::Wgpu,
+        },
+        hardware_acceleration: if software {
+            eframe::HardwareAcceleration::Off
+        } else {
+            eframe::HardwareAcceleration::Preferred
+        },
+        vsync: !args.no_vsync,
+        multisampling: args.msaa,
+        wgpu_options: WgpuConfiguration {
+            present_mode: if args.no_vsync {
+                eframe::wgpu::PresentMode::AutoNoVsync
+            } else {
+                eframe::wgpu::PresentMode::AutoVsync
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+// `started` is set once the window has its graphics context
+fn run(
+    options: eframe::NativeOptions,
+    loader: Arc<dyn data_source::DataSource>,
+    workspace: workspace::Workspace,
+    sample: Option<usize>,
+    started: &AtomicBool,
+) -> eframe::Result<()> {
     eframe::run_native(
         "Vizr - Parquet Data Visualizer",
         options,
-        Box::new(|_cc| {
-            Ok(Box::new(app::DashboardApp::new(Arc::new(loader))) as Box<dyn eframe::App>)
+        Box::new(move |cc| {
+            started.store(true, Ordering::Relaxed);
+            // Extra panels can be registered here, after the built-in ones
+            let panels = panel::PanelRegistry::with_builtin();
+            Ok(Box::new(app::DashboardApp::new(
+                cc.egui_ctx.clone(),
+                loader,
+                panels,
+                workspace,
+                sample,
+                cc.storage,
+            )) as Box<dyn eframe::App>)
         }),
     )
-    .map_err(|e| anyhow::anyhow!("GUI error: {}", e))?;
+}
+
+// `vizr dump`: every page of the matching series, in one list
+async fn dump(
+    data_dir: &str,
+    workspace: &std::path::Path,
+    filters: Option<&std::path::Path>,
+    output: Option<&std::path::Path>,
+    stats: Option<metric::MetricParams>,
+) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let filters: data_loader::Filters = match filters {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            serde_json::from_str(&text)
+                .with_context(|| format!("failed to parse {}", path.display()))?
+        }
+        None => data_loader::Filters::default(),
+    };
+    // Only read: a workspace that doesn't parse is an error rather than moved aside
+    let workspace = workspace::Workspace::load(workspace.to_path_buf())?;
+    let loader = data_loader::DataLoader::new(data_dir).await?;
+    let data = loader.filter_all(&filters).await?;
+
+    let refs: Vec<_> = data.iter().map(|(s, a)| (s, a.iter().collect())).collect();
+    let note = |series_id, accel_info: &_| workspace.record_note(series_id, accel_info);
+    let write = |writer: &mut dyn std::io::Write| match &stats {
+        Some(params) => metric::export_json(&refs, params, note, writer),
+        None => data_loader::export_json(&refs, note, writer),
+    };
+    match output {
+        Some(path) => {
+            let mut file = std::fs::File::create(path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            write(&mut file)?;
+            eprintln!("{} series written to {}", data.len(), path.display());
+        }
+        None => write(&mut std::io::stdout().lock())?,
+    }
     Ok(())
 }
+
+// Failures to get a graphics context, which software rendering may get past
+fn is_context_error(e: &eframe::Error) -> bool {
+    matches!(
+        e,
+        eframe::Error::Glutin(_)
+            | eframe::Error::NoGlutinConfigs(..)
+            | eframe::Error::OpenGL(_)
+            | eframe::Error::Wgpu(_)
+    )
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+    if args.software {
+        // SAFETY: the runtime is built below, there are no other threads yet
+        unsafe { std::env::set_var("LIBGL_ALWAYS_SOFTWARE", "1") };
+    }
+    let rt = tokio::runtime::Runtime::new()?;
+
+    if let Some(Command::Render { manifest }) = &args.command {
+        return rt.block_on(render::render_manifest(
+            manifest,
+            &panel::PanelRegistry::with_builtin(),
+        ));
+    }
+    if let Some(Command::Dump {
+        data_dir,
+        json: _,
+        filters,
+        output,
+        stats,
+        tolerance_exp,
+        n,
+    }) = &args.command
+    {
+        let stats = stats.then_some(metric::MetricParams {
+            tolerance_exp: *tolerance_exp,
+            n: *n,
+        });
+        let workspace = args
+            .workspace
+            .clone()
+            .unwrap_or_else(|| workspace::Workspace::default_path(data_dir));
+        return rt.block_on(dump(
+            data_dir,
+            &workspace,
+            filters.as_deref(),
+            output.as_deref(),
+            stats,
+        ));
+    }
+    let (loader, default_workspace): (Arc<dyn data_source::DataSource>, _) = if args.demo {
+        println!("Using synthetic demo data");
+        // Nothing to keep the workspace next to
+        (
+            Arc::new(demo::DemoSource::default()),
+            std::env::temp_dir().join("vizr-demo-workspace.json"),
+        )
+    } else {
+        // Required by clap without a subcommand or `--demo`
+        let data_dir = args.data_dir.clone().unwrap();
+        println!("Loading parquet data from: {}", data_dir);
+        // Metadata is computed by the GUI in the background
+        let engine = match args.engine {
+            Engine::DataFusion => data_loader::Engine::DataFusion,
+            Engine::DuckDb => data_loader::Engine::DuckDb,
+        };
+        (
+            Arc::new(rt.block_on(data_loader::DataLoader::with_engine(&data_dir, engine))?),
+            workspace::Workspace::default_path(&data_dir),
+        )
+    };
+
+    let workspace =
+        workspace::Workspace::load_or_reset(args.workspace.clone().unwrap_or(default_workspace))?;
+
+    // Запускаем GUI
+    let started = AtomicBool::new(false);
+    let res = run(
+        native_options(&args),
+        loader,
+        workspace,
+        args.sample,
+        &started,
+    );
+    match res {
+        // The window couldn't get a graphics context: try again without the GPU, in a new process
+        // so that the environment is set before any thread exists
+        Err(e) if !args.software && !started.load(Ordering::Relaxed) && is_context_error(&e) => {
+            eprintln!(
+                "Failed to start the GUI ({}), retrying with software rendering",
+                e
+            );
+            drop(rt);
+            let status = std::process::Command::new(std::env::current_exe()?)
+                .args(std::env::args_os().skip(1))
+                .arg("--software")
+                .status()?;
+            std::process::exit(status.code().unwrap_or(1));
+        }
+        res => res.map_err(|e| anyhow::anyhow!("GUI error: {}", e)),
+    }
+}
@@ -0,0 +1,225 @@
+//! Metrics of an accel record, defined once and shared by the performance plot, the pivot table,
+//! the runs plot, the recommendations, the records table and `vizr dump --stats`. A new metric
+//! only needs a variant here to show up in all of them
+
+use crate::app::{PlotOptions, RowStats, SeriesDataRef, magnitude};
+use crate::data_loader::AccelInfo;
+use crate::symlog::Scientific;
+use anyhow::Result;
+use eframe::egui;
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+/// Axis a metric is best plotted on
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scale {
+    /// Errors: symlog, or log10 where there's no symlog
+    Log,
+    /// Iteration counts
+    Linear,
+}
+
+#[derive(Clone, Copy)]
+pub enum MetricValue {
+    Error(Scientific),
+    Iterations(i32),
+}
+
+impl MetricValue {
+    /// Lower is better; errors by their order of magnitude, so that tiny ones don't underflow
+    pub fn score(self) -> f64 {
+        match self {
+            Self::Error(e) => magnitude(&e),
+            Self::Iterations(n) => n as f64,
+        }
+    }
+
+    pub fn format(self) -> String {
+        match self {
+            Self::Error(e) => e.format(),
+            Self::Iterations(n) => n.to_string(),
+        }
+    }
+
+    // With the given significant digits regardless of the display format, for exports
+    pub fn format_exact(self, digits: usize) -> String {
+        match self {
+            Self::Error(e) => e.format_exact(digits),
+            Self::Iterations(n) => n.to_string(),
+        }
+    }
+
+    fn to_json(self) -> Value {
+        match self {
+            Self::Error(e) => json!(e),
+            Self::Iterations(n) => json!(n),
+        }
+    }
+}
+
+/// What some metrics depend on, taken from the plot options
+#[derive(Clone, Copy, PartialEq)]
+pub struct MetricParams {
+    /// Tolerance 10^tolerance_exp of `Metric::IterationsToTolerance`
+    pub tolerance_exp: i32,
+    /// Iteration of `Metric::ErrorAt`
+    pub n: i32,
+}
+
+/// A number per accel record. Variant names are stored in bookmarks and workspaces
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Metric {
+    #[default]
+    MinError,
+    IterationOfMin,
+    FinalError,
+    MedianError,
+    P90Error,
+    ErrorAt,
+    IterationsToTolerance,
+}
+
+impl Metric {
+    pub const ALL: [Self; 7] = [
+        Self::MinError,
+        Self::IterationOfMin,
+        Self::FinalError,
+        Self::MedianError,
+        Self::P90Error,
+        Self::ErrorAt,
+        Self::IterationsToTolerance,
+    ];
+
+    /// Stable name for exports and plot ids
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::MinError => "min_error",
+            Self::IterationOfMin => "iteration_of_min",
+            Self::FinalError => "final_error",
+            Self::MedianError => "median_error",
+            Self::P90Error => "p90_error",
+            Self::ErrorAt => "error_at",
+            Self::IterationsToTolerance => "iterations_to_tolerance",
+        }
+    }
+
+    pub fn label(self, params: &MetricParams) -> String {
+        match self {
+            Self::MinError => "Минимальная ошибка".to_string(),
+            Self::IterationOfMin => "Итерация достижения минимальной ошибки".to_string(),
+            Self::FinalError => "Последняя ошибка".to_string(),
+            Self::MedianError => "Медиана ошибки".to_string(),
+            Self::P90Error => "P90 ошибки".to_string(),
+            Self::ErrorAt => format!("Ошибка при n = {}", params.n),
+            Self::IterationsToTolerance => format!("Итераций до 1e{}", params.tolerance_exp),
+        }
+    }
+
+    pub fn scale(self) -> Scale {
+        match self {
+            Self::IterationOfMin | Self::IterationsToTolerance => Scale::Linear,
+            _ => Scale::Log,
+        }
+    }
+
+    /// `None` if the record has no such value, e.g. never reaches the tolerance
+    pub fn of(self, stats: &RowStats, params: &MetricParams) -> Option<MetricValue> {
+        use MetricValue::{Error, Iterations};
+        match self {
+            Self::MinError => stats.min.map(Error),
+            Self::IterationOfMin => stats.iteration_of_min().map(Iterations),
+            Self::FinalError => stats.last.map(Error),
+            Self::MedianError => stats.median.map(Error),
+            Self::P90Error => stats.p90.map(Error),
+            Self::ErrorAt => stats.at(params.n).map(Error),
+            Self::IterationsToTolerance => {
+                stats.iterations_to(params.tolerance_exp).map(Iterations)
+            }
+        }
+    }
+
+    /// Cell text, with the reason a value is missing where there's one
+    pub fn format(self, value: Option<MetricValue>) -> String {
+        match (value, self) {
+            (Some(value), _) => value.format(),
+            (None, Self::IterationsToTolerance) => "не достигнут".to_string(),
+            (None, _) => "—".to_string(),
+        }
+    }
+}
+
+/// Metric selector, with the parameter of the chosen metric next to it
+pub fn metric_ui(ui: &mut Ui, id_salt: &str, metric: &mut Metric, opts: &mut PlotOptions) {
+    let params = opts.metric_params();
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(metric.label(&params))
+        .show_ui(ui, |ui| {
+            for m in Metric::ALL {
+                ui.selectable_value(metric, m, m.label(&params));
+            }
+        });
+    match metric {
+        Metric::IterationsToTolerance => {
+            ui.add(
+                egui::DragValue::new(&mut opts.tolerance_exp)
+                    .range(-1000..=10)
+                    .prefix("1e"),
+            );
+        }
+        Metric::ErrorAt => {
+            ui.add(
+                egui::DragValue::new(&mut opts.metric_n)
+                    .range(0..=i32::MAX)
+                    .prefix("n = "),
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Writes every metric of every accel record as a JSON list of objects, errors as
+/// `[mantissa, exponent]` and missing values as nulls, with the note of the record if any
+pub fn export_json<'a>(
+    data: &[SeriesDataRef],
+    params: &MetricParams,
+    note: impl Fn(i32, &AccelInfo) -> Option<&'a str>,
+    writer: impl std::io::Write,
+) -> Result<()> {
+    let mut records = Vec::new();
+    for (series, accel_records) in data {
+        for accel_record in accel_records {
+            let stats = RowStats::of(series, accel_record);
+            let metrics: Map<String, Value> = Metric::ALL
+                .into_iter()
+                .map(|metric| {
+                    let value = metric.of(&stats, params);
+                    (
+                        metric.id().to_string(),
+                        value.map_or(Value::Null, MetricValue::to_json),
+                    )
+                })
+                .collect();
+            let accel = &accel_record.accel_info;
+            let mut record = json!({
+                "series_id": series.series_id,
+                "precision": series.precision.to_string(),
+                "series": series.name.to_string(),
+                "method": accel.name.to_string(),
+                "m": accel.m_value,
+                "params": accel.additional_args,
+                "metrics": metrics,
+            });
+            if let Some(note) = note(series.series_id, accel) {
+                record["note"] = json!(note);
+            }
+            records.push(record);
+        }
+    }
+    let params = json!({ "tolerance_exp": params.tolerance_exp, "n": params.n });
+    serde_json::to_writer(
+        std::io::BufWriter::new(writer),
+        &json!({ "params": params, "records": records }),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! Command palette (Ctrl+P): any action of the dashboard found by typing a few letters of it
+
+use eframe::egui;
+use egui::{Context, Key, Modifiers};
+
+// Rows shown at once; typing narrows the rest down
+const MAX_SHOWN: usize = 12;
+
+/// Entry of the palette
+pub struct PaletteItem<A> {
+    pub label: String,
+    // Current state of toggles, shown as a checkbox
+    pub checked: Option<bool>,
+    pub action: A,
+}
+
+impl<A> PaletteItem<A> {
+    pub fn new(label: impl Into<String>, action: A) -> Self {
+        Self {
+            label: label.into(),
+            checked: None,
+            action,
+        }
+    }
+
+    pub fn toggle(label: impl Into<String>, checked: bool, action: A) -> Self {
+        Self {
+            checked: Some(checked),
+            ..Self::new(label, action)
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    // Highlighted row among the matches
+    selected: usize,
+}
+
+/// Score of `text` for a fuzzy `query`: its characters must appear in `text` in order, ignoring
+/// case. Runs of adjacent characters and matches at word starts score higher
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase);
+    let Some(mut wanted) = query.next() else {
+        return Some(0);
+    };
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut adjacent = false;
+    for c in text.chars() {
+        let word_start = previous.is_none_or(|p| !p.is_alphanumeric());
+        previous = Some(c);
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            score += 1 + if adjacent { 4 } else { 0 } + if word_start { 3 } else { 0 };
+            adjacent = true;
+            match query.next() {
+                Some(next) => wanted = next,
+                None => return Some(score),
+            }
+        } else {
+            adjacent = false;
+            score -= 1;
+        }
+    }
+    None
+}
+
+impl CommandPalette {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Opens or closes the palette on Ctrl+P
+    pub fn handle_shortcut(&mut self, ctx: &Context) {
+        if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::P)) {
+            self.open = !self.open;
+            self.query.clear();
+            self.selected = 0;
+        }
+    }
+
+    /// Shows the items matching the typed text; returns the action of the chosen one. The
+    /// palette closes once an item is chosen or on Escape
+    pub fn ui<A>(&mut self, ctx: &Context, items: Vec<PaletteItem<A>>) -> Option<A> {
+        if !self.open {
+            return None;
+        }
+        let mut matches: Vec<(i32, PaletteItem<A>)> = items
+            .into_iter()
+            .filter_map(|item| Some((fuzzy_score(&self.query, &item.label)?, item)))
+            .collect();
+        // Stable: equal scores keep the order of the items
+        matches.sort_by_key(|(score, _)| -score);
+        matches.truncate(MAX_SHOWN);
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+                i.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        });
+        if escape {
+            self.open = false;
+            return None;
+        }
+        if down {
+            self.selected += 1;
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let mut chosen = enter.then_some(self.selected);
+        egui::Window::new("Команды")
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .fixed_size([480.0, 0.0])
+            .show(ctx, |ui| {
+                let edit = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Команда, раздел, закладка или значение фильтра")
+                        .desired_width(f32::INFINITY),
+                );
+                edit.request_focus();
+                if edit.changed() {
+                    self.selected = 0;
+                }
+                ui.separator();
+                if matches.is_empty() {
+                    ui.weak("Ничего не найдено");
+                }
+                for (i, (_, item)) in matches.iter().enumerate() {
+                    let label = match item.checked {
+                        Some(true) => format!("☑ {}", item.label),
+                        Some(false) => format!("☐ {}", item.label),
+                        None => item.label.clone(),
+                    };
+                    let row = ui.selectable_label(i == self.selected, label);
+                    if row.clicked() {
+                        chosen = Some(i);
+                    }
+                    if row.hovered() {
+                        self.selected = i;
+                    }
+                }
+                ui.weak("↑↓ выбрать, Enter выполнить, Esc закрыть");
+            });
+
+        let (_, item) = matches.into_iter().nth(chosen?)?;
+        self.open = false;
+        Some(item.action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts() {
+        assert_eq!(fuzzy_score("", "Пределы"), Some(0));
+        assert!(fuzzy_score("чс", "Частичные суммы").is_some());
+        assert!(fuzzy_score("мч", "Частичные суммы").is_none());
+        let initials = fuzzy_score("чс", "Частичные суммы").unwrap();
+        let inside = fuzzy_score("чс", "Точность").unwrap_or(i32::MIN);
+        assert!(initials > inside);
+        assert!(
+            fuzzy_score("ПРЕД", "Пределы").unwrap() > fuzzy_score("пред", "Опции: предел").unwrap()
+        );
+    }
+}
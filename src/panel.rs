@@ -0,0 +1,79 @@
+use crate::app::{
+    AccelRecordsTablePanel, ConvergencePanel, ErrorPanel, PerformancePanel, SeriesDataRef, Vis,
+};
+use crate::degradation::DegradationPanel;
+use crate::dual_axis::DualAxisPanel;
+use crate::phase_plane::PhasePlanePanel;
+use crate::pivot::PivotPanel;
+use crate::recommend::RecommendPanel;
+use crate::runs::RunsPanel;
+use crate::timeline::TimelinePanel;
+use egui::{Rect, Ui};
+
+/// A collapsible view over the filtered data.
+///
+/// The built-in plots and the table implement this; specialized views (e.g. Shanks tables) can be
+/// added by implementing it and registering the type in a [`PanelRegistry`].
+pub trait PlotPanel {
+    /// Builds the panel from the data left after quick filtering.
+    fn build(data: &[SeriesDataRef], symlog: bool) -> Self
+    where
+        Self: Sized;
+
+    /// Stable identifier, used for egui ids and screenshot file names.
+    fn id(&self) -> &'static str;
+
+    /// Heading of the collapsible section.
+    fn title(&self) -> &str;
+
+    /// Renders the panel. Returns the area a screenshot should capture, if the panel supports it.
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect>;
+
+    /// Whether the panel shows acceleration records. Such panels are replaced by a notice when
+    /// the data has no accelerations table.
+    fn needs_accelerations(&self) -> bool {
+        true
+    }
+}
+
+pub type BuildPanel = fn(&[SeriesDataRef], bool) -> Box<dyn PlotPanel>;
+
+fn build_boxed<P: PlotPanel + 'static>(data: &[SeriesDataRef], symlog: bool) -> Box<dyn PlotPanel> {
+    Box::new(P::build(data, symlog))
+}
+
+/// Ordered list of panels shown under the quick filters.
+#[derive(Clone, Default)]
+pub struct PanelRegistry {
+    builders: Vec<BuildPanel>,
+}
+
+impl PanelRegistry {
+    /// Registry with the standard views.
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::default();
+        registry
+            .register::<ConvergencePanel>()
+            .register::<ErrorPanel>()
+            .register::<DualAxisPanel>()
+            .register::<PhasePlanePanel>()
+            .register::<PerformancePanel>()
+            .register::<DegradationPanel>()
+            .register::<RunsPanel>()
+            .register::<TimelinePanel>()
+            .register::<PivotPanel>()
+            .register::<RecommendPanel>()
+            .register::<AccelRecordsTablePanel>();
+        registry
+    }
+
+    /// Appends a panel; panels are displayed in registration order.
+    pub fn register<P: PlotPanel + 'static>(&mut self) -> &mut Self {
+        self.builders.push(build_boxed::<P>);
+        self
+    }
+
+    pub fn build(&self, data: &[SeriesDataRef], symlog: bool) -> Vec<Box<dyn PlotPanel>> {
+        self.builders.iter().map(|b| b(data, symlog)).collect()
+    }
+}
@@ -0,0 +1,177 @@
+use crate::accessibility::describe_plot;
+use crate::app::{
+    LineKey, SeriesDataRef, Vis, format_item_name, format_series_name_with_args, line_id,
+    plot_controls,
+};
+use crate::data_loader::ComplexNumber;
+use crate::legend::{method_color, series_key, value_color};
+use crate::panel::PlotPanel;
+use crate::symlog::format_f64;
+use eframe::egui;
+use egui::{Color32, Rect, Ui};
+use egui_plot::{Legend, Line, MarkerShape, Plot, PlotPoint, Points};
+
+// Limits of all series in one color, as on the convergence plot
+const LIMIT_COLOR: Color32 = Color32::from_rgb(255, 0, 0);
+
+/// Path of the partial sums or of an accel record through the complex plane, in order of n
+struct Trajectory {
+    name: String,
+    color: Color32,
+    points: Vec<PlotPoint>,
+    key: Option<LineKey>,
+}
+
+/// Imaginary against real part of the partial sums and of the accelerated values, with the limit
+/// of each series marked: oscillating convergence shows up as a spiral into the limit.
+///
+/// Only series with complex values are shown; real ones would be flat lines on the real axis.
+pub struct PhasePlanePanel {
+    trajectories: Vec<Trajectory>,
+    // Series name and limit
+    limits: Vec<(String, PlotPoint)>,
+}
+
+fn point(value: &ComplexNumber) -> PlotPoint {
+    PlotPoint::new(value.real.approx_f64(), value.imag.approx_f64())
+}
+
+impl PlotPanel for PhasePlanePanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        let mut trajectories = Vec::new();
+        let mut limits = Vec::new();
+        for (series, accel_records) in data {
+            let complex = series.series_limit.imag.0 != 0.0
+                || series.computed.iter().any(|c| c.value.imag.0 != 0.0)
+                || accel_records.iter().any(|accel_record| {
+                    accel_record
+                        .computed
+                        .iter()
+                        .any(|accel| accel.is_some_and(|ap| ap.value.imag.0 != 0.0))
+                });
+            if !complex || series.computed.is_empty() {
+                continue;
+            }
+            let series_name = format_series_name_with_args(series);
+            trajectories.push(Trajectory {
+                name: format!("частичные суммы {series_name}"),
+                color: value_color(&series_key(series)),
+                points: series.computed.iter().map(|c| point(&c.value)).collect(),
+                key: None,
+            });
+            limits.push((series_name, point(&series.series_limit)));
+            for accel_record in accel_records {
+                let points: Vec<PlotPoint> = accel_record
+                    .computed
+                    .iter()
+                    .filter_map(|accel| Some(point(&accel?.value)))
+                    .collect();
+                if points.is_empty() {
+                    continue;
+                }
+                trajectories.push(Trajectory {
+                    name: format_item_name(series, &accel_record.accel_info),
+                    color: method_color(&accel_record.accel_info),
+                    points,
+                    key: Some(LineKey::new(series, &accel_record.accel_info)),
+                });
+            }
+        }
+        Self {
+            trajectories,
+            limits,
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        "phase_plane"
+    }
+
+    fn title(&self) -> &str {
+        "Траектории на комплексной плоскости"
+    }
+
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect> {
+        if self.trajectories.is_empty() {
+            ui.label("Нет рядов с комплексными значениями");
+            return None;
+        }
+
+        let plot = Plot::new("phase_plane")
+            .allow_zoom(true)
+            .allow_drag(true)
+            .height(600.0)
+            // Circles around the limit stay circles
+            .data_aspect(1.0)
+            .x_axis_formatter(|mark, _| format_f64(mark.value))
+            .y_axis_formatter(|mark, _| format_f64(mark.value))
+            .label_formatter(|name, value| {
+                format!(
+                    "{name}\nRe={}\nIm={}",
+                    format_f64(value.x),
+                    format_f64(value.y)
+                )
+            })
+            .legend(Legend::default());
+        let plot = vis.axis_labels("phase_plane", plot, "Re", "Im");
+        let plot = vis.plot_wheel(plot);
+
+        let restore = vis.take_bounds("phase_plane");
+        let plot = plot.show(ui, |plot_ui| {
+            plot_controls(plot_ui);
+            if let Some(bounds) = restore {
+                plot_ui.set_plot_bounds(bounds);
+            }
+            for (i, trajectory) in self.trajectories.iter().enumerate() {
+                let selected = trajectory.key.as_ref().is_some_and(|k| vis.is_selected(k));
+                plot_ui.line(
+                    Line::new(trajectory.points.as_slice())
+                        .name(&trajectory.name)
+                        .id(line_id("phase_plane", i))
+                        .color(trajectory.color)
+                        .highlight(selected)
+                        .width(if selected { 3.0 } else { 1.0 }),
+                );
+                plot_ui.points(
+                    Points::new(trajectory.points.as_slice())
+                        .name(&trajectory.name)
+                        .color(trajectory.color)
+                        .shape(MarkerShape::Circle)
+                        .radius(2.0),
+                );
+            }
+            for (name, limit) in &self.limits {
+                plot_ui.points(
+                    Points::new(std::slice::from_ref(limit))
+                        .name(format!("предел {name}"))
+                        .color(LIMIT_COLOR)
+                        .shape(MarkerShape::Cross)
+                        .radius(8.0),
+                );
+            }
+            vis.draw_annotations("phase_plane", plot_ui, false, false);
+        });
+        vis.select_from_plot(&plot, |id| {
+            (0..self.trajectories.len())
+                .find(|i| line_id("phase_plane", i) == id)
+                .and_then(|i| self.trajectories[i].key.as_ref())
+        });
+        vis.annotation_menu("phase_plane", &plot, false, false);
+        describe_plot(
+            &plot.response,
+            format!(
+                "Мнимая часть против действительной, траекторий: {}, пределов: {}",
+                self.trajectories.len(),
+                self.limits.len()
+            ),
+        );
+        vis.track_hover(&plot.response);
+        vis.remember_bounds("phase_plane", plot.transform.bounds());
+        Some(plot.response.rect)
+    }
+
+    // Partial sums and limits are plotted on their own
+    fn needs_accelerations(&self) -> bool {
+        false
+    }
+}
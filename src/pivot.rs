@@ -0,0 +1,297 @@
+use crate::app::{LineKey, RowStats, SeriesDataRef, Vis, format_series_name_with_args};
+use crate::data_loader::AccelInfo;
+use crate::metric::{Metric, MetricParams, MetricValue, metric_ui};
+use crate::panel::PlotPanel;
+use crate::symlog::number_format;
+use anyhow::Result;
+use eframe::egui;
+use egui::{Color32, Rect, Ui};
+use std::collections::{BTreeMap, BTreeSet};
+
+struct PivotCell {
+    key: LineKey,
+    stats: RowStats,
+}
+
+/// Series as rows, acceleration method × m as columns, one metric per cell.
+pub struct PivotPanel {
+    rows: Vec<String>,
+    columns: Vec<String>,
+    // cells[row][column]; reruns of a configuration kept by `DuplicatePolicy::KeepAll` share a
+    // cell, which shows the best of them by the metric
+    cells: Vec<Vec<Vec<PivotCell>>>,
+}
+
+// Method name, m and parameters; parameters are sorted so that the label is stable
+fn column_name(accel: &AccelInfo) -> String {
+    let mut name = format!("{} (m={})", accel.name, accel.m_value);
+    if !accel.additional_args.is_empty() {
+        let mut params: Vec<String> = accel
+            .additional_args
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        params.sort();
+        name.push_str(&format!(" ({})", params.join(", ")));
+    }
+    name
+}
+
+impl PivotPanel {
+    // The best record of every cell of a row by the metric
+    fn best_of<'a>(
+        row: &'a [Vec<PivotCell>],
+        metric: Metric,
+        params: &MetricParams,
+    ) -> Vec<Option<&'a PivotCell>> {
+        let score = |c: &PivotCell| {
+            metric
+                .of(&c.stats, params)
+                .map_or(f64::INFINITY, MetricValue::score)
+        };
+        row.iter()
+            .map(|cell| cell.iter().min_by(|a, b| score(a).total_cmp(&score(b))))
+            .collect()
+    }
+
+    // Scores of a row, with the best of them and the range of the finite ones: an exact zero error
+    // scores -inf
+    fn row_scores(
+        row: &[Option<&PivotCell>],
+        metric: Metric,
+        params: &MetricParams,
+    ) -> (Vec<Option<f64>>, f64, (f64, f64)) {
+        let scores: Vec<Option<f64>> = row
+            .iter()
+            .map(|c| {
+                c.and_then(|c| metric.of(&c.stats, params))
+                    .map(MetricValue::score)
+            })
+            .collect();
+        let best = scores
+            .iter()
+            .flatten()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        let finite = scores.iter().flatten().copied().filter(|s| s.is_finite());
+        let range = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), s| {
+            (lo.min(s), hi.max(s))
+        });
+        (scores, best, range)
+    }
+
+    /// booktabs table; numbers are wrapped in siunitx `\num`, the best value of each row is bold
+    pub fn to_latex(&self, metric: Metric, params: &MetricParams) -> String {
+        let digits = number_format().digits;
+        let mut res = String::new();
+        res.push_str("% \\usepackage{booktabs, siunitx}\n");
+        res.push_str("% \\sisetup{detect-weight, mode=text}\n");
+        res.push_str(&format!(
+            "\\begin{{tabular}}{{l{}}}\n\\toprule\n",
+            "c".repeat(self.columns.len())
+        ));
+        res.push_str("Ряд");
+        for column in &self.columns {
+            res.push_str(" & ");
+            res.push_str(&latex_escape(column));
+        }
+        res.push_str(" \\\\\n\\midrule\n");
+        for (name, row) in self.rows.iter().zip(&self.cells) {
+            let row = Self::best_of(row, metric, params);
+            let (scores, best, _) = Self::row_scores(&row, metric, params);
+            res.push_str(&latex_escape(name));
+            for (cell, score) in row.iter().zip(&scores) {
+                res.push_str(" & ");
+                let value = cell
+                    .and_then(|cell| metric.of(&cell.stats, params))
+                    .map(|value| value.format_exact(digits));
+                match value {
+                    Some(value) if *score == Some(best) => {
+                        res.push_str(&format!("\\textbf{{\\num{{{value}}}}}"))
+                    }
+                    Some(value) => res.push_str(&format!("\\num{{{value}}}")),
+                    None => res.push_str("--"),
+                }
+            }
+            res.push_str(" \\\\\n");
+        }
+        res.push_str("\\bottomrule\n\\end{tabular}\n");
+        res
+    }
+
+    fn save_latex(&self, latex: &str) -> Result<()> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let filename = format!("pivot_{}.tex", timestamp);
+        std::fs::write(&filename, latex)?;
+        println!("LaTeX table saved: {}", filename);
+        Ok(())
+    }
+}
+
+impl PlotPanel for PivotPanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        let columns: Vec<String> = data
+            .iter()
+            .flat_map(|(_, accel_records)| accel_records.iter())
+            .map(|a| column_name(&a.accel_info))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let column_index: BTreeMap<&str, usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.as_str(), i))
+            .collect();
+
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        for (series, accel_records) in data {
+            if accel_records.is_empty() {
+                continue;
+            }
+            let mut row: Vec<Vec<PivotCell>> = (0..columns.len()).map(|_| Vec::new()).collect();
+            for accel_record in accel_records {
+                let i = column_index[column_name(&accel_record.accel_info).as_str()];
+                row[i].push(PivotCell {
+                    key: LineKey::new(series, &accel_record.accel_info),
+                    stats: RowStats::of(series, accel_record),
+                });
+            }
+            rows.push(format_series_name_with_args(series));
+            cells.push(row);
+        }
+
+        Self {
+            rows,
+            columns,
+            cells,
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        "pivot"
+    }
+
+    fn title(&self) -> &str {
+        "Сводная таблица"
+    }
+
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect> {
+        if self.rows.is_empty() {
+            ui.label("Нет данных для отображения");
+            return None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Метрика:");
+            let mut metric = vis.opts.pivot_metric;
+            metric_ui(ui, "pivot_metric", &mut metric, &mut vis.opts);
+            vis.opts.pivot_metric = metric;
+        });
+        let metric = vis.opts.pivot_metric;
+        let params = vis.opts.metric_params();
+        ui.horizontal(|ui| {
+            if ui
+                .button("📋 LaTeX")
+                .on_hover_text("Копировать как booktabs")
+                .clicked()
+            {
+                ui.ctx().copy_text(self.to_latex(metric, &params));
+            }
+            if ui.button("💾 Сохранить .tex").clicked()
+                && let Err(e) = self.save_latex(&self.to_latex(metric, &params))
+            {
+                eprintln!("Failed to save LaTeX table: {}", e);
+            }
+        });
+
+        let response = egui::ScrollArea::horizontal()
+            .id_salt("pivot_scroll")
+            .show(ui, |ui| {
+                egui::Grid::new("pivot_table")
+                    .striped(true)
+                    .spacing(egui::vec2(12.0, 6.0))
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("Ряд").strong());
+                        for column in &self.columns {
+                            ui.label(egui::RichText::new(column).strong());
+                        }
+                        ui.end_row();
+
+                        for (name, records) in self.rows.iter().zip(&self.cells) {
+                            ui.label(name);
+                            // Color by position between the best and the worst value of the row
+                            let row = Self::best_of(records, metric, &params);
+                            let (scores, best, (lo, hi)) = Self::row_scores(&row, metric, &params);
+                            for ((cell, records), score) in row.iter().zip(records).zip(&scores) {
+                                let Some(cell) = cell else {
+                                    ui.label("");
+                                    continue;
+                                };
+                                let mut label = metric.format(metric.of(&cell.stats, &params));
+                                if records.len() > 1 {
+                                    label.push_str(&format!(" (×{})", records.len()));
+                                }
+                                let mut text = egui::RichText::new(label);
+                                if let Some(score) = score {
+                                    let t = if *score == f64::NEG_INFINITY {
+                                        0.0
+                                    } else if *score == f64::INFINITY {
+                                        1.0
+                                    } else if hi > lo {
+                                        ((score - lo) / (hi - lo)) as f32
+                                    } else {
+                                        0.0
+                                    };
+                                    text = text.background_color(Color32::from_rgba_unmultiplied(
+                                        (255.0 * t) as u8,
+                                        (255.0 * (1.0 - t)) as u8,
+                                        0,
+                                        70,
+                                    ));
+                                    if *score == best {
+                                        text = text.strong();
+                                    }
+                                }
+                                if vis.is_selected(&cell.key) {
+                                    text = text.underline();
+                                }
+                                let mut response =
+                                    ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                                if records.len() > 1 {
+                                    response = response.on_hover_text(format!(
+                                        "{} запусков этой конфигурации, показан лучший",
+                                        records.len()
+                                    ));
+                                }
+                                if response.clicked() {
+                                    let toggle = ui.input(|i| i.modifiers.command);
+                                    vis.click_select(&cell.key, toggle);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        Some(response.inner_rect)
+    }
+}
+
+fn latex_escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                res.push('\\');
+                res.push(c);
+            }
+            '~' => res.push_str("\\textasciitilde{}"),
+            '^' => res.push_str("\\textasciicircum{}"),
+            '\\' => res.push_str("\\textbackslash{}"),
+            c => res.push(c),
+        }
+    }
+    res
+}
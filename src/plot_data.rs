@@ -0,0 +1,174 @@
+//! Points behind a plot as a table, each y value as loaded and as plotted, e.g. to check what the
+//! symlog transform did to a suspicious point
+
+use crate::app::{PlotOptions, SeriesDataRef, format_item_name, format_series_name_with_args};
+use crate::symlog::Scientific;
+use eframe::egui;
+use egui::Context;
+
+// Significant digits of the loaded values; all that an f64 mantissa holds
+const RAW_DIGITS: usize = 17;
+
+struct Row {
+    line: String,
+    n: i32,
+    raw: Scientific,
+    // `None` if the underflow policy drops the point
+    plotted: Option<f64>,
+}
+
+/// Panels whose points can be listed, see `PlotDataWindow::new`
+pub fn supports(panel_id: &str) -> bool {
+    matches!(panel_id, "convergence" | "error" | "performance")
+}
+
+fn rows(panel_id: &str, data: &[SeriesDataRef], symlog: bool, opts: &PlotOptions) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for (series, accel_records) in data {
+        if opts.show_partial_sums && panel_id != "performance" {
+            let line = format!("{} (частичные суммы)", format_series_name_with_args(series));
+            for c in series.computed.iter() {
+                let raw = match panel_id {
+                    "convergence" => c.value.real,
+                    _ => c.deviation,
+                };
+                let plotted = match panel_id {
+                    "convergence" => Some(raw.approx_f64()),
+                    _ => opts.deviation_y(raw, symlog),
+                };
+                rows.push(Row {
+                    line: line.clone(),
+                    n: c.n,
+                    raw,
+                    plotted,
+                });
+            }
+        }
+        for accel_record in accel_records {
+            let line = format_item_name(series, &accel_record.accel_info);
+            let points = series
+                .computed
+                .iter()
+                .zip(accel_record.computed.iter())
+                .filter_map(|(c, accel)| Some((c.n, accel?)));
+            match panel_id {
+                "convergence" => rows.extend(points.map(|(n, accel)| Row {
+                    line: line.clone(),
+                    n,
+                    raw: accel.value.real,
+                    plotted: Some(accel.value.real.approx_f64()),
+                })),
+                "error" => rows.extend(points.map(|(n, accel)| Row {
+                    line: line.clone(),
+                    n,
+                    raw: accel.deviation,
+                    plotted: opts.deviation_y(accel.deviation, symlog),
+                })),
+                // The point of the minimal error, as on the plot
+                _ => {
+                    let min = points.reduce(|min, point| {
+                        if point.1.deviation < min.1.deviation {
+                            point
+                        } else {
+                            min
+                        }
+                    });
+                    if let Some((n, accel)) = min {
+                        rows.push(Row {
+                            line,
+                            n,
+                            raw: accel.deviation,
+                            plotted: opts.deviation_y(accel.deviation, symlog),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// Window with the points of one plot, taken when it was opened
+pub struct PlotDataWindow {
+    title: String,
+    rows: Vec<Row>,
+    // Text the line names are narrowed by
+    search: String,
+}
+
+impl PlotDataWindow {
+    /// `None` for panels that aren't `supports`ed
+    pub fn new(
+        panel_id: &str,
+        title: &str,
+        data: &[SeriesDataRef],
+        symlog: bool,
+        opts: &PlotOptions,
+    ) -> Option<Self> {
+        supports(panel_id).then(|| Self {
+            title: format!("Данные: {title}"),
+            rows: rows(panel_id, data, symlog, opts),
+            search: String::new(),
+        })
+    }
+
+    /// Shows the window; returns false once it's closed
+    pub fn ui(&mut self, ctx: &Context) -> bool {
+        let mut open = true;
+        egui::Window::new(&self.title)
+            .open(&mut open)
+            .default_size([720.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Линия:");
+                    ui.text_edit_singleline(&mut self.search);
+                });
+                let search = self.search.to_lowercase();
+                let rows: Vec<&Row> = self
+                    .rows
+                    .iter()
+                    .filter(|row| search.is_empty() || row.line.to_lowercase().contains(&search))
+                    .collect();
+                ui.label(format!("Точек: {}", rows.len()));
+                ui.separator();
+
+                let widths = [320.0, 50.0, 180.0, 120.0];
+                let row_ui = |ui: &mut egui::Ui, cells: [egui::WidgetText; 4]| {
+                    ui.horizontal(|ui| {
+                        for (cell, width) in cells.into_iter().zip(widths) {
+                            ui.add_sized(
+                                [width, ui.spacing().interact_size.y],
+                                egui::Label::new(cell).truncate(),
+                            );
+                        }
+                    });
+                };
+                row_ui(
+                    ui,
+                    ["Линия", "n", "Значение", "На графике"]
+                        .map(|title| egui::RichText::new(title).strong().into()),
+                );
+                let height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y;
+                egui::ScrollArea::vertical()
+                    .id_salt("plot_data_rows")
+                    .show_rows(ui, height, rows.len(), |ui, range| {
+                        for row in &rows[range] {
+                            row_ui(
+                                ui,
+                                [
+                                    row.line.clone().into(),
+                                    row.n.to_string().into(),
+                                    row.raw.format_exact(RAW_DIGITS).into(),
+                                    row.plotted
+                                        .map_or("не показана".to_string(), |y| {
+                                            format!("{y:.6}")
+                                        })
+                                        .into(),
+                                ],
+                            );
+                        }
+                    });
+            });
+        open
+    }
+}
@@ -0,0 +1,261 @@
+use crate::app::{LineKey, RowStats, SeriesDataRef, Vis, format_series_name_with_args};
+use crate::data_loader::{AccelInfo, IStr};
+use crate::grouping::{GroupKey, Grouping, grouping_ui, series_family};
+use crate::metric::{Metric, MetricParams, MetricValue, metric_ui};
+use crate::panel::PlotPanel;
+use crate::symlog::number_format;
+use anyhow::Result;
+use eframe::egui;
+use egui::{Rect, Ui};
+use std::collections::BTreeMap;
+
+struct Candidate {
+    accel_info: AccelInfo,
+    stats: RowStats,
+}
+
+struct SeriesCandidates {
+    series_id: i32,
+    precision: String,
+    name: String,
+    // Without the arguments, for `GroupKey::SeriesFamily`
+    base_name: IStr,
+    candidates: Vec<Candidate>,
+}
+
+/// Best configuration by the criterion, with the number of configurations it beat
+struct Recommendation<'a> {
+    series: &'a SeriesCandidates,
+    best: &'a Candidate,
+    value: String,
+    rivals: usize,
+}
+
+/// The single best (method, m, parameters) of every filtered series.
+pub struct RecommendPanel {
+    series: Vec<SeriesCandidates>,
+}
+
+// Parameters sorted by name, so that exported rows are stable
+fn params(accel: &AccelInfo) -> String {
+    let mut params: Vec<String> = accel
+        .additional_args
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    params.sort();
+    params.join(", ")
+}
+
+fn csv_escape(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl RecommendPanel {
+    fn recommendations(
+        &self,
+        criterion: Metric,
+        metric_params: &MetricParams,
+    ) -> Vec<Recommendation<'_>> {
+        let digits = number_format().digits;
+        self.series
+            .iter()
+            .filter_map(|series| {
+                let scored: Vec<(&Candidate, MetricValue)> = series
+                    .candidates
+                    .iter()
+                    .filter_map(|c| Some((c, criterion.of(&c.stats, metric_params)?)))
+                    .collect();
+                // The first of equal ones wins, so the choice doesn't flicker between frames
+                let (best, value) = scored
+                    .iter()
+                    .copied()
+                    .reduce(|a, b| if b.1.score() < a.1.score() { b } else { a })?;
+                Some(Recommendation {
+                    series,
+                    best,
+                    value: value.format_exact(digits),
+                    rivals: scored.len() - 1,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_csv(&self, criterion: Metric, metric_params: &MetricParams) -> String {
+        let mut res = String::from("series_id,precision,series,method,m,params,value,rivals\n");
+        for r in self.recommendations(criterion, metric_params) {
+            let accel = &r.best.accel_info;
+            let fields = [
+                r.series.series_id.to_string(),
+                r.series.precision.clone(),
+                r.series.name.clone(),
+                accel.name.to_string(),
+                accel.m_value.to_string(),
+                params(accel),
+                r.value,
+                r.rivals.to_string(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+            res.push_str(&fields.join(","));
+            res.push('\n');
+        }
+        res
+    }
+
+    fn save_csv(&self, csv: &str) -> Result<()> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let filename = format!("recommendations_{}.csv", timestamp);
+        std::fs::write(&filename, csv)?;
+        println!("Recommendations saved: {}", filename);
+        Ok(())
+    }
+}
+
+impl PlotPanel for RecommendPanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        let series = data
+            .iter()
+            .filter(|(_, accel_records)| !accel_records.is_empty())
+            .map(|(series, accel_records)| SeriesCandidates {
+                series_id: series.series_id,
+                precision: series.precision.to_string(),
+                name: format_series_name_with_args(series),
+                base_name: series.name.clone(),
+                candidates: accel_records
+                    .iter()
+                    .map(|a| Candidate {
+                        accel_info: a.accel_info.clone(),
+                        stats: RowStats::of(series, a),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self { series }
+    }
+
+    fn id(&self) -> &'static str {
+        "recommend"
+    }
+
+    fn title(&self) -> &str {
+        "Лучшая конфигурация для ряда"
+    }
+
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect> {
+        if self.series.is_empty() {
+            ui.label("Нет данных для отображения");
+            return None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Критерий:");
+            let mut criterion = vis.opts.recommend_criterion;
+            metric_ui(ui, "recommend_criterion", &mut criterion, &mut vis.opts);
+            vis.opts.recommend_criterion = criterion;
+        });
+        let criterion = vis.opts.recommend_criterion;
+        let metric_params = vis.opts.metric_params();
+        ui.horizontal(|ui| {
+            if ui.button("📋 CSV").clicked() {
+                ui.ctx().copy_text(self.to_csv(criterion, &metric_params));
+            }
+            if ui.button("💾 Сохранить .csv").clicked()
+                && let Err(e) = self.save_csv(&self.to_csv(criterion, &metric_params))
+            {
+                eprintln!("Failed to save recommendations: {}", e);
+            }
+        });
+
+        ui.horizontal(|ui| grouping_ui(ui, &mut vis.opts));
+        let grouping = Grouping::new(&vis.opts);
+
+        let recommendations = self.recommendations(criterion, &metric_params);
+        // How often each group wins, to answer "which one to use for this family"; grouped by
+        // family, how often each method wins within it
+        let mut wins = BTreeMap::<String, BTreeMap<String, usize>>::new();
+        for r in &recommendations {
+            let accel = &r.best.accel_info;
+            let (family, winner) = match grouping.key() {
+                GroupKey::SeriesFamily => {
+                    (series_family(&r.series.base_name), accel.name.to_string())
+                }
+                _ => (String::new(), grouping.of(&r.series.base_name, accel).label),
+            };
+            *wins.entry(family).or_default().entry(winner).or_default() += 1;
+        }
+        let wins: Vec<(String, String)> = wins
+            .into_iter()
+            .map(|(family, wins)| {
+                let mut wins: Vec<_> = wins.into_iter().collect();
+                wins.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+                let wins: Vec<String> = wins
+                    .iter()
+                    .map(|(name, count)| format!("{name}: {count}"))
+                    .collect();
+                (family, wins.join(", "))
+            })
+            .collect();
+        let summary = format!(
+            "Лучший метод в {} из {} рядов",
+            recommendations.len(),
+            self.series.len()
+        );
+        match wins.as_slice() {
+            [(family, wins)] if family.is_empty() => {
+                ui.label(format!("{summary} — {wins}"));
+            }
+            _ => {
+                ui.label(summary);
+                for (family, wins) in &wins {
+                    ui.label(format!("{family}: {wins}"));
+                }
+            }
+        }
+
+        let response = egui::ScrollArea::horizontal()
+            .id_salt("recommend_scroll")
+            .show(ui, |ui| {
+                egui::Grid::new("recommend_table")
+                    .striped(true)
+                    .spacing(egui::vec2(12.0, 6.0))
+                    .show(ui, |ui| {
+                        for title in ["Ряд", "Метод", "M", "Параметры", "Значение", "Сравнено"]
+                        {
+                            ui.label(egui::RichText::new(title).strong());
+                        }
+                        ui.end_row();
+
+                        for r in &recommendations {
+                            let accel = &r.best.accel_info;
+                            let key = LineKey {
+                                series_id: r.series.series_id,
+                                accel_info: accel.clone(),
+                            };
+                            ui.label(&r.series.name);
+                            let mut method = egui::RichText::new(accel.name.as_ref()).strong();
+                            if vis.is_selected(&key) {
+                                method = method.underline();
+                            }
+                            let response =
+                                ui.add(egui::Label::new(method).sense(egui::Sense::click()));
+                            if response.clicked() {
+                                let toggle = ui.input(|i| i.modifiers.command);
+                                vis.click_select(&key, toggle);
+                            }
+                            ui.label(accel.m_value.to_string());
+                            ui.label(params(accel));
+                            ui.label(&r.value);
+                            ui.label(format!("с {} другими", r.rivals));
+                            ui.end_row();
+                        }
+                    });
+            });
+        Some(response.inner_rect)
+    }
+}
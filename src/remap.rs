@@ -0,0 +1,274 @@
+//! Filter values of a bookmark that the data no longer has, e.g. after a method was renamed from
+//! `wynn_eps` to `wynn-epsilon`, with the closest existing values offered as replacements
+
+use crate::data_loader::{Filters, IStr, Metadata, NO_EVENTS, NOT_SET, intern};
+use crate::workspace::Bookmark;
+use eframe::egui;
+use egui::{Context, Id};
+use std::collections::{HashMap, HashSet};
+
+// Suggestions less similar than this (1 is equal, 0 shares nothing) are not offered
+const MIN_SIMILARITY: f64 = 0.4;
+
+/// Edits turning one string into the other, by characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// Case and the choice of separator don't count as differences
+fn normalized(text: &str) -> String {
+    text.to_lowercase().replace(['-', ' '], "_")
+}
+
+/// Most similar of the candidates, if any is similar enough
+pub fn closest<'a>(value: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let value = normalized(value);
+    candidates
+        .into_iter()
+        .map(|candidate| {
+            let other = normalized(candidate);
+            let longest = value.chars().count().max(other.chars().count()).max(1);
+            let similarity = 1.0 - levenshtein(&value, &other) as f64 / longest as f64;
+            (candidate, similarity)
+        })
+        .filter(|(_, similarity)| *similarity >= MIN_SIMILARITY)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, _)| candidate)
+}
+
+/// Part of the filters a value belongs to
+#[derive(Clone, PartialEq)]
+pub enum FilterField {
+    Precision,
+    Series,
+    Accel,
+    Event,
+    SeriesParam(IStr),
+    AccelParam(IStr),
+}
+
+impl FilterField {
+    // As in the headings of the quick filters
+    fn label(&self) -> String {
+        match self {
+            Self::Precision => "prec".to_string(),
+            Self::Series => "series".to_string(),
+            Self::Accel => "accel".to_string(),
+            Self::Event => "события".to_string(),
+            Self::SeriesParam(key) | Self::AccelParam(key) => key.to_string(),
+        }
+    }
+
+    fn values_mut<'a>(&self, filters: &'a mut Filters) -> Option<&'a mut HashSet<IStr>> {
+        match self {
+            Self::Precision => Some(&mut filters.precisions),
+            Self::Series => Some(&mut filters.base_series),
+            Self::Accel => Some(&mut filters.base_accel),
+            Self::Event => Some(&mut filters.event_names),
+            Self::SeriesParam(key) => filters.series_params.get_mut(key),
+            Self::AccelParam(key) => filters.accel_params.get_mut(key),
+        }
+    }
+}
+
+/// Filter value matching nothing in the data
+pub struct StaleValue {
+    pub field: FilterField,
+    pub value: IStr,
+    pub suggestion: Option<String>,
+}
+
+impl StaleValue {
+    /// Replaces the value with the suggestion, wherever the filters select it
+    pub fn apply(&self, filters: &mut Filters) {
+        let Some(suggestion) = &self.suggestion else {
+            return;
+        };
+        if let Some(values) = self.field.values_mut(filters)
+            && values.remove(&self.value)
+        {
+            values.insert(intern(suggestion));
+        }
+    }
+}
+
+// Values of `filters` missing from `known`; fields the metadata has nothing on yet are skipped
+fn push_stale(
+    stale: &mut Vec<StaleValue>,
+    field: FilterField,
+    values: &HashSet<IStr>,
+    known: &[String],
+) {
+    if known.is_empty() {
+        return;
+    }
+    for value in values {
+        let special = [NOT_SET, NO_EVENTS].contains(&&**value);
+        let missing = !special && !known.iter().any(|k| **k == **value);
+        if missing && !stale.iter().any(|s| s.field == field && s.value == *value) {
+            stale.push(StaleValue {
+                field: field.clone(),
+                value: value.clone(),
+                suggestion: closest(value, known.iter().map(String::as_str)).map(str::to_string),
+            });
+        }
+    }
+}
+
+fn push_stale_params(
+    stale: &mut Vec<StaleValue>,
+    field: fn(IStr) -> FilterField,
+    params: &HashMap<IStr, HashSet<IStr>>,
+    known: &HashMap<String, Vec<String>>,
+) {
+    for (key, values) in params {
+        let known = known.get(&**key).map_or(&[][..], Vec::as_slice);
+        push_stale(stale, field(key.clone()), values, known);
+    }
+}
+
+/// Values of all the filters of a bookmark that the metadata doesn't list
+pub fn stale_values(bookmark: &Bookmark, metadata: &Metadata) -> Vec<StaleValue> {
+    let mut stale = Vec::new();
+    let filters = [&bookmark.filters, &bookmark.quick_filters]
+        .into_iter()
+        .chain(&bookmark.compare_filters);
+    for filters in filters {
+        let fields = [
+            (
+                FilterField::Precision,
+                &filters.precisions,
+                &metadata.precisions,
+            ),
+            (
+                FilterField::Series,
+                &filters.base_series,
+                &metadata.series_names,
+            ),
+            (
+                FilterField::Accel,
+                &filters.base_accel,
+                &metadata.accel_names,
+            ),
+            (
+                FilterField::Event,
+                &filters.event_names,
+                &metadata.event_names,
+            ),
+        ];
+        for (field, values, known) in fields {
+            push_stale(&mut stale, field, values, known);
+        }
+        push_stale_params(
+            &mut stale,
+            FilterField::SeriesParam,
+            &filters.series_params,
+            &metadata.series_param_info,
+        );
+        push_stale_params(
+            &mut stale,
+            FilterField::AccelParam,
+            &filters.accel_params,
+            &metadata.accel_param_info,
+        );
+    }
+    stale
+}
+
+/// Stale values of an opened bookmark, see `stale_values`
+pub struct RemapWindow {
+    /// Index of the bookmark in the workspace
+    pub bookmark: usize,
+    name: String,
+    stale: Vec<StaleValue>,
+}
+
+impl RemapWindow {
+    /// `None` if every value of the bookmark is in the data
+    pub fn new(bookmark: usize, view: &Bookmark, metadata: &Metadata) -> Option<Self> {
+        let stale = stale_values(view, metadata);
+        (!stale.is_empty()).then(|| Self {
+            bookmark,
+            name: view.name.clone(),
+            stale,
+        })
+    }
+
+    /// Replaces the picked values in every filter of the bookmark
+    pub fn apply(&self, picked: &[usize], bookmark: &mut Bookmark) {
+        for value in picked.iter().map(|&i| &self.stale[i]) {
+            value.apply(&mut bookmark.filters);
+            value.apply(&mut bookmark.quick_filters);
+            if let Some(filters) = &mut bookmark.compare_filters {
+                value.apply(filters);
+            }
+        }
+    }
+
+    /// Shows the window; returns false once it's closed. Values whose replacement was clicked
+    /// are added to `picked`, as indices for `apply`
+    pub fn ui(&self, ctx: &Context, picked: &mut Vec<usize>) -> bool {
+        let mut open = true;
+        egui::Window::new(format!("Закладка «{}»: значения не найдены", self.name))
+            .id(Id::new("remap_window"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Этих значений фильтров нет в данных, поэтому они ничего не выбирают");
+                ui.separator();
+                egui::Grid::new("remap_values")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, stale) in self.stale.iter().enumerate() {
+                            ui.label(
+                                egui::RichText::new(format!("{}:", stale.field.label())).strong(),
+                            );
+                            ui.label(stale.value.as_ref());
+                            match &stale.suggestion {
+                                Some(suggestion) => {
+                                    if ui.button(format!("→ {suggestion}")).clicked() {
+                                        picked.push(i);
+                                    }
+                                }
+                                None => {
+                                    ui.weak("нет похожих");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                let suggested: Vec<usize> = (0..self.stale.len())
+                    .filter(|&i| self.stale[i].suggestion.is_some())
+                    .collect();
+                if suggested.len() > 1 && ui.button("Заменить все").clicked() {
+                    *picked = suggested;
+                }
+            });
+        open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_finds_a_renamed_method() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        let methods = ["wynn-epsilon", "shanks", "levin_t"];
+        assert_eq!(closest("wynn_eps", methods), Some("wynn-epsilon"));
+        assert_eq!(closest("Levin-T", methods), Some("levin_t"));
+        assert_eq!(closest("richardson", methods), None);
+    }
+}
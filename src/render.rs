@@ -0,0 +1,382 @@
+use crate::app::{FilteredData, PlotOptions, Vis, labelled_panel_ui};
+use crate::data_loader::{DataLoader, Filters};
+use crate::grouping::sync_family_pattern;
+use crate::panel::{PanelRegistry, PlotPanel};
+use crate::workspace::{PlotLabels, Workspace};
+use anyhow::{Context, Result, bail};
+use eframe::egui;
+use egui::epaint::{ClippedPrimitive, ImageData, Primitive, Vertex};
+use egui::{Color32, Pos2, Rect, TextureId, Vec2};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// List of figures for `vizr render`, e.g. all figures of a paper.
+///
+/// ```toml
+/// data_dir = "results"
+///
+/// [[figure]]
+/// plot = "error"
+/// output = "figures/error_f64.png"
+/// scale = 2.0
+/// options = { log_x = true }
+/// labels = { title = "Shanks vs Levin", caption = "Double precision" }
+/// quick_filters = { precisions = ["f64"], base_accel = ["shanks", "levin"] }
+/// ```
+///
+/// Relative paths are resolved against the directory of the manifest.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    data_dir: PathBuf,
+    // Annotations and custom labels are taken from it (default: the one of the data)
+    #[serde(default)]
+    workspace: Option<PathBuf>,
+    #[serde(rename = "figure")]
+    figures: Vec<Figure>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Figure {
+    // Panel id, as in screenshot file names: "convergence", "error", "performance"...
+    plot: String,
+    // Image file; the format follows the extension
+    output: PathBuf,
+    // Query filters, then the quick filters applied to the loaded data
+    #[serde(default)]
+    filters: Filters,
+    #[serde(default)]
+    quick_filters: Filters,
+    #[serde(default = "default_symlog")]
+    symlog: bool,
+    #[serde(default)]
+    options: PlotOptions,
+    // Visible area [min_x, min_y, max_x, max_y] in plot coordinates; automatic if not set
+    #[serde(default)]
+    bounds: Option<[f64; 4]>,
+    #[serde(default)]
+    labels: Option<PlotLabels>,
+    // Area available to the panel, in points; the image is cropped to what it draws
+    #[serde(default = "default_size")]
+    size: [f32; 2],
+    // Pixels per point
+    #[serde(default = "default_scale")]
+    scale: f32,
+}
+
+fn default_symlog() -> bool {
+    true
+}
+
+fn default_size() -> [f32; 2] {
+    [1600.0, 1000.0]
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+// Frames drawn before the captured one, so that plot bounds and layout settle
+const SETTLE_FRAMES: usize = 3;
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        let mut manifest: Self = toml::from_str(&text)
+            .with_context(|| format!("Failed to parse manifest {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        manifest.data_dir = base.join(&manifest.data_dir);
+        if let Some(workspace) = &mut manifest.workspace {
+            *workspace = base.join(&*workspace);
+        }
+        for figure in &mut manifest.figures {
+            figure.output = base.join(&figure.output);
+        }
+        Ok(manifest)
+    }
+}
+
+/// Renders every figure of the manifest without opening a window. Failed figures are reported
+/// and skipped; the error at the end tells how many there were.
+pub async fn render_manifest(path: &Path, panels: &PanelRegistry) -> Result<()> {
+    let manifest = Manifest::load(path)?;
+    let data_dir = manifest.data_dir.to_string_lossy().to_string();
+    println!("Loading parquet data from: {}", data_dir);
+    let loader = DataLoader::new(&data_dir).await?;
+    let workspace = Workspace::load(
+        manifest
+            .workspace
+            .clone()
+            .unwrap_or_else(|| Workspace::default_path(&data_dir)),
+    )?;
+
+    let mut failed = 0;
+    for figure in &manifest.figures {
+        match render_figure(&loader, &workspace, figure, panels).await {
+            Ok(()) => println!("Figure saved: {}", figure.output.display()),
+            Err(e) => {
+                eprintln!("Failed to render {}: {:#}", figure.output.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} figures failed", failed, manifest.figures.len());
+    }
+    Ok(())
+}
+
+async fn render_figure(
+    loader: &DataLoader,
+    workspace: &Workspace,
+    figure: &Figure,
+    panels: &PanelRegistry,
+) -> Result<()> {
+    // All pages: a figure is drawn from every matching series
+    let data = loader.filter_all(&figure.filters).await?;
+    // Families of the quick filters and the legend are those of this figure's options
+    sync_family_pattern(&figure.options.family_pattern);
+    let filtered = FilteredData::filter_data_items(&data, &figure.quick_filters);
+    let panels = panels.build(&filtered, figure.symlog);
+    let Some(panel) = panels.iter().find(|p| p.id() == figure.plot) else {
+        let ids: Vec<_> = panels.iter().map(|p| p.id()).collect();
+        bail!(
+            "unknown plot `{}`, expected one of {}",
+            figure.plot,
+            ids.join(", ")
+        );
+    };
+
+    if !loader.has_accelerations() && panel.needs_accelerations() {
+        bail!("`{}` needs the accelerations table", figure.plot);
+    }
+
+    let mut vis = Vis::new(workspace.clone(), loader.path().to_string());
+    vis.opts = figure.options.clone();
+    if let Some(labels) = &figure.labels {
+        vis.set_plot_labels(panel.id(), labels.clone());
+    }
+    if let Some(parent) = figure.output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if let Some(bounds) = figure.bounds {
+        vis.restore_bounds(panel.id(), bounds);
+    }
+    let Some(image) = render_panel(panel.as_ref(), &mut vis, figure.size, figure.scale) else {
+        bail!("`{}` can't be rendered as an image", figure.plot);
+    };
+    image.save(&figure.output)?;
+    Ok(())
+}
+
+/// Draws the panel alone on a light background in `size` points and rasterizes it on the CPU,
+/// cropped to the area it reports. `None` for panels without one, e.g. the table.
+pub fn render_panel(
+    panel: &dyn PlotPanel,
+    vis: &mut Vis,
+    size: [f32; 2],
+    pixels_per_point: f32,
+) -> Option<image::RgbaImage> {
+    let ctx = egui::Context::default();
+    ctx.set_visuals(egui::Visuals::light());
+    ctx.set_pixels_per_point(pixels_per_point);
+    let input = egui::RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::from(size))),
+        ..Default::default()
+    };
+
+    let mut textures = Textures::default();
+    let mut area = None;
+    let mut output = None;
+    for _ in 0..=SETTLE_FRAMES {
+        let full_output = ctx.run(input.clone(), |ctx| {
+            let frame = egui::Frame::central_panel(&ctx.style()).inner_margin(8.0);
+            egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
+                // The axes of a plot lie outside its rect: take everything below its top edge
+                area = labelled_panel_ui(panel, vis, ui).map(|rect| {
+                    let drawn = ui.min_rect();
+                    Rect::from_x_y_ranges(drawn.x_range(), rect.top()..=drawn.bottom())
+                });
+            });
+        });
+        textures.update(&full_output.textures_delta);
+        output = Some(full_output);
+    }
+    let output = output.unwrap();
+    let area = area?;
+
+    let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+    let mut canvas = Canvas::new(
+        size,
+        output.pixels_per_point,
+        ctx.style().visuals.panel_fill,
+    );
+    for primitive in &primitives {
+        canvas.draw(primitive, &textures);
+    }
+    Some(canvas.crop(area.expand(8.0)))
+}
+
+// Managed textures (the font atlas), as premultiplied RGBA
+#[derive(Default)]
+struct Textures(HashMap<TextureId, ([usize; 2], Vec<Color32>)>);
+
+impl Textures {
+    fn update(&mut self, delta: &egui::TexturesDelta) {
+        for (id, image_delta) in &delta.set {
+            let size = image_delta.image.size();
+            let pixels: Vec<Color32> = match &image_delta.image {
+                ImageData::Color(image) => image.pixels.clone(),
+                ImageData::Font(image) => image.srgba_pixels(None).collect(),
+            };
+            match image_delta.pos {
+                None => {
+                    self.0.insert(*id, (size, pixels));
+                }
+                Some([x0, y0]) => {
+                    let Some(([width, _], texture)) = self.0.get_mut(id) else {
+                        continue;
+                    };
+                    for (row, chunk) in pixels.chunks(size[0]).enumerate() {
+                        let start = (y0 + row) * *width + x0;
+                        texture[start..start + chunk.len()].copy_from_slice(chunk);
+                    }
+                }
+            }
+        }
+        for id in &delta.free {
+            self.0.remove(id);
+        }
+    }
+
+    // Bilinear sample at normalized coordinates
+    fn sample(&self, id: TextureId, uv: Pos2) -> [f32; 4] {
+        let Some(([width, height], pixels)) = self.0.get(&id) else {
+            return [1.0; 4];
+        };
+        let x = (uv.x * *width as f32 - 0.5).clamp(0.0, (*width - 1) as f32);
+        let y = (uv.y * *height as f32 - 0.5).clamp(0.0, (*height - 1) as f32);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let texel = |x: usize, y: usize| pixels[y * width + x].to_array();
+        let mut res = [0.0; 4];
+        for (i, value) in res.iter_mut().enumerate() {
+            let top = texel(x0, y0)[i] as f32 * (1.0 - fx) + texel(x1, y0)[i] as f32 * fx;
+            let bottom = texel(x0, y1)[i] as f32 * (1.0 - fx) + texel(x1, y1)[i] as f32 * fx;
+            *value = (top * (1.0 - fy) + bottom * fy) / 255.0;
+        }
+        res
+    }
+}
+
+// Software rasterizer for egui meshes. Blends premultiplied colors in gamma space, like the glow
+// renderer; anti-aliasing comes from egui's feathering
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels_per_point: f32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(size: [f32; 2], pixels_per_point: f32, background: Color32) -> Self {
+        let width = (size[0] * pixels_per_point).round() as usize;
+        let height = (size[1] * pixels_per_point).round() as usize;
+        let background = background.to_array().map(|c| c as f32 / 255.0);
+        Self {
+            width,
+            height,
+            pixels_per_point,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    fn draw(&mut self, primitive: &ClippedPrimitive, textures: &Textures) {
+        let Primitive::Mesh(mesh) = &primitive.primitive else {
+            return;
+        };
+        let clip = Rect::from_min_max(
+            (primitive.clip_rect.min.to_vec2() * self.pixels_per_point).to_pos2(),
+            (primitive.clip_rect.max.to_vec2() * self.pixels_per_point).to_pos2(),
+        )
+        .intersect(Rect::from_min_size(
+            Pos2::ZERO,
+            Vec2::new(self.width as f32, self.height as f32),
+        ));
+        if !clip.is_positive() {
+            return;
+        }
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            self.triangle([a, b, c], clip, mesh.texture_id, textures);
+        }
+    }
+
+    fn triangle(
+        &mut self,
+        vertices: [Vertex; 3],
+        clip: Rect,
+        texture: TextureId,
+        textures: &Textures,
+    ) {
+        let p = vertices.map(|v| v.pos.to_vec2() * self.pixels_per_point);
+        let area = (p[1] - p[0]).x * (p[2] - p[0]).y - (p[1] - p[0]).y * (p[2] - p[0]).x;
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        let min = p[0].min(p[1]).min(p[2]).max(clip.min.to_vec2());
+        let max = p[0].max(p[1]).max(p[2]).min(clip.max.to_vec2());
+        let colors = vertices.map(|v| v.color.to_array().map(|c| c as f32 / 255.0));
+
+        for y in min.y.floor() as usize..(max.y.ceil() as usize).min(self.height) {
+            for x in min.x.floor() as usize..(max.x.ceil() as usize).min(self.width) {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                // Barycentric weights; all of the same sign as `area` inside the triangle
+                let edge = |a: Vec2, b: Vec2| (b - a).x * (point - a).y - (b - a).y * (point - a).x;
+                let w = [edge(p[1], p[2]), edge(p[2], p[0]), edge(p[0], p[1])].map(|w| w / area);
+                if w.iter().any(|w| *w < 0.0) {
+                    continue;
+                }
+                let uv = vertices[0].uv.to_vec2() * w[0]
+                    + vertices[1].uv.to_vec2() * w[1]
+                    + vertices[2].uv.to_vec2() * w[2];
+                let texel = textures.sample(texture, uv.to_pos2());
+                let dst = &mut self.pixels[y * self.width + x];
+                let src: [f32; 4] = std::array::from_fn(|i| {
+                    (colors[0][i] * w[0] + colors[1][i] * w[1] + colors[2][i] * w[2]) * texel[i]
+                });
+                for i in 0..4 {
+                    dst[i] = src[i] + dst[i] * (1.0 - src[3]);
+                }
+            }
+        }
+    }
+
+    // Image of `area` (in points), without alpha
+    fn crop(&self, area: Rect) -> image::RgbaImage {
+        let area = Rect::from_min_max(
+            (area.min.to_vec2() * self.pixels_per_point).to_pos2(),
+            (area.max.to_vec2() * self.pixels_per_point).to_pos2(),
+        )
+        .intersect(Rect::from_min_size(
+            Pos2::ZERO,
+            Vec2::new(self.width as f32, self.height as f32),
+        ));
+        let (x0, y0) = (area.min.x.floor() as u32, area.min.y.floor() as u32);
+        let width = (area.max.x.ceil() as u32).saturating_sub(x0);
+        let height = (area.max.y.ceil() as u32).saturating_sub(y0);
+        image::RgbaImage::from_fn(width, height, |x, y| {
+            let pixel = self.pixels[(y0 + y) as usize * self.width + (x0 + x) as usize];
+            image::Rgba([
+                (pixel[0].clamp(0.0, 1.0) * 255.0).round() as u8,
+                (pixel[1].clamp(0.0, 1.0) * 255.0).round() as u8,
+                (pixel[2].clamp(0.0, 1.0) * 255.0).round() as u8,
+                255,
+            ])
+        })
+    }
+}
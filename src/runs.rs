@@ -0,0 +1,200 @@
+use crate::accessibility::describe_plot;
+use crate::app::{LineKey, RowStats, SeriesDataRef, Vis, format_item_name, line_id, plot_controls};
+use crate::legend::{method_color, method_key, series_key};
+use crate::metric::{Scale, metric_ui};
+use crate::panel::PlotPanel;
+use eframe::egui;
+use egui::{Color32, Rect, Ui};
+use egui_plot::{Line, MarkerShape, Plot, PlotPoint, Points, uniform_grid_spacer};
+use std::collections::BTreeMap;
+
+/// Same configuration (precision, series, method, m, parameters) in one run
+struct RunPoint {
+    run: usize,
+    key: LineKey,
+    stats: RowStats,
+}
+
+struct Configuration {
+    name: String,
+    color: Color32,
+    // Ordered by run
+    points: Vec<RunPoint>,
+}
+
+/// A metric of every configuration across runs, to spot trends and regressions between
+/// experiment batches. Needs the optional `run_id` column of the series table.
+///
+/// Runs are placed at x = 0, 1, 2... in the order of their ids: numerically if all of them are
+/// numbers (e.g. timestamps), otherwise lexicographically.
+pub struct RunsPanel {
+    runs: Vec<String>,
+    configurations: Vec<Configuration>,
+}
+
+impl PlotPanel for RunsPanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        let mut runs: Vec<String> = data
+            .iter()
+            .filter_map(|(series, _)| series.run.as_ref().map(|r| r.to_string()))
+            .collect();
+        runs.sort();
+        runs.dedup();
+        if runs.iter().all(|r| r.parse::<f64>().is_ok()) {
+            runs.sort_by(|a, b| a.parse::<f64>().unwrap().total_cmp(&b.parse().unwrap()));
+        }
+        let index: BTreeMap<&str, usize> = runs
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.as_str(), i))
+            .collect();
+
+        let mut configurations = BTreeMap::<(String, String, String), Configuration>::new();
+        for (series, accel_records) in data {
+            let Some(run) = &series.run else {
+                continue;
+            };
+            for accel_record in accel_records {
+                let accel_info = &accel_record.accel_info;
+                let configuration = configurations
+                    .entry((
+                        series.precision.to_string(),
+                        series_key(series),
+                        method_key(accel_info),
+                    ))
+                    .or_insert_with(|| Configuration {
+                        name: format_item_name(series, accel_info),
+                        color: method_color(accel_info),
+                        points: Vec::new(),
+                    });
+                configuration.points.push(RunPoint {
+                    run: index[run.as_ref()],
+                    key: LineKey::new(series, accel_info),
+                    stats: RowStats::of(series, accel_record),
+                });
+            }
+        }
+
+        let mut configurations: Vec<Configuration> = configurations.into_values().collect();
+        for configuration in &mut configurations {
+            configuration.points.sort_by_key(|p| p.run);
+        }
+
+        Self {
+            runs,
+            configurations,
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        "runs"
+    }
+
+    fn title(&self) -> &str {
+        "Динамика по запускам"
+    }
+
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect> {
+        if self.runs.len() < 2 {
+            ui.label("Нужно несколько запусков (столбец run_id в таблице рядов)");
+            return None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Метрика:");
+            let mut metric = vis.opts.run_metric;
+            metric_ui(ui, "run_metric", &mut metric, &mut vis.opts);
+            vis.opts.run_metric = metric;
+        });
+        let metric = vis.opts.run_metric;
+        let params = vis.opts.metric_params();
+
+        let run_name = |x: f64| {
+            let i = x.round();
+            if (x - i).abs() > 1e-6 || i < 0.0 {
+                return None;
+            }
+            self.runs.get(i as usize)
+        };
+        // Errors are plotted as log10
+        let format_y = move |y: f64| match metric.scale() {
+            Scale::Log => format!("1e{y:.1}"),
+            Scale::Linear => format!("{y:.0}"),
+        };
+        let plot = Plot::new("runs")
+            .allow_zoom(true)
+            .allow_drag(true)
+            .height(600.0)
+            .x_grid_spacer(uniform_grid_spacer(|_| [1.0, 5.0, 25.0]))
+            .x_axis_formatter(move |mark, _| run_name(mark.value).cloned().unwrap_or_default())
+            .y_axis_formatter(move |mark, _| format_y(mark.value))
+            .label_formatter(move |name, value| {
+                let run = run_name(value.x).map_or("", String::as_str);
+                format!("{name}\nзапуск {run}\ny={}", format_y(value.y))
+            })
+            .legend(egui_plot::Legend::default());
+        let y_label = metric.label(&params);
+        let plot = vis.axis_labels("runs", plot, "Запуск", &y_label);
+        let plot = vis.plot_wheel(plot);
+
+        let lines: Vec<Vec<PlotPoint>> = self
+            .configurations
+            .iter()
+            .map(|configuration| {
+                configuration
+                    .points
+                    .iter()
+                    .filter_map(|p| {
+                        let y = metric.of(&p.stats, &params)?.score();
+                        y.is_finite().then(|| PlotPoint::new(p.run as f64, y))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let restore = vis.take_bounds("runs");
+        let plot = plot.show(ui, |plot_ui| {
+            plot_controls(plot_ui);
+            if let Some(bounds) = restore {
+                plot_ui.set_plot_bounds(bounds);
+            }
+            for (i, (configuration, points)) in self.configurations.iter().zip(&lines).enumerate() {
+                let selected = configuration.points.iter().any(|p| vis.is_selected(&p.key));
+                plot_ui.line(
+                    Line::new(points.as_slice())
+                        .name(&configuration.name)
+                        .id(line_id("runs", i))
+                        .color(configuration.color)
+                        .highlight(selected)
+                        .width(if selected { 3.0 } else { 1.5 }),
+                );
+                plot_ui.points(
+                    Points::new(points.as_slice())
+                        .name(&configuration.name)
+                        .color(configuration.color)
+                        .shape(MarkerShape::Circle)
+                        .radius(4.0),
+                );
+            }
+            vis.draw_annotations("runs", plot_ui, false, false);
+        });
+        // A line spans one series per run; clicking it selects the latest one
+        vis.select_from_plot(&plot, |id| {
+            (0..self.configurations.len())
+                .find(|i| line_id("runs", i) == id)
+                .and_then(|i| self.configurations[i].points.last())
+                .map(|p| &p.key)
+        });
+        vis.annotation_menu("runs", &plot, false, false);
+        describe_plot(
+            &plot.response,
+            format!(
+                "{y_label} по запускам, конфигураций: {}",
+                self.configurations.len()
+            ),
+        );
+        vis.track_hover(&plot.response);
+        vis.remember_bounds("runs", plot.transform.bounds());
+        Some(plot.response.rect)
+    }
+}
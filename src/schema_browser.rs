@@ -0,0 +1,138 @@
+use crate::data_loader::TableSchema;
+use crate::data_source::DataSource;
+use anyhow::Result;
+use datafusion::arrow::datatypes::{DataType, Field};
+use eframe::egui;
+use egui::Ui;
+use std::sync::{Arc, mpsc};
+
+/// Shows the Arrow schemas of the registered tables, to diagnose missing or mistyped columns.
+#[derive(Default)]
+pub struct SchemaBrowser {
+    tables: Option<Result<Vec<TableSchema>, String>>,
+    receiver: Option<mpsc::Receiver<Result<Vec<TableSchema>>>>,
+}
+
+fn field_ui(ui: &mut Ui, field: &Field, partition: bool) {
+    let mut title = format!("{}: {}", field.name(), short_type(field.data_type()));
+    if field.is_nullable() {
+        title.push_str(" (nullable)");
+    }
+    if partition {
+        title.push_str(" 📁 partition");
+    }
+    let children: Vec<&Field> = match field.data_type() {
+        DataType::Struct(fields) => fields.iter().map(|f| f.as_ref()).collect(),
+        DataType::List(item)
+        | DataType::LargeList(item)
+        | DataType::FixedSizeList(item, _)
+        | DataType::Map(item, _) => vec![item.as_ref()],
+        _ => Vec::new(),
+    };
+    if children.is_empty() {
+        ui.label(title);
+    } else {
+        egui::CollapsingHeader::new(title)
+            .id_salt(field as *const Field)
+            .show(ui, |ui| {
+                for child in children {
+                    field_ui(ui, child, false);
+                }
+            });
+    }
+}
+
+// Nested types are expanded as children, so only their kind is named
+fn short_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Struct(_) => "Struct".to_string(),
+        DataType::List(_) => "List".to_string(),
+        DataType::LargeList(_) => "LargeList".to_string(),
+        DataType::FixedSizeList(_, n) => format!("FixedSizeList[{n}]"),
+        DataType::Map(_, _) => "Map".to_string(),
+        t => t.to_string(),
+    }
+}
+
+impl SchemaBrowser {
+    fn load(&mut self, loader: &Arc<dyn DataSource>, ctx: &egui::Context) {
+        let (tx, rx) = mpsc::channel();
+        let loader = loader.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let _ = tx.send(rt.block_on(loader.table_schemas()));
+            ctx.request_repaint();
+        });
+        self.tables = None;
+        self.receiver = Some(rx);
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, loader: &Arc<dyn DataSource>) {
+        if self.tables.is_none() && self.receiver.is_none() {
+            self.load(loader, ui.ctx());
+        }
+        if let Some(receiver) = &self.receiver
+            && let Ok(result) = receiver.try_recv()
+        {
+            self.tables = Some(result.map_err(|e| e.to_string()));
+            self.receiver = None;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("↻ Обновить").clicked() {
+                self.load(loader, ui.ctx());
+            }
+            ui.label(loader.schema_version().description());
+        });
+        let tables = match &self.tables {
+            None => {
+                ui.spinner();
+                return;
+            }
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+                return;
+            }
+            Some(Ok(tables)) => tables,
+        };
+
+        egui::ScrollArea::vertical()
+            .id_salt("schema_browser")
+            .show(ui, |ui| {
+                for table in tables {
+                    ui.heading(&table.name);
+                    for field in table.schema.fields() {
+                        let partition = table.partition_cols.iter().any(|c| c == field.name());
+                        field_ui(ui, field, partition);
+                    }
+                    let total: i64 = table
+                        .files
+                        .iter()
+                        .filter_map(|(_, r)| r.as_ref().ok())
+                        .sum();
+                    egui::CollapsingHeader::new(format!(
+                        "Файлы: {}, строк: {}",
+                        table.files.len(),
+                        total
+                    ))
+                    .id_salt((&table.name, "files"))
+                    .show(ui, |ui| {
+                        egui::Grid::new((&table.name, "files_grid"))
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (path, rows) in &table.files {
+                                    ui.label(path);
+                                    match rows {
+                                        Ok(rows) => ui.label(rows.to_string()),
+                                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e),
+                                    };
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                    ui.add_space(10.0);
+                }
+            });
+    }
+}
@@ -0,0 +1,313 @@
+use crate::app::{RowStats, SeriesDataRef, format_method_name, magnitude};
+use crate::data_loader::AccelInfo;
+use crate::symlog::format_f64;
+use eframe::egui;
+use egui::Ui;
+use std::collections::HashMap;
+
+// Up to this many pairs the p-value is computed from the exact distribution
+const EXACT_MAX_PAIRS: usize = 30;
+
+/// Error of a record that the methods are compared by
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ErrorMetric {
+    #[default]
+    Minimum,
+    Final,
+}
+
+/// Paired test of two methods over the series both of them were run on.
+#[derive(Default)]
+pub struct SignificanceTest {
+    method_a: Option<AccelInfo>,
+    method_b: Option<AccelInfo>,
+    metric: ErrorMetric,
+    result: Option<Result<TestResult, String>>,
+}
+
+struct TestResult {
+    // Pairs with a nonzero difference, and the ties left out of the test
+    pairs: usize,
+    ties: usize,
+    // Pairs where A has the smaller error
+    a_better: usize,
+    // In orders of magnitude, A minus B
+    median_difference: f64,
+    wilcoxon: Wilcoxon,
+    sign_p_value: f64,
+}
+
+struct Wilcoxon {
+    w_plus: f64,
+    w_minus: f64,
+    p_value: f64,
+    exact: bool,
+}
+
+impl Wilcoxon {
+    /// Matched-pairs rank-biserial correlation: -1 when every difference is negative
+    fn effect_size(&self) -> f64 {
+        (self.w_plus - self.w_minus) / (self.w_plus + self.w_minus)
+    }
+}
+
+// Average ranks of the values, 1-based, ties share the mean of their positions
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut res = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &k in &order[i..=j] {
+            res[k] = rank;
+        }
+        i = j + 1;
+    }
+    res
+}
+
+// Two-sided Wilcoxon signed-rank test of nonzero differences
+fn wilcoxon(differences: &[f64]) -> Wilcoxon {
+    let n = differences.len();
+    let abs: Vec<f64> = differences.iter().map(|d| d.abs()).collect();
+    let ranks = ranks(&abs);
+    let w_plus: f64 = differences
+        .iter()
+        .zip(&ranks)
+        .filter(|(d, _)| **d > 0.0)
+        .map(|(_, r)| r)
+        .sum();
+    let total = (n * (n + 1)) as f64 / 2.0;
+    let w_minus = total - w_plus;
+
+    let p_value = if n <= EXACT_MAX_PAIRS {
+        // Ranks are multiples of 1/2; count the sign assignments by doubled rank sum
+        let doubled: Vec<usize> = ranks.iter().map(|r| (r * 2.0).round() as usize).collect();
+        let mut counts = vec![0.0f64; doubled.iter().sum::<usize>() + 1];
+        counts[0] = 1.0;
+        let mut max = 0;
+        for r in doubled {
+            for s in (0..=max).rev() {
+                counts[s + r] += counts[s];
+            }
+            max += r;
+        }
+        let w = (w_plus * 2.0).round() as usize;
+        let all = 2f64.powi(n as i32);
+        let lower: f64 = counts[..=w].iter().sum::<f64>() / all;
+        let upper: f64 = counts[w..].iter().sum::<f64>() / all;
+        (2.0 * lower.min(upper)).min(1.0)
+    } else {
+        // Normal approximation with the tie correction and a continuity correction
+        let mut tie_sum = 0.0;
+        let mut sorted = abs.clone();
+        sorted.sort_by(f64::total_cmp);
+        for group in sorted.chunk_by(|a, b| a == b) {
+            let t = group.len() as f64;
+            tie_sum += t * t * t - t;
+        }
+        let nf = n as f64;
+        let mean = nf * (nf + 1.0) / 4.0;
+        let var = nf * (nf + 1.0) * (2.0 * nf + 1.0) / 24.0 - tie_sum / 48.0;
+        let z = ((w_plus - mean).abs() - 0.5).max(0.0) / var.sqrt();
+        erfc(z / std::f64::consts::SQRT_2).min(1.0)
+    };
+
+    Wilcoxon {
+        w_plus,
+        w_minus,
+        p_value,
+        exact: n <= EXACT_MAX_PAIRS,
+    }
+}
+
+// Two-sided exact sign test: `k` of `n` differences are negative
+fn sign_test(k: usize, n: usize) -> f64 {
+    let k = k.min(n - k);
+    // P(X <= k) for X ~ Bin(n, 1/2), with the binomial coefficients in log space
+    let mut log_c = 0.0;
+    let mut p = 0.0;
+    for i in 0..=k {
+        if i > 0 {
+            log_c += ((n - i + 1) as f64).ln() - (i as f64).ln();
+        }
+        p += (log_c - n as f64 * std::f64::consts::LN_2).exp();
+    }
+    (2.0 * p).min(1.0)
+}
+
+// Complementary error function, Abramowitz & Stegun 7.1.26 (error below 1.5e-7)
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let res = poly * (-x * x).exp();
+    if x >= 0.0 { res } else { 2.0 - res }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) / 2.0
+    }
+}
+
+impl SignificanceTest {
+    // log10 of the chosen error of each series the method was run on
+    fn errors(&self, data: &[SeriesDataRef], method: &AccelInfo) -> HashMap<i32, f64> {
+        let mut res = HashMap::new();
+        for (series, accel_records) in data {
+            for accel_record in accel_records {
+                if accel_record.accel_info != *method {
+                    continue;
+                }
+                let stats = RowStats::of(series, accel_record);
+                let error = match self.metric {
+                    ErrorMetric::Minimum => stats.min,
+                    ErrorMetric::Final => stats.last,
+                };
+                if let Some(error) = error {
+                    res.insert(series.series_id, magnitude(&error));
+                }
+            }
+        }
+        res
+    }
+
+    fn run(&self, data: &[SeriesDataRef]) -> Result<TestResult, String> {
+        let (Some(a), Some(b)) = (&self.method_a, &self.method_b) else {
+            return Err("Выберите оба метода".to_string());
+        };
+        let errors_b = self.errors(data, b);
+        let mut differences = Vec::new();
+        let mut ties = 0;
+        for (series_id, error_a) in self.errors(data, a) {
+            let Some(error_b) = errors_b.get(&series_id) else {
+                continue;
+            };
+            // Exact zeros are -inf on both sides
+            let d = if error_a == *error_b {
+                0.0
+            } else {
+                error_a - error_b
+            };
+            if d == 0.0 {
+                ties += 1;
+            } else {
+                differences.push(d);
+            }
+        }
+        if differences.is_empty() {
+            return Err("Нет рядов, на которых методы различаются".to_string());
+        }
+        let a_better = differences.iter().filter(|d| **d < 0.0).count();
+        Ok(TestResult {
+            pairs: differences.len(),
+            ties,
+            a_better,
+            sign_p_value: sign_test(a_better, differences.len()),
+            wilcoxon: wilcoxon(&differences),
+            median_difference: median(differences),
+        })
+    }
+
+    fn method_combo(ui: &mut Ui, id: &str, method: &mut Option<AccelInfo>, methods: &[AccelInfo]) {
+        egui::ComboBox::from_id_salt(id)
+            .selected_text(method.as_ref().map_or("—".to_string(), format_method_name))
+            .show_ui(ui, |ui| {
+                for m in methods {
+                    let selected = method.as_ref() == Some(m);
+                    if ui
+                        .selectable_label(selected, format_method_name(m))
+                        .clicked()
+                    {
+                        *method = Some(m.clone());
+                    }
+                }
+            });
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, data: &[SeriesDataRef]) {
+        let mut methods: Vec<AccelInfo> = Vec::new();
+        for accel_record in data.iter().flat_map(|(_, a)| a) {
+            if !methods.contains(&accel_record.accel_info) {
+                methods.push(accel_record.accel_info.clone());
+            }
+        }
+        methods.sort_by_cached_key(format_method_name);
+
+        egui::Grid::new("significance_inputs").show(ui, |ui| {
+            ui.label("Метод A:");
+            Self::method_combo(ui, "significance_a", &mut self.method_a, &methods);
+            ui.end_row();
+            ui.label("Метод B:");
+            Self::method_combo(ui, "significance_b", &mut self.method_b, &methods);
+            ui.end_row();
+            ui.label("Ошибка:");
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.metric, ErrorMetric::Minimum, "Минимальная");
+                ui.selectable_value(&mut self.metric, ErrorMetric::Final, "Последняя");
+            });
+            ui.end_row();
+        });
+        if ui
+            .button("Рассчитать")
+            .on_hover_text("Парный критерий Уилкоксона по рядам, на которых запущены оба метода")
+            .clicked()
+        {
+            self.result = Some(self.run(data));
+        }
+
+        match &self.result {
+            Some(Ok(res)) => {
+                ui.separator();
+                ui.label(format!(
+                    "Пар рядов: {} (одинаковых ошибок: {})",
+                    res.pairs, res.ties
+                ));
+                ui.label(format!(
+                    "A лучше на {} из {} рядов",
+                    res.a_better, res.pairs
+                ));
+                ui.label(format!(
+                    "Медиана log₁₀(A) − log₁₀(B): {}",
+                    format_f64(res.median_difference)
+                ));
+                let w = &res.wilcoxon;
+                ui.label(format!(
+                    "Уилкоксон: W+ = {}, W− = {}, p = {} ({})",
+                    format_f64(w.w_plus),
+                    format_f64(w.w_minus),
+                    format_f64(w.p_value),
+                    if w.exact {
+                        "точное распределение"
+                    } else {
+                        "нормальное приближение"
+                    }
+                ));
+                ui.label(format!(
+                    "Размер эффекта (ранговая бисериальная корреляция): {}",
+                    format_f64(w.effect_size())
+                ))
+                .on_hover_text("От −1 (A всегда лучше) до 1 (B всегда лучше)");
+                ui.label(format!(
+                    "Критерий знаков: p = {}",
+                    format_f64(res.sign_p_value)
+                ));
+            }
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            }
+            None => {}
+        }
+    }
+}
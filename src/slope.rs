@@ -0,0 +1,280 @@
+//! Convergence rate over a range of iterations: the slope of log10 of the error against n, picked
+//! by Shift+dragging over the error plot or fitted to the tail of a curve to extrapolate it
+
+use crate::app::magnitude;
+use crate::symlog::{Scientific, format_f64};
+use eframe::egui;
+use egui::{Color32, Context, Id, PointerButton, Rect, Response, Ui};
+use egui_plot::PlotTransform;
+
+/// Median of the slopes between all pairs of points; robust to a few outliers, e.g. a point
+/// where the error happened to cross zero
+pub fn theil_sen(points: &[(f64, f64)]) -> Option<f64> {
+    let mut slopes = Vec::new();
+    for (i, &(x0, y0)) in points.iter().enumerate() {
+        for &(x1, y1) in &points[i + 1..] {
+            if x1 != x0 {
+                slopes.push((y1 - y0) / (x1 - x0));
+            }
+        }
+    }
+    slopes.sort_by(f64::total_cmp);
+    match slopes.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(slopes[len / 2]),
+        len => Some((slopes[len / 2 - 1] + slopes[len / 2]) / 2.0),
+    }
+}
+
+// Median of y - slope * x over the points
+fn theil_sen_intercept(points: &[(f64, f64)], slope: f64) -> f64 {
+    let mut intercepts: Vec<f64> = points.iter().map(|(x, y)| y - slope * x).collect();
+    intercepts.sort_by(f64::total_cmp);
+    let len = intercepts.len();
+    if len % 2 == 1 {
+        intercepts[len / 2]
+    } else {
+        (intercepts[len / 2 - 1] + intercepts[len / 2]) / 2.0
+    }
+}
+
+pub fn least_squares(points: &[(f64, f64)]) -> Option<f64> {
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / count;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / count;
+    let (mut sxy, mut sxx) = (0.0, 0.0);
+    for &(x, y) in points {
+        sxy += (x - mean_x) * (y - mean_y);
+        sxx += (x - mean_x) * (x - mean_x);
+    }
+    (sxx > 0.0).then(|| sxy / sxx)
+}
+
+/// (n, log10 of the deviation) of the points within `range`; exact zeros have no logarithm and
+/// are left out
+pub fn log_errors(deviations: &[(f64, Scientific)], (from, to): (f64, f64)) -> Vec<(f64, f64)> {
+    deviations
+        .iter()
+        .filter(|(n, _)| (from..=to).contains(n))
+        .map(|(n, d)| (*n, magnitude(d)))
+        .filter(|(_, log)| log.is_finite())
+        .collect()
+}
+
+/// 10^log without going through an f64 value, which would underflow for tiny errors
+pub fn power_of_ten(log: f64) -> Scientific {
+    let exponent = log.floor();
+    Scientific(10f64.powf(log - exponent), exponent as i32)
+}
+
+// Points at the end of a curve that its extrapolation is fitted to
+const TAIL_POINTS: usize = 10;
+// Extrapolations further than this many times the last computed n are not shown
+const PROJECTION_REACH: f64 = 100.0;
+
+/// Theil–Sen line through the last points of a curve, log10 of the error against n
+pub struct TailFit {
+    slope: f64,
+    intercept: f64,
+    /// Last computed iteration
+    pub last_n: f64,
+}
+
+impl TailFit {
+    /// `None` if fewer than two of the last `TAIL_POINTS` points have a nonzero error
+    pub fn of(deviations: &[(f64, Scientific)]) -> Option<Self> {
+        let start = deviations.len().saturating_sub(TAIL_POINTS);
+        let last_n = deviations.last()?.0;
+        let points = log_errors(&deviations[start..], (f64::NEG_INFINITY, f64::INFINITY));
+        let slope = theil_sen(&points)?;
+        Some(Self {
+            slope,
+            intercept: theil_sen_intercept(&points, slope),
+            last_n,
+        })
+    }
+
+    /// Fitted log10 of the error at `n`
+    pub fn at(&self, n: f64) -> f64 {
+        self.slope * n + self.intercept
+    }
+
+    /// Extrapolated n at which the error gets down to 10^tolerance_exp; `None` if the fit
+    /// doesn't decrease, is already there at the last point, or gets there only further than
+    /// `PROJECTION_REACH` times the last n
+    pub fn iterations_to(&self, tolerance_exp: i32) -> Option<f64> {
+        let n = (tolerance_exp as f64 - self.intercept) / self.slope;
+        (self.slope < 0.0
+            && self.at(self.last_n) > tolerance_exp as f64
+            && n <= self.last_n.max(1.0) * PROJECTION_REACH)
+            .then(|| n.ceil())
+    }
+}
+
+/// Fitted slopes of one curve
+pub struct SlopeFit {
+    pub name: String,
+    pub color: Color32,
+    points: usize,
+    theil_sen: Option<f64>,
+    least_squares: Option<f64>,
+}
+
+impl SlopeFit {
+    pub fn new(name: String, color: Color32, points: &[(f64, f64)]) -> Self {
+        Self {
+            name,
+            color,
+            points: points.len(),
+            theil_sen: theil_sen(points),
+            least_squares: least_squares(points),
+        }
+    }
+
+    // Error ratio per iteration, 10^slope
+    fn factor(&self) -> Option<f64> {
+        self.theil_sen.map(|slope| 10f64.powf(slope))
+    }
+
+    fn cells(&self) -> [String; 5] {
+        let format = |value: Option<f64>| value.map_or("—".to_string(), format_f64);
+        [
+            self.name.clone(),
+            self.points.to_string(),
+            format(self.theil_sen),
+            format(self.least_squares),
+            format(self.factor()),
+        ]
+    }
+}
+
+const HEADERS: [&str; 5] = [
+    "Линия",
+    "Точек",
+    "Наклон (Тейл — Сен)",
+    "Наклон (МНК)",
+    "Множитель за итерацию",
+];
+
+/// Slopes of the visible curves over the picked range of n
+pub struct SlopeWindow {
+    /// Iterations, not plot coordinates, so the range survives switching the n axis to log10
+    pub range: (f64, f64),
+    fits: Vec<SlopeFit>,
+}
+
+impl SlopeWindow {
+    pub fn new(range: (f64, f64), fits: Vec<SlopeFit>) -> Self {
+        Self { range, fits }
+    }
+
+    // Tab-separated, to paste into a spreadsheet
+    fn text(&self) -> String {
+        let mut text = HEADERS.join("\t");
+        for fit in &self.fits {
+            text.push('\n');
+            text.push_str(&fit.cells().join("\t"));
+        }
+        text
+    }
+
+    /// Shows the window; returns false once it's closed
+    pub fn ui(&self, ctx: &Context) -> bool {
+        let mut open = true;
+        let (from, to) = self.range;
+        egui::Window::new(format!("Скорость сходимости при n от {from:.0} до {to:.0}"))
+            .id(Id::new("slope_window"))
+            .open(&mut open)
+            .default_size([640.0, 320.0])
+            .show(ctx, |ui| {
+                ui.label("Наклон прямой log10 ошибки от n; множитель — во сколько раз ошибка меняется за итерацию");
+                if ui.button("📋 Копировать").clicked() {
+                    ctx.copy_text(self.text());
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("slope_fits").striped(true).show(ui, |ui| {
+                        for header in HEADERS {
+                            ui.label(egui::RichText::new(header).strong());
+                        }
+                        ui.end_row();
+                        for fit in &self.fits {
+                            let [name, rest @ ..] = fit.cells();
+                            ui.colored_label(fit.color, name);
+                            for cell in rest {
+                                ui.label(cell);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        open
+    }
+}
+
+// Shades the columns of a plot between the plot coordinates `x0` and `x1`
+pub fn paint_range(ui: &Ui, transform: &PlotTransform, (x0, x1): (f64, f64)) {
+    let frame = *transform.frame();
+    let (left, right) = (
+        transform.position_from_point_x(x0),
+        transform.position_from_point_x(x1),
+    );
+    let rect = Rect::from_x_y_ranges(left.min(right)..=left.max(right), frame.y_range());
+    ui.painter_at(frame).rect_filled(
+        rect,
+        0.0,
+        Color32::from_rgb(80, 140, 255).gamma_multiply(0.15),
+    );
+}
+
+/// Shift+drag over a plot: shades the dragged columns, and returns their range of plot x once the
+/// button is released. The plot shouldn't pan meanwhile, see `Plot::allow_drag`
+pub fn drag_range(ui: &Ui, response: &Response, transform: &PlotTransform) -> Option<(f64, f64)> {
+    let id = response.id.with("range_drag");
+    let pointer = response
+        .interact_pointer_pos()
+        .map(|pos| transform.value_from_position(pos).x);
+    let shift = ui.input(|i| i.modifiers.shift);
+    if shift
+        && response.drag_started_by(PointerButton::Primary)
+        && let Some(x) = pointer
+    {
+        ui.data_mut(|d| d.insert_temp(id, (x, x)));
+    }
+    let (start, end): (f64, f64) = ui.data(|d| d.get_temp(id))?;
+    let end = pointer.unwrap_or(end);
+    if response.drag_stopped() {
+        ui.data_mut(|d| d.remove::<(f64, f64)>(id));
+        return (start != end).then(|| (start.min(end), start.max(end)));
+    }
+    ui.data_mut(|d| d.insert_temp(id, (start, end)));
+    paint_range(ui, transform, (start, end));
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theil_sen_ignores_an_outlier() {
+        // log10 error dropping by 2 per iteration, with one spike
+        let mut points: Vec<(f64, f64)> = (0..9).map(|n| (n as f64, -2.0 * n as f64)).collect();
+        points[8].1 = 5.0;
+        assert_eq!(theil_sen(&points), Some(-2.0));
+        assert!(least_squares(&points).unwrap() > -2.0);
+        assert_eq!(theil_sen(&points[..1]), None);
+    }
+
+    #[test]
+    fn tail_fit_extrapolates_to_tolerance() {
+        // Error 10^(-n) up to n = 20
+        let deviations: Vec<(f64, Scientific)> =
+            (0..=20).map(|n| (n as f64, Scientific(1.0, -n))).collect();
+        let fit = TailFit::of(&deviations).unwrap();
+        assert_eq!(fit.iterations_to(-30), Some(30.0));
+        assert_eq!(fit.iterations_to(-10), None);
+        assert_eq!(fit.iterations_to(-10_000), None);
+    }
+}
@@ -1,92 +1,256 @@
+use eframe::egui;
+use egui::{Color32, Rect, Ui};
+use egui_plot::{GridInput, GridMark, PlotTransform};
+use std::sync::RwLock;
+
 #[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Scientific(pub f64, pub i32);
 
 const LINTHRESH: f64 = 1e-50;
 const LOG_LINTHRESH: f64 = -50.0;
 
-// impl std::ops::Add for Scientific {
-//     type Output = Self;
-
-//     fn add(self, other: Self) -> Self {
-//         // Convert to f64, add, then convert back
-//         let result = self.approx_f64() + other.approx_f64();
-//         Scientific::from_f64(result)
-//     }
-// }
-
-// impl std::ops::Sub for Scientific {
-//     type Output = Self;
-
-//     fn sub(self, other: Self) -> Self {
-//         let result = self.approx_f64() - other.approx_f64();
-//         Scientific::from_f64(result)
-//     }
-// }
-
-// impl std::ops::Mul for Scientific {
-//     type Output = Self;
-
-//     fn mul(self, other: Self) -> Self {
-//         // Multiply mantissas and add exponents
-//         Scientific(self.0 * other.0, self.1 + other.1)
-//     }
-// }
-
-// impl std::ops::Div for Scientific {
-//     type Output = Self;
-
-//     fn div(self, other: Self) -> Self {
-//         // Divide mantissas and subtract exponents
-//         Scientific(self.0 / other.0, self.1 - other.1)
-//     }
-// }
-
-// impl std::cmp::PartialOrd for Scientific {
-//     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-//         self.approx_f64().partial_cmp(&other.approx_f64())
-//     }
-// }
-
-// impl std::cmp::PartialEq for Scientific {
-//     fn eq(&self, other: &Self) -> bool {
-//         self.approx_f64() == other.approx_f64()
-//     }
-// }
-
-// impl std::cmp::PartialOrd<f64> for Scientific {
-//     fn partial_cmp(&self, other: &f64) -> Option<std::cmp::Ordering> {
-//         self.approx_f64().partial_cmp(other)
-//     }
-// }
-
-// impl std::cmp::PartialEq<f64> for Scientific {
-//     fn eq(&self, other: &f64) -> bool {
-//         self.approx_f64() == *other
-//     }
-// }
+/// How numbers are printed in table cells, tooltips and axis labels.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct NumberFormat {
+    /// Significant digits
+    pub digits: usize,
+    pub always_scientific: bool,
+    /// Use "," as the decimal separator
+    pub decimal_comma: bool,
+}
+
+impl NumberFormat {
+    const DEFAULT: Self = Self {
+        digits: 3,
+        always_scientific: false,
+        decimal_comma: false,
+    };
+
+    // Formats `±mantissa * 10^exponent`, `mantissa` being normalized to [1, 10)
+    fn format_parts(&self, negative: bool, mantissa: f64, exponent: i32) -> String {
+        let sign_str = if negative { "-" } else { "" };
+        let digits = self.digits.max(1);
+
+        // Rounding may carry into the next power of ten (9.99 -> 10.0)
+        let decimals = digits - 1;
+        let scale = 10f64.powi(decimals as i32);
+        let (mut mantissa, mut exponent) = (mantissa, exponent);
+        if (mantissa * scale).round() / scale >= 10.0 {
+            mantissa /= 10.0;
+            exponent += 1;
+        }
+
+        // If the exponent is very small (e.g. -6), we prefer "1.0e-6" over "0.000001"
+        let res = if self.always_scientific || !(-2..=3).contains(&exponent) {
+            format!("{}{:.*}e{}", sign_str, decimals, mantissa, exponent)
+        } else {
+            // For numbers like 0.5, 0.01, 10.0
+            let real_val = mantissa * 10f64.powi(exponent);
+            let decimals = (digits as i32 - 1 - exponent).max(0) as usize;
+            format!("{}{:.*}", sign_str, decimals, real_val)
+        };
+        if self.decimal_comma {
+            res.replace('.', ",")
+        } else {
+            res
+        }
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static NUMBER_FORMAT: RwLock<NumberFormat> = RwLock::new(NumberFormat::DEFAULT);
+
+pub fn number_format() -> NumberFormat {
+    *NUMBER_FORMAT.read().unwrap()
+}
+
+/// Changes the global format. Strings already built (e.g. table cells) are not updated.
+pub fn set_number_format(format: NumberFormat) {
+    *NUMBER_FORMAT.write().unwrap() = format;
+}
+
+/// Formats a plain f64 according to the global [`NumberFormat`]
+pub fn format_f64(val: f64) -> String {
+    if val == 0.0 || !val.is_finite() {
+        return val.to_string();
+    }
+    let exponent = val.abs().log10().floor();
+    let mantissa = val.abs() / 10f64.powf(exponent);
+    number_format().format_parts(val < 0.0, mantissa, exponent as i32)
+}
+
+// Past this many orders of magnitude the smaller addend doesn't change an f64 mantissa
+const ADD_PRECISION_DIGITS: i32 = 20;
+
+impl std::ops::Neg for Scientific {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Scientific(-self.0, self.1)
+    }
+}
+
+impl std::ops::Add for Scientific {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (a, b) = (self.normalize(), other.normalize());
+        if a.0 == 0.0 {
+            return b;
+        } else if b.0 == 0.0 {
+            return a;
+        }
+        // Align to the larger exponent, so neither side over- or underflows
+        let (big, small) = if a.1 >= b.1 { (a, b) } else { (b, a) };
+        let shift = big.1 - small.1;
+        if shift > ADD_PRECISION_DIGITS {
+            return big;
+        }
+        Scientific(big.0 + small.0 / 10f64.powi(shift), big.1).normalize()
+    }
+}
+
+impl std::ops::Sub for Scientific {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl std::ops::Mul for Scientific {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        // Multiply mantissas and add exponents
+        let (a, b) = (self.normalize(), other.normalize());
+        Scientific(a.0 * b.0, a.1 + b.1).normalize()
+    }
+}
+
+impl std::ops::Div for Scientific {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        // Divide mantissas and subtract exponents
+        let (a, b) = (self.normalize(), other.normalize());
+        Scientific(a.0 / b.0, a.1 - b.1).normalize()
+    }
+}
+
+impl PartialEq for Scientific {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Scientific {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        use std::cmp::Ordering;
+        let (a, b) = (self.normalize(), other.normalize());
+        if a.0.is_nan() || b.0.is_nan() {
+            return None;
+        }
+        // Infinities and zeros have no meaningful exponent
+        if !a.0.is_finite() || !b.0.is_finite() || a.0 == 0.0 || b.0 == 0.0 {
+            return a.0.partial_cmp(&b.0);
+        }
+        let sign = a.0.signum().partial_cmp(&b.0.signum())?;
+        if sign != Ordering::Equal {
+            return Some(sign);
+        }
+        // Same sign: a larger exponent means a larger magnitude
+        let by_magnitude = a.1.cmp(&b.1).then(a.0.abs().total_cmp(&b.0.abs()));
+        Some(if a.0 > 0.0 {
+            by_magnitude
+        } else {
+            by_magnitude.reverse()
+        })
+    }
+}
 
 impl Scientific {
-    // pub fn from_f64(val: f64) -> Self {
-    //     if val == 0.0 {
-    //         return Scientific(0.0, 0);
-    //     }
+    pub fn from_f64(val: f64) -> Self {
+        Scientific(val, 0).normalize()
+    }
 
-    //     let abs_val = val.abs();
-    //     let sign = val.signum();
-    //     let log10 = abs_val.log10();
-    //     let exponent = log10.floor() as i32;
-    //     let mantissa = sign * abs_val / 10f64.powi(exponent);
+    /// Same value with the mantissa in [1, 10) (or zero), so that exponents are comparable.
+    /// Non-finite mantissas are kept as they are
+    pub fn normalize(self) -> Self {
+        if self.0 == 0.0 {
+            return Scientific(0.0, 0);
+        } else if !self.0.is_finite() {
+            return self;
+        }
+        let shift = self.0.abs().log10().floor();
+        let mut mantissa = self.0 / 10f64.powf(shift);
+        let mut exponent = self.1 + shift as i32;
+        // log10 may be off by one ulp around the powers of ten
+        if mantissa.abs() >= 10.0 {
+            mantissa /= 10.0;
+            exponent += 1;
+        } else if mantissa.abs() < 1.0 {
+            mantissa *= 10.0;
+            exponent -= 1;
+        }
+        Scientific(mantissa, exponent)
+    }
 
-    //     Scientific(mantissa, exponent)
-    // }
+    pub fn abs(self) -> Self {
+        Scientific(self.0.abs(), self.1)
+    }
+
+    /// Total order for sorting, NaN last
+    pub fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.partial_cmp(other)
+            .unwrap_or_else(|| self.0.is_nan().cmp(&other.0.is_nan()))
+    }
 
     pub fn approx_f64(&self) -> f64 {
         self.0 * 10f64.powi(self.1)
     }
 
-    // pub fn abs(&self) -> Self {
-    //     Scientific(self.0.abs(), self.1)
-    // }
+    /// Arithmetic mean with compensated summation, relative to the largest exponent so that
+    /// values far outside the f64 range neither overflow nor vanish
+    pub fn mean(values: &[Self]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let values: Vec<Self> = values.iter().map(|v| v.normalize()).collect();
+        let exponent = values
+            .iter()
+            .filter(|v| v.0 != 0.0)
+            .map(|v| v.1)
+            .max()
+            .unwrap_or(0);
+        let sum = kahan_sum(values.iter().map(|v| v.0 * 10f64.powi(v.1 - exponent)));
+        Some(Scientific(sum / values.len() as f64, exponent).normalize())
+    }
+
+    /// Mean in log space of the absolute values (geometric mean). Exact zeros are left out; zero
+    /// if there is nothing else
+    pub fn geometric_mean(values: &[Self]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let logs: Vec<f64> = values
+            .iter()
+            .filter(|v| v.0 != 0.0)
+            .map(|v| v.0.abs().log10() + v.1 as f64)
+            .collect();
+        if logs.is_empty() {
+            return Some(Scientific(0.0, 0));
+        }
+        let log = kahan_sum(logs.iter().copied()) / logs.len() as f64;
+        let exponent = log.floor();
+        Some(Scientific(10f64.powf(log - exponent), exponent as i32))
+    }
 
     pub fn symlog(&self) -> f64 {
         let mantissa = self.0;
@@ -119,68 +283,294 @@ impl Scientific {
             // --- SMALL / TRANSITION NUMBERS (Exact Math) ---
             // Formula: log10(1 + |x|/L)
             // We need this because near the threshold, the "+ 1" creates the smooth curve.
-            // Since magnitude_diff < 16, val_f64 will not overflow f64.
+            // |x|/L is computed from the logs, so tiny exponents don't underflow in between.
 
-            sign * (1.0 + self.approx_f64().abs() / LINTHRESH).log10()
+            sign * (1.0 + 10f64.powf(magnitude_diff)).log10()
         }
     }
 
+    // Sign, mantissa normalized to [1, 10) and exponent. The stored mantissa isn't guaranteed to
+    // be normalized
+    fn normalized(&self) -> (bool, f64, i32) {
+        let normalized = self.normalize();
+        (normalized.0 < 0.0, normalized.0.abs(), normalized.1)
+    }
+
+    /// Always-scientific form with `digits` significant digits and "." as the separator, for
+    /// machine-readable output
+    pub fn format_exact(&self, digits: usize) -> String {
+        if self.0 == 0.0 {
+            return "0".to_string();
+        }
+        let format = NumberFormat {
+            digits,
+            always_scientific: true,
+            decimal_comma: false,
+        };
+        let (negative, mantissa, exponent) = self.normalized();
+        format.format_parts(negative, mantissa, exponent)
+    }
+
     pub fn format(&self) -> String {
         if self.0 == 0.0 {
             return "0".to_string();
         }
 
-        let mantissa = self.0;
-        let exponent = self.1;
-        let sign_str = if mantissa < 0.0 { "-" } else { "" };
-        let abs_mantissa = mantissa.abs();
+        let (negative, mantissa, exponent) = self.normalized();
+        number_format().format_parts(negative, mantissa, exponent)
+    }
+}
 
-        // Formatting rules
-        // If the exponent is very small (e.g. -6), we prefer "1.0e-6" over "0.000001"
-        if exponent < -2 || exponent > 3 {
-            format!("{}{:.1}e{:.0}", sign_str, abs_mantissa, exponent)
-        } else {
-            // For numbers like 0.5, 0.01, 10.0
-            let real_val = abs_mantissa * 10f64.powi(exponent);
-            // limit decimal places to avoid clutter
-            format!("{}{:.6}", sign_str, real_val)
-        }
+// Kahan summation: the running compensation keeps the low-order bits lost by each addition
+fn kahan_sum(values: impl Iterator<Item = f64>) -> f64 {
+    let mut sum = 0.0;
+    let mut compensation = 0.0;
+    for v in values {
+        let y = v - compensation;
+        let t = sum + y;
+        compensation = (t - sum) - y;
+        sum = t;
+    }
+    sum
+}
+
+/// Inverse of [`Scientific::symlog`], for values representable as f64
+pub fn symlog_inverse(val: f64) -> f64 {
+    let sign = val.signum();
+    let abs_val = val.abs();
+    if abs_val > 16.0 {
+        // Log region, see `symlog`
+        sign * 10f64.powf(LOG_LINTHRESH + abs_val)
+    } else {
+        sign * LINTHRESH * (10f64.powf(abs_val) - 1.0)
     }
 }
 
 pub fn symlog_formatter(val: f64) -> String {
     if val == 0.0 {
         return "0".to_string();
-    } else if (val + LOG_LINTHRESH).abs() < 0.00001 {
-        return "1".to_string();
     }
 
-    let sign_str = if val < 0.0 { "-" } else { "" };
-    let abs_plot_y = val.abs();
-
-    // INVERSE TRANSFORM
-    // |x| = L * (10^|y| - 1)
-
-    // Because we are using a tiny LINTHRESH (1e-20),
-    // almost all visible numbers on your plot will be in the "Log" region.
-    // In the log region: |x| ~= L * 10^|y|
-    // So: log10(|x|) = log10(L) + |y|
-
-    let target_log10 = LOG_LINTHRESH + abs_plot_y;
+    // Exact inverse, so that labels inside the linear region near zero aren't off
+    let target_log10 = symlog_log10(val);
 
     // Reconstruct Scientific Notation
     let exponent = target_log10.floor();
     let fractional = target_log10 - exponent;
     let mantissa = 10f64.powf(fractional);
 
-    // Formatting rules
-    // If the exponent is very small (e.g. -6), we prefer "1.0e-6" over "0.000001"
-    if exponent < -2.0 || exponent > 3.0 {
-        format!("{}{:.1}e{:.0}", sign_str, mantissa, exponent)
+    number_format().format_parts(val < 0.0, mantissa, exponent as i32)
+}
+
+/// Shades |x| < [`LINTHRESH`], where the symlog scale is linear rather than logarithmic. Values of
+/// either sign there are squeezed towards zero, so their distances don't mean orders of magnitude.
+///
+/// Painted over the shown plot rather than added as an item, so it doesn't affect auto-bounds.
+pub fn paint_linear_region(ui: &Ui, transform: &PlotTransform) {
+    let limit = Scientific(LINTHRESH, 0).symlog();
+    let frame = *transform.frame();
+    let top = transform.position_from_point_y(limit);
+    let bottom = transform.position_from_point_y(-limit);
+    let rect = Rect::from_x_y_ranges(frame.x_range(), top.min(bottom)..=top.max(bottom));
+    let rect = rect.intersect(frame);
+    if rect.is_positive() {
+        ui.painter_at(frame)
+            .rect_filled(rect, 0.0, Color32::from_gray(128).gamma_multiply(0.15));
+    }
+}
+
+// log10 of |x| for a symlog coordinate, without going through f64 values of x
+fn symlog_log10(val: f64) -> f64 {
+    let abs_val = val.abs();
+    if abs_val > 16.0 {
+        LOG_LINTHRESH + abs_val
+    } else {
+        (LINTHRESH * (10f64.powf(abs_val) - 1.0)).log10()
+    }
+}
+
+// Decades per major tick: 1, 2, 5, 10, 20, 50... so that ticks are at least `base_step_size`
+// apart, a decade being about one unit of the axis
+fn decade_step(base_step_size: f64) -> i64 {
+    let mut step = 1i64;
+    let mut factors = [2, 5, 2].into_iter().cycle();
+    while (step as f64) < base_step_size {
+        step *= factors.next().unwrap();
+    }
+    step
+}
+
+// Major marks at 10^k for the decades first..=last that are multiples of `step`, and with `step`
+// of 1 minor ones at m * 10^k within `low..=high`. `position` places m * 10^k on the axis.
+fn decade_marks(
+    (first, last): (i64, i64),
+    step: i64,
+    (low, high): (f64, f64),
+    position: impl Fn(f64, i64) -> f64,
+    marks: &mut Vec<GridMark>,
+) {
+    for k in (first.div_euclid(step) * step..=last).step_by(step as usize) {
+        if k >= first {
+            marks.push(GridMark {
+                value: position(1.0, k),
+                step_size: if k % (step * 10) == 0 {
+                    step as f64 * 10.0
+                } else {
+                    step as f64
+                },
+            });
+        }
+    }
+    if step == 1 {
+        for k in first - 1..=last {
+            for m in 2..=9 {
+                let value = position(m as f64, k);
+                if (low..=high).contains(&value) {
+                    marks.push(GridMark {
+                        value,
+                        step_size: 0.1,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Grid of a symlog axis: major ticks at exact powers of ten, minor ones at 2..9 times them.
+///
+/// Decades are about one unit of the axis apart, so when zoomed out only every 2nd, 5th, 10th...
+/// decade is marked. Decades below [`LINTHRESH`] crowd around zero and are skipped.
+pub fn symlog_grid_spacer(input: GridInput) -> Vec<GridMark> {
+    let (min, max) = input.bounds;
+    if input.base_step_size < f64::EPSILON || !min.is_finite() || !max.is_finite() {
+        return Vec::new();
+    }
+    let step = decade_step(input.base_step_size);
+
+    let mut marks = Vec::new();
+    if min <= 0.0 && 0.0 <= max {
+        marks.push(GridMark {
+            value: 0.0,
+            step_size: step as f64 * 10.0,
+        });
+    }
+    for sign in [1.0, -1.0] {
+        // Visible part of this half of the axis, as |y|
+        let (low, high) = if sign > 0.0 {
+            (min.max(0.0), max)
+        } else {
+            ((-max).max(0.0), -min)
+        };
+        if high <= low {
+            continue;
+        }
+        let first = symlog_log10(low).max(LOG_LINTHRESH).ceil() as i64;
+        let last = symlog_log10(high).floor() as i64;
+        let mut half = Vec::new();
+        decade_marks(
+            (first, last),
+            step,
+            (low, high),
+            |m, k| Scientific(m, k as i32).symlog(),
+            &mut half,
+        );
+        let limit = Scientific(LINTHRESH, 0).symlog();
+        half.retain(|mark| mark.value >= limit);
+        marks.extend(half.into_iter().map(|mark| GridMark {
+            value: sign * mark.value,
+            ..mark
+        }));
+    }
+    marks
+}
+
+/// Grid of an axis plotted as log10 of the value, e.g. the log-x iteration axis.
+pub fn log10_grid_spacer(input: GridInput) -> Vec<GridMark> {
+    let (min, max) = input.bounds;
+    if input.base_step_size < f64::EPSILON || !min.is_finite() || !max.is_finite() {
+        return Vec::new();
+    }
+    let mut marks = Vec::new();
+    decade_marks(
+        (min.ceil() as i64, max.floor() as i64),
+        decade_step(input.base_step_size),
+        (min, max),
+        |m, k| m.log10() + k as f64,
+        &mut marks,
+    );
+    marks
+}
+
+/// Label of a log10-plotted axis: whole values as they are, the rest in the global format
+pub fn log10_formatter(val: f64) -> String {
+    let value = 10f64.powf(val);
+    if value < 1e6 && (value - value.round()).abs() < 1e-6 * value {
+        format!("{}", value.round())
     } else {
-        // For numbers like 0.5, 0.01, 10.0
-        let real_val = mantissa * 10f64.powi(exponent as i32);
-        // limit decimal places to avoid clutter
-        format!("{}{:.4}", sign_str, real_val)
+        format_f64(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Values whose f64 form is exact enough to check against
+    fn moderate() -> impl Strategy<Value = Scientific> {
+        (-9.99f64..9.99, -100i32..100).prop_map(|(m, e)| Scientific(m, e))
+    }
+
+    // Exponents far outside the f64 range
+    fn extreme() -> impl Strategy<Value = Scientific> {
+        (1.0f64..10.0, any::<bool>(), -5000i32..5000)
+            .prop_map(|(m, negative, e)| Scientific(if negative { -m } else { m }, e))
+    }
+
+    fn close(a: Scientific, b: f64) -> bool {
+        let a = a.approx_f64();
+        a == b || (a - b).abs() <= 1e-12 * a.abs().max(b.abs())
+    }
+
+    proptest! {
+        #[test]
+        fn normalize_keeps_value(a in moderate()) {
+            let n = a.normalize();
+            prop_assert!(n.0 == 0.0 || (1.0..10.0).contains(&n.0.abs()));
+            prop_assert!(close(n, a.approx_f64()));
+        }
+
+        #[test]
+        fn arithmetic_matches_f64(a in moderate(), b in moderate()) {
+            let (x, y) = (a.approx_f64(), b.approx_f64());
+            prop_assert!(close(a * b, x * y));
+            prop_assert!(close(a + b, x + y) || (x + y).abs() < 1e-12 * x.abs().max(y.abs()));
+            prop_assert!(close(a - b, x - y) || (x - y).abs() < 1e-12 * x.abs().max(y.abs()));
+            if b.0 != 0.0 {
+                prop_assert!(close(a / b, x / y));
+            }
+        }
+
+        #[test]
+        fn ordering_matches_f64(a in moderate(), b in moderate()) {
+            prop_assert_eq!(a.partial_cmp(&b), a.approx_f64().partial_cmp(&b.approx_f64()));
+        }
+
+        #[test]
+        fn extreme_values_round_trip(a in extreme(), b in extreme()) {
+            let q = (a * b) / b;
+            prop_assert_eq!(q.1, a.normalize().1);
+            prop_assert!((q.0 - a.normalize().0).abs() < 1e-12);
+            prop_assert_eq!(a - a, Scientific(0.0, 0));
+        }
+
+        #[test]
+        fn extreme_ordering_follows_exponents(a in extreme(), b in extreme()) {
+            let (a, b) = (a.abs(), b.abs());
+            if a.1 != b.1 {
+                prop_assert_eq!(a < b, a.1 < b.1);
+            }
+            prop_assert!(a + b >= a && a + b >= b);
+        }
     }
 }
@@ -0,0 +1,155 @@
+use crate::accessibility::describe_plot;
+use crate::app::{LineKey, SeriesDataRef, Vis, format_item_name, line_id, plot_controls};
+use crate::panel::PlotPanel;
+use eframe::egui;
+use egui::{Color32, Rect, Ui};
+use egui_plot::{Line, MarkerShape, Plot, PlotPoint, Points, uniform_grid_spacer};
+use std::collections::BTreeMap;
+
+/// Events and errors of every filtered accel record against n, one swimlane per record.
+///
+/// Only records that reported something get a lane, otherwise the interesting ones drown in empty
+/// rows. Lane `i` is drawn at y = -i, so the first record is on top.
+pub struct TimelinePanel {
+    lanes: Vec<String>,
+    keys: Vec<LineKey>,
+    // Extent of the computed range of each lane
+    spans: Vec<[PlotPoint; 2]>,
+    // Event name -> markers
+    events: BTreeMap<String, Vec<PlotPoint>>,
+    errors: Vec<PlotPoint>,
+}
+
+impl PlotPanel for TimelinePanel {
+    fn build(data: &[SeriesDataRef], _symlog: bool) -> Self {
+        let mut lanes = Vec::new();
+        let mut keys = Vec::new();
+        let mut spans = Vec::new();
+        let mut events = BTreeMap::<String, Vec<PlotPoint>>::new();
+        let mut errors = Vec::new();
+
+        for (series, accel_records) in data {
+            for accel_record in accel_records {
+                if accel_record.events.is_empty() && accel_record.errors.is_empty() {
+                    continue;
+                }
+                let y = -(lanes.len() as f64);
+                lanes.push(format_item_name(series, &accel_record.accel_info));
+                keys.push(LineKey::new(series, &accel_record.accel_info));
+
+                let first = series.computed.first().map_or(0, |c| c.n);
+                let last = series.computed.last().map_or(first, |c| c.n);
+                spans.push([
+                    PlotPoint::new(first as f64, y),
+                    PlotPoint::new(last as f64, y),
+                ]);
+
+                for event in &accel_record.events {
+                    events
+                        .entry(event.name.clone())
+                        .or_default()
+                        .push(PlotPoint::new(event.n as f64, y));
+                }
+                errors.extend(
+                    accel_record
+                        .errors
+                        .iter()
+                        .map(|e| PlotPoint::new(e.n as f64, y)),
+                );
+            }
+        }
+
+        Self {
+            lanes,
+            keys,
+            spans,
+            events,
+            errors,
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        "timeline"
+    }
+
+    fn title(&self) -> &str {
+        "События и ошибки"
+    }
+
+    fn ui(&self, vis: &mut Vis, ui: &mut Ui) -> Option<Rect> {
+        if self.lanes.is_empty() {
+            ui.label("Нет событий или ошибок для отображения");
+            return None;
+        }
+
+        let lane_name = |y: f64| {
+            let i = -y.round();
+            if (y + i).abs() > 1e-6 || i < 0.0 {
+                return None;
+            }
+            self.lanes.get(i as usize)
+        };
+        let plot = Plot::new("timeline")
+            .allow_zoom(true)
+            .allow_drag(true)
+            .height((self.lanes.len() as f32 * 24.0).clamp(200.0, 900.0))
+            .y_grid_spacer(uniform_grid_spacer(|_| [1.0, 5.0, 25.0]))
+            .y_axis_formatter(move |mark, _| lane_name(mark.value).cloned().unwrap_or_default())
+            .label_formatter(move |name, value| {
+                let lane = lane_name(value.y).map_or("", String::as_str);
+                format!("{name}\nn={:.0}\n{lane}", value.x)
+            })
+            .legend(egui_plot::Legend::default());
+        let plot = vis.axis_labels("timeline", plot, "Итерация n", "");
+        let plot = vis.plot_wheel(plot);
+
+        let restore = vis.take_bounds("timeline");
+        let plot = plot.show(ui, |plot_ui| {
+            plot_controls(plot_ui);
+            if let Some(bounds) = restore {
+                plot_ui.set_plot_bounds(bounds);
+            }
+            for (i, (span, key)) in self.spans.iter().zip(&self.keys).enumerate() {
+                let selected = vis.is_selected(key);
+                plot_ui.line(
+                    Line::new(span.as_slice())
+                        .id(line_id("timeline", i))
+                        .color(Color32::from_gray(90))
+                        .highlight(selected)
+                        .width(if selected { 4.0 } else { 1.0 }),
+                );
+            }
+            for (name, points) in &self.events {
+                plot_ui.points(
+                    Points::new(points.as_slice())
+                        .name(name)
+                        .shape(MarkerShape::Diamond)
+                        .radius(5.0),
+                );
+            }
+            if !self.errors.is_empty() {
+                plot_ui.points(
+                    Points::new(self.errors.as_slice())
+                        .name("ошибки")
+                        .color(Color32::RED)
+                        .shape(MarkerShape::Cross)
+                        .radius(6.0),
+                );
+            }
+            vis.draw_annotations("timeline", plot_ui, false, false);
+        });
+        vis.select_from_plot(&plot, |id| {
+            (0..self.keys.len())
+                .find(|i| line_id("timeline", i) == id)
+                .map(|i| &self.keys[i])
+        });
+        vis.annotation_menu("timeline", &plot, false, false);
+        describe_plot(
+            &plot.response,
+            format!("Хронология событий и ошибок, записей: {}", self.lanes.len()),
+        );
+        vis.track_hover(&plot.response);
+        vis.remember_bounds("timeline", plot.transform.bounds());
+        Some(plot.response.rect)
+    }
+}
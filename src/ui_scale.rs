@@ -0,0 +1,90 @@
+//! Size of the whole interface: egui's zoom (Ctrl+= / Ctrl+- / Ctrl+0) and the font size. Kept
+//! in the app data directory next to the window geometry, since it depends on the display rather
+//! than on the data
+
+use eframe::egui;
+use egui::{Context, Ui};
+use serde::{Deserialize, Serialize};
+
+const STORAGE_KEY: &str = "ui_scale";
+// Font sizes are relative to egui's body text, so headings stay proportionate
+const BASE_FONT_SIZE: f32 = 12.5;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiScale {
+    // Points per pixel relative to the display's native scale
+    pub zoom: f32,
+    // Body text size in points; the other text styles are scaled with it
+    pub font_size: f32,
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            font_size: BASE_FONT_SIZE,
+        }
+    }
+}
+
+impl UiScale {
+    /// Saved scale, or the default one on the first start
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        storage
+            .and_then(|s| s.get_string(STORAGE_KEY))
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        match serde_json::to_string(self) {
+            Ok(text) => storage.set_string(STORAGE_KEY, text),
+            Err(e) => eprintln!("Failed to save the UI scale: {}", e),
+        }
+    }
+
+    pub fn apply(&self, ctx: &Context) {
+        ctx.set_zoom_factor(self.zoom);
+        let base = egui::Style::default().text_styles;
+        let factor = self.font_size / BASE_FONT_SIZE;
+        ctx.all_styles_mut(|style| {
+            for (text_style, font) in &mut style.text_styles {
+                if let Some(default) = base.get(text_style) {
+                    font.size = default.size * factor;
+                }
+            }
+        });
+    }
+
+    /// Takes over the zoom changed with the keyboard shortcuts, which egui handles itself
+    pub fn follow_zoom(&mut self, ctx: &Context) {
+        self.zoom = ctx.zoom_factor();
+    }
+
+    /// Contents of the scale menu
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let mut changed = ui
+            .add(
+                egui::Slider::new(&mut self.zoom, 0.5..=3.0)
+                    .step_by(0.1)
+                    .text("Масштаб"),
+            )
+            .on_hover_text("Ctrl+= / Ctrl+- / Ctrl+0")
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut self.font_size, 8.0..=32.0)
+                    .step_by(0.5)
+                    .text("Размер шрифта"),
+            )
+            .changed();
+        if ui.button("Сбросить").clicked() {
+            *self = Self::default();
+            changed = true;
+        }
+        if changed {
+            self.apply(ui.ctx());
+        }
+    }
+}
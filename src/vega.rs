@@ -0,0 +1,195 @@
+//! Plots as Vega-Lite specifications with their data inline, so figures can be restyled in other
+//! tools while the data still comes from vizr
+
+use crate::app::{PlotOptions, SeriesDataRef, format_method_name, format_series_name};
+use crate::grouping::series_family;
+use crate::legend::Dimension;
+use crate::symlog::Scientific;
+use crate::workspace::{Annotation, PlotLabels};
+use serde_json::{Value, json};
+
+const SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+/// Panels that can be exported, see `spec`
+pub fn supports(panel_id: &str) -> bool {
+    matches!(panel_id, "convergence" | "error")
+}
+
+// Field of the data rows holding the value of a dimension
+fn field(dimension: Dimension) -> &'static str {
+    match dimension {
+        Dimension::Precision => "precision",
+        Dimension::Method => "method",
+        Dimension::Series => "series",
+        Dimension::Family => "family",
+    }
+}
+
+// NaN and infinities become nulls, which Vega-Lite leaves out
+fn finite(value: Scientific) -> Option<f64> {
+    let value = value.approx_f64();
+    value.is_finite().then_some(value)
+}
+
+/// Specification of the convergence (real parts) or error plot of `data`, with the colors and
+/// line styles of the options, the custom texts and the annotations of the plot; `None` for
+/// other panels
+pub fn spec(
+    panel_id: &str,
+    title: &str,
+    data: &[SeriesDataRef],
+    opts: &PlotOptions,
+    labels: &PlotLabels,
+    annotations: &[Annotation],
+    description: &str,
+) -> Option<Value> {
+    let error = match panel_id {
+        "convergence" => false,
+        "error" => true,
+        _ => return None,
+    };
+    let y = if error { "deviation" } else { "value" };
+
+    // One row per point; limits are rows without n
+    let mut rows = Vec::new();
+    for (series, accel_records) in data {
+        let series_name = format_series_name(series);
+        let family = series_family(&series.name);
+        let row = |line: String,
+                   kind: &str,
+                   method: Option<String>,
+                   n: Option<i32>,
+                   value: Option<f64>,
+                   deviation: Option<f64>| {
+            json!({
+                "line": line,
+                "kind": kind,
+                "series": series_name,
+                "family": family,
+                "precision": series.precision.to_string(),
+                "method": method,
+                "n": n,
+                "value": value,
+                "deviation": deviation,
+            })
+        };
+        if opts.show_partial_sums {
+            let line = format!("{} {series_name}", series.precision);
+            for c in series.computed.iter() {
+                rows.push(row(
+                    line.clone(),
+                    "partial_sums",
+                    None,
+                    Some(c.n),
+                    finite(c.value.real),
+                    finite(c.deviation),
+                ));
+            }
+        }
+        if opts.show_limits && !error {
+            let line = format!("{} {series_name}", series.precision);
+            let value = finite(series.series_limit.real);
+            rows.push(row(line, "limit", None, None, value, None));
+        }
+        for accel_record in accel_records {
+            let method = format_method_name(&accel_record.accel_info);
+            let line = format!("{} {method} {series_name}", series.precision);
+            let points = series.computed.iter().zip(accel_record.computed.iter());
+            for (c, accel) in points {
+                let Some(accel) = accel else {
+                    continue;
+                };
+                rows.push(row(
+                    line.clone(),
+                    "accel",
+                    Some(method.clone()),
+                    Some(c.n),
+                    finite(accel.value.real),
+                    finite(accel.deviation),
+                ));
+            }
+        }
+    }
+
+    let x_title = labels.x_axis_or("Итерация n");
+    let y_title = labels.y_axis_or(if error {
+        "Ошибка"
+    } else {
+        "Значение"
+    });
+    let mut x = json!({ "field": "n", "type": "quantitative", "title": x_title });
+    if opts.log_x {
+        x["scale"] = json!({ "type": "log" });
+    }
+    let mut y_encoding = json!({ "field": y, "type": "quantitative", "title": y_title });
+    if error {
+        y_encoding["scale"] = json!({ "type": "log" });
+    }
+    let color = field(opts.color_by);
+    let mut lines = json!({
+        "transform": [{ "filter": "datum.kind != 'limit' && isValid(datum.n)" }],
+        "mark": { "type": "line", "point": false },
+        "encoding": {
+            "x": x,
+            "y": y_encoding,
+            "color": { "field": color, "type": "nominal", "title": opts.color_by.label() },
+            "detail": { "field": "line", "type": "nominal" },
+            "tooltip": [
+                { "field": "line", "type": "nominal" },
+                { "field": "n", "type": "quantitative" },
+                { "field": y, "type": "quantitative", "format": ".3e" },
+            ],
+        },
+    });
+    if let Some(style) = opts.style_by {
+        lines["encoding"]["strokeDash"] =
+            json!({ "field": field(style), "type": "nominal", "title": style.label() });
+    }
+    let mut layers = vec![lines];
+    if !error && opts.show_limits {
+        layers.push(json!({
+            "transform": [{ "filter": "datum.kind == 'limit'" }],
+            "mark": { "type": "rule", "color": "red", "strokeWidth": 2 },
+            "encoding": {
+                "y": { "field": "value", "type": "quantitative" },
+                "tooltip": [{ "field": "line", "type": "nominal" }],
+            },
+        }));
+    }
+    // Annotations are in data coordinates, as the rows
+    let texts: Vec<Value> = annotations
+        .iter()
+        .filter(|a| a.plot == panel_id)
+        .map(|a| json!({ "n": a.x, y: a.y, "text": format!("◆ {}", a.text) }))
+        .collect();
+    if !texts.is_empty() {
+        layers.push(json!({
+            "data": { "values": texts },
+            "mark": { "type": "text", "align": "left", "baseline": "bottom" },
+            "encoding": {
+                "x": { "field": "n", "type": "quantitative" },
+                "y": { "field": y, "type": "quantitative" },
+                "text": { "field": "text", "type": "nominal" },
+            },
+        }));
+    }
+
+    let title = if labels.title.is_empty() {
+        title
+    } else {
+        &labels.title
+    };
+    let mut spec = json!({
+        "$schema": SCHEMA,
+        "title": title,
+        "description": description,
+        "width": 800,
+        "height": 500,
+        "data": { "values": rows },
+        "layer": layers,
+    });
+    if !labels.caption.is_empty() {
+        spec["title"] = json!({ "text": title, "subtitle": labels.caption });
+    }
+    Some(spec)
+}
@@ -0,0 +1,185 @@
+use crate::app::{PlotOptions, TableColumn};
+use crate::data_loader::{AccelInfo, Filters};
+use crate::metric::Metric;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Saved view: everything needed to return to what was on screen.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bookmark {
+    pub name: String,
+    pub note: String,
+    pub filters: Filters,
+    pub quick_filters: Filters,
+    pub compare_filters: Option<Filters>,
+    pub symlog: bool,
+    pub plot_options: PlotOptions,
+    // Plot id -> [min_x, min_y, max_x, max_y]
+    pub plot_bounds: HashMap<String, [f64; 4]>,
+}
+
+/// Text dropped onto a plot. Coordinates are in data space, i.e. before the symlog transform.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub plot: String,
+    pub x: f64,
+    pub y: f64,
+    pub text: String,
+}
+
+/// Texts of a plot for figures; empty fields keep the built-in ones.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlotLabels {
+    pub title: String,
+    pub x_axis: String,
+    pub y_axis: String,
+    pub caption: String,
+}
+
+impl PlotLabels {
+    pub fn x_axis_or<'a>(&'a self, default: &'a str) -> &'a str {
+        if self.x_axis.is_empty() {
+            default
+        } else {
+            &self.x_axis
+        }
+    }
+
+    pub fn y_axis_or<'a>(&'a self, default: &'a str) -> &'a str {
+        if self.y_axis.is_empty() {
+            default
+        } else {
+            &self.y_axis
+        }
+    }
+}
+
+/// Free-form note attached to an accel record.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecordNote {
+    pub series_id: i32,
+    pub accel_info: AccelInfo,
+    pub text: String,
+}
+
+/// Per-dataset state that outlives a session, stored as JSON (by default next to the data).
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    #[serde(skip)]
+    path: PathBuf,
+    pub bookmarks: Vec<Bookmark>,
+    pub annotations: Vec<Annotation>,
+    pub record_notes: Vec<RecordNote>,
+    // Table column order and visibility, empty for the default layout
+    #[serde(deserialize_with = "known_columns")]
+    pub table_columns: Vec<(TableColumn, bool)>,
+    // Panel id -> custom title, axis labels and caption
+    pub plot_labels: HashMap<String, PlotLabels>,
+}
+
+/// Saved table layout with columns of older versions mapped to their metrics and the
+/// unknown ones dropped; `TableColumn::normalize` appends whatever is missing.
+fn known_columns<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(TableColumn, bool)>, D::Error> {
+    let saved = Vec::<(serde_json::Value, bool)>::deserialize(deserializer)?;
+    Ok(saved
+        .into_iter()
+        .filter_map(|(column, visible)| {
+            let legacy = match column.as_str() {
+                Some("MinDeviation") => Some(Metric::MinError),
+                Some("MedianDeviation") => Some(Metric::MedianError),
+                Some("FinalDeviation") => Some(Metric::FinalError),
+                Some("P90Deviation") => Some(Metric::P90Error),
+                Some("ToTolerance") => Some(Metric::IterationsToTolerance),
+                _ => None,
+            };
+            let column = match legacy {
+                Some(metric) => TableColumn::Metric(metric),
+                None => serde_json::from_value(column).ok()?,
+            };
+            Some((column, visible))
+        })
+        .collect())
+}
+
+impl Workspace {
+    pub fn default_path(data_dir: &str) -> PathBuf {
+        Path::new(data_dir).join(".vizr-workspace.json")
+    }
+
+    /// Loads the workspace, starting an empty one if the file doesn't exist yet.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let mut workspace: Self = if path.exists() {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read workspace {}", path.display()))?;
+            serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse workspace {}", path.display()))?
+        } else {
+            Self::default()
+        };
+        workspace.path = path;
+        Ok(workspace)
+    }
+
+    /// Like `load`, but a file that doesn't parse, e.g. edited by hand or written by a newer
+    /// build, is moved aside to `<name>.bak` and an empty workspace is started instead.
+    pub fn load_or_reset(path: PathBuf) -> Result<Self> {
+        match Self::load(path.clone()) {
+            Err(e) if e.is::<serde_json::Error>() => {
+                let backup = path.with_extension("json.bak");
+                eprintln!(
+                    "{:#}; starting an empty workspace, the old one is kept as {}",
+                    e,
+                    backup.display()
+                );
+                std::fs::rename(&path, &backup).with_context(|| {
+                    format!("Failed to back up workspace to {}", backup.display())
+                })?;
+                Ok(Self {
+                    path,
+                    ..Self::default()
+                })
+            }
+            res => res,
+        }
+    }
+
+    pub fn record_note(&self, series_id: i32, accel_info: &AccelInfo) -> Option<&str> {
+        self.record_notes
+            .iter()
+            .find(|n| n.series_id == series_id && &n.accel_info == accel_info)
+            .map(|n| n.text.as_str())
+    }
+
+    /// Replaces the note of a record; an empty text removes it
+    pub fn set_record_note(&mut self, series_id: i32, accel_info: &AccelInfo, text: String) {
+        let i = self
+            .record_notes
+            .iter()
+            .position(|n| n.series_id == series_id && &n.accel_info == accel_info);
+        match i {
+            Some(i) if text.is_empty() => {
+                self.record_notes.remove(i);
+            }
+            Some(i) => self.record_notes[i].text = text,
+            None if text.is_empty() => {}
+            None => self.record_notes.push(RecordNote {
+                series_id,
+                accel_info: accel_info.clone(),
+                text,
+            }),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let text = serde_json::to_string_pretty(self)?;
+        std::fs::write(&self.path, text)
+            .with_context(|| format!("Failed to write workspace {}", self.path.display()))
+    }
+}
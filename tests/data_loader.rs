@@ -0,0 +1,786 @@
+//! `DataLoader::filter_data` on small datasets written by `support`, one per kind of input the
+//! conversion has to cope with.
+
+#![allow(dead_code)]
+
+#[path = "../src/data_loader.rs"]
+mod data_loader;
+#[cfg(feature = "duckdb")]
+#[path = "../src/duckdb_engine.rs"]
+mod duckdb_engine;
+mod support;
+#[path = "../src/symlog.rs"]
+mod symlog;
+
+use data_loader::{
+    DataLoader, Filters, NOT_SET, SchemaVersion, SeriesData, export_dataset, export_json, intern,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use std::collections::{BTreeMap, HashMap};
+use support::*;
+use symlog::Scientific;
+
+// Series 0 and 1 (f64) and 2 (f128) of the same family; the methods on series 0 cover nulls in
+// the middle of the points, errors and events
+fn write_standard(dataset: &Dataset, convert: impl Fn(&RecordBatch) -> RecordBatch) {
+    dataset.series(
+        "f64",
+        "geometric",
+        &convert(&batch(vec![
+            ("series_id", ints(&[0, 1])),
+            (
+                "arguments",
+                structure(vec![("x", strings(&[Some("0.5"), Some("0.9")]))], None),
+            ),
+            (
+                "series_limit",
+                complex(&[Some(("2", Some("0"))), Some(("1e1", Some("0")))]),
+            ),
+            (
+                "computed",
+                series_points(&[
+                    &[(1, "1", "1"), (2, "1.5", "5e-1"), (3, "1.75", "2.5e-1")],
+                    &[(1, "1", "9e0"), (2, "1.9", "8.1")],
+                ]),
+            ),
+        ])),
+    );
+    dataset.series(
+        "f128",
+        "geometric",
+        &convert(&batch(vec![
+            ("series_id", ints(&[2])),
+            (
+                "arguments",
+                structure(vec![("x", strings(&[Some("0.5")]))], None),
+            ),
+            ("series_limit", complex(&[Some(("2", Some("0")))])),
+            ("computed", series_points(&[&[(1, "1", "1")]])),
+        ])),
+    );
+
+    dataset.accelerations(
+        0,
+        &convert(&batch(vec![
+            ("accel_name", strings(&[Some("shanks"), Some("levin")])),
+            ("m_value", ints(&[1, 2])),
+            (
+                "additional_args",
+                structure(vec![("variant", strings(&[None, Some("t")]))], None),
+            ),
+            (
+                "computed",
+                accel_points(&[
+                    &[None, Some(("2", "1e-2")), Some(("2", "1e-4"))],
+                    &[None, None, Some(("2", "-3e-5"))],
+                ]),
+            ),
+            ("errors", errors(&[Some(&[]), Some(&[(3, "overflow")])])),
+            (
+                "events",
+                events(&[Some(&[]), Some(&[(2, "restart", "reset")])]),
+            ),
+        ])),
+    );
+    dataset.accelerations(
+        1,
+        &convert(&batch(vec![
+            ("accel_name", strings(&[Some("shanks")])),
+            ("m_value", ints(&[1])),
+            (
+                "additional_args",
+                structure(vec![("variant", strings(&[None]))], None),
+            ),
+            ("computed", accel_points(&[&[None, Some(("10", "1e-1"))]])),
+            ("errors", errors(&[Some(&[])])),
+            ("events", events(&[None])),
+        ])),
+    );
+    dataset.accelerations(
+        2,
+        &convert(&batch(vec![
+            ("accel_name", strings(&[Some("levin")])),
+            ("m_value", ints(&[2])),
+            (
+                "additional_args",
+                structure(vec![("variant", strings(&[Some("u")]))], None),
+            ),
+            ("computed", accel_points(&[&[None]])),
+            ("errors", errors(&[None])),
+            ("events", events(&[Some(&[])])),
+        ])),
+    );
+}
+
+// Loaded series by id, their methods by name and m: the query itself gives no order
+fn load(path: &str, filters: &Filters) -> Vec<SeriesData> {
+    load_with(data_loader::Engine::DataFusion, path, filters)
+}
+
+fn load_with(engine: data_loader::Engine, path: &str, filters: &Filters) -> Vec<SeriesData> {
+    let page = block_on(async {
+        DataLoader::with_engine(path, engine)
+            .await?
+            .filter_data(filters, None)
+            .await
+    })
+    .unwrap_or_else(|e| panic!("Failed to load {path}: {e:?}"));
+    let mut data = page.data;
+    data.sort_by_key(|(series, _)| series.series_id);
+    for (_, accel_records) in &mut data {
+        accel_records.sort_by(|a, b| {
+            let key =
+                |r: &data_loader::AccelRecord| (r.accel_info.name.clone(), r.accel_info.m_value);
+            key(a).cmp(&key(b))
+        });
+    }
+    data
+}
+
+fn ids(data: &[SeriesData]) -> Vec<i32> {
+    data.iter().map(|(series, _)| series.series_id).collect()
+}
+
+fn methods(data: &[SeriesData]) -> Vec<(i32, Vec<String>)> {
+    data.iter()
+        .map(|(series, accel_records)| {
+            let names = accel_records
+                .iter()
+                .map(|r| format!("{} m={}", r.accel_info.name, r.accel_info.m_value))
+                .collect();
+            (series.series_id, names)
+        })
+        .collect()
+}
+
+fn sorted(map: &HashMap<data_loader::IStr, data_loader::IStr>) -> BTreeMap<String, String> {
+    map.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+// Everything `filter_data` returns, in a comparable form
+fn summary(data: &[SeriesData]) -> Vec<String> {
+    let mut res = Vec::new();
+    for (series, accel_records) in data {
+        res.push(format!(
+            "{} {} {} {:?} {:?} {:?} {:?}",
+            series.series_id,
+            series.precision,
+            series.name,
+            sorted(&series.arguments),
+            series.series_limit,
+            series.computed.iter().collect::<Vec<_>>(),
+            series.run,
+        ));
+        for accel_record in accel_records {
+            res.push(format!(
+                "  {} {} {:?} {:?} {:?} {:?}",
+                accel_record.accel_info.name,
+                accel_record.accel_info.m_value,
+                sorted(&accel_record.accel_info.additional_args),
+                accel_record.computed.iter().collect::<Vec<_>>(),
+                accel_record.errors,
+                accel_record.events,
+            ));
+        }
+    }
+    res
+}
+
+#[test]
+fn loads_series_with_their_accelerations() {
+    let dataset = Dataset::new("standard");
+    write_standard(&dataset, RecordBatch::clone);
+    let data = load(&dataset.path(), &Filters::default());
+
+    assert_eq!(ids(&data), [0, 1, 2]);
+    let (series, accel_records) = &data[0];
+    assert_eq!(series.precision, intern("f64"));
+    assert_eq!(series.name, intern("geometric"));
+    assert_eq!(sorted(&series.arguments)["x"], "0.5");
+    assert_eq!(series.series_limit.real, Scientific(2.0, 0));
+    assert_eq!(series.computed.n(), [1, 2, 3]);
+    let point = series.computed.get(1);
+    assert_eq!(point.value.real, Scientific(1.5, 0));
+    assert_eq!(point.value.imag, Scientific(0.0, 0));
+    assert_eq!(point.deviation, Scientific(5.0, -1));
+    assert_eq!(series.run, None);
+    assert_eq!(data[2].0.precision, intern("f128"));
+
+    assert_eq!(methods(&data)[0].1, ["levin m=2", "shanks m=1"]);
+    let (levin, shanks) = (&accel_records[0], &accel_records[1]);
+    assert_eq!(levin.computed.len(), 3);
+    assert!(levin.computed.get(0).is_none() && levin.computed.get(1).is_none());
+    assert_eq!(
+        levin.computed.get(2).unwrap().deviation,
+        Scientific(-3.0, -5)
+    );
+    assert_eq!(sorted(&levin.accel_info.additional_args)["variant"], "t");
+    assert_eq!(levin.errors.len(), 1);
+    assert_eq!(levin.errors[0].n, 3);
+    assert_eq!(levin.errors[0].message, "overflow");
+    assert_eq!(levin.events.len(), 1);
+    assert_eq!(levin.events[0].name, "restart");
+    assert_eq!(levin.events[0].description, "reset");
+    // A null parameter is left out rather than read as an empty string
+    assert!(shanks.accel_info.additional_args.is_empty());
+    assert!(shanks.errors.is_empty() && shanks.events.is_empty());
+    assert_eq!(
+        shanks.computed.get(2).unwrap().deviation,
+        Scientific(1.0, -4)
+    );
+
+    // Null `events` and `errors` lists are empty
+    assert!(data[1].1[0].events.is_empty());
+    assert!(data[2].1[0].errors.is_empty());
+}
+
+#[test]
+fn filters_select_partitions_and_methods() {
+    let dataset = Dataset::new("standard");
+    write_standard(&dataset, RecordBatch::clone);
+    let path = dataset.path();
+    let filtered = |f: fn(&mut Filters)| {
+        let mut filters = Filters::default();
+        f(&mut filters);
+        methods(&load(&path, &filters))
+    };
+    let expect = |ids: &[(i32, &[&str])]| -> Vec<(i32, Vec<String>)> {
+        ids.iter()
+            .map(|(id, names)| (*id, names.iter().map(|n| n.to_string()).collect()))
+            .collect()
+    };
+
+    assert_eq!(
+        filtered(|f| f.precisions = [intern("f128")].into()),
+        expect(&[(2, &["levin m=2"])])
+    );
+    assert_eq!(
+        filtered(|f| f.base_series = [intern("geometric")].into()),
+        expect(&[
+            (0, &["levin m=2", "shanks m=1"]),
+            (1, &["shanks m=1"]),
+            (2, &["levin m=2"]),
+        ])
+    );
+    // Series stay when none of their methods match
+    assert_eq!(
+        filtered(|f| f.base_accel = [intern("shanks")].into()),
+        expect(&[(0, &["shanks m=1"]), (1, &["shanks m=1"]), (2, &[])])
+    );
+    assert_eq!(
+        filtered(|f| f.m_values = [2].into()),
+        expect(&[(0, &["levin m=2"]), (1, &[]), (2, &["levin m=2"])])
+    );
+    assert_eq!(
+        filtered(|f| f.has_errors = Some(true)),
+        expect(&[(0, &["levin m=2"]), (1, &[]), (2, &[])])
+    );
+    assert_eq!(
+        filtered(|f| f.event_names = [intern("restart")].into()),
+        expect(&[(0, &["levin m=2"]), (1, &[]), (2, &[])])
+    );
+    assert_eq!(
+        filtered(|f| f.sample = Some(1)),
+        expect(&[(0, &["levin m=2", "shanks m=1"])])
+    );
+    // A parameter filter applies only to the methods that set it: Levin here, so Shanks stays
+    let variant = |values: &[&str]| {
+        let mut filters = Filters::default();
+        let values = values.iter().map(|v| intern(v)).collect();
+        filters.accel_params = [(intern("variant"), values)].into();
+        methods(&load(&path, &filters))
+    };
+    assert_eq!(
+        variant(&["t"]),
+        expect(&[
+            (0, &["levin m=2", "shanks m=1"]),
+            (1, &["shanks m=1"]),
+            (2, &[])
+        ])
+    );
+    assert_eq!(
+        variant(&["u"]),
+        expect(&[
+            (0, &["shanks m=1"]),
+            (1, &["shanks m=1"]),
+            (2, &["levin m=2"])
+        ])
+    );
+    // `NOT_SET` selects the Levin records without it, of which there are none
+    assert_eq!(
+        variant(&[NOT_SET]),
+        expect(&[(0, &["shanks m=1"]), (1, &["shanks m=1"]), (2, &[])])
+    );
+}
+
+#[test]
+fn parameter_values_sort_by_kind() {
+    let mut numbers = ["10", "2", NOT_SET, "1e-3", "-1"];
+    data_loader::sort_param_values(&mut numbers);
+    assert_eq!(numbers, [NOT_SET, "-1", "1e-3", "2", "10"]);
+    let mut text = ["u", "t", "10", "2"];
+    data_loader::sort_param_values(&mut text);
+    assert_eq!(text, ["10", "2", "t", "u"]);
+
+    assert_eq!(
+        data_loader::ParamKind::of(["true", "False", NOT_SET]),
+        data_loader::ParamKind::Bool
+    );
+    assert_eq!(
+        data_loader::ParamKind::of(["1", "2.5"]),
+        data_loader::ParamKind::Number
+    );
+    assert_eq!(
+        data_loader::ParamKind::of(["1", "t"]),
+        data_loader::ParamKind::Text
+    );
+}
+
+#[test]
+fn metadata_groups_parameters_by_method() {
+    let dataset = Dataset::new("param_methods");
+    write_standard(&dataset, RecordBatch::clone);
+    let mut metadata = data_loader::Metadata::default();
+    block_on(async {
+        DataLoader::new(&dataset.path())
+            .await?
+            .compute_metadata(|part| metadata.apply(part))
+            .await
+    })
+    .unwrap();
+
+    assert_eq!(
+        metadata.accel_param_info["variant"],
+        [NOT_SET, "t", "u"].map(str::to_string)
+    );
+    assert_eq!(
+        metadata.accel_param_methods,
+        HashMap::from([("variant".to_string(), vec!["levin".to_string()])])
+    );
+}
+
+#[test]
+fn reruns_keep_the_latest_run() {
+    let dataset = Dataset::new("reruns");
+    dataset.series(
+        "f64",
+        "geometric",
+        &batch(vec![
+            ("series_id", ints(&[0])),
+            ("computed", series_points(&[&[(1, "1", "1")]])),
+        ]),
+    );
+    dataset.accelerations(
+        0,
+        &batch(vec![
+            (
+                "accel_name",
+                strings(&[
+                    Some("shanks"),
+                    Some("shanks"),
+                    Some("shanks"),
+                    Some("levin"),
+                ]),
+            ),
+            ("m_value", ints(&[1, 1, 1, 2])),
+            (
+                "computed",
+                accel_points(&[
+                    &[Some(("1", "1e-1"))],
+                    &[Some(("1", "1e-2"))],
+                    &[Some(("1", "1e-3"))],
+                    &[Some(("1", "1e-4"))],
+                ]),
+            ),
+            // Numerically "10" is the latest
+            ("run_id", strings(&[Some("9"), Some("10"), Some("2"), None])),
+        ]),
+    );
+    let filters = Filters {
+        duplicates: data_loader::DuplicatePolicy::KeepLatest,
+        ..Filters::default()
+    };
+    let data = load(&dataset.path(), &filters);
+
+    assert_eq!(methods(&data)[0].1, ["levin m=2", "shanks m=1"]);
+    let shanks = &data[0].1[1];
+    assert_eq!(shanks.run, Some(intern("10")));
+    assert_eq!(shanks.reruns, 2);
+    assert_eq!(
+        shanks.computed.get(0).unwrap().deviation,
+        Scientific(1.0, -2)
+    );
+    assert_eq!(data[0].1[0].run, None);
+}
+
+#[test]
+fn nulls_are_missing_values() {
+    let dataset = Dataset::new("nulls");
+    dataset.series(
+        "f64",
+        "geometric",
+        &batch(vec![
+            ("series_id", ints(&[0, 1])),
+            (
+                "arguments",
+                structure(
+                    vec![
+                        ("x", strings(&[Some("1"), None])),
+                        ("y", strings(&[None, None])),
+                    ],
+                    None,
+                ),
+            ),
+            ("series_limit", complex(&[None, Some(("3", None))])),
+            (
+                "computed",
+                series_points(&[&[(1, "1", "1")], &[(1, "2", "1")]]),
+            ),
+            ("run_id", strings(&[Some("7"), None])),
+        ]),
+    );
+    dataset.accelerations(
+        0,
+        &batch(vec![
+            ("accel_name", strings(&[Some("shanks")])),
+            ("m_value", ints(&[1])),
+            (
+                "additional_args",
+                structure(vec![("variant", strings(&[None]))], None),
+            ),
+            ("computed", accel_points(&[&[None, Some(("1", "1e-3"))]])),
+            ("errors", errors(&[None])),
+            ("events", events(&[None])),
+        ]),
+    );
+    let data = load(&dataset.path(), &Filters::default());
+
+    assert_eq!(ids(&data), [0, 1]);
+    let (first, second) = (&data[0].0, &data[1].0);
+    assert_eq!(
+        sorted(&first.arguments).into_iter().collect::<Vec<_>>(),
+        [("x".to_string(), "1".to_string())]
+    );
+    assert!(second.arguments.is_empty());
+    // A null limit is read as zero, a null imaginary part too
+    assert_eq!(first.series_limit.real, Scientific(0.0, 0));
+    assert_eq!(second.series_limit.real, Scientific(3.0, 0));
+    assert_eq!(second.series_limit.imag, Scientific(0.0, 0));
+    assert_eq!(first.run, Some(intern("7")));
+    assert_eq!(second.run, None);
+
+    let accel_record = &data[0].1[0];
+    assert!(accel_record.accel_info.additional_args.is_empty());
+    assert!(accel_record.computed.get(0).is_none());
+    assert_eq!(
+        accel_record.computed.get(1).unwrap().deviation,
+        Scientific(1.0, -3)
+    );
+    assert!(accel_record.errors.is_empty() && accel_record.events.is_empty());
+    assert!(data[1].1.is_empty());
+}
+
+#[test]
+fn null_typed_columns_are_nulls() {
+    let dataset = Dataset::new("null_typed");
+    dataset.series(
+        "f64",
+        "geometric",
+        &batch(vec![
+            ("series_id", ints(&[0])),
+            (
+                "arguments",
+                structure(vec![("x", strings(&[Some("1")]))], None),
+            ),
+            ("series_limit", complex(&[Some(("1", None))])),
+            ("computed", series_points(&[&[(1, "1", "1")]])),
+            ("run_id", nulls(1)),
+        ]),
+    );
+    dataset.accelerations(
+        0,
+        &batch(vec![
+            ("accel_name", strings(&[Some("shanks"), Some("levin")])),
+            ("m_value", ints(&[1, 2])),
+            ("computed", accel_points(&[&[None], &[None]])),
+            ("errors", nulls(2)),
+            ("events", nulls(2)),
+        ]),
+    );
+    let data = load(&dataset.path(), &Filters::default());
+
+    assert_eq!(ids(&data), [0]);
+    assert_eq!(data[0].0.run, None);
+    assert_eq!(methods(&data)[0].1, ["levin m=2", "shanks m=1"]);
+    for accel_record in &data[0].1 {
+        assert!(accel_record.errors.is_empty() && accel_record.events.is_empty());
+    }
+}
+
+#[test]
+fn string_views_read_like_strings() {
+    let plain = Dataset::new("plain");
+    write_standard(&plain, RecordBatch::clone);
+    let views = Dataset::new("views");
+    write_standard(&views, string_views);
+
+    let expected = summary(&load(&plain.path(), &Filters::default()));
+    assert!(!expected.is_empty());
+    assert_eq!(summary(&load(&views.path(), &Filters::default())), expected);
+}
+
+#[test]
+fn optional_columns_may_be_missing() {
+    let dataset = Dataset::new("minimal");
+    dataset.series(
+        "f64",
+        "geometric",
+        &batch(vec![
+            ("series_id", ints(&[0])),
+            (
+                "arguments",
+                structure(vec![("x", strings(&[Some("1")]))], None),
+            ),
+            ("series_limit", complex(&[Some(("1", None))])),
+            ("computed", series_points(&[&[(1, "1", "1")]])),
+        ]),
+    );
+    dataset.accelerations(
+        0,
+        &batch(vec![
+            ("accel_name", strings(&[Some("shanks")])),
+            ("m_value", ints(&[1])),
+            ("computed", accel_points(&[&[None, Some(("1", "1e-2"))]])),
+        ]),
+    );
+    let path = dataset.path();
+
+    let data = load(&path, &Filters::default());
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].0.run, None);
+    let accel_record = &data[0].1[0];
+    assert!(accel_record.accel_info.additional_args.is_empty());
+    assert!(accel_record.errors.is_empty() && accel_record.events.is_empty());
+    assert!(accel_record.uncertainty.is_empty());
+
+    // Missing lists have no entries
+    let with = |f: fn(&mut Filters)| {
+        let mut filters = Filters::default();
+        f(&mut filters);
+        methods(&load(&path, &filters))
+    };
+    assert_eq!(with(|f| f.has_errors = Some(true)), [(0, vec![])]);
+    assert_eq!(
+        with(|f| f.has_errors = Some(false)),
+        [(0, vec!["shanks m=1".to_string()])]
+    );
+    assert_eq!(with(|f| f.has_events = Some(true)), [(0, vec![])]);
+}
+
+#[test]
+fn unusual_data_paths() {
+    // Spaces, non-ASCII and a trailing separator. On Windows the loader then appends `/series`
+    // to a backslash path, mixing the separators
+    let dataset = Dataset::new("data dir/данные №1");
+    write_standard(&dataset, RecordBatch::clone);
+    let path = format!("{}{}", dataset.path(), std::path::MAIN_SEPARATOR);
+
+    assert!(dataset.root().is_dir());
+    assert_eq!(ids(&load(&path, &Filters::default())), [0, 1, 2]);
+}
+
+#[test]
+fn series_only_data() {
+    let dataset = Dataset::new("series_only");
+    write_standard(&dataset, RecordBatch::clone);
+    std::fs::remove_dir_all(dataset.root().join("accelerations")).unwrap();
+    let path = dataset.path();
+
+    let data = load(&path, &Filters::default());
+    assert_eq!(ids(&data), [0, 1, 2]);
+    assert!(
+        data.iter()
+            .all(|(_, accel_records)| accel_records.is_empty())
+    );
+    assert_eq!(data[0].0.computed.len(), 3);
+
+    let filters = Filters {
+        base_accel: [intern("shanks")].into(),
+        has_errors: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(ids(&load(&path, &filters)), [0, 1, 2]);
+}
+
+#[test]
+fn unreadable_files_are_skipped() {
+    let dataset = Dataset::new("unreadable");
+    write_standard(&dataset, RecordBatch::clone);
+    let expected = methods(&load(&dataset.path(), &Filters::default()));
+    for dir in [
+        "series/precision=f64/series_name=broken",
+        "accelerations/series_id=0",
+    ] {
+        let dir = dataset.root().join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("truncated.parquet"), b"PAR1").unwrap();
+    }
+
+    assert_eq!(
+        methods(&load(&dataset.path(), &Filters::default())),
+        expected
+    );
+}
+
+fn schema_version(path: &str) -> SchemaVersion {
+    block_on(DataLoader::new(path)).unwrap().schema_version()
+}
+
+// Deviations of all points in order, missing accel points left out
+fn deviations(data: &[SeriesData]) -> Vec<f64> {
+    let mut res = Vec::new();
+    for (series, accel_records) in data {
+        res.extend(series.computed.iter().map(|p| p.deviation.approx_f64()));
+        for accel_record in accel_records {
+            res.extend(
+                accel_record
+                    .computed
+                    .iter()
+                    .flatten()
+                    .map(|p| p.deviation.approx_f64()),
+            );
+        }
+    }
+    res
+}
+
+#[test]
+fn schema_v1_float_deviations() {
+    let v1 = Dataset::new("v1");
+    write_standard(&v1, float_deviations);
+    let latest = Dataset::new("latest");
+    write_standard(&latest, RecordBatch::clone);
+    assert_eq!(schema_version(&v1.path()), SchemaVersion::V1);
+    assert_eq!(schema_version(&latest.path()), SchemaVersion::LATEST);
+
+    let (v1, latest) = (
+        load(&v1.path(), &Filters::default()),
+        load(&latest.path(), &Filters::default()),
+    );
+    assert_eq!(methods(&v1), methods(&latest));
+    let (v1, latest) = (deviations(&v1), deviations(&latest));
+    assert_eq!(v1.len(), latest.len());
+    for (a, b) in v1.iter().zip(&latest) {
+        assert!((a - b).abs() <= 1e-12 * b.abs(), "{a} != {b}");
+    }
+}
+
+#[test]
+fn schema_v2_without_events() {
+    let dataset = Dataset::new("minimal");
+    dataset.series(
+        "f64",
+        "geometric",
+        &batch(vec![
+            ("series_id", ints(&[0])),
+            (
+                "arguments",
+                structure(vec![("x", strings(&[Some("1")]))], None),
+            ),
+            ("series_limit", complex(&[Some(("1", None))])),
+            ("computed", series_points(&[&[(1, "1", "1")]])),
+        ]),
+    );
+    dataset.accelerations(
+        0,
+        &batch(vec![
+            ("accel_name", strings(&[Some("shanks")])),
+            ("m_value", ints(&[1])),
+            ("computed", accel_points(&[&[None]])),
+            ("errors", errors(&[Some(&[(1, "overflow")])])),
+        ]),
+    );
+    assert_eq!(schema_version(&dataset.path()), SchemaVersion::V2);
+}
+
+#[test]
+fn exported_dataset_loads_back() {
+    for (name, convert) in [
+        (
+            "latest",
+            RecordBatch::clone as fn(&RecordBatch) -> RecordBatch,
+        ),
+        ("v1", float_deviations),
+    ] {
+        let dataset = Dataset::new(name);
+        write_standard(&dataset, convert);
+        let data = load(&dataset.path(), &Filters::default());
+        let target = dataset.root().join("exported");
+        let refs: Vec<_> = data.iter().map(|(s, a)| (s, a.iter().collect())).collect();
+        // With a note column, which the loader leaves alone
+        export_dataset(&refs, |id, _| (id == 0).then_some("checked"), &target).unwrap();
+
+        let exported = target.display().to_string();
+        assert_eq!(schema_version(&exported), SchemaVersion::LATEST);
+        assert_eq!(
+            summary(&load(&exported, &Filters::default())),
+            summary(&data)
+        );
+        // Never written over
+        assert!(export_dataset(&refs, |_, _| None, &target).is_err());
+    }
+}
+
+#[test]
+fn json_export_reads_back() {
+    let dataset = Dataset::new("json");
+    write_standard(&dataset, RecordBatch::clone);
+    let data = load(&dataset.path(), &Filters::default());
+    let refs: Vec<_> = data.iter().map(|(s, a)| (s, a.iter().collect())).collect();
+    let mut json = Vec::new();
+    export_json(&refs, |id, _| (id == 0).then_some("checked"), &mut json).unwrap();
+
+    let parsed: Vec<SeriesData> = serde_json::from_slice(&json).unwrap();
+    assert_eq!(summary(&parsed), summary(&data));
+    // Numbers as mantissa and exponent
+    let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert!(value[0][0]["series_limit"]["real"].is_array());
+    // Notes only on the records that have one
+    for pair in value.as_array().unwrap() {
+        let noted = pair[0]["series_id"] == 0;
+        for record in pair[1].as_array().unwrap() {
+            assert_eq!(record.get("note").is_some(), noted);
+        }
+    }
+}
+
+#[cfg(feature = "duckdb")]
+#[test]
+fn engines_agree() {
+    use data_loader::Engine;
+
+    let dataset = Dataset::new("standard");
+    write_standard(&dataset, RecordBatch::clone);
+    let path = dataset.path();
+    let filters: [fn(&mut Filters); 7] = [
+        |_| {},
+        |f| f.precisions = [intern("f128")].into(),
+        |f| f.base_accel = [intern("shanks")].into(),
+        |f| f.m_values = [2].into(),
+        |f| f.has_errors = Some(false),
+        |f| f.sample = Some(1),
+        |f| f.accel_params = [(intern("variant"), [intern("u")].into())].into(),
+    ];
+    for f in filters {
+        let mut filters = Filters::default();
+        f(&mut filters);
+        assert_eq!(
+            summary(&load_with(Engine::DuckDb, &path, &filters)),
+            summary(&load_with(Engine::DataFusion, &path, &filters)),
+            "{}",
+            filters.summary()
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Small parquet datasets written on the fly, laid out like the real data:
+//! `series/precision=<p>/series_name=<name>/*.parquet` and
+//! `accelerations/series_id=<id>/*.parquet`. Partition columns live in the directory names only.
+//!
+//! Arrays are built with plain `Utf8` strings; [`string_views`] converts a batch to `Utf8View`.
+
+use datafusion::arrow::array::*;
+use datafusion::arrow::buffer::{NullBuffer, OffsetBuffer};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Fields};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::parquet::arrow::ArrowWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Dataset in a fresh temporary directory, removed on drop
+pub struct Dataset {
+    temp: PathBuf,
+    root: PathBuf,
+}
+
+impl Dataset {
+    /// `name` is the directory of the dataset itself and may contain anything a path can
+    pub fn new(name: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let temp = std::env::temp_dir().join(format!(
+            "vizr-test-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let root = temp.join(name);
+        for table in ["series", "accelerations"] {
+            std::fs::create_dir_all(root.join(table)).unwrap();
+        }
+        Self { temp, root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Data directory as `DataLoader::new` takes it
+    pub fn path(&self) -> String {
+        self.root.display().to_string()
+    }
+
+    /// Writes `batch` as a new file of the partition `precision`/`name` of the series table
+    pub fn series(&self, precision: &str, name: &str, batch: &RecordBatch) {
+        let dir = self
+            .root
+            .join("series")
+            .join(format!("precision={precision}"))
+            .join(format!("series_name={name}"));
+        write(&dir, batch);
+    }
+
+    /// Writes `batch` as a new file of the partition `series_id` of the accelerations table
+    pub fn accelerations(&self, series_id: i32, batch: &RecordBatch) {
+        let dir = self
+            .root
+            .join("accelerations")
+            .join(format!("series_id={series_id}"));
+        write(&dir, batch);
+    }
+}
+
+impl Drop for Dataset {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.temp);
+    }
+}
+
+fn write(dir: &Path, batch: &RecordBatch) {
+    std::fs::create_dir_all(dir).unwrap();
+    let index = std::fs::read_dir(dir).unwrap().count();
+    let file = std::fs::File::create(dir.join(format!("part-{index}.parquet"))).unwrap();
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+    writer.write(batch).unwrap();
+    writer.close().unwrap();
+}
+
+pub fn batch(columns: Vec<(&str, ArrayRef)>) -> RecordBatch {
+    RecordBatch::try_from_iter(columns).unwrap()
+}
+
+pub fn strings(values: &[Option<&str>]) -> ArrayRef {
+    Arc::new(StringArray::from(values.to_vec()))
+}
+
+pub fn ints(values: &[i32]) -> ArrayRef {
+    Arc::new(Int32Array::from(values.to_vec()))
+}
+
+/// Column of type `Null`, as written for columns that are null everywhere
+pub fn nulls(len: usize) -> ArrayRef {
+    Arc::new(NullArray::new(len))
+}
+
+/// Struct with nullable fields; `valid` marks the non-null rows, all of them if `None`
+pub fn structure(fields: Vec<(&str, ArrayRef)>, valid: Option<Vec<bool>>) -> ArrayRef {
+    let (fields, columns): (Vec<Field>, Vec<ArrayRef>) = fields
+        .into_iter()
+        .map(|(name, column)| (Field::new(name, column.data_type().clone(), true), column))
+        .unzip();
+    Arc::new(StructArray::new(
+        Fields::from(fields),
+        columns,
+        valid.map(NullBuffer::from),
+    ))
+}
+
+/// List over `values`, split into rows of the given lengths; `None` rows are null
+pub fn list(values: ArrayRef, rows: &[Option<usize>]) -> ArrayRef {
+    let field = Arc::new(Field::new_list_field(values.data_type().clone(), true));
+    let offsets = OffsetBuffer::from_lengths(rows.iter().map(|r| r.unwrap_or(0)));
+    let valid: Vec<bool> = rows.iter().map(Option::is_some).collect();
+    Arc::new(ListArray::new(
+        field,
+        offsets,
+        values,
+        Some(NullBuffer::from(valid)),
+    ))
+}
+
+/// `{ real, imag }` of strings; `None` rows are null
+pub fn complex(values: &[Option<(&str, Option<&str>)>]) -> ArrayRef {
+    let real: Vec<Option<&str>> = values.iter().map(|v| v.map(|(real, _)| real)).collect();
+    let imag: Vec<Option<&str>> = values
+        .iter()
+        .map(|v| v.and_then(|(_, imag)| imag))
+        .collect();
+    structure(
+        vec![("real", strings(&real)), ("imag", strings(&imag))],
+        Some(values.iter().map(Option::is_some).collect()),
+    )
+}
+
+/// `computed` of the series table: rows of (n, real value, deviation), imaginary parts null
+pub fn series_points(rows: &[&[(i32, &str, &str)]]) -> ArrayRef {
+    let points: Vec<(i32, &str, &str)> = rows.iter().flat_map(|r| r.iter().copied()).collect();
+    let values: Vec<Option<(&str, Option<&str>)>> = points
+        .iter()
+        .map(|(_, real, _)| Some((*real, None)))
+        .collect();
+    let deviations: Vec<Option<&str>> = points.iter().map(|(_, _, d)| Some(*d)).collect();
+    let points = structure(
+        vec![
+            (
+                "n",
+                ints(&points.iter().map(|(n, _, _)| *n).collect::<Vec<_>>()),
+            ),
+            ("value", complex(&values)),
+            ("deviation", strings(&deviations)),
+        ],
+        None,
+    );
+    list(
+        points,
+        &rows.iter().map(|r| Some(r.len())).collect::<Vec<_>>(),
+    )
+}
+
+/// `computed` of the accelerations table: rows of (real value, deviation), `None` points are null
+pub fn accel_points(rows: &[&[Option<(&str, &str)>]]) -> ArrayRef {
+    let points: Vec<Option<(&str, &str)>> = rows.iter().flat_map(|r| r.iter().copied()).collect();
+    let values: Vec<Option<(&str, Option<&str>)>> = points
+        .iter()
+        .map(|p| p.map(|(real, _)| (real, Some("0"))))
+        .collect();
+    let deviations: Vec<Option<&str>> = points.iter().map(|p| p.map(|(_, d)| d)).collect();
+    let points = structure(
+        vec![
+            ("value", complex(&values)),
+            ("deviation", strings(&deviations)),
+        ],
+        Some(points.iter().map(Option::is_some).collect()),
+    );
+    list(
+        points,
+        &rows.iter().map(|r| Some(r.len())).collect::<Vec<_>>(),
+    )
+}
+
+/// `errors` of the accelerations table: rows of (n, message)
+pub fn errors(rows: &[Option<&[(i32, &str)]>]) -> ArrayRef {
+    let errors: Vec<(i32, &str)> = rows
+        .iter()
+        .flatten()
+        .flat_map(|r| r.iter().copied())
+        .collect();
+    let errors = structure(
+        vec![
+            (
+                "n",
+                ints(&errors.iter().map(|(n, _)| *n).collect::<Vec<_>>()),
+            ),
+            (
+                "message",
+                strings(&errors.iter().map(|(_, m)| Some(*m)).collect::<Vec<_>>()),
+            ),
+        ],
+        None,
+    );
+    list(
+        errors,
+        &rows.iter().map(|r| r.map(<[_]>::len)).collect::<Vec<_>>(),
+    )
+}
+
+// n, name, description
+type Event<'a> = (i32, &'a str, &'a str);
+
+/// `events` of the accelerations table
+pub fn events(rows: &[Option<&[Event]>]) -> ArrayRef {
+    let events: Vec<Event> = rows
+        .iter()
+        .flatten()
+        .flat_map(|r| r.iter().copied())
+        .collect();
+    let events = structure(
+        vec![
+            (
+                "n",
+                ints(&events.iter().map(|(n, _, _)| *n).collect::<Vec<_>>()),
+            ),
+            (
+                "name",
+                strings(&events.iter().map(|(_, e, _)| Some(*e)).collect::<Vec<_>>()),
+            ),
+            (
+                "description",
+                strings(&events.iter().map(|(_, _, d)| Some(*d)).collect::<Vec<_>>()),
+            ),
+        ],
+        None,
+    );
+    list(
+        events,
+        &rows.iter().map(|r| r.map(<[_]>::len)).collect::<Vec<_>>(),
+    )
+}
+
+// Field type with `retype` applied to the field and, where it returns `None`, to its children
+fn field_type(
+    name: &str,
+    data_type: &DataType,
+    retype: &impl Fn(&str, &DataType) -> Option<DataType>,
+) -> DataType {
+    if let Some(data_type) = retype(name, data_type) {
+        return data_type;
+    }
+    let field = |f: &Field| {
+        f.clone()
+            .with_data_type(field_type(f.name(), f.data_type(), retype))
+    };
+    match data_type {
+        DataType::Struct(fields) => DataType::Struct(fields.iter().map(|f| field(f)).collect()),
+        DataType::List(f) => DataType::List(Arc::new(field(f))),
+        other => other.clone(),
+    }
+}
+
+fn convert(
+    batch: &RecordBatch,
+    retype: impl Fn(&str, &DataType) -> Option<DataType>,
+) -> RecordBatch {
+    let schema = batch.schema();
+    RecordBatch::try_from_iter(schema.fields().iter().zip(batch.columns()).map(|(f, c)| {
+        let c = cast(c, &field_type(f.name(), c.data_type(), &retype)).unwrap();
+        (f.name().clone(), c)
+    }))
+    .unwrap()
+}
+
+/// `batch` with every string, nested ones included, as `Utf8View`
+pub fn string_views(batch: &RecordBatch) -> RecordBatch {
+    convert(batch, |_, data_type| {
+        (*data_type == DataType::Utf8).then_some(DataType::Utf8View)
+    })
+}
+
+/// `batch` with the deviations of the points as `f64`, as written by schema v1
+pub fn float_deviations(batch: &RecordBatch) -> RecordBatch {
+    convert(batch, |name, data_type| {
+        (name == "deviation" && *data_type == DataType::Utf8).then_some(DataType::Float64)
+    })
+}
+
+/// Runs a future of the loader to completion
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Runtime::new().unwrap().block_on(future)
+}